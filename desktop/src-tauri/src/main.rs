@@ -4,25 +4,94 @@ use std::fs::OpenOptions;
 use std::fs::{create_dir_all, read_to_string, write, File};
 use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
-use chrono::{DateTime, Datelike, Duration as ChronoDuration, NaiveDate, Utc};
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, NaiveDate, SecondsFormat, Utc};
 use polars::io::csv::{CsvReader, CsvWriter};
 use polars::io::SerWriter;
 use polars::prelude::*;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::collections::{hash_map::Entry, HashMap};
+use tauri::Manager;
 
 #[derive(Serialize, Deserialize, Clone)]
 struct Transaction {
     date: String,
     stock: String,
     transaction_type: String,
+    #[serde(
+        deserialize_with = "string_or_number",
+        serialize_with = "number_or_null"
+    )]
     quantity: String,
+    #[serde(
+        deserialize_with = "string_or_number",
+        serialize_with = "number_or_null"
+    )]
     price: String,
+    #[serde(
+        deserialize_with = "string_or_number",
+        serialize_with = "number_or_null"
+    )]
     fees: String,
+    #[serde(
+        deserialize_with = "string_or_number",
+        serialize_with = "ratio_or_null"
+    )]
     split_ratio: String,
     currency: String,
+    /// Lot a sale was taken from (`<open_date>_<open_price>`); empty for FIFO.
+    #[serde(default)]
+    lot_id: String,
+    /// File and 1-based line the row was read from, for parse errors.
+    #[serde(skip)]
+    source: Option<(String, usize)>,
+}
+
+/// Writes a numeric transaction field as a JSON number, or null when the CSV text is
+/// empty or not a number. The text itself is kept so rewrites stay byte for byte.
+fn number_or_null<S>(raw: &str, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    match parse_number(raw) {
+        Ok(Some(value)) => serializer.serialize_f64(value),
+        _ => serializer.serialize_none(),
+    }
+}
+
+/// `number_or_null` for a split ratio, which may also be written as `4:1`.
+fn ratio_or_null<S>(raw: &str, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    match parse_ratio(raw) {
+        Ok(Some(value)) => serializer.serialize_f64(value),
+        _ => serializer.serialize_none(),
+    }
+}
+
+/// Accepts either a JSON string or number for a numeric transaction field, keeping
+/// the CSV text representation.
+fn string_or_number<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrNumber {
+        String(String),
+        Number(f64),
+        Null(()),
+    }
+
+    Ok(match StringOrNumber::deserialize(deserializer)? {
+        StringOrNumber::String(value) => value,
+        StringOrNumber::Number(value) => value.to_string(),
+        StringOrNumber::Null(()) => String::new(),
+    })
 }
 
 #[tauri::command]
@@ -32,12 +101,69 @@ fn greet(name: &str) -> String {
 
 const SETTINGS_HEADER: &str = "key,value\n";
 const SECURITIES_HEADER: &str =
-    "ticker,name,exchange,currency,type,sector,data_source,api_symbol,last_updated\n";
+    "ticker,name,exchange,currency,type,sector,data_source,api_symbol,last_updated,tags,notes\n";
 const PRICE_FILE_HEADER: &str =
     "date,close,open,high,low,volume,adjusted_close,split_unadjusted_close,source,updated_at";
 const FX_RATES_HEADER: &str = "from_currency,to_currency,date,rate,source,updated_at\n";
-const DIVIDEND_FILE_HEADER: &str = "ex_date,amount,currency,updated_at";
-#[derive(Clone, Debug)]
+const DIVIDEND_FILE_HEADER: &str = "ex_date,amount,currency,updated_at,source";
+const SPLIT_FILE_HEADER: &str = "date,numerator,denominator,before_price,after_price\n";
+
+/// Row order of price and NAV files on disk: newest first, so the latest rows are
+/// at the top. Writers sort to it; readers that need an order sort explicitly
+/// instead of trusting the layout, since older files may be ascending.
+const CANONICAL_ROW_ORDER: RowOrder = RowOrder::Desc;
+
+#[derive(Clone, Copy, PartialEq)]
+enum RowOrder {
+    Asc,
+    Desc,
+}
+
+impl RowOrder {
+    /// `"asc"` or `"desc"`; absent means the on-disk order.
+    fn parse(value: Option<&str>) -> Result<RowOrder, AppError> {
+        match value.map(|v| v.trim().to_ascii_lowercase()).as_deref() {
+            None | Some("") => Ok(CANONICAL_ROW_ORDER),
+            Some("asc") => Ok(RowOrder::Asc),
+            Some("desc") => Ok(RowOrder::Desc),
+            Some(other) => Err(AppError::validation(
+                "order",
+                format!("Unknown order '{}'; expected asc or desc", other),
+            )),
+        }
+    }
+
+    fn compare<T: Ord>(self, a: &T, b: &T) -> std::cmp::Ordering {
+        match self {
+            RowOrder::Asc => a.cmp(b),
+            RowOrder::Desc => b.cmp(a),
+        }
+    }
+}
+
+/// Re-emits CSV `content` with its data rows sorted by the first (date) column.
+/// The header stays first and blank lines are dropped.
+fn order_csv_rows(content: &str, order: RowOrder) -> String {
+    let mut lines = content.lines();
+    let Some(header) = lines.next() else {
+        return String::new();
+    };
+    let mut rows: Vec<&str> = lines.filter(|line| !line.trim().is_empty()).collect();
+    rows.sort_by(|a, b| {
+        let key = |line: &str| line.split(',').next().unwrap_or("").trim().to_string();
+        order.compare(&key(a), &key(b))
+    });
+
+    let mut output = String::with_capacity(content.len() + 1);
+    output.push_str(header);
+    output.push('\n');
+    for row in rows {
+        output.push_str(row);
+        output.push('\n');
+    }
+    output
+}
+#[derive(Clone, Debug, PartialEq)]
 struct PriceRecordEntry {
     symbol: String,
     date: NaiveDate,
@@ -239,7 +365,10 @@ struct PriceRecordResponse {
     updated_at: String,
 }
 
-fn read_price_file_with_polars(path: &Path, symbol: &str) -> Result<Vec<PriceRecordResponse>, String> {
+fn read_price_file_with_polars(
+    path: &Path,
+    symbol: &str,
+) -> Result<Vec<PriceRecordResponse>, String> {
     if !path.exists() {
         return Ok(Vec::new());
     }
@@ -338,7 +467,7 @@ fn load_price_with_polars(
     include_overrides: bool,
 ) -> Result<Vec<PriceRecordResponse>, String> {
     let prices_dir = get_prices_dir(app_handle)?;
-    let safe_symbol = symbol.replace(':', "_");
+    let safe_symbol = symbol_file_stem(symbol);
     let base_path = prices_dir.join(format!("{}.csv", safe_symbol));
     let override_path = prices_dir.join(format!("{}-override.csv", safe_symbol));
 
@@ -363,12 +492,10 @@ fn load_price_with_polars(
 fn read_prices_polars(
     app_handle: tauri::AppHandle,
     symbol: String,
-    #[allow(non_snake_case)]
-    latestOnly: Option<bool>,
-    #[allow(non_snake_case)]
-    includeOverrides: Option<bool>,
+    #[allow(non_snake_case)] latestOnly: Option<bool>,
+    #[allow(non_snake_case)] includeOverrides: Option<bool>,
     limit: Option<usize>,
-) -> Result<Vec<PriceRecordResponse>, String> {
+) -> Result<Vec<PriceRecordResponse>, AppError> {
     let include_overrides = includeOverrides.unwrap_or(true);
     let mut records = load_price_with_polars(&app_handle, &symbol, include_overrides)?;
 
@@ -388,32 +515,40 @@ fn read_prices_polars(
     Ok(records)
 }
 
-fn build_price_csv_content(entries: &[PriceRecordEntry]) -> String {
+fn build_price_csv_content(entries: &[PriceRecordEntry]) -> Result<String, String> {
     if entries.is_empty() {
-        return format!("{}\n", PRICE_FILE_HEADER);
+        return Ok(format!("{}\n", PRICE_FILE_HEADER));
     }
 
     let updated_at = Utc::now().to_rfc3339();
     let n_rows = entries.len();
-
-    // Build columns
-    let dates: Vec<String> = entries
-        .iter()
-        .map(|e| e.date.format("%Y-%m-%d").to_string())
-        .collect();
-    let closes: Vec<f64> = entries.iter().map(|e| e.close).collect();
-    let opens: Vec<Option<f64>> = entries.iter().map(|e| e.open).collect();
-    let highs: Vec<Option<f64>> = entries.iter().map(|e| e.high).collect();
-    let lows: Vec<Option<f64>> = entries.iter().map(|e| e.low).collect();
-    let volumes: Vec<Option<f64>> = entries.iter().map(|e| e.volume).collect();
-    let adjusted_closes: Vec<Option<f64>> = entries.iter().map(|e| e.adjusted_close).collect();
-    let split_unadjusted_closes: Vec<Option<f64>> =
-        entries.iter().map(|e| e.split_unadjusted_close).collect();
-    let sources: Vec<&str> = entries.iter().map(|e| e.source.as_str()).collect();
-    let updated_ats: Vec<&str> = vec![updated_at.as_str(); n_rows];
+    let mut entries: Vec<&PriceRecordEntry> = entries.iter().collect();
+    entries.sort_by(|a, b| CANONICAL_ROW_ORDER.compare(&a.date, &b.date));
+
+    // Build columns in one pass
+    let mut dates: Vec<String> = Vec::with_capacity(n_rows);
+    let mut closes: Vec<f64> = Vec::with_capacity(n_rows);
+    let mut opens: Vec<Option<f64>> = Vec::with_capacity(n_rows);
+    let mut highs: Vec<Option<f64>> = Vec::with_capacity(n_rows);
+    let mut lows: Vec<Option<f64>> = Vec::with_capacity(n_rows);
+    let mut volumes: Vec<Option<f64>> = Vec::with_capacity(n_rows);
+    let mut adjusted_closes: Vec<Option<f64>> = Vec::with_capacity(n_rows);
+    let mut split_unadjusted_closes: Vec<Option<f64>> = Vec::with_capacity(n_rows);
+    let mut sources: Vec<&str> = Vec::with_capacity(n_rows);
+    for entry in entries {
+        dates.push(entry.date.format("%Y-%m-%d").to_string());
+        closes.push(entry.close);
+        opens.push(entry.open);
+        highs.push(entry.high);
+        lows.push(entry.low);
+        volumes.push(entry.volume);
+        adjusted_closes.push(entry.adjusted_close);
+        split_unadjusted_closes.push(entry.split_unadjusted_close);
+        sources.push(entry.source.as_str());
+    }
 
     // Create DataFrame
-    let df = DataFrame::new(vec![
+    let mut df = DataFrame::new(vec![
         Series::new("date", dates),
         Series::new("close", closes),
         Series::new("open", opens),
@@ -423,17 +558,17 @@ fn build_price_csv_content(entries: &[PriceRecordEntry]) -> String {
         Series::new("adjusted_close", adjusted_closes),
         Series::new("split_unadjusted_close", split_unadjusted_closes),
         Series::new("source", sources),
-        Series::new("updated_at", updated_ats),
+        StringChunked::full("updated_at", &updated_at, n_rows).into_series(),
     ])
-    .expect("Failed to create price DataFrame");
+    .map_err(|e| format!("Failed to build price DataFrame: {}", e))?;
 
     // Write to CSV string
     let mut buf = Vec::new();
     CsvWriter::new(&mut buf)
-        .finish(&mut df.clone())
-        .expect("Failed to write CSV");
+        .finish(&mut df)
+        .map_err(|e| format!("Failed to write price CSV: {}", e))?;
 
-    String::from_utf8(buf).unwrap_or_else(|_| format!("{}\n", PRICE_FILE_HEADER))
+    String::from_utf8(buf).map_err(|e| format!("Price CSV is not valid UTF-8: {}", e))
 }
 
 #[derive(Deserialize)]
@@ -518,71 +653,685 @@ fn ensure_file_with_header(file_path: &Path, header: &str) -> Result<(), String>
         .map_err(|e| format!("Failed to write header for {:?}: {}", file_path, e))
 }
 
-fn read_csv_file(file_path: &str, currency: &str) -> Result<Vec<Transaction>, String> {
-    let file = File::open(file_path).map_err(|e| format!("Failed to open {}: {}", file_path, e))?;
+/// Error returned by commands. Serialized with a `kind` tag and always a
+/// human-readable `message`, so the frontend can branch on the variant instead of
+/// matching message text. The transaction, dividend and price file readers return
+/// `NotFound`, `Io` and `Parse` directly; other internal helpers still return
+/// `String` errors, which convert to `Other`.
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum AppError {
+    Io {
+        message: String,
+    },
+    NotFound {
+        what: String,
+        message: String,
+    },
+    Network {
+        status: Option<u16>,
+        message: String,
+    },
+    Parse {
+        file: String,
+        line: Option<usize>,
+        message: String,
+    },
+    Validation {
+        field: String,
+        reason: String,
+        message: String,
+    },
+    RateLimited {
+        retry_after: Option<u64>,
+        message: String,
+    },
+    Other {
+        message: String,
+    },
+}
+
+impl AppError {
+    fn message(&self) -> &str {
+        match self {
+            AppError::Io { message }
+            | AppError::NotFound { message, .. }
+            | AppError::Network { message, .. }
+            | AppError::Parse { message, .. }
+            | AppError::Validation { message, .. }
+            | AppError::RateLimited { message, .. }
+            | AppError::Other { message } => message,
+        }
+    }
+
+    fn not_found(what: impl Into<String>, message: impl Into<String>) -> Self {
+        AppError::NotFound {
+            what: what.into(),
+            message: message.into(),
+        }
+    }
+
+    fn validation(field: &str, reason: impl Into<String>) -> Self {
+        let reason = reason.into();
+        AppError::Validation {
+            field: field.to_string(),
+            message: reason.clone(),
+            reason,
+        }
+    }
+
+    /// `NotFound` when `err` is a missing file, `Io` otherwise.
+    fn io(err: &std::io::Error, message: impl Into<String>) -> Self {
+        if err.kind() == std::io::ErrorKind::NotFound {
+            AppError::not_found("file", message)
+        } else {
+            AppError::Io {
+                message: message.into(),
+            }
+        }
+    }
+
+    /// A CSV read failure in `file`: `io` for I/O errors, `Parse` at the record's
+    /// line otherwise.
+    fn csv(file: &str, err: &csv::Error, message: impl Into<String>) -> Self {
+        match err.kind() {
+            csv::ErrorKind::Io(io) => AppError::io(io, message),
+            _ => AppError::Parse {
+                file: file.to_string(),
+                line: err.position().map(|p| p.line() as usize),
+                message: message.into(),
+            },
+        }
+    }
+
+    /// A polars read failure in `file`: `io` for I/O errors, `Parse` otherwise.
+    fn polars(file: &Path, err: &PolarsError, message: impl Into<String>) -> Self {
+        match err {
+            PolarsError::Io(io) => AppError::io(io, message),
+            _ => AppError::Parse {
+                file: file.display().to_string(),
+                line: None,
+                message: message.into(),
+            },
+        }
+    }
+
+    /// Maps an HTTP failure onto `RateLimited` (429) or `Network`.
+    fn from_status(status: reqwest::StatusCode, message: impl Into<String>) -> Self {
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            AppError::RateLimited {
+                retry_after: None,
+                message: message.into(),
+            }
+        } else {
+            AppError::Network {
+                status: Some(status.as_u16()),
+                message: message.into(),
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        AppError::Other { message }
+    }
+}
+
+impl From<&str> for AppError {
+    fn from(message: &str) -> Self {
+        AppError::Other {
+            message: message.to_string(),
+        }
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(err: std::io::Error) -> Self {
+        AppError::Io {
+            message: err.to_string(),
+        }
+    }
+}
+
+impl From<AppError> for String {
+    fn from(err: AppError) -> Self {
+        err.to_string()
+    }
+}
+
+/// Per-path write locks shared by every writer (commands and the history worker), so
+/// two writes to the same file serialize while different files proceed in parallel.
+/// Keys come from `file_lock_key`.
+#[derive(Default)]
+struct FileLockRegistry {
+    locks: Mutex<HashMap<PathBuf, Arc<Mutex<()>>>>,
+}
+
+impl FileLockRegistry {
+    fn lock_for(&self, key: &Path) -> Arc<Mutex<()>> {
+        let mut locks = self.locks.lock().unwrap_or_else(|e| e.into_inner());
+        locks.entry(key.to_path_buf()).or_default().clone()
+    }
+}
+
+/// Registry key for `path`, resolved through its parent directory (the file itself
+/// may not exist yet) so relative and absolute spellings of one file share a lock.
+fn file_lock_key(path: &Path) -> PathBuf {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    match (parent.canonicalize(), path.file_name()) {
+        (Ok(parent), Some(name)) => parent.join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+thread_local! {
+    /// Lock keys this thread holds through `with_file_lock`.
+    static HELD_FILE_LOCKS: std::cell::RefCell<std::collections::HashSet<PathBuf>> =
+        Default::default();
+}
+
+/// Runs `f` holding `path`'s lock from `FileLockRegistry`, for read-modify-write
+/// updates. Re-entrant on one thread, so `f` can call `atomic_write` on the same path.
+fn with_file_lock<T>(app_handle: &tauri::AppHandle, path: &Path, f: impl FnOnce() -> T) -> T {
+    let Some(registry) = app_handle.try_state::<FileLockRegistry>() else {
+        return f();
+    };
+    let key = file_lock_key(path);
+    if HELD_FILE_LOCKS.with(|held| held.borrow().contains(&key)) {
+        return f();
+    }
+
+    struct Held(PathBuf);
+    impl Drop for Held {
+        fn drop(&mut self) {
+            HELD_FILE_LOCKS.with(|held| held.borrow_mut().remove(&self.0));
+        }
+    }
+
+    let lock = registry.lock_for(&key);
+    let _guard = lock.lock().unwrap_or_else(|e| e.into_inner());
+    HELD_FILE_LOCKS.with(|held| held.borrow_mut().insert(key.clone()));
+    let _held = Held(key);
+    f()
+}
+
+#[tauri::command]
+fn is_file_locked(app_handle: tauri::AppHandle, path: String) -> bool {
+    let Some(registry) = app_handle.try_state::<FileLockRegistry>() else {
+        return false;
+    };
+    let key = file_lock_key(Path::new(&path));
+    let lock = registry
+        .locks
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&key)
+        .cloned();
+    lock.is_some_and(|lock| lock.try_lock().is_err())
+}
+
+/// Kinds of data tracked by `PortfolioState` dirty flags.
+#[derive(Clone, Copy)]
+enum DataKind {
+    Prices,
+    Transactions,
+    FxRates,
+    Splits,
+    Dividends,
+    Navs,
+    Other,
+}
+
+impl DataKind {
+    /// Classifies a data file by its name for transaction files, otherwise by the
+    /// directory it lives in.
+    fn for_path(path: &Path) -> Self {
+        let file_name = path.file_name().and_then(|n| n.to_str());
+        if TRANSACTION_FILES
+            .iter()
+            .any(|(name, _)| Some(*name) == file_name)
+        {
+            return DataKind::Transactions;
+        }
+        let parent = path
+            .parent()
+            .and_then(|p| p.file_name())
+            .and_then(|n| n.to_str());
+        match parent {
+            Some("prices") => DataKind::Prices,
+            Some("fx_rates") => DataKind::FxRates,
+            Some("splits") => DataKind::Splits,
+            Some("dividends") => DataKind::Dividends,
+            Some("navs") => DataKind::Navs,
+            _ => DataKind::Other,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Default)]
+struct DirtyFlags {
+    prices: bool,
+    transactions: bool,
+    fx_rates: bool,
+    splits: bool,
+    dividends: bool,
+    navs: bool,
+    other: bool,
+}
+
+impl DirtyFlags {
+    fn flag_mut(&mut self, kind: DataKind) -> &mut bool {
+        match kind {
+            DataKind::Prices => &mut self.prices,
+            DataKind::Transactions => &mut self.transactions,
+            DataKind::FxRates => &mut self.fx_rates,
+            DataKind::Splits => &mut self.splits,
+            DataKind::Dividends => &mut self.dividends,
+            DataKind::Navs => &mut self.navs,
+            DataKind::Other => &mut self.other,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Default)]
+struct PortfolioStatePayload {
+    last_sync_time: Option<String>,
+    sync_active: bool,
+    dirty: DirtyFlags,
+    revision: u64,
+}
+
+/// State shared by every window. Dirty flags mark data written since the last
+/// completed history sync; `revision` bumps on every write so a window can compare it
+/// with the value it last loaded.
+#[derive(Default)]
+struct PortfolioState {
+    inner: Mutex<PortfolioStatePayload>,
+}
+
+/// Applies `update` and emits `state_changed` when it reports a visible change.
+fn update_portfolio_state(
+    app_handle: &tauri::AppHandle,
+    update: impl FnOnce(&mut PortfolioStatePayload) -> bool,
+) {
+    let Some(state) = app_handle.try_state::<PortfolioState>() else {
+        return;
+    };
+    let changed = {
+        let mut inner = state.inner.lock().unwrap_or_else(|e| e.into_inner());
+        inner.revision += 1;
+        update(&mut inner).then(|| inner.clone())
+    };
+    if let Some(payload) = changed {
+        let _ = app_handle.emit_all("state_changed", &payload);
+    }
+}
+
+/// Sets the dirty flag for `kind`; only the first write after a sync emits an event.
+fn mark_data_dirty(app_handle: &tauri::AppHandle, kind: DataKind) {
+    update_portfolio_state(app_handle, |state| {
+        !std::mem::replace(state.dirty.flag_mut(kind), true)
+    });
+}
+
+#[tauri::command]
+fn get_portfolio_state(app_handle: tauri::AppHandle) -> Result<PortfolioStatePayload, AppError> {
+    let state = app_handle
+        .try_state::<PortfolioState>()
+        .ok_or("Portfolio state is not available")?;
+    let payload = state
+        .inner
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone();
+    Ok(payload)
+}
+
+/// Writes to a sibling temp file and renames it over the target so readers never
+/// observe a half-written file. Holds the path's lock from `FileLockRegistry`.
+fn atomic_write(
+    app_handle: &tauri::AppHandle,
+    file_path: &Path,
+    content: &[u8],
+) -> Result<(), String> {
+    with_file_lock(app_handle, file_path, || {
+        replace_file(app_handle, file_path, content)
+    })
+}
+
+fn replace_file(
+    app_handle: &tauri::AppHandle,
+    file_path: &Path,
+    content: &[u8],
+) -> Result<(), String> {
+    if let Some(parent) = file_path.parent() {
+        create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory {:?}: {}", parent, e))?;
+    }
+
+    let file_name = file_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| format!("Invalid file path {:?}", file_path))?;
+    let tmp_path = file_path.with_file_name(format!(".{}.tmp", file_name));
+
+    let mut file = File::create(&tmp_path)
+        .map_err(|e| format!("Failed to create temp file {:?}: {}", tmp_path, e))?;
+    file.write_all(content)
+        .and_then(|_| file.sync_all())
+        .map_err(|e| format!("Failed to write temp file {:?}: {}", tmp_path, e))?;
+    drop(file);
+
+    let result = std::fs::rename(&tmp_path, file_path).map_err(|e| {
+        let _ = std::fs::remove_file(&tmp_path);
+        format!("Failed to replace {:?}: {}", file_path, e)
+    });
+    if let Some(store) = app_handle.try_state::<PriceStore>() {
+        store.invalidate(file_path);
+    }
+    if result.is_ok() {
+        match DataKind::for_path(file_path) {
+            DataKind::Transactions => invalidate_transaction_cache(app_handle),
+            kind => mark_data_dirty(app_handle, kind),
+        }
+    }
+    result
+}
+
+fn read_csv_file(file_path: &str, currency: &str) -> Result<Vec<Transaction>, AppError> {
+    let file = File::open(file_path)
+        .map_err(|e| AppError::io(&e, format!("Failed to open {}: {}", file_path, e)))?;
 
-    let mut reader = csv::Reader::from_reader(file);
+    // Rows written by `sell_from_lot` carry an optional eighth `lot_id` field, demo
+    // rows a ninth `demo` tag, and older exports stop before `split_ratio`; missing
+    // trailing fields read as empty.
+    // Exports without a header are common, so the first row is only skipped when
+    // it is clearly one.
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(file);
     let mut transactions = Vec::new();
 
-    for result in reader.records() {
-        let record = result.map_err(|e| format!("Failed to parse CSV record: {}", e))?;
+    for (idx, result) in reader.records().enumerate() {
+        let record = result.map_err(|e| {
+            AppError::csv(file_path, &e, format!("Failed to parse CSV record: {}", e))
+        })?;
+        let field = |i: usize| record.get(i).unwrap_or("").trim();
 
+        if idx == 0 && is_transaction_header(field(0)) {
+            continue;
+        }
         // Skip empty rows
-        if record.len() >= 7 && !record.get(0).unwrap_or("").is_empty() {
-            transactions.push(Transaction {
-                date: record.get(0).unwrap_or("").to_string(),
-                stock: record.get(1).unwrap_or("").to_string(),
-                transaction_type: record.get(2).unwrap_or("").to_string(),
-                quantity: record.get(3).unwrap_or("").to_string(),
-                price: record.get(4).unwrap_or("").to_string(),
-                fees: record.get(5).unwrap_or("").to_string(),
-                split_ratio: record.get(6).unwrap_or("").to_string(),
-                currency: currency.to_string(),
-            });
+        if field(0).is_empty() || field(1).is_empty() {
+            continue;
         }
+        transactions.push(Transaction {
+            date: field(0).to_string(),
+            stock: canonical_symbol(field(1)),
+            transaction_type: field(2).to_string(),
+            quantity: field(3).to_string(),
+            price: field(4).to_string(),
+            fees: field(5).to_string(),
+            split_ratio: field(6).to_string(),
+            currency: currency.to_string(),
+            lot_id: field(7).to_string(),
+            source: Some((file_path.to_string(), idx + 1)),
+        });
     }
 
     Ok(transactions)
 }
 
-#[tauri::command]
-fn read_csv(app_handle: tauri::AppHandle) -> Result<String, String> {
+/// Whether the first field of a transaction file's first row is a header label
+/// rather than a trade date.
+fn is_transaction_header(first_field: &str) -> bool {
+    first_field
+        .trim_start_matches('\u{feff}')
+        .trim()
+        .eq_ignore_ascii_case("date")
+}
+
+const TRANSACTION_FILES: [(&str, &str); 4] = [
+    ("US_Trx.csv", "USD"),
+    ("TW_Trx.csv", "TWD"),
+    ("JP_Trx.csv", "JPY"),
+    ("HK_Trx.csv", "HKD"),
+];
+const TRANSACTION_FILE_HEADER: &str = "date,stock,type,quantity,price,fees,split_ratio\n";
+
+/// Where a transaction file may live. Only the default portfolio also looks in the
+/// bundled resources and the legacy relative folders; other portfolios read their
+/// own directory.
+fn transaction_file_candidates(
+    app_handle: &tauri::AppHandle,
+    filename: &str,
+) -> Result<Vec<PathBuf>, String> {
+    if active_portfolio_id(app_handle)? != DEFAULT_PORTFOLIO_ID {
+        return Ok(vec![get_data_dir(app_handle)?.join(filename)]);
+    }
     let resource_dir = app_handle
         .path_resolver()
         .resource_dir()
         .ok_or("Failed to get resource directory")?;
 
-    let mut all_transactions = Vec::new();
+    Ok(vec![
+        resource_dir.join("data").join(filename),
+        std::path::PathBuf::from(format!("imported_data/{}", filename)),
+        std::path::PathBuf::from(format!("../imported_data/{}", filename)),
+        std::path::PathBuf::from(format!("data/{}", filename)), // legacy path for compatibility
+        std::path::PathBuf::from(format!("../data/{}", filename)), // legacy path for compatibility
+        get_data_dir(app_handle)?.join(filename),
+    ])
+}
+
+/// The transaction file `read_csv` would load for a currency, or a new file in the
+/// data directory when none exists yet.
+fn resolve_transaction_file(
+    app_handle: &tauri::AppHandle,
+    currency: &str,
+) -> Result<PathBuf, String> {
+    let (filename, _) = TRANSACTION_FILES
+        .iter()
+        .find(|(_, file_currency)| file_currency.eq_ignore_ascii_case(currency))
+        .ok_or_else(|| format!("No transaction file for currency {}", currency))?;
+
+    transaction_file_path(app_handle, filename)
+}
+
+/// Where `read_csv` finds the transaction file named `filename` (one of
+/// `TRANSACTION_FILES`), or a new file in the data directory.
+fn transaction_file_path(app_handle: &tauri::AppHandle, filename: &str) -> Result<PathBuf, String> {
+    let candidates = transaction_file_candidates(app_handle, filename)?;
+    if let Some(existing) = candidates.iter().find(|path| path.is_file()) {
+        return Ok(existing.clone());
+    }
+    Ok(get_data_dir(app_handle)?.join(filename))
+}
 
-    let files = vec![
-        ("US_Trx.csv", "USD"),
-        ("TW_Trx.csv", "TWD"),
-        ("JP_Trx.csv", "JPY"),
-        ("HK_Trx.csv", "HKD"),
+/// A transaction as a transaction file row; `lot_id` is only written when set.
+fn transaction_row(txn: &Transaction) -> Vec<String> {
+    let mut row = vec![
+        txn.date.trim().to_string(),
+        txn.stock.trim().to_string(),
+        txn.transaction_type.trim().to_string(),
+        txn.quantity.trim().to_string(),
+        txn.price.trim().to_string(),
+        txn.fees.trim().to_string(),
+        txn.split_ratio.trim().to_string(),
     ];
+    if !txn.lot_id.trim().is_empty() {
+        row.push(txn.lot_id.trim().to_string());
+    }
+    row
+}
 
-    for (filename, currency) in files {
-        let paths = vec![
-            resource_dir.join("data").join(filename),
-            std::path::PathBuf::from(format!("imported_data/{}", filename)),
-            std::path::PathBuf::from(format!("../imported_data/{}", filename)),
-            std::path::PathBuf::from(format!("data/{}", filename)), // legacy path for compatibility
-            std::path::PathBuf::from(format!("../data/{}", filename)), // legacy path for compatibility
-        ];
+/// Appends `rows` to a CSV file, creating it with `header` first, under the file's
+/// `FileLockRegistry` lock.
+fn append_csv_rows(
+    app_handle: &tauri::AppHandle,
+    path: &Path,
+    header: &str,
+    rows: &[Vec<String>],
+) -> Result<(), String> {
+    with_file_lock(app_handle, path, || {
+        ensure_file_with_header(path, header)?;
+        let existing = read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(path)
+            .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        if !existing.is_empty() && !existing.ends_with('\n') {
+            file.write_all(b"\n")
+                .map_err(|e| format!("Failed to append to {}: {}", path.display(), e))?;
+        }
 
-        for path in paths {
-            if let Ok(mut txns) = read_csv_file(path.to_str().unwrap_or(""), currency) {
-                all_transactions.append(&mut txns);
-                break;
+        let mut writer = csv::WriterBuilder::new()
+            .has_headers(false)
+            .from_writer(file);
+        for row in rows {
+            writer
+                .write_record(row)
+                .map_err(|e| format!("Failed to append to {}: {}", path.display(), e))?;
+        }
+        writer
+            .flush()
+            .map_err(|e| format!("Failed to append to {}: {}", path.display(), e))
+    })
+}
+
+#[tauri::command]
+fn read_csv(app_handle: tauri::AppHandle) -> Result<Vec<Transaction>, AppError> {
+    Ok(load_all_transactions(&app_handle)?.to_vec())
+}
+
+/// Pre-serialized form of `read_csv`, kept for one release for older frontends.
+#[tauri::command]
+fn read_csv_json(app_handle: tauri::AppHandle) -> Result<String, AppError> {
+    let all_transactions = load_all_transactions(&app_handle)?;
+    Ok(serde_json::to_string(all_transactions.as_slice())
+        .map_err(|e| format!("Failed to serialize transactions: {}", e))?)
+}
+
+/// Reads the first existing candidate of each transaction file. A candidate that
+/// exists but cannot be read or parsed is an error rather than skipped.
+fn read_transaction_files(app_handle: &tauri::AppHandle) -> Result<Vec<Transaction>, AppError> {
+    let mut all_transactions = Vec::new();
+
+    for (filename, currency) in TRANSACTION_FILES {
+        for path in transaction_file_candidates(app_handle, filename)? {
+            match read_csv_file(path.to_str().unwrap_or(""), currency) {
+                Ok(mut txns) => {
+                    all_transactions.append(&mut txns);
+                    break;
+                }
+                Err(AppError::NotFound { .. }) => continue,
+                Err(err) => return Err(err),
             }
         }
     }
 
     all_transactions.sort_by(|a, b| a.date.cmp(&b.date));
+    Ok(all_transactions)
+}
+
+type TransactionFingerprint = Vec<(Option<std::time::SystemTime>, u64)>;
+
+/// Parsed transactions, plus per-symbol processed variants, reused while the mtime and
+/// size of every transaction file candidate are unchanged, so edits made outside the
+/// app are picked up on the next read. Writers inside the app invalidate explicitly
+/// (appends in `append_transaction_rows_to`, rewrites in `replace_file`), since an
+/// mtime can miss same-second edits.
+#[derive(Default)]
+struct TransactionCache {
+    all: Mutex<Option<(TransactionFingerprint, Arc<Vec<Transaction>>)>>,
+    processed: Mutex<HashMap<String, Vec<ProcessedTransaction>>>,
+}
+
+impl TransactionCache {
+    fn invalidate(&self) {
+        *self.all.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        self.processed
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+}
+
+fn transaction_fingerprint(
+    app_handle: &tauri::AppHandle,
+) -> Result<TransactionFingerprint, String> {
+    let mut fingerprint = Vec::new();
+    for (filename, _) in TRANSACTION_FILES {
+        for path in transaction_file_candidates(app_handle, filename)? {
+            fingerprint.push(file_fingerprint(&path));
+        }
+    }
+    Ok(fingerprint)
+}
+
+/// Every transaction, shared from `TransactionCache` rather than copied per caller.
+fn load_all_transactions(app_handle: &tauri::AppHandle) -> Result<Arc<Vec<Transaction>>, AppError> {
+    let Some(cache) = app_handle.try_state::<TransactionCache>() else {
+        return Ok(Arc::new(read_transaction_files(app_handle)?));
+    };
+
+    let fingerprint = transaction_fingerprint(app_handle)?;
+    let mut all = cache.all.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((cached_fingerprint, transactions)) = all.as_ref() {
+        if *cached_fingerprint == fingerprint {
+            return Ok(Arc::clone(transactions));
+        }
+    }
+
+    let transactions = Arc::new(read_transaction_files(app_handle)?);
+    *all = Some((fingerprint, Arc::clone(&transactions)));
+    cache
+        .processed
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clear();
+    Ok(transactions)
+}
+
+fn invalidate_transaction_cache(app_handle: &tauri::AppHandle) {
+    if let Some(cache) = app_handle.try_state::<TransactionCache>() {
+        cache.invalidate();
+    }
+    mark_data_dirty(app_handle, DataKind::Transactions);
+}
+
+/// Appends rows to the transaction file for `currency` and drops cached transactions.
+fn append_transaction_rows(
+    app_handle: &tauri::AppHandle,
+    currency: &str,
+    rows: &[Vec<String>],
+) -> Result<(), String> {
+    let transaction_file = resolve_transaction_file(app_handle, currency)?;
+    append_transaction_rows_to(app_handle, &transaction_file, rows)
+}
 
-    // Serialize to JSON for frontend
-    serde_json::to_string(&all_transactions)
-        .map_err(|e| format!("Failed to serialize transactions: {}", e))
+fn append_transaction_rows_to(
+    app_handle: &tauri::AppHandle,
+    transaction_file: &Path,
+    rows: &[Vec<String>],
+) -> Result<(), String> {
+    let result = append_csv_rows(app_handle, transaction_file, TRANSACTION_FILE_HEADER, rows);
+    invalidate_transaction_cache(app_handle);
+    invalidate_coverage_cache(app_handle);
+    result
 }
 
 fn ensure_dir(path: &Path) -> Result<(), String> {
@@ -593,39 +1342,97 @@ fn ensure_dir(path: &Path) -> Result<(), String> {
     Ok(())
 }
 
+const KNOWN_EXCHANGES: [&str; 22] = [
+    "NASDAQ",
+    "NYSE",
+    "NYSEARCA",
+    "NYSEAMERICAN",
+    "OTCMKTS",
+    "TWSE",
+    "TPE",
+    "TPEX",
+    "JPX",
+    "TYO",
+    "HKEX",
+    "LSE",
+    "ASX",
+    "TSX",
+    "FRA",
+    "PAR",
+    "AMS",
+    "STO",
+    "KRX",
+    "KSE",
+    "KOSDAQ",
+    "WSE",
+];
+
+/// Currencies `currency_for_exchange` can produce.
+const KNOWN_CURRENCIES: [&str; 11] = [
+    "USD", "TWD", "JPY", "HKD", "GBP", "AUD", "CAD", "EUR", "SEK", "KRW", "PLN",
+];
+
 fn get_exchange_and_symbol(stock: &str) -> (Option<String>, String) {
     if !stock.contains(':') {
+        // Yahoo-style `0700.HK`; US share classes such as `BRK.B` have no known suffix.
+        if let Some((base, suffix)) = stock.rsplit_once('.') {
+            if let Some((_, exchange)) = YAHOO_SUFFIX_EXCHANGES
+                .iter()
+                .find(|(s, _)| s.eq_ignore_ascii_case(suffix))
+                .filter(|_| !base.is_empty())
+            {
+                return (Some(exchange.to_string()), base.to_string());
+            }
+        }
         return (None, stock.to_string());
     }
 
     let mut parts = stock.splitn(2, ':');
     let first = parts.next().unwrap_or("").to_string();
     let second = parts.next().unwrap_or("").to_string();
-    let known = [
-        "NASDAQ",
-        "NYSE",
-        "NYSEARCA",
-        "NYSEAMERICAN",
-        "OTCMKTS",
-        "TWSE",
-        "JPX",
-        "HKEX",
-    ];
 
-    if known.iter().any(|ex| ex == &first) {
+    if KNOWN_EXCHANGES.iter().any(|ex| ex == &first) {
         return (Some(first), second);
     }
-    if known.iter().any(|ex| ex == &second) {
+    if KNOWN_EXCHANGES.iter().any(|ex| ex == &second) {
         return (Some(second), first);
     }
 
     (None, stock.to_string())
 }
 
+/// Canonical `EXCHANGE:TICKER` form of a symbol, so `0700.HK`, `0700:HKEX` and
+/// `HKEX:0700` all key the same stock. Symbols with no recognised exchange are
+/// only trimmed.
+fn canonical_symbol(stock: &str) -> String {
+    match get_exchange_and_symbol(stock.trim()) {
+        (Some(exchange), symbol) => format!("{}:{}", exchange, symbol),
+        (None, symbol) => symbol,
+    }
+}
+
+/// Trading currency of an exchange, for when transaction data does not say.
+fn currency_for_exchange(exchange: Option<&str>) -> &'static str {
+    match exchange {
+        Some("TWSE") | Some("TPE") | Some("TPEX") => "TWD",
+        Some("JPX") | Some("TYO") => "JPY",
+        Some("HKEX") => "HKD",
+        Some("LSE") => "GBP",
+        Some("ASX") => "AUD",
+        Some("TSX") => "CAD",
+        Some("FRA") | Some("PAR") | Some("AMS") => "EUR",
+        Some("STO") => "SEK",
+        Some("KRX") | Some("KSE") | Some("KOSDAQ") => "KRW",
+        Some("WSE") => "PLN",
+        _ => "USD",
+    }
+}
+
 fn yahoo_symbol_for(exchange: Option<&str>, base_symbol: &str) -> String {
     match exchange {
         Some("HKEX") => format!("{}.HK", base_symbol),
         Some("TWSE") | Some("TPE") => format!("{}.TW", base_symbol),
+        Some("TPEX") => format!("{}.TWO", base_symbol),
         Some("JPX") | Some("TYO") => format!("{}.T", base_symbol),
         Some("LSE") => format!("{}.L", base_symbol),
         Some("ASX") => format!("{}.AX", base_symbol),
@@ -636,102 +1443,629 @@ fn yahoo_symbol_for(exchange: Option<&str>, base_symbol: &str) -> String {
         Some("STO") => format!("{}.ST", base_symbol),
         Some("KRX") | Some("KSE") => format!("{}.KS", base_symbol),
         Some("KOSDAQ") => format!("{}.KQ", base_symbol),
+        Some("WSE") => format!("{}.WA", base_symbol),
         Some("NYSE") | Some("NASDAQ") | Some("NYSEARCA") | Some("NYSEAMERICAN")
         | Some("OTCMKTS") => base_symbol.replace('.', "-"),
         _ => base_symbol.replace('.', "-"),
     }
 }
 
-fn fetch_yahoo_chunk(
-    yahoo_symbol: &str,
-    canonical_symbol: &str,
-    start: NaiveDate,
-    end: NaiveDate,
-) -> Result<
-    (
-        Vec<PriceRecordEntry>,
-        Vec<(NaiveDate, f64)>,
-        Option<serde_json::Value>,
-    ),
-    String,
-> {
-    let mut url = url::Url::parse(&format!(
-        "https://query1.finance.yahoo.com/v8/finance/chart/{}",
-        yahoo_symbol
-    ))
-    .map_err(|e| format!("Failed to build Yahoo URL: {}", e))?;
-
-    url.query_pairs_mut()
-        .append_pair(
-            "period1",
-            &start
-                .and_hms_opt(0, 0, 0)
-                .unwrap()
-                .and_utc()
-                .timestamp()
-                .to_string(),
-        )
-        .append_pair(
-            "period2",
-            &end.and_hms_opt(23, 59, 59)
-                .unwrap()
-                .and_utc()
-                .timestamp()
-                .max(start.and_hms_opt(0, 0, 1).unwrap().and_utc().timestamp())
-                .to_string(),
-        )
-        .append_pair("interval", "1d")
-        .append_pair("events", "div,splits")
-        .append_pair("includeAdjustedClose", "true");
+/// Stooq symbol for an exchange listing, e.g. `AAPL.US` or `CDR.PL`. Stooq only
+/// lists a handful of markets; `None` for the rest, so the fallback skips Stooq
+/// instead of querying a bare symbol that could match another market's listing.
+fn stooq_symbol_for(exchange: Option<&str>, base_symbol: &str) -> Option<String> {
+    let suffix = match exchange {
+        None | Some("NYSE") | Some("NASDAQ") | Some("NYSEARCA") | Some("NYSEAMERICAN")
+        | Some("OTCMKTS") => "US",
+        Some("WSE") => "PL",
+        Some("LSE") => "UK",
+        Some("FRA") => "DE",
+        Some("JPX") | Some("TYO") => "JP",
+        Some("HKEX") => "HK",
+        _ => return None,
+    };
+    Some(format!("{}.{}", base_symbol.replace('.', "-"), suffix))
+}
 
-    println!(
-        "[RUST] Fetching Yahoo data for {} from {} to {}",
-        yahoo_symbol, start, end
-    );
-    println!("[RUST] URL: {}", url.as_str());
+/// Yahoo suffixes and the exchange they map back to, for symbols arriving in Yahoo
+/// form. Aliases (TPE, TYO, KSE) resolve to their canonical exchange; Taipei
+/// Exchange listings (`.TWO`) keep their own exchange so they fetch as `.TWO`.
+const YAHOO_SUFFIX_EXCHANGES: [(&str, &str); 14] = [
+    ("HK", "HKEX"),
+    ("TW", "TWSE"),
+    ("TWO", "TPEX"),
+    ("T", "JPX"),
+    ("L", "LSE"),
+    ("AX", "ASX"),
+    ("TO", "TSX"),
+    ("F", "FRA"),
+    ("PA", "PAR"),
+    ("AS", "AMS"),
+    ("ST", "STO"),
+    ("KS", "KRX"),
+    ("KQ", "KOSDAQ"),
+    ("WA", "WSE"),
+];
+
+/// Yahoo symbol -> app symbol for `symbols` and every override, so imports reuse
+/// the exact symbols the exporter produced.
+fn yahoo_reverse_map(app_handle: &tauri::AppHandle, symbols: &[String]) -> HashMap<String, String> {
+    let mut reverse: HashMap<String, String> = symbols
+        .iter()
+        .map(|symbol| (resolve_yahoo_symbol(app_handle, symbol).0, symbol.clone()))
+        .collect();
+    for o in load_symbol_overrides(app_handle).unwrap_or_default() {
+        reverse.insert(o.yahoo_symbol, o.app_symbol);
+    }
+    reverse
+}
 
-    let client = reqwest::blocking::Client::new();
-    let response = client
-        .get(url)
-        .header("User-Agent", "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36")
-        .send()
-        .map_err(|e| format!("Yahoo request failed: {}", e))?;
+/// App symbol for a Yahoo symbol: a known mapping first, then the suffix table.
+/// Unsuffixed symbols are treated as US tickers (`BRK-B` -> `BRK.B`).
+fn app_symbol_for_yahoo(yahoo_symbol: &str, known: &HashMap<String, String>) -> String {
+    if let Some(symbol) = known.get(yahoo_symbol) {
+        return symbol.clone();
+    }
+    if let Some((base, suffix)) = yahoo_symbol.rsplit_once('.') {
+        if let Some((_, exchange)) = YAHOO_SUFFIX_EXCHANGES
+            .iter()
+            .find(|(s, _)| s.eq_ignore_ascii_case(suffix))
+        {
+            return format!("{}:{}", exchange, base);
+        }
+    }
+    yahoo_symbol.replace('-', ".")
+}
 
-    // Rate limiting: sleep for 100ms after each API call
-    std::thread::sleep(Duration::from_millis(100));
+const SYMBOL_OVERRIDES_HEADER: &str = "app_symbol,yahoo_symbol\n";
 
-    let status = response.status();
-    println!("[RUST] Yahoo response status: {}", status);
+#[derive(Serialize, Clone)]
+struct SymbolOverride {
+    app_symbol: String,
+    yahoo_symbol: String,
+}
 
-    let text = response
-        .text()
-        .map_err(|e| format!("Failed to read Yahoo response: {}", e))?;
+/// Hand-maintained Yahoo symbols from data/symbol_overrides.csv, for tickers the
+/// suffix heuristics get wrong. Read on every lookup so edits apply to the next
+/// download without a restart.
+fn load_symbol_overrides(app_handle: &tauri::AppHandle) -> Result<Vec<SymbolOverride>, String> {
+    let path = get_data_dir(app_handle)?.join("symbol_overrides.csv");
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .comment(Some(b'#'))
+        .trim(csv::Trim::All)
+        .from_path(&path)
+        .map_err(|e| format!("Failed to read symbol_overrides.csv: {}", e))?;
 
-    if text.is_empty() {
-        eprintln!("[RUST] ✗ Empty response from Yahoo for {}", yahoo_symbol);
-        return Err("Empty response from Yahoo Finance".to_string());
+    let mut overrides: Vec<SymbolOverride> = Vec::new();
+    for record in reader.records().flatten() {
+        let (app_symbol, yahoo_symbol) = (record.get(0).unwrap_or(""), record.get(1).unwrap_or(""));
+        if app_symbol.is_empty() || yahoo_symbol.is_empty() {
+            continue;
+        }
+        // Later rows win so appending a correction is enough.
+        overrides.retain(|o| o.app_symbol != app_symbol);
+        overrides.push(SymbolOverride {
+            app_symbol: app_symbol.to_string(),
+            yahoo_symbol: yahoo_symbol.to_string(),
+        });
     }
+    Ok(overrides)
+}
 
-    if text.len() < 100 {
-        eprintln!("[RUST] ⚠ Short response ({}bytes): {}", text.len(), &text);
-    } else {
-        println!("[RUST] Received {} bytes of data", text.len());
+/// Yahoo symbol for an app symbol, preferring symbol_overrides.csv over
+/// `yahoo_symbol_for`'s suffix rules.
+fn resolve_yahoo_symbol(app_handle: &tauri::AppHandle, symbol: &str) -> (String, bool) {
+    let overridden = load_symbol_overrides(app_handle)
+        .unwrap_or_default()
+        .into_iter()
+        .find(|o| o.app_symbol == symbol);
+    match overridden {
+        Some(o) => (o.yahoo_symbol, true),
+        None => {
+            let (exchange, base_symbol) = get_exchange_and_symbol(symbol);
+            (yahoo_symbol_for(exchange.as_deref(), &base_symbol), false)
+        }
     }
+}
 
-    let parsed: YahooChartResponse = serde_json::from_str(&text).map_err(|e| {
-        eprintln!("[RUST] ✗ JSON parse error: {}", e);
-        eprintln!(
-            "[RUST] First 500 chars of response: {}",
-            &text.chars().take(500).collect::<String>()
-        );
-        format!("Invalid Yahoo JSON: {}", e)
+#[derive(Serialize)]
+struct ParsedSymbol {
+    exchange: Option<String>,
+    base_symbol: String,
+    yahoo_symbol: String,
+    currency_hint: String,
+    overridden: bool,
+}
+
+/// The backend's reading of an app symbol, so the frontend does not need its own copy
+/// of the exchange and Yahoo suffix rules.
+#[tauri::command]
+fn parse_symbol(app_handle: tauri::AppHandle, raw: String) -> Result<ParsedSymbol, AppError> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return Err(AppError::validation("raw", "Symbol is required"));
+    }
+    let (exchange, base_symbol) = get_exchange_and_symbol(raw);
+    let (yahoo_symbol, overridden) = resolve_yahoo_symbol(&app_handle, raw);
+    Ok(ParsedSymbol {
+        currency_hint: currency_for_exchange(exchange.as_deref()).to_string(),
+        exchange,
+        base_symbol,
+        yahoo_symbol,
+        overridden,
+    })
+}
+
+#[tauri::command]
+fn list_symbol_overrides(app_handle: tauri::AppHandle) -> Result<Vec<SymbolOverride>, AppError> {
+    Ok(load_symbol_overrides(&app_handle)?)
+}
+
+/// Sets the Yahoo symbol used for `app_symbol`; an empty `yahoo_symbol` removes the
+/// override. Comment lines in the file are preserved.
+#[tauri::command]
+fn set_symbol_override(
+    app_handle: tauri::AppHandle,
+    app_symbol: String,
+    yahoo_symbol: String,
+) -> Result<Vec<SymbolOverride>, AppError> {
+    let app_symbol = app_symbol.trim().to_string();
+    let yahoo_symbol = yahoo_symbol.trim().to_string();
+    if app_symbol.is_empty() {
+        return Err(AppError::validation("app_symbol", "Symbol is required"));
+    }
+    if app_symbol.contains(',') || yahoo_symbol.contains(',') {
+        return Err(AppError::validation(
+            "yahoo_symbol",
+            "Symbols cannot contain commas",
+        ));
+    }
+
+    let path = get_data_dir(&app_handle)?.join("symbol_overrides.csv");
+    with_file_lock(&app_handle, &path, || -> Result<(), AppError> {
+        let existing = if path.exists() {
+            read_to_string(&path)?
+        } else {
+            SYMBOL_OVERRIDES_HEADER.to_string()
+        };
+        let mut lines: Vec<String> = existing
+            .lines()
+            .filter(|line| {
+                line.trim_start().starts_with('#')
+                    || line.split(',').next().map(str::trim) != Some(app_symbol.as_str())
+            })
+            .map(str::to_string)
+            .collect();
+        if !yahoo_symbol.is_empty() {
+            lines.push(format!("{},{}", app_symbol, yahoo_symbol));
+        }
+        let mut content = lines.join("\n");
+        content.push('\n');
+        atomic_write(&app_handle, &path, content.as_bytes())?;
+        Ok(())
+    })?;
+
+    write_worker_log(
+        &app_handle,
+        &if yahoo_symbol.is_empty() {
+            format!("Removed Yahoo symbol override for {}", app_symbol)
+        } else {
+            format!("Yahoo symbol for {} set to {}", app_symbol, yahoo_symbol)
+        },
+    )?;
+    Ok(load_symbol_overrides(&app_handle)?)
+}
+
+const SYMBOL_CONFIG_HEADER: &str = "symbol,fetch_start_date,fetch_end_date,data_source_override\n";
+/// Values accepted in `data_source_override`; `manual` turns fetching off.
+const DATA_SOURCE_OVERRIDES: [&str; 4] = ["yahoo", "stooq", "tiingo", MANUAL_SOURCE];
+
+/// Per-symbol fetch settings from data/symbol_config.csv. Empty fields fall back to
+/// the defaults: the transaction-derived start, today, and Yahoo with fallbacks.
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct SymbolConfig {
+    fetch_start_date: Option<String>,
+    fetch_end_date: Option<String>,
+    data_source_override: Option<String>,
+}
+
+/// The symbol_config.csv row for `symbol`, or an empty config. Read on every
+/// lookup like symbol_overrides.csv; later rows win.
+fn load_symbol_config(app_handle: &tauri::AppHandle, symbol: &str) -> Result<SymbolConfig, String> {
+    let path = get_data_dir(app_handle)?.join("symbol_config.csv");
+    if !path.exists() {
+        return Ok(SymbolConfig::default());
+    }
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .comment(Some(b'#'))
+        .trim(csv::Trim::All)
+        .from_path(&path)
+        .map_err(|e| format!("Failed to read symbol_config.csv: {}", e))?;
+
+    let symbol = canonical_symbol(symbol);
+    let field = |record: &csv::StringRecord, idx: usize| {
+        record
+            .get(idx)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+    let mut config = SymbolConfig::default();
+    for record in reader.records().flatten() {
+        if canonical_symbol(record.get(0).unwrap_or("")) == symbol {
+            config = SymbolConfig {
+                fetch_start_date: field(&record, 1),
+                fetch_end_date: field(&record, 2),
+                data_source_override: field(&record, 3),
+            };
+        }
+    }
+    Ok(config)
+}
+
+#[tauri::command]
+fn get_symbol_config(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+) -> Result<SymbolConfig, AppError> {
+    Ok(load_symbol_config(&app_handle, symbol.trim())?)
+}
+
+/// Replaces the symbol_config.csv row for `symbol`; a config with every field empty
+/// removes it. Comment lines in the file are preserved.
+#[tauri::command]
+fn set_symbol_config(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+    config: SymbolConfig,
+) -> Result<SymbolConfig, AppError> {
+    let symbol = canonical_symbol(symbol.trim());
+    if symbol.is_empty() {
+        return Err(AppError::validation("symbol", "Symbol is required"));
+    }
+    if symbol.contains(',') {
+        return Err(AppError::validation(
+            "symbol",
+            "Symbols cannot contain commas",
+        ));
+    }
+    let clean = |value: Option<String>| {
+        value
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    let start = clean(config.fetch_start_date)
+        .map(|d| parse_manual_date("fetch_start_date", &d))
+        .transpose()?;
+    let end = clean(config.fetch_end_date)
+        .map(|d| parse_manual_date("fetch_end_date", &d))
+        .transpose()?;
+    if let (Some(start), Some(end)) = (start, end) {
+        if start > end {
+            return Err(AppError::validation(
+                "fetch_start_date",
+                "Start date must not be after the end date",
+            ));
+        }
+    }
+    let source = clean(config.data_source_override).map(|s| s.to_lowercase());
+    if let Some(source) = source.as_deref() {
+        if !DATA_SOURCE_OVERRIDES.contains(&source) {
+            return Err(AppError::validation(
+                "data_source_override",
+                format!(
+                    "'{}' is not one of {}",
+                    source,
+                    DATA_SOURCE_OVERRIDES.join(", ")
+                ),
+            ));
+        }
+    }
+
+    let format_date = |d: Option<NaiveDate>| d.map(|d| d.format("%Y-%m-%d").to_string());
+    let config = SymbolConfig {
+        fetch_start_date: format_date(start),
+        fetch_end_date: format_date(end),
+        data_source_override: source,
+    };
+    let cleared = config.fetch_start_date.is_none()
+        && config.fetch_end_date.is_none()
+        && config.data_source_override.is_none();
+
+    let path = get_data_dir(&app_handle)?.join("symbol_config.csv");
+    with_file_lock(&app_handle, &path, || -> Result<(), AppError> {
+        let existing = if path.exists() {
+            read_to_string(&path)?
+        } else {
+            SYMBOL_CONFIG_HEADER.to_string()
+        };
+        let mut lines: Vec<String> = existing
+            .lines()
+            .filter(|line| {
+                line.trim_start().starts_with('#')
+                    || line
+                        .split(',')
+                        .next()
+                        .map(|s| canonical_symbol(s.trim()))
+                        .as_deref()
+                        != Some(symbol.as_str())
+            })
+            .map(str::to_string)
+            .collect();
+        if !cleared {
+            lines.push(format!(
+                "{},{},{},{}",
+                symbol,
+                config.fetch_start_date.as_deref().unwrap_or(""),
+                config.fetch_end_date.as_deref().unwrap_or(""),
+                config.data_source_override.as_deref().unwrap_or("")
+            ));
+        }
+        let mut content = lines.join("\n");
+        content.push('\n');
+        atomic_write(&app_handle, &path, content.as_bytes())?;
+        Ok(())
     })?;
 
+    write_worker_log(
+        &app_handle,
+        &if cleared {
+            format!("Removed fetch config for {}", symbol)
+        } else {
+            format!("Updated fetch config for {}", symbol)
+        },
+    )?;
+    Ok(config)
+}
+
+type YahooChunk = (
+    Vec<PriceRecordEntry>,
+    Vec<(NaiveDate, f64)>,
+    Option<serde_json::Value>,
+);
+
+/// Prices and dividends from a fallback source, which carries no chart meta.
+type HistoryChunk = (Vec<PriceRecordEntry>, Vec<(NaiveDate, f64)>);
+
+/// Source of Yahoo chart responses. Sync code resolves it through
+/// [`data_fetcher`] so offline mode can swap in canned fixtures.
+trait DataFetcher: Send + Sync {
+    fn fetch_chart(
+        &self,
+        yahoo_symbol: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+        interval: &str,
+    ) -> Result<YahooChartResponse, AppError>;
+}
+
+struct YahooFetcher;
+
+impl DataFetcher for YahooFetcher {
+    fn fetch_chart(
+        &self,
+        yahoo_symbol: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+        interval: &str,
+    ) -> Result<YahooChartResponse, AppError> {
+        let mut url = url::Url::parse(&format!(
+            "https://query1.finance.yahoo.com/v8/finance/chart/{}",
+            yahoo_symbol
+        ))
+        .map_err(|e| format!("Failed to build Yahoo URL: {}", e))?;
+
+        url.query_pairs_mut()
+            .append_pair(
+                "period1",
+                &start
+                    .and_hms_opt(0, 0, 0)
+                    .unwrap()
+                    .and_utc()
+                    .timestamp()
+                    .to_string(),
+            )
+            .append_pair(
+                "period2",
+                &end.and_hms_opt(23, 59, 59)
+                    .unwrap()
+                    .and_utc()
+                    .timestamp()
+                    .max(start.and_hms_opt(0, 0, 1).unwrap().and_utc().timestamp())
+                    .to_string(),
+            )
+            .append_pair("interval", interval)
+            .append_pair("events", "div,splits")
+            .append_pair("includeAdjustedClose", "true");
+
+        let client = reqwest::blocking::Client::new();
+        let response = client
+            .get(url)
+            .header("User-Agent", "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36")
+            .send()
+            .map_err(|e| AppError::Network {
+                status: None,
+                message: format!("Yahoo request failed: {}", e),
+            })?;
+
+        // Rate limiting: sleep for 100ms after each API call
+        std::thread::sleep(Duration::from_millis(100));
+
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Err(AppError::not_found(
+                yahoo_symbol,
+                format!("Yahoo Finance has no data for {}", yahoo_symbol),
+            ));
+        }
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(AppError::RateLimited {
+                retry_after: response
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.trim().parse().ok()),
+                message: "Yahoo Finance rate limit reached".to_string(),
+            });
+        }
+
+        let text = response
+            .text()
+            .map_err(|e| format!("Failed to read Yahoo response: {}", e))?;
+
+        if text.is_empty() {
+            return Err(AppError::from_status(
+                status,
+                "Empty response from Yahoo Finance",
+            ));
+        }
+
+        parse_yahoo_chart(yahoo_symbol, &text)
+    }
+}
+
+/// Serves `<dir>/<yahoo_symbol>.json` as the chart response, whatever range is
+/// asked for; `fetch_yahoo_chunk` trims it to the requested dates.
+struct FixtureFetcher {
+    dir: PathBuf,
+}
+
+impl DataFetcher for FixtureFetcher {
+    fn fetch_chart(
+        &self,
+        yahoo_symbol: &str,
+        _start: NaiveDate,
+        _end: NaiveDate,
+        _interval: &str,
+    ) -> Result<YahooChartResponse, AppError> {
+        let path = self.dir.join(format!(
+            "{}.json",
+            yahoo_symbol.replace(['/', '\\', ':'], "_")
+        ));
+        if !path.exists() {
+            return Err(AppError::not_found(
+                yahoo_symbol,
+                format!(
+                    "No offline fixture for {} in {}",
+                    yahoo_symbol,
+                    self.dir.display()
+                ),
+            ));
+        }
+        parse_yahoo_chart(yahoo_symbol, &read_to_string(&path)?)
+    }
+}
+
+/// Parses a chart response. The error quotes the start of the body, which is
+/// usually an HTML or plain-text error page, so the sync run log shows what Yahoo
+/// actually sent.
+fn parse_yahoo_chart(yahoo_symbol: &str, text: &str) -> Result<YahooChartResponse, AppError> {
+    serde_json::from_str(text).map_err(|e| AppError::Parse {
+        file: format!("Yahoo chart response for {}", yahoo_symbol),
+        line: None,
+        message: format!(
+            "Invalid Yahoo JSON: {} (response starts: {:?})",
+            e,
+            text.chars().take(200).collect::<String>()
+        ),
+    })
+}
+
+/// Canned chart responses used in offline mode, one `<yahoo_symbol>.json` each.
+const OFFLINE_FIXTURE_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/yahoo");
+
+/// The fetcher chart downloads go through, chosen from `offline_mode` in
+/// app_settings.csv on first use and replaced by `set_offline_mode`.
+#[derive(Default)]
+struct ChartFetcher {
+    inner: Mutex<Option<Arc<dyn DataFetcher>>>,
+}
+
+fn offline_fetcher() -> Arc<dyn DataFetcher> {
+    Arc::new(FixtureFetcher {
+        dir: PathBuf::from(OFFLINE_FIXTURE_DIR),
+    })
+}
+
+fn offline_mode_enabled() -> bool {
+    data_root()
+        .and_then(|root| read_key_value_file(&root.join(APP_SETTINGS_FILE), "offline_mode"))
+        .ok()
+        .flatten()
+        .is_some_and(|value| value.trim() == "true")
+}
+
+fn data_fetcher(app_handle: &tauri::AppHandle) -> Arc<dyn DataFetcher> {
+    let Some(state) = app_handle.try_state::<ChartFetcher>() else {
+        return Arc::new(YahooFetcher);
+    };
+    let mut inner = state.inner.lock().unwrap_or_else(|e| e.into_inner());
+    inner
+        .get_or_insert_with(|| {
+            if offline_mode_enabled() {
+                offline_fetcher()
+            } else {
+                Arc::new(YahooFetcher)
+            }
+        })
+        .clone()
+}
+
+/// Switches chart downloads between Yahoo and the bundled fixtures. The choice
+/// is stored in app_settings.csv so it survives restarts and portfolio switches.
+#[tauri::command]
+fn set_offline_mode(app_handle: tauri::AppHandle, enabled: bool) -> Result<bool, AppError> {
+    write_key_value_file(
+        &app_handle,
+        &data_root()?.join(APP_SETTINGS_FILE),
+        "offline_mode",
+        if enabled { "true" } else { "false" },
+    )?;
+    let fetcher: Arc<dyn DataFetcher> = if enabled {
+        offline_fetcher()
+    } else {
+        Arc::new(YahooFetcher)
+    };
+    *app_handle
+        .state::<ChartFetcher>()
+        .inner
+        .lock()
+        .unwrap_or_else(|e| e.into_inner()) = Some(fetcher);
+    write_worker_log(
+        &app_handle,
+        if enabled {
+            "Offline mode enabled; chart data served from fixtures"
+        } else {
+            "Offline mode disabled"
+        },
+    )?;
+    Ok(enabled)
+}
+
+#[tauri::command]
+fn get_offline_mode() -> bool {
+    offline_mode_enabled()
+}
+
+fn fetch_yahoo_chunk(
+    fetcher: &dyn DataFetcher,
+    yahoo_symbol: &str,
+    canonical_symbol: &str,
+    start: NaiveDate,
+    end: NaiveDate,
+    interval: &str,
+) -> Result<YahooChunk, AppError> {
+    let parsed = fetcher.fetch_chart(yahoo_symbol, start, end, interval)?;
+
     let result = parsed
         .chart
         .and_then(|c| c.result)
         .and_then(|mut r| r.pop())
-        .ok_or_else(|| "Yahoo response missing result".to_string())?;
+        .ok_or_else(|| AppError::not_found(yahoo_symbol, "Yahoo response missing result"))?;
 
     let timestamps = result.timestamp.unwrap_or_default();
 
@@ -783,10 +2117,7 @@ fn fetch_yahoo_chunk(
                 // Calculate split_unadjusted_close by reverse-applying splits
                 // Yahoo's close is already split-adjusted backward
                 // We need to multiply by split ratios for all splits AFTER this date
-                let split_unadjusted = splits_data
-                    .iter()
-                    .filter(|(split_date, _)| *split_date > date)
-                    .fold(*close, |price, (_, ratio)| price * ratio);
+                let split_unadjusted = *close * split_unadjust_factor(date, &splits_data);
 
                 records.push(PriceRecordEntry {
                     symbol: canonical_symbol.to_string(),
@@ -813,16 +2144,14 @@ fn fetch_yahoo_chunk(
             let mut dividend_list: Vec<(NaiveDate, f64)> = divs
                 .values()
                 .filter_map(|div| {
-                    DateTime::from_timestamp(div.date, 0)
-                        .map(|dt| {
-                            let date = dt.date_naive();
-                            if date >= start && date <= end {
-                                Some((date, div.amount))
-                            } else {
-                                None
-                            }
-                        })
-                        .flatten()
+                    DateTime::from_timestamp(div.date, 0).and_then(|dt| {
+                        let date = dt.date_naive();
+                        if date >= start && date <= end {
+                            Some((date, div.amount))
+                        } else {
+                            None
+                        }
+                    })
                 })
                 .collect();
             dividend_list.sort_by_key(|d| std::cmp::Reverse(d.0)); // newest first
@@ -835,96 +2164,634 @@ fn fetch_yahoo_chunk(
     Ok((records, dividends, meta))
 }
 
-fn ensure_history_for_symbol(
-    app_handle: &tauri::AppHandle,
-    records_map: &mut HashMap<String, Vec<PriceRecordEntry>>,
-    symbol: &str,
-    earliest_date: NaiveDate,
-) -> Result<(), String> {
-    let today = Utc::now().date_naive();
-    let (exchange, base_symbol) = get_exchange_and_symbol(symbol);
+/// Daily bars from Stooq's CSV download for `stooq_symbol` between `start` and `end`.
+/// Stooq has no adjusted close and no split or dividend events, so those fields are
+/// left empty; Stooq answers an unknown symbol with `No data` rather than an error.
+fn fetch_stooq_chunk(
+    stooq_symbol: &str,
+    canonical_symbol: &str,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<PriceRecordEntry>, String> {
+    let mut url = url::Url::parse("https://stooq.com/q/d/l/")
+        .map_err(|e| format!("Failed to build Stooq URL: {}", e))?;
+    url.query_pairs_mut()
+        .append_pair("s", &stooq_symbol.to_lowercase())
+        .append_pair("d1", &start.format("%Y%m%d").to_string())
+        .append_pair("d2", &end.format("%Y%m%d").to_string())
+        .append_pair("i", "d");
 
-    let existing_min_date = records_map
-        .get(symbol)
-        .and_then(|records| records.iter().map(|r| r.date).min());
-    if let Some(min_date) = existing_min_date {
-        if min_date <= earliest_date {
-            return Ok(());
-        }
+    let response = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))?
+        .get(url)
+        .send()
+        .map_err(|e| format!("Stooq request failed: {}", e))?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("Stooq returned {} for {}", status, stooq_symbol));
     }
+    let text = response
+        .text()
+        .map_err(|e| format!("Failed to read Stooq response: {}", e))?;
 
-    let mut all_dividends: Vec<(NaiveDate, f64)> = Vec::new();
-
-    // Fetch all data in one request instead of chunking
-    let yahoo_symbol = yahoo_symbol_for(exchange.as_deref(), &base_symbol);
-    let (new_records, dividends, meta) =
-        fetch_yahoo_chunk(&yahoo_symbol, symbol, earliest_date, today)?;
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(text.as_bytes());
+    let headers = reader
+        .headers()
+        .map_err(|e| format!("Invalid Stooq response: {}", e))?
+        .clone();
+    let column = |name: &str| headers.iter().position(|h| h.eq_ignore_ascii_case(name));
+    let (Some(date_idx), Some(close_idx)) = (column("Date"), column("Close")) else {
+        return Ok(Vec::new());
+    };
+    let value = |record: &csv::StringRecord, name: &str| {
+        column(name)
+            .and_then(|idx| record.get(idx))
+            .and_then(parse_f64_str)
+    };
 
-    if let Some(meta_json) = meta {
-        let metas_dir = get_yahoo_metas_dir(app_handle)?;
-        let safe_symbol = symbol.replace(':', "_");
-        let file_path = metas_dir.join(format!("{}.json", safe_symbol));
-        let json_content = serde_json::to_string_pretty(&meta_json)
-            .map_err(|e| format!("Failed to serialize meta JSON: {}", e))?;
-        write(&file_path, json_content)
-            .map_err(|e| format!("Failed to write meta file for '{}': {}", symbol, e))?;
+    let mut records = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| format!("Invalid Stooq row: {}", e))?;
+        let Some(date) = record
+            .get(date_idx)
+            .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+        else {
+            continue;
+        };
+        let Some(close) = record.get(close_idx).and_then(parse_f64_str) else {
+            continue;
+        };
+        if date < start || date > end {
+            continue;
+        }
+        records.push(PriceRecordEntry {
+            symbol: canonical_symbol.to_string(),
+            date,
+            close,
+            open: value(&record, "Open"),
+            high: value(&record, "High"),
+            low: value(&record, "Low"),
+            volume: value(&record, "Volume"),
+            adjusted_close: None,
+            split_unadjusted_close: None,
+            source: "stooq".into(),
+        });
     }
+    Ok(records)
+}
 
-    if !new_records.is_empty() {
-        let entries = records_map.entry(symbol.to_string()).or_default();
-        for record in new_records {
-            if let Some(existing) = entries.iter_mut().find(|r| r.date == record.date) {
-                *existing = record.clone();
-            } else {
-                entries.push(record.clone());
-            }
-        }
+/// Daily prices from Tiingo for a US `ticker`, with the dividends paid in the range.
+/// Tiingo quotes raw closes, so `close` is split-adjusted here from Tiingo's own
+/// split factors to match Yahoo, while the raw close becomes
+/// `split_unadjusted_close` and Tiingo's `adjClose` becomes `adjusted_close`.
+fn fetch_tiingo_chunk(
+    api_token: &str,
+    ticker: &str,
+    canonical_symbol: &str,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<HistoryChunk, String> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct TiingoBar {
+        date: String,
+        close: Option<f64>,
+        open: Option<f64>,
+        high: Option<f64>,
+        low: Option<f64>,
+        volume: Option<f64>,
+        adj_close: Option<f64>,
+        div_cash: Option<f64>,
+        split_factor: Option<f64>,
+    }
 
-        // Accumulate dividends
-        all_dividends.extend(dividends);
+    let mut url = url::Url::parse(&format!(
+        "https://api.tiingo.com/tiingo/daily/{}/prices",
+        ticker
+    ))
+    .map_err(|e| format!("Failed to build Tiingo URL: {}", e))?;
+    url.query_pairs_mut()
+        .append_pair("startDate", &start.format("%Y-%m-%d").to_string())
+        .append_pair("endDate", &end.format("%Y-%m-%d").to_string())
+        .append_pair("token", api_token);
 
-        // Sort entries
-        entries.sort_by(|a, b| b.date.cmp(&a.date));
+    let response = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))?
+        .get(url)
+        .send()
+        .map_err(|e| format!("Tiingo request failed: {}", e))?;
+    let status = response.status();
+    let text = response
+        .text()
+        .map_err(|e| format!("Failed to read Tiingo response: {}", e))?;
+    if !status.is_success() {
+        return Err(format!(
+            "Tiingo returned {} for {}: {}",
+            status, ticker, text
+        ));
     }
+    let bars: Vec<TiingoBar> =
+        serde_json::from_str(&text).map_err(|e| format!("Invalid Tiingo JSON: {}", e))?;
+
+    let dated: Vec<(NaiveDate, TiingoBar)> = bars
+        .into_iter()
+        .filter_map(|bar| {
+            let date = NaiveDate::parse_from_str(bar.date.get(..10)?, "%Y-%m-%d").ok()?;
+            Some((date, bar))
+        })
+        .collect();
+    let splits: Vec<(NaiveDate, f64)> = dated
+        .iter()
+        .filter_map(|(date, bar)| {
+            bar.split_factor
+                .filter(|f| *f > 0.0 && (f - 1.0).abs() > 1e-9)
+                .map(|f| (*date, f))
+        })
+        .collect();
 
-    // Save dividend data if any
-    if !all_dividends.is_empty() {
-        all_dividends.sort_by_key(|d| std::cmp::Reverse(d.0)); // newest first
-        all_dividends.dedup_by_key(|d| d.0); // remove duplicates
+    let mut records = Vec::new();
+    let mut dividends = Vec::new();
+    for (date, bar) in dated {
+        if let Some(amount) = bar.div_cash.filter(|a| *a > 0.0) {
+            dividends.push((date, amount));
+        }
+        let Some(raw_close) = bar.close else {
+            continue;
+        };
+        let factor = split_unadjust_factor(date, &splits);
+        records.push(PriceRecordEntry {
+            symbol: canonical_symbol.to_string(),
+            date,
+            close: raw_close / factor,
+            open: bar.open.map(|v| v / factor),
+            high: bar.high.map(|v| v / factor),
+            low: bar.low.map(|v| v / factor),
+            volume: bar.volume.map(|v| (v * factor).round()),
+            adjusted_close: bar.adj_close,
+            split_unadjusted_close: Some(raw_close),
+            source: "tiingo".into(),
+        });
+    }
+    Ok((records, dividends))
+}
 
-        let mut dividend_csv = String::from(DIVIDEND_FILE_HEADER);
-        dividend_csv.push('\n');
-        let updated_at = Utc::now().to_rfc3339();
+/// Secondary sources tried, in order, when Yahoo has no history.
+const FALLBACK_SOURCES: [&str; 2] = ["stooq", "tiingo"];
 
-        for (date, amount) in all_dividends {
-            // Get currency from symbol or default to USD
-            let currency = if symbol.contains(':') {
-                // Extract currency based on exchange, or default to USD
-                "USD" // TODO: improve currency detection
-            } else {
-                "USD"
-            };
-            dividend_csv.push_str(&format!(
-                "{},{},{},{}\n",
-                date.format("%Y-%m-%d"),
-                amount,
-                currency,
-                updated_at
-            ));
+/// History from the secondary `sources` in order: Stooq (Yahoo covers some
+/// markets, Warsaw in particular, poorly), then Tiingo for US listings when a
+/// `tiingo_api_token` is set. `None` when none returns rows.
+fn fallback_history(
+    app_handle: &tauri::AppHandle,
+    run_id: &str,
+    symbol: &str,
+    start: NaiveDate,
+    end: NaiveDate,
+    sources: &[&str],
+) -> Result<Option<HistoryChunk>, String> {
+    let (exchange, base_symbol) = get_exchange_and_symbol(symbol);
+
+    let stooq_symbol =
+        stooq_symbol_for(exchange.as_deref(), &base_symbol).filter(|_| sources.contains(&"stooq"));
+    let stooq = match &stooq_symbol {
+        Some(stooq_symbol) => fetch_stooq_chunk(stooq_symbol, symbol, start, end),
+        None => Ok(Vec::new()),
+    };
+    match stooq {
+        Ok(mut records) if !records.is_empty() => {
+            let splits = load_split_events(app_handle, symbol).unwrap_or_default();
+            for record in records.iter_mut() {
+                record.split_unadjusted_close =
+                    Some(record.close * split_unadjust_factor(record.date, &splits));
+            }
+            write_run_log(
+                app_handle,
+                run_id,
+                LogLevel::Info,
+                &format!(
+                    "Loaded {} rows for {} from Stooq ({})",
+                    records.len(),
+                    symbol,
+                    stooq_symbol.as_deref().unwrap_or_default()
+                ),
+            )?;
+            return Ok(Some((records, Vec::new())));
         }
+        Ok(_) => {}
+        Err(err) => write_run_log(
+            app_handle,
+            run_id,
+            LogLevel::Warn,
+            &format!("Stooq fallback failed: {}", err),
+        )?,
+    }
 
-        // Write dividend file
-        let dividends_dir = get_dividends_dir(app_handle)?;
-        let safe_symbol = symbol.replace(':', "_");
-        let file_path = dividends_dir.join(format!("{}.csv", safe_symbol));
-        write(&file_path, dividend_csv)
-            .map_err(|e| format!("Failed to write dividend file for '{}': {}", symbol, e))?;
+    let is_us = currency_for_exchange(exchange.as_deref()) == "USD";
+    let token = read_setting_value_internal(app_handle, "tiingo_api_token")?
+        .map(|token| token.trim().to_string())
+        .filter(|token| !token.is_empty());
+    if let Some(token) = token.filter(|_| is_us && sources.contains(&"tiingo")) {
+        let ticker = base_symbol.replace('.', "-");
+        match fetch_tiingo_chunk(&token, &ticker, symbol, start, end) {
+            Ok((records, dividends)) if !records.is_empty() => {
+                write_run_log(
+                    app_handle,
+                    run_id,
+                    LogLevel::Info,
+                    &format!("Loaded {} rows for {} from Tiingo", records.len(), symbol),
+                )?;
+                return Ok(Some((records, dividends)));
+            }
+            Ok(_) => {}
+            Err(err) => write_run_log(
+                app_handle,
+                run_id,
+                LogLevel::Warn,
+                &format!("Tiingo fallback failed: {}", err),
+            )?,
+        }
     }
+    Ok(None)
+}
 
-    Ok(())
+/// Default for the `max_price_rows_per_symbol` setting.
+const DEFAULT_MAX_PRICE_ROWS_PER_SYMBOL: usize = 10_000;
+/// Days before a symbol's first transaction that row truncation always keeps.
+const PRICE_ROWS_KEEP_BEFORE_DAYS: i64 = 30;
+
+fn max_price_rows_per_symbol(app_handle: &tauri::AppHandle) -> usize {
+    read_setting_value_internal(app_handle, "max_price_rows_per_symbol")
+        .ok()
+        .flatten()
+        .and_then(|value| value.trim().parse::<usize>().ok())
+        .filter(|limit| *limit > 0)
+        .unwrap_or(DEFAULT_MAX_PRICE_ROWS_PER_SYMBOL)
+}
+
+/// Drops the oldest of the newest-first `records` beyond `limit`, but never rows
+/// dated `keep_from` or later. Returns how many rows were dropped.
+fn truncate_price_rows(
+    records: &mut Vec<PriceRecordEntry>,
+    limit: usize,
+    keep_from: NaiveDate,
+) -> usize {
+    let protected = records.iter().take_while(|r| r.date >= keep_from).count();
+    let keep = limit.max(protected);
+    let dropped = records.len().saturating_sub(keep);
+    records.truncate(keep);
+    dropped
+}
+
+fn ensure_history_for_symbol(
+    app_handle: &tauri::AppHandle,
+    run_id: &str,
+    records_map: &mut HashMap<String, Vec<PriceRecordEntry>>,
+    symbol: &str,
+    earliest_date: NaiveDate,
+) -> Result<bool, AppError> {
+    let (exchange, _) = get_exchange_and_symbol(symbol);
+    let config = load_symbol_config(app_handle, symbol)?;
+    let config_date = |value: &Option<String>| {
+        value
+            .as_deref()
+            .and_then(|d| NaiveDate::parse_from_str(d.trim(), "%Y-%m-%d").ok())
+    };
+    let source_override = config
+        .data_source_override
+        .as_deref()
+        .map(|s| s.trim().to_lowercase())
+        .filter(|s| !s.is_empty() && s != "yahoo");
+    if source_override.as_deref() == Some(MANUAL_SOURCE) {
+        return Ok(false);
+    }
+    let today = Utc::now().date_naive();
+    let fetch_end = config_date(&config.fetch_end_date).map_or(today, |end| end.min(today));
+    let configured_type = security_type(app_handle, symbol);
+    let profile = configured_type
+        .unwrap_or(SecurityType::Stock)
+        .fetch_profile();
+    // Rows from a month before the first trade onward are never truncated away.
+    let keep_from = earliest_date - ChronoDuration::days(PRICE_ROWS_KEEP_BEFORE_DAYS);
+    let earliest_date = config_date(&config.fetch_start_date)
+        .or_else(|| history_start_override(app_handle, symbol))
+        .unwrap_or(earliest_date);
+
+    let existing_min_date = records_map
+        .get(symbol)
+        .and_then(|records| records.iter().map(|r| r.date).min());
+    if let Some(min_date) = existing_min_date {
+        if min_date <= earliest_date {
+            return Ok(false);
+        }
+    }
+
+    let mut all_dividends: Vec<(NaiveDate, f64)> = Vec::new();
+
+    let (mut new_records, mut dividends, meta) = if let Some(source) = source_override {
+        // symbol_config.csv pins this symbol to one secondary source.
+        match fallback_history(
+            app_handle,
+            run_id,
+            symbol,
+            earliest_date,
+            fetch_end,
+            &[source.as_str()],
+        )? {
+            Some((records, dividends)) => (records, dividends, None),
+            None => {
+                return Err(AppError::not_found(
+                    symbol,
+                    format!("No {} history for {}", source, symbol),
+                ))
+            }
+        }
+    } else {
+        // Fetch all data in one request instead of chunking
+        let (yahoo_symbol, _) = resolve_yahoo_symbol(app_handle, symbol);
+        let fetcher = data_fetcher(app_handle);
+        let yahoo = fetch_yahoo_chunk(
+            fetcher.as_ref(),
+            &yahoo_symbol,
+            symbol,
+            earliest_date,
+            fetch_end,
+            profile.interval,
+        );
+        match yahoo {
+            Ok(chunk) if !chunk.0.is_empty() => chunk,
+            // Offline mode never leaves the fixtures.
+            yahoo if !offline_mode_enabled() => {
+                match fallback_history(
+                    app_handle,
+                    run_id,
+                    symbol,
+                    earliest_date,
+                    fetch_end,
+                    &FALLBACK_SOURCES,
+                )? {
+                    Some((records, dividends)) => (records, dividends, None),
+                    None => yahoo?,
+                }
+            }
+            yahoo => yahoo?,
+        }
+    };
+    profile.apply(&mut new_records, &mut dividends);
+
+    if let Some(mut meta_json) = meta {
+        let instrument_type = meta_json.get("instrumentType").and_then(|v| v.as_str());
+        if let (Some(configured), Some(instrument_type)) = (configured_type, instrument_type) {
+            if !configured
+                .yahoo_instrument_types()
+                .contains(&instrument_type)
+            {
+                write_run_log(
+                    app_handle,
+                    run_id,
+                    LogLevel::Warn,
+                    &format!(
+                        "Yahoo lists {} as {} but securities.csv has type {}",
+                        symbol,
+                        instrument_type,
+                        configured.label()
+                    ),
+                )?;
+            }
+        }
+        let metas_dir = get_yahoo_metas_dir(app_handle)?;
+        let safe_symbol = symbol_file_stem(symbol);
+        let file_path = metas_dir.join(format!("{}.json", safe_symbol));
+        let previous = load_yahoo_meta(app_handle, symbol);
+        if let Some((date, reason)) = detect_delisting(previous.as_ref(), &meta_json, today) {
+            record_detected_delisting(app_handle, symbol, date, &reason)?;
+        }
+        // Chart metas lack fundamentals; keep any fetched by refresh_symbol_fundamentals.
+        if let (Some(previous), Some(object)) = (previous, meta_json.as_object_mut()) {
+            for key in FUNDAMENTAL_META_KEYS {
+                if let Some(value) = previous.get(key) {
+                    object.entry(key).or_insert_with(|| value.clone());
+                }
+            }
+        }
+        let json_content = serde_json::to_string_pretty(&meta_json)
+            .map_err(|e| format!("Failed to serialize meta JSON: {}", e))?;
+        write(&file_path, json_content)
+            .map_err(|e| format!("Failed to write meta file for '{}': {}", symbol, e))?;
+    }
+
+    let mut changed = false;
+    if !new_records.is_empty() {
+        let entries = records_map.entry(symbol.to_string()).or_default();
+        let mut merged = merge_price_entries(symbol, entries, &new_records)?;
+        merged.sort_by_key(|r| std::cmp::Reverse(r.date));
+        let dropped = truncate_price_rows(
+            &mut merged,
+            max_price_rows_per_symbol(app_handle),
+            keep_from.min(earliest_date),
+        );
+        if dropped > 0 {
+            write_run_log(
+                app_handle,
+                run_id,
+                LogLevel::Warn,
+                &format!(
+                    "Dropped {} oldest price rows for {} to stay within max_price_rows_per_symbol",
+                    dropped, symbol
+                ),
+            )?;
+        }
+        entries.sort_by_key(|r| std::cmp::Reverse(r.date));
+        changed = merged != *entries;
+        *entries = merged;
+
+        // Accumulate dividends
+        all_dividends.extend(dividends);
+    }
+
+    // Save dividend data if any, keeping manually entered rows over Yahoo's
+    if !all_dividends.is_empty() {
+        all_dividends.sort_by_key(|d| std::cmp::Reverse(d.0)); // newest first
+        all_dividends.dedup_by_key(|d| d.0); // remove duplicates
+
+        let file_path = dividend_file_path(app_handle, symbol)?;
+        with_file_lock(app_handle, &file_path, || -> Result<(), String> {
+            let mut rows: Vec<Vec<String>> = read_dividend_rows(&file_path)?
+                .into_iter()
+                .filter(|row| row[4] == MANUAL_SOURCE)
+                .collect();
+            let updated_at = Utc::now().to_rfc3339();
+            let currency = currency_for_exchange(exchange.as_deref());
+            for (date, amount) in all_dividends {
+                let date = date.format("%Y-%m-%d").to_string();
+                if rows.iter().any(|row| row[0] == date) {
+                    continue;
+                }
+                rows.push(vec![
+                    date,
+                    amount.to_string(),
+                    currency.to_string(),
+                    updated_at.clone(),
+                    "yahoo_finance".to_string(),
+                ]);
+            }
+            write_dividend_rows(app_handle, &file_path, rows)
+                .map_err(|e| format!("Failed to write dividend file for '{}': {}", symbol, e))?;
+            Ok(())
+        })?;
+    }
+
+    Ok(changed)
+}
+
+const PRICE_VALUE_COLUMNS: [&str; 7] = [
+    "close",
+    "open",
+    "high",
+    "low",
+    "volume",
+    "adjusted_close",
+    "split_unadjusted_close",
+];
+
+/// Price entries as a frame keyed by `day` (days since the common era).
+fn price_entries_frame(entries: &[PriceRecordEntry]) -> Result<DataFrame, String> {
+    let values = |pick: fn(&PriceRecordEntry) -> Option<f64>| -> Vec<Option<f64>> {
+        entries.iter().map(pick).collect()
+    };
+    DataFrame::new(vec![
+        Series::new(
+            "day",
+            entries
+                .iter()
+                .map(|r| r.date.num_days_from_ce())
+                .collect::<Vec<i32>>(),
+        ),
+        Series::new("close", values(|r| Some(r.close))),
+        Series::new("open", values(|r| r.open)),
+        Series::new("high", values(|r| r.high)),
+        Series::new("low", values(|r| r.low)),
+        Series::new("volume", values(|r| r.volume)),
+        Series::new("adjusted_close", values(|r| r.adjusted_close)),
+        Series::new(
+            "split_unadjusted_close",
+            values(|r| r.split_unadjusted_close),
+        ),
+        Series::new(
+            "source",
+            entries
+                .iter()
+                .map(|r| r.source.as_str())
+                .collect::<Vec<&str>>(),
+        ),
+    ])
+    .map_err(|e| format!("Failed to build price frame: {}", e))
+}
+
+/// Merges downloaded rows into existing ones with an outer join on date. For each
+/// column the downloaded value wins when present, except that manual rows are kept
+/// as they are.
+fn merge_price_entries(
+    symbol: &str,
+    existing: &[PriceRecordEntry],
+    incoming: &[PriceRecordEntry],
+) -> Result<Vec<PriceRecordEntry>, String> {
+    let existing_manual = col("source").eq(lit(MANUAL_SOURCE));
+    let pick = |name: &str| {
+        let new_name = format!("{}_new", name);
+        when(existing_manual.clone())
+            .then(col(name))
+            .otherwise(
+                when(col(&new_name).is_not_null())
+                    .then(col(&new_name))
+                    .otherwise(col(name)),
+            )
+            .alias(name)
+    };
+    let mut columns = vec![col("day")];
+    columns.extend(PRICE_VALUE_COLUMNS.iter().map(|name| pick(name)));
+    columns.push(pick("source"));
+
+    let merged = price_entries_frame(existing)?
+        .lazy()
+        .join(
+            price_entries_frame(incoming)?.lazy(),
+            [col("day")],
+            [col("day")],
+            JoinArgs {
+                how: JoinType::Outer { coalesce: true },
+                suffix: Some("_new".to_string()),
+                ..Default::default()
+            },
+        )
+        .select(columns)
+        .sort(["day"], SortMultipleOptions::default())
+        .collect()
+        .map_err(|e| format!("Failed to merge prices for {}: {}", symbol, e))?;
+    price_entries_from_frame(symbol, &merged)
+}
+
+/// Rows of a frame in the `price_entries_frame` layout, in frame order. Rows
+/// without a day or close are skipped.
+fn price_entries_from_frame(
+    symbol: &str,
+    frame: &DataFrame,
+) -> Result<Vec<PriceRecordEntry>, String> {
+    let column = |name: &str| {
+        frame
+            .column(name)
+            .map_err(|e| format!("Prices for {} missing {}: {}", symbol, name, e))
+    };
+    let days = column("day")?
+        .i32()
+        .map_err(|e| format!("Invalid day column for {}: {}", symbol, e))?
+        .clone();
+    let mut values = Vec::with_capacity(PRICE_VALUE_COLUMNS.len());
+    for name in PRICE_VALUE_COLUMNS {
+        values.push(
+            column(name)?
+                .f64()
+                .map_err(|e| format!("Invalid {} column for {}: {}", name, symbol, e))?
+                .clone(),
+        );
+    }
+    let sources = column("source")?
+        .str()
+        .map_err(|e| format!("Invalid source column for {}: {}", symbol, e))?
+        .clone();
+
+    let mut entries = Vec::with_capacity(frame.height());
+    for idx in 0..frame.height() {
+        let Some(date) = days.get(idx).and_then(NaiveDate::from_num_days_from_ce_opt) else {
+            continue;
+        };
+        let value = |col: usize| values[col].get(idx);
+        let Some(close) = value(0) else {
+            continue;
+        };
+        entries.push(PriceRecordEntry {
+            symbol: symbol.to_string(),
+            date,
+            close,
+            open: value(1),
+            high: value(2),
+            low: value(3),
+            volume: value(4),
+            adjusted_close: value(5),
+            split_unadjusted_close: value(6),
+            source: sources.get(idx).unwrap_or_default().to_string(),
+        });
+    }
+    Ok(entries)
 }
 
-fn get_data_dir(_app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+fn data_root() -> Result<PathBuf, String> {
     // Always use the repo's src-tauri/data directory (relative to the Cargo manifest).
     // This keeps a single authoritative location for price/FX/split files.
     static DATA_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/data");
@@ -933,6 +2800,338 @@ fn get_data_dir(_app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
     Ok(path)
 }
 
+/// The active portfolio's directory, `data/portfolios/<id>`. Every data helper
+/// resolves through here, so each portfolio has its own prices, settings,
+/// securities and transaction files.
+fn get_data_dir(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let id = active_portfolio_id(app_handle)?;
+    let path = data_root()?.join("portfolios").join(id);
+    ensure_dir(&path)?;
+    Ok(path)
+}
+
+const PORTFOLIOS_HEADER: &str = "id,name,created_at\n";
+const DEFAULT_PORTFOLIO_ID: &str = "default";
+/// Root-level key/value file for settings that span portfolios.
+const APP_SETTINGS_FILE: &str = "app_settings.csv";
+
+/// The selected portfolio id, read from `active_portfolio` in app_settings.csv once
+/// and then kept here until `switch_portfolio` changes it.
+#[derive(Default)]
+struct ActivePortfolio {
+    id: Mutex<Option<String>>,
+}
+
+fn active_portfolio_id(app_handle: &tauri::AppHandle) -> Result<String, String> {
+    let state = app_handle.try_state::<ActivePortfolio>();
+    if let Some(id) = state
+        .as_ref()
+        .and_then(|s| s.id.lock().unwrap_or_else(|e| e.into_inner()).clone())
+    {
+        return Ok(id);
+    }
+
+    let root = data_root()?;
+    migrate_flat_data_layout(app_handle, &root)?;
+    let id = read_key_value_file(&root.join(APP_SETTINGS_FILE), "active_portfolio")?
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty() && root.join("portfolios").join(id).is_dir())
+        .unwrap_or_else(|| DEFAULT_PORTFOLIO_ID.to_string());
+    if let Some(state) = state {
+        *state.id.lock().unwrap_or_else(|e| e.into_inner()) = Some(id.clone());
+    }
+    Ok(id)
+}
+
+/// Where `portfolios/` is assembled during the flat layout migration. It is renamed
+/// to `portfolios/` only once everything has moved, so an interrupted migration is
+/// resumed on the next start instead of leaving files behind in the root.
+const MIGRATING_PORTFOLIOS_DIR: &str = ".portfolios.migrating";
+
+/// Moves a pre-portfolio flat data directory into `portfolios/default` the first
+/// time it is seen and registers that portfolio.
+fn migrate_flat_data_layout(app_handle: &tauri::AppHandle, root: &Path) -> Result<(), String> {
+    let portfolios_dir = root.join("portfolios");
+    if portfolios_dir.exists() {
+        return Ok(());
+    }
+    let moved = stage_flat_data_layout(root)?;
+
+    let registry = root.join("portfolios.csv");
+    let registered = read_to_string(&registry).is_ok_and(|content| {
+        content
+            .lines()
+            .any(|line| line.split(',').next() == Some(DEFAULT_PORTFOLIO_ID))
+    });
+    if !registered {
+        append_csv_rows(
+            app_handle,
+            &registry,
+            PORTFOLIOS_HEADER,
+            &[vec![
+                DEFAULT_PORTFOLIO_ID.to_string(),
+                "Default".to_string(),
+                Utc::now().to_rfc3339(),
+            ]],
+        )?;
+    }
+    write_key_value_file(
+        app_handle,
+        &root.join(APP_SETTINGS_FILE),
+        "active_portfolio",
+        DEFAULT_PORTFOLIO_ID,
+    )?;
+    std::fs::rename(root.join(MIGRATING_PORTFOLIOS_DIR), &portfolios_dir)
+        .map_err(|e| format!("Failed to finish moving data into portfolios: {}", e))?;
+    write_worker_log(
+        app_handle,
+        &format!("Moved {} data entries into the default portfolio", moved),
+    )
+}
+
+/// Moves every flat-layout entry in `root` into the default portfolio under
+/// `MIGRATING_PORTFOLIOS_DIR`, creating it or continuing an earlier attempt.
+/// Returns how many entries were moved by this call.
+fn stage_flat_data_layout(root: &Path) -> Result<usize, String> {
+    let default_dir = root
+        .join(MIGRATING_PORTFOLIOS_DIR)
+        .join(DEFAULT_PORTFOLIO_ID);
+    ensure_dir(&default_dir)?;
+
+    let mut moved = 0;
+    for entry in std::fs::read_dir(root)
+        .map_err(|e| format!("Failed to read {}: {}", root.display(), e))?
+        .flatten()
+    {
+        let name = entry.file_name();
+        if name == "portfolios"
+            || name == MIGRATING_PORTFOLIOS_DIR
+            || name == "portfolios.csv"
+            || name == APP_SETTINGS_FILE
+        {
+            continue;
+        }
+        std::fs::rename(entry.path(), default_dir.join(&name)).map_err(|e| {
+            format!(
+                "Failed to move {:?} into the default portfolio: {}",
+                name, e
+            )
+        })?;
+        moved += 1;
+    }
+    Ok(moved)
+}
+
+#[derive(Serialize)]
+struct PortfolioInfo {
+    id: String,
+    name: String,
+    created_at: String,
+    active: bool,
+}
+
+fn load_portfolios(app_handle: &tauri::AppHandle) -> Result<Vec<PortfolioInfo>, String> {
+    let active = active_portfolio_id(app_handle)?;
+    let path = data_root()?.join("portfolios.csv");
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_path(&path)
+        .map_err(|e| format!("Failed to read portfolios.csv: {}", e))?;
+    Ok(reader
+        .records()
+        .flatten()
+        .filter(|record| !record.get(0).unwrap_or("").is_empty())
+        .map(|record| {
+            let id = record.get(0).unwrap_or("").to_string();
+            PortfolioInfo {
+                active: id == active,
+                name: record.get(1).unwrap_or(&id).to_string(),
+                created_at: record.get(2).unwrap_or("").to_string(),
+                id,
+            }
+        })
+        .collect())
+}
+
+/// Rewrites portfolios.csv through `atomic_write`; callers that read the registry
+/// first hold its lock across both steps.
+fn write_portfolios(
+    app_handle: &tauri::AppHandle,
+    portfolios: &[PortfolioInfo],
+) -> Result<(), String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record(PORTFOLIOS_HEADER.trim_end().split(','))
+        .map_err(|e| format!("Failed to write portfolios.csv header: {}", e))?;
+    for p in portfolios {
+        writer
+            .write_record([&p.id, &p.name, &p.created_at])
+            .map_err(|e| format!("Failed to write portfolios.csv row: {}", e))?;
+    }
+    let content = writer
+        .into_inner()
+        .map_err(|e| format!("Failed to build portfolios.csv: {}", e))?;
+    atomic_write(app_handle, &data_root()?.join("portfolios.csv"), &content)
+        .map_err(|e| format!("Failed to write portfolios.csv: {}", e))
+}
+
+#[tauri::command]
+fn list_portfolios(app_handle: tauri::AppHandle) -> Result<Vec<PortfolioInfo>, AppError> {
+    Ok(load_portfolios(&app_handle)?)
+}
+
+/// Registers a new, empty portfolio. The id is derived from `name` and made unique.
+#[tauri::command]
+fn create_portfolio(app_handle: tauri::AppHandle, name: String) -> Result<PortfolioInfo, AppError> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::validation("name", "Portfolio name is required"));
+    }
+    let slug: String = name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect::<String>()
+        .split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let slug = if slug.is_empty() {
+        "portfolio".to_string()
+    } else {
+        slug
+    };
+
+    let existing = load_portfolios(&app_handle)?;
+    let portfolios_dir = data_root()?.join("portfolios");
+    let taken = |id: &str| existing.iter().any(|p| p.id == id) || portfolios_dir.join(id).exists();
+    let id = (1..)
+        .map(|n| {
+            if n == 1 {
+                slug.clone()
+            } else {
+                format!("{}-{}", slug, n)
+            }
+        })
+        .find(|id| !taken(id))
+        .unwrap_or(slug);
+
+    let dir = portfolios_dir.join(&id);
+    ensure_dir(&dir)?;
+    ensure_file_with_header(&dir.join("settings.csv"), SETTINGS_HEADER)?;
+    ensure_file_with_header(&dir.join("securities.csv"), SECURITIES_HEADER)?;
+    let created_at = Utc::now().to_rfc3339();
+    append_csv_rows(
+        &app_handle,
+        &data_root()?.join("portfolios.csv"),
+        PORTFOLIOS_HEADER,
+        &[vec![id.clone(), name.clone(), created_at.clone()]],
+    )?;
+    write_worker_log(&app_handle, &format!("Created portfolio {} ({})", id, name))?;
+
+    Ok(PortfolioInfo {
+        id,
+        name,
+        created_at,
+        active: false,
+    })
+}
+
+/// Makes `id` the active portfolio and drops every cache built from the previous one.
+/// Refused while a history sync is running.
+#[tauri::command]
+fn switch_portfolio(app_handle: tauri::AppHandle, id: String) -> Result<PortfolioInfo, AppError> {
+    let id = id.trim().to_string();
+    let portfolio = load_portfolios(&app_handle)?
+        .into_iter()
+        .find(|p| p.id == id)
+        .ok_or_else(|| AppError::not_found("portfolio", format!("No portfolio {}", id)))?;
+    let sync_active = app_handle.try_state::<PortfolioState>().is_some_and(|s| {
+        s.inner
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .sync_active
+    });
+    if sync_active {
+        return Err(AppError::validation(
+            "id",
+            "Cannot switch portfolios while a sync is running",
+        ));
+    }
+
+    write_key_value_file(
+        &app_handle,
+        &data_root()?.join(APP_SETTINGS_FILE),
+        "active_portfolio",
+        &id,
+    )?;
+    if let Some(state) = app_handle.try_state::<ActivePortfolio>() {
+        *state.id.lock().unwrap_or_else(|e| e.into_inner()) = Some(id.clone());
+    }
+    initialize_storage(&app_handle)?;
+    if let Some(store) = app_handle.try_state::<PriceStore>() {
+        store.clear();
+    }
+    invalidate_coverage_cache(&app_handle);
+    invalidate_transaction_cache(&app_handle);
+    update_portfolio_state(&app_handle, |_| true);
+    write_worker_log(&app_handle, &format!("Switched to portfolio {}", id))?;
+
+    Ok(PortfolioInfo {
+        active: true,
+        ..portfolio
+    })
+}
+
+/// Removes a portfolio other than the active one. Its directory is copied into the
+/// backups folder first; the backup path is returned.
+#[tauri::command]
+fn delete_portfolio(app_handle: tauri::AppHandle, id: String) -> Result<String, AppError> {
+    let id = id.trim().to_string();
+    let portfolios = load_portfolios(&app_handle)?;
+    let Some(index) = portfolios.iter().position(|p| p.id == id) else {
+        return Err(AppError::not_found(
+            "portfolio",
+            format!("No portfolio {}", id),
+        ));
+    };
+    if portfolios[index].active {
+        return Err(AppError::validation(
+            "id",
+            "Switch to another portfolio before deleting this one",
+        ));
+    }
+
+    let dir = data_root()?.join("portfolios").join(&id);
+    let backup_dir = get_backups_dir(&app_handle)?.join(format!(
+        "portfolio_{}_{}",
+        id,
+        Utc::now().format("%Y%m%d_%H%M%S")
+    ));
+    if dir.exists() {
+        copy_dir_recursive(&dir, &backup_dir)?;
+        std::fs::remove_dir_all(&dir)?;
+    }
+    with_file_lock(&app_handle, &data_root()?.join("portfolios.csv"), || {
+        let mut portfolios = load_portfolios(&app_handle)?;
+        portfolios.retain(|p| p.id != id);
+        write_portfolios(&app_handle, &portfolios)
+    })?;
+    write_worker_log(
+        &app_handle,
+        &format!(
+            "Deleted portfolio {} (backup at {})",
+            id,
+            backup_dir.display()
+        ),
+    )?;
+    Ok(backup_dir.display().to_string())
+}
+
 fn get_yahoo_metas_dir(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
     let data_dir = get_data_dir(app_handle)?;
     let path = data_dir.join("yahoo_metas");
@@ -963,6 +3162,67 @@ fn get_logs_dir(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
     Ok(logs_dir)
 }
 
+/// File stem for a per-symbol data file. ':' becomes '_' as it always has, and every
+/// character that would make that ambiguous or is unsafe on some filesystem is
+/// percent-encoded: a literal '_', '%', slashes, the other characters Windows
+/// forbids, control characters, a trailing '.' or space, and the first letter of a
+/// Windows device name. `symbol_from_file_stem` reverses it exactly.
+fn symbol_file_stem(symbol: &str) -> String {
+    let mut stem = String::with_capacity(symbol.len());
+    let last = symbol.chars().count().saturating_sub(1);
+    for (idx, c) in symbol.chars().enumerate() {
+        let reserved = matches!(
+            c,
+            '_' | '%' | '/' | '\\' | '*' | '?' | '"' | '<' | '>' | '|'
+        ) || c.is_control()
+            || (idx == last && matches!(c, '.' | ' '));
+        match c {
+            ':' => stem.push('_'),
+            c if reserved => {
+                for byte in c.encode_utf8(&mut [0; 4]).bytes() {
+                    stem.push_str(&format!("%{:02X}", byte));
+                }
+            }
+            c => stem.push(c),
+        }
+    }
+
+    let device = stem.split('.').next().unwrap_or("").to_ascii_uppercase();
+    let is_device = matches!(device.as_str(), "CON" | "PRN" | "AUX" | "NUL")
+        || (device.len() == 4
+            && (device.starts_with("COM") || device.starts_with("LPT"))
+            && device.as_bytes()[3].is_ascii_digit());
+    if is_device {
+        let first = stem.remove(0);
+        stem.insert_str(0, &format!("%{:02X}", first as u32));
+    }
+    stem
+}
+
+/// Symbol for a file stem written by `symbol_file_stem`.
+fn symbol_from_file_stem(stem: &str) -> String {
+    let bytes = stem.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let escaped = (bytes[idx] == b'%')
+            .then(|| stem.get(idx + 1..idx + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[idx], escaped) {
+            (_, Some(byte)) => {
+                decoded.push(byte);
+                idx += 3;
+                continue;
+            }
+            (b'_', None) => decoded.push(b':'),
+            (byte, None) => decoded.push(byte),
+        }
+        idx += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
 fn get_prices_dir(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
     let data_dir = get_data_dir(app_handle)?;
     let prices_dir = data_dir.join("prices");
@@ -1018,25 +3278,165 @@ fn read_file_head(path: &Path, lines: usize) -> Result<String, String> {
     Ok(output)
 }
 
-fn write_worker_log(app_handle: &tauri::AppHandle, message: &str) -> Result<(), String> {
+/// Last `lines` lines of a file, read backwards from the end in fixed-size chunks so
+/// long histories are never loaded whole. The first (header) line is prepended so the
+/// result stays parseable CSV.
+fn read_file_tail(path: &Path, lines: usize) -> Result<String, String> {
+    use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+
+    const CHUNK_SIZE: u64 = 8 * 1024;
+
+    let mut file = File::open(path).map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+    let len = file
+        .metadata()
+        .map_err(|e| format!("Failed to read {:?}: {}", path, e))?
+        .len();
+
+    let mut position = len;
+    let mut buffer: Vec<u8> = Vec::new();
+    // One extra newline for the final line terminator, one for the line before the
+    // first wanted line.
+    while position > 0 && buffer.iter().filter(|b| **b == b'\n').count() <= lines + 1 {
+        let read_size = CHUNK_SIZE.min(position);
+        position -= read_size;
+        let mut chunk = vec![0u8; read_size as usize];
+        file.seek(SeekFrom::Start(position))
+            .and_then(|_| file.read_exact(&mut chunk))
+            .map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+        chunk.extend_from_slice(&buffer);
+        buffer = chunk;
+    }
+
+    let text = String::from_utf8_lossy(&buffer);
+    let all_lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).collect();
+    let reached_start = position == 0;
+    // The first collected line is either the header or, mid-file, possibly partial.
+    let candidates = all_lines.get(1..).unwrap_or(&[]);
+    let tail = &candidates[candidates.len().saturating_sub(lines)..];
+
+    let mut output = String::new();
+    if reached_start {
+        if let Some(header) = all_lines.first() {
+            output.push_str(header);
+            output.push('\n');
+        }
+    } else {
+        file.seek(SeekFrom::Start(0))
+            .map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+        let mut header = String::new();
+        BufReader::new(&mut file)
+            .read_line(&mut header)
+            .map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+        output.push_str(header.trim_end());
+        output.push('\n');
+    }
+    for line in tail {
+        output.push_str(line);
+        output.push('\n');
+    }
+    Ok(output)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum LogLevel {
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    fn as_str(self) -> &'static str {
+        match self {
+            LogLevel::Info => "INFO",
+            LogLevel::Warn => "WARN",
+            LogLevel::Error => "ERROR",
+        }
+    }
+}
+
+/// Short id stamped on every log line of one sync run so its lines can be grepped.
+fn new_run_id() -> String {
+    format!("{:08x}", rand::random::<u32>())
+}
+
+/// RFC 3339 timestamp with offset, in UTC or local time per the `log_timezone`
+/// setting (local when unset).
+fn log_timestamp(app_handle: &tauri::AppHandle) -> String {
+    let utc = read_setting_value_internal(app_handle, "log_timezone")
+        .ok()
+        .flatten()
+        .is_some_and(|tz| tz.trim().eq_ignore_ascii_case("utc"));
+    if utc {
+        Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+    } else {
+        chrono::Local::now().to_rfc3339_opts(SecondsFormat::Secs, false)
+    }
+}
+
+/// Appends `[timestamp] LEVEL [run] message` to the history worker log; the run id
+/// is left out for lines that don't belong to a sync run.
+fn write_log_line(
+    app_handle: &tauri::AppHandle,
+    level: LogLevel,
+    run_id: Option<&str>,
+    message: &str,
+) -> Result<(), String> {
     let logs_dir = get_logs_dir(app_handle)?;
     let log_file = logs_dir.join("history_worker.log");
-    let timestamp = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let timestamp = log_timestamp(app_handle);
     let mut file = OpenOptions::new()
         .create(true)
         .append(true)
         .open(&log_file)
         .map_err(|e| format!("Failed to open log file {:?}: {}", log_file, e))?;
-    writeln!(file, "[{}] {}", timestamp, message).map_err(|e| format!("Failed to write log: {}", e))
+    let line = match run_id {
+        Some(run_id) => format!(
+            "[{}] {} [{}] {}",
+            timestamp,
+            level.as_str(),
+            run_id,
+            message
+        ),
+        None => format!("[{}] {} {}", timestamp, level.as_str(), message),
+    };
+    writeln!(file, "{}", line).map_err(|e| format!("Failed to write log: {}", e))
 }
 
-fn initialize_storage(app_handle: &tauri::AppHandle) -> Result<(), String> {
-    let data_dir = get_data_dir(app_handle)?;
-    let _ = get_backups_dir(app_handle)?;
-    let _ = get_logs_dir(app_handle)?;
-    let _ = get_navs_dir(app_handle)?;
+fn write_worker_log(app_handle: &tauri::AppHandle, message: &str) -> Result<(), String> {
+    write_log_line(app_handle, LogLevel::Info, None, message)
+}
 
-    let required_files = vec![
+fn write_run_log(
+    app_handle: &tauri::AppHandle,
+    run_id: &str,
+    level: LogLevel,
+    message: &str,
+) -> Result<(), String> {
+    write_log_line(app_handle, level, Some(run_id), message)
+}
+
+/// Run id of a history log line. Lines written before levels and run ids existed
+/// (`[2024-01-01 09:00:00] message`) and lines outside a run have none.
+fn log_line_run_id(line: &str) -> Option<&str> {
+    let (_, rest) = line.strip_prefix('[')?.split_once("] ")?;
+    let (level, rest) = rest.split_once(' ')?;
+    if ![LogLevel::Info, LogLevel::Warn, LogLevel::Error]
+        .iter()
+        .any(|l| l.as_str() == level)
+    {
+        return None;
+    }
+    let (run_id, _) = rest.strip_prefix('[')?.split_once(']')?;
+    Some(run_id).filter(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+fn initialize_storage(app_handle: &tauri::AppHandle) -> Result<(), String> {
+    let data_dir = get_data_dir(app_handle)?;
+    let _ = get_backups_dir(app_handle)?;
+    let _ = get_logs_dir(app_handle)?;
+    let _ = get_navs_dir(app_handle)?;
+
+    let required_files = vec![
         (data_dir.join("settings.csv"), SETTINGS_HEADER),
         (data_dir.join("securities.csv"), SECURITIES_HEADER),
     ];
@@ -1045,6 +3445,26 @@ fn initialize_storage(app_handle: &tauri::AppHandle) -> Result<(), String> {
         ensure_file_with_header(&path, header)?;
     }
 
+    if let Err(err) = migrate_securities_columns(app_handle) {
+        let _ = write_worker_log(
+            app_handle,
+            &format!("securities.csv column migration failed: {}", err),
+        );
+    }
+
+    if let Err(err) = rename_legacy_symbol_files(app_handle) {
+        let _ = write_worker_log(
+            app_handle,
+            &format!("Symbol filename migration failed: {}", err),
+        );
+    }
+
+    // The frontend offers `migrate_price_files` when this fires.
+    let status = schema_status(app_handle)?;
+    if status.migration_pending {
+        let _ = app_handle.emit_all("schema_migration_pending", &status);
+    }
+
     Ok(())
 }
 
@@ -1052,15 +3472,17 @@ fn read_setting_value_internal(
     app_handle: &tauri::AppHandle,
     key: &str,
 ) -> Result<Option<String>, String> {
-    let data_dir = get_data_dir(&app_handle)?;
-    let settings_file = data_dir.join("settings.csv");
+    read_key_value_file(&get_data_dir(app_handle)?.join("settings.csv"), key)
+}
 
-    if !settings_file.exists() {
+/// Value for `key` in a `key,value` file such as settings.csv.
+fn read_key_value_file(path: &Path, key: &str) -> Result<Option<String>, String> {
+    if !path.exists() {
         return Ok(None);
     }
 
-    let content = read_to_string(&settings_file)
-        .map_err(|e| format!("Failed to read settings.csv: {}", e))?;
+    let content =
+        read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
 
     for line in content.lines().skip(1) {
         let parts: Vec<&str> = line.split(',').collect();
@@ -1072,47 +3494,188 @@ fn read_setting_value_internal(
     Ok(None)
 }
 
+/// Currency that single-currency outputs are reported in: the `base_currency`
+/// setting, else the frontend's `baseCurrency` selector, else USD. Unknown codes
+/// fall back to USD.
+fn configured_base_currency(app_handle: &tauri::AppHandle) -> String {
+    ["base_currency", "baseCurrency"]
+        .iter()
+        .find_map(|key| read_setting_value_internal(app_handle, key).ok().flatten())
+        .map(|code| code.trim().to_uppercase())
+        .filter(|code| KNOWN_CURRENCIES.contains(&code.as_str()))
+        .unwrap_or_else(|| "USD".to_string())
+}
+
+/// `code` upper-cased when it is a known currency, else a validation error on `field`.
+fn validate_currency_code(field: &str, code: &str) -> Result<String, AppError> {
+    let code = code.trim().to_uppercase();
+    if !KNOWN_CURRENCIES.contains(&code.as_str()) {
+        return Err(AppError::validation(
+            field,
+            format!("Unknown currency code '{}'", code),
+        ));
+    }
+    Ok(code)
+}
+
 #[tauri::command]
-fn get_setting(app_handle: tauri::AppHandle, key: String) -> Result<String, String> {
+fn get_setting(app_handle: tauri::AppHandle, key: String) -> Result<String, AppError> {
     Ok(read_setting_value_internal(&app_handle, &key)?.unwrap_or_default())
 }
 
 #[tauri::command]
-fn set_setting(app_handle: tauri::AppHandle, key: String, value: String) -> Result<(), String> {
-    let data_dir = get_data_dir(&app_handle)?;
-    let settings_file = data_dir.join("settings.csv");
+fn set_setting(app_handle: tauri::AppHandle, key: String, value: String) -> Result<(), AppError> {
+    let value = if key == "base_currency" {
+        validate_currency_code("base_currency", &value)?
+    } else if key == "log_timezone" {
+        let tz = value.trim().to_lowercase();
+        if tz != "utc" && tz != "local" {
+            return Err(AppError::validation(
+                "log_timezone",
+                format!("'{}' is not 'utc' or 'local'", value.trim()),
+            ));
+        }
+        tz
+    } else {
+        value
+    };
+    let settings_file = get_data_dir(&app_handle)?.join("settings.csv");
+    Ok(write_key_value_file(
+        &app_handle,
+        &settings_file,
+        &key,
+        &value,
+    )?)
+}
 
-    let mut lines = vec!["key,value".to_string()];
-    let mut found = false;
+/// Sets `key` in a `key,value` file, replacing an existing row or appending one.
+fn write_key_value_file(
+    app_handle: &tauri::AppHandle,
+    path: &Path,
+    key: &str,
+    value: &str,
+) -> Result<(), String> {
+    with_file_lock(app_handle, path, || {
+        let mut lines = vec!["key,value".to_string()];
+        let mut found = false;
 
-    if settings_file.exists() {
-        let content = read_to_string(&settings_file)
-            .map_err(|e| format!("Failed to read settings.csv: {}", e))?;
+        if path.exists() {
+            let content = read_to_string(path)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
 
-        for (i, line) in content.lines().enumerate() {
-            if i == 0 {
-                continue;
-            }
-            let parts: Vec<&str> = line.split(',').collect();
-            if parts.len() >= 1 && parts[0] == key {
-                lines.push(format!("{},{}", key, value));
-                found = true;
-            } else if !line.trim().is_empty() {
-                lines.push(line.to_string());
+            for (i, line) in content.lines().enumerate() {
+                if i == 0 {
+                    continue;
+                }
+                let parts: Vec<&str> = line.split(',').collect();
+                if !parts.is_empty() && parts[0] == key {
+                    lines.push(format!("{},{}", key, value));
+                    found = true;
+                } else if !line.trim().is_empty() {
+                    lines.push(line.to_string());
+                }
             }
         }
+
+        if !found {
+            lines.push(format!("{},{}", key, value));
+        }
+
+        write(path, lines.join("\n"))
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    })
+}
+
+fn copy_dir_recursive(from: &Path, to: &Path) -> Result<usize, String> {
+    create_dir_all(to).map_err(|e| format!("Failed to create directory {:?}: {}", to, e))?;
+    let mut copied = 0;
+    let entries =
+        std::fs::read_dir(from).map_err(|e| format!("Failed to read {:?}: {}", from, e))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name();
+        // Skip in-flight temp files from atomic_write.
+        if name.to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let target = to.join(&name);
+        if path.is_dir() {
+            copied += copy_dir_recursive(&path, &target)?;
+        } else {
+            std::fs::copy(&path, &target)
+                .map_err(|e| format!("Failed to copy {:?}: {}", path, e))?;
+            copied += 1;
+        }
+    }
+    Ok(copied)
+}
+
+/// Copies the whole data directory into `backups/backup_<timestamp>[_<label>]`.
+#[tauri::command]
+fn create_backup(app_handle: tauri::AppHandle, label: Option<String>) -> Result<String, AppError> {
+    let data_dir = get_data_dir(&app_handle)?;
+    let mut name = format!("backup_{}", Utc::now().format("%Y%m%d_%H%M%S"));
+    if let Some(label) = label.filter(|l| !l.trim().is_empty()) {
+        let safe_label: String = label
+            .trim()
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        name.push('_');
+        name.push_str(&safe_label);
+    }
+    let backup_dir = get_backups_dir(&app_handle)?.join(name);
+    let copied = copy_dir_recursive(&data_dir, &backup_dir)?;
+    write_worker_log(
+        &app_handle,
+        &format!("Backed up {} files to {}", copied, backup_dir.display()),
+    )?;
+    Ok(backup_dir.to_string_lossy().to_string())
+}
+
+/// Backs up the data directory before a sync unless disabled or one was taken
+/// within `auto_backup_min_interval_hours` (default 24).
+fn maybe_auto_backup(app_handle: &tauri::AppHandle) -> Result<Option<String>, String> {
+    let enabled = read_setting_value_internal(app_handle, "auto_backup_before_sync")?
+        .map(|v| !v.trim().eq_ignore_ascii_case("false"))
+        .unwrap_or(true);
+    if !enabled {
+        return Ok(None);
     }
 
-    if !found {
-        lines.push(format!("{},{}", key, value));
+    let min_interval_hours =
+        read_setting_value_internal(app_handle, "auto_backup_min_interval_hours")?
+            .and_then(|v| v.trim().parse::<f64>().ok())
+            .filter(|h| *h >= 0.0)
+            .unwrap_or(24.0);
+    let now = Utc::now();
+    let last_backup = read_setting_value_internal(app_handle, "last_auto_backup_at")?
+        .and_then(|v| DateTime::parse_from_rfc3339(v.trim()).ok())
+        .map(|dt| dt.with_timezone(&Utc));
+    if let Some(last) = last_backup {
+        let elapsed_hours = (now - last).num_seconds() as f64 / 3600.0;
+        if elapsed_hours < min_interval_hours {
+            return Ok(None);
+        }
     }
 
-    write(&settings_file, lines.join("\n"))
-        .map_err(|e| format!("Failed to write settings.csv: {}", e))
+    let path = create_backup(app_handle.clone(), Some("pre_sync".to_string()))?;
+    set_setting(
+        app_handle.clone(),
+        "last_auto_backup_at".to_string(),
+        now.to_rfc3339(),
+    )?;
+    Ok(Some(path))
 }
 
 #[tauri::command]
-fn read_storage_csv(app_handle: tauri::AppHandle, filename: String) -> Result<String, String> {
+fn read_storage_csv(app_handle: tauri::AppHandle, filename: String) -> Result<String, AppError> {
     let data_dir = get_data_dir(&app_handle)?;
     let file_path = data_dir.join(&filename);
 
@@ -1120,8 +3683,8 @@ fn read_storage_csv(app_handle: tauri::AppHandle, filename: String) -> Result<St
         return Ok(String::new());
     }
 
-    read_to_string(&file_path)
-        .map_err(|e| format!("Failed to read data file '{}': {}", filename, e))
+    Ok(read_to_string(&file_path)
+        .map_err(|e| format!("Failed to read data file '{}': {}", filename, e))?)
 }
 
 #[tauri::command]
@@ -1129,12 +3692,12 @@ fn write_storage_csv(
     app_handle: tauri::AppHandle,
     filename: String,
     content: String,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let data_dir = get_data_dir(&app_handle)?;
     let file_path = data_dir.join(&filename);
 
-    write(&file_path, content)
-        .map_err(|e| format!("Failed to write data file '{}': {}", filename, e))
+    Ok(write(&file_path, content)
+        .map_err(|e| format!("Failed to write data file '{}': {}", filename, e))?)
 }
 
 #[tauri::command]
@@ -1142,7 +3705,7 @@ fn append_storage_csv(
     app_handle: tauri::AppHandle,
     filename: String,
     content: String,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     use std::fs::OpenOptions;
 
     let data_dir = get_data_dir(&app_handle)?;
@@ -1154,13 +3717,14 @@ fn append_storage_csv(
         .open(&file_path)
         .map_err(|e| format!("Failed to open data file '{}': {}", filename, e))?;
 
-    file.write_all(content.as_bytes())
-        .map_err(|e| format!("Failed to append to data file '{}': {}", filename, e))
+    Ok(file
+        .write_all(content.as_bytes())
+        .map_err(|e| format!("Failed to append to data file '{}': {}", filename, e))?)
 }
 
 // Aliases for data directory operations (same as storage commands)
 #[tauri::command]
-fn read_data_csv(app_handle: tauri::AppHandle, filename: String) -> Result<String, String> {
+fn read_data_csv(app_handle: tauri::AppHandle, filename: String) -> Result<String, AppError> {
     read_storage_csv(app_handle, filename)
 }
 
@@ -1169,7 +3733,7 @@ fn write_data_csv(
     app_handle: tauri::AppHandle,
     filename: String,
     content: String,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     write_storage_csv(app_handle, filename, content)
 }
 
@@ -1178,7 +3742,7 @@ fn append_data_csv(
     app_handle: tauri::AppHandle,
     filename: String,
     content: String,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     append_storage_csv(app_handle, filename, content)
 }
 
@@ -1188,11 +3752,14 @@ fn persist_price_file_content(
     content: &str,
 ) -> Result<(), String> {
     let prices_dir = get_prices_dir(app_handle)?;
-    let safe_symbol = symbol.replace(':', "_");
+    let safe_symbol = symbol_file_stem(symbol);
     let file_path = prices_dir.join(format!("{}.csv", safe_symbol));
+    let content = order_csv_rows(content, CANONICAL_ROW_ORDER);
 
-    write(&file_path, content)
-        .map_err(|e| format!("Failed to write price file for '{}': {}", symbol, e))
+    let result = atomic_write(app_handle, &file_path, content.as_bytes())
+        .map_err(|e| format!("Failed to write price file for '{}': {}", symbol, e));
+    invalidate_coverage_cache(app_handle);
+    result
 }
 
 #[tauri::command]
@@ -1200,1587 +3767,13747 @@ fn write_price_file(
     app_handle: tauri::AppHandle,
     symbol: String,
     content: String,
-) -> Result<(), String> {
-    persist_price_file_content(&app_handle, &symbol, &content)
+) -> Result<(), AppError> {
+    Ok(persist_price_file_content(&app_handle, &symbol, &content)?)
 }
 
-#[tauri::command]
-fn read_price_file(app_handle: tauri::AppHandle, symbol: String) -> Result<String, String> {
-    let prices_dir = get_prices_dir(&app_handle)?;
-    let safe_symbol = symbol.replace(':', "_");
-    let base_path = prices_dir.join(format!("{}.csv", safe_symbol));
-    let override_path = prices_dir.join(format!("{}-override.csv", safe_symbol));
-
-    // Read base file
-    let base_content = if base_path.exists() {
-        read_to_string(&base_path)
-            .map_err(|e| format!("Failed to read price file for '{}': {}", symbol, e))?
-    } else {
-        String::new()
-    };
-
-    // Read override file
-    let override_content = if override_path.exists() {
-        read_to_string(&override_path)
-            .map_err(|e| format!("Failed to read price override file for '{}': {}", symbol, e))?
-    } else {
-        String::new()
-    };
-
-    // If no override data, just return base
-    if override_content.trim().is_empty() || override_content.lines().count() <= 1 {
-        return Ok(base_content);
-    }
-
-    // If no base data, just return override
-    if base_content.trim().is_empty() || base_content.lines().count() <= 1 {
-        return Ok(override_content);
-    }
-
-    // Merge: parse both files and combine by date, with override taking precedence
-    use std::collections::HashMap;
-    
-    let mut records: HashMap<String, String> = HashMap::new();
-    let header = "date,close,open,high,low,volume,source,updated_at";
+/// Source tag for hand-entered price and dividend rows; syncs never overwrite these.
+const MANUAL_SOURCE: &str = "manual";
 
-    // Parse base file (skip header) - convert old format to new format
-    for line in base_content.lines().skip(1) {
-        if line.trim().is_empty() {
-            continue;
-        }
-        let fields: Vec<&str> = line.split(',').collect();
-        if fields.len() >= 10 {
-            // Old format: date,close,open,high,low,volume,adjusted_close,split_unadjusted_close,source,updated_at
-            // New format: date,close,open,high,low,volume,source,updated_at
-            let date = fields[0];
-            let close = fields[1];
-            let open = fields[2];
-            let high = fields[3];
-            let low = fields[4];
-            let volume = fields[5];
-            let source = fields[8];
-            let updated_at = fields[9];
-            let new_line = format!("{},{},{},{},{},{},{},{}", date, close, open, high, low, volume, source, updated_at);
-            records.insert(date.to_string(), new_line);
-        } else if fields.len() >= 8 {
-            // Already in new format
-            if let Some(date) = fields.first() {
-                records.insert(date.to_string(), line.to_string());
-            }
-        }
-    }
+/// Source read for price rows with no source, as in files written before the column
+/// existed. Unlike manual rows these are replaced by synced prices.
+const LEGACY_SOURCE: &str = "legacy";
 
-    // Parse override file and override base records (skip header)
-    for line in override_content.lines().skip(1) {
-        if line.trim().is_empty() {
-            continue;
-        }
-        if let Some(date) = line.split(',').next() {
-            records.insert(date.to_string(), line.to_string());
-        }
+/// Current rows of a symbol's base price file, or none if it does not exist yet.
+fn price_file_entries(
+    app_handle: &tauri::AppHandle,
+    symbol: &str,
+) -> Result<Vec<PriceRecordEntry>, String> {
+    let path = get_prices_dir(app_handle)?.join(format!("{}.csv", symbol_file_stem(symbol)));
+    if !path.exists() {
+        return Ok(Vec::new());
     }
+    Ok(cached_price_series(app_handle, &path)?.as_ref().clone())
+}
 
-    // Sort by date descending
-    let mut sorted_dates: Vec<String> = records.keys().cloned().collect();
-    sorted_dates.sort_by(|a, b| b.cmp(a));
-
-    // Build output
-    let mut output = String::from(header);
-    output.push('\n');
-    for date in sorted_dates {
-        if let Some(line) = records.get(&date) {
-            output.push_str(line);
-            output.push('\n');
-        }
+fn manual_price_entry(
+    symbol: &str,
+    date: NaiveDate,
+    close: f64,
+    open: Option<f64>,
+    high: Option<f64>,
+    low: Option<f64>,
+    volume: Option<f64>,
+) -> PriceRecordEntry {
+    PriceRecordEntry {
+        symbol: symbol.to_string(),
+        date,
+        close,
+        open,
+        high,
+        low,
+        volume,
+        adjusted_close: None,
+        split_unadjusted_close: None,
+        source: MANUAL_SOURCE.to_string(),
     }
+}
 
-    Ok(output)
+/// Replaces same-date rows in the symbol's price file with `rows` and rewrites it
+/// newest first.
+fn upsert_manual_prices(
+    app_handle: &tauri::AppHandle,
+    symbol: &str,
+    rows: Vec<PriceRecordEntry>,
+) -> Result<(), String> {
+    let dates: std::collections::HashSet<NaiveDate> = rows.iter().map(|r| r.date).collect();
+    let mut entries = price_file_entries(app_handle, symbol)?;
+    entries.retain(|r| !dates.contains(&r.date));
+    entries.extend(rows);
+    entries.sort_by_key(|r| std::cmp::Reverse(r.date));
+    let content = build_price_csv_content(&entries)?;
+    persist_price_file_content(app_handle, symbol, &content)
 }
 
+/// Inserts or replaces the row for `date` in the symbol's price file, tagged as a
+/// manual entry so later syncs keep it.
 #[tauri::command]
-fn read_price_file_head(
+#[allow(clippy::too_many_arguments)]
+fn add_manual_price(
     app_handle: tauri::AppHandle,
     symbol: String,
-    lines: Option<usize>,
-) -> Result<String, String> {
-    // Read full merged data and return first N lines
-    let full_content = read_price_file(app_handle, symbol)?;
-    if full_content.is_empty() {
-        return Ok(String::new());
+    date: String,
+    close: f64,
+    open: Option<f64>,
+    high: Option<f64>,
+    low: Option<f64>,
+    volume: Option<f64>,
+) -> Result<(), AppError> {
+    let symbol = symbol.trim().to_string();
+    if symbol.is_empty() {
+        return Err(AppError::validation("symbol", "Symbol is required"));
     }
-    
-    let max_lines = lines.unwrap_or(8).max(1);
-    let mut output = String::new();
-    for (idx, line) in full_content.lines().enumerate() {
-        if idx >= max_lines {
-            break;
-        }
-        output.push_str(line);
-        output.push('\n');
+    let date = NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+        .map_err(|_| AppError::validation("date", format!("Invalid date {}", date)))?;
+    if date > Utc::now().date_naive() {
+        return Err(AppError::validation(
+            "date",
+            format!("{} is in the future", date),
+        ));
     }
-    Ok(output)
-}
-
-#[tauri::command]
-fn list_price_files(app_handle: tauri::AppHandle) -> Result<Vec<String>, String> {
-    let prices_dir = get_prices_dir(&app_handle)?;
-    let mut symbols = Vec::new();
-
-    if let Ok(entries) = std::fs::read_dir(&prices_dir) {
-        for entry in entries.flatten() {
-            if let Some(filename) = entry.file_name().to_str() {
-                if filename.ends_with(".csv") {
-                    let symbol = filename.trim_end_matches(".csv").replace('_', ":");
-                    symbols.push(symbol);
-                }
-            }
+    if !close.is_finite() || close <= 0.0 {
+        return Err(AppError::validation(
+            "close",
+            format!("Close must be positive, got {}", close),
+        ));
+    }
+    if let (Some(high), Some(low)) = (high, low) {
+        if high < low {
+            return Err(AppError::validation(
+                "high",
+                format!("High {} is below low {}", high, low),
+            ));
         }
     }
 
-    symbols.sort();
-    Ok(symbols)
+    upsert_manual_prices(
+        &app_handle,
+        &symbol,
+        vec![manual_price_entry(
+            &symbol, date, close, open, high, low, volume,
+        )],
+    )?;
+
+    Ok(write_worker_log(
+        &app_handle,
+        &format!("Manual price for {} on {}: {}", symbol, date, close),
+    )?)
 }
 
 #[tauri::command]
-fn read_price_override_file(app_handle: tauri::AppHandle, symbol: String) -> Result<String, String> {
-    let prices_dir = get_prices_dir(&app_handle)?;
-    let safe_symbol = symbol.replace(':', "_");
-    let file_path = prices_dir.join(format!("{}-override.csv", safe_symbol));
-
-    if !file_path.exists() {
-        return Ok(String::new());
+fn delete_price_row(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+    date: String,
+) -> Result<(), AppError> {
+    let symbol = symbol.trim().to_string();
+    let date = NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+        .map_err(|_| AppError::validation("date", format!("Invalid date {}", date)))?;
+
+    let mut entries = price_file_entries(&app_handle, &symbol)?;
+    let before = entries.len();
+    entries.retain(|r| r.date != date);
+    if entries.len() == before {
+        return Err(AppError::not_found(
+            "price_row",
+            format!("No price row for {} on {}", symbol, date),
+        ));
     }
+    let content = build_price_csv_content(&entries)?;
+    persist_price_file_content(&app_handle, &symbol, &content)?;
 
-    read_to_string(&file_path)
-        .map_err(|e| format!("Failed to read price override file for '{}': {}", symbol, e))
+    Ok(write_worker_log(
+        &app_handle,
+        &format!("Deleted price row for {} on {}", symbol, date),
+    )?)
 }
 
-#[tauri::command]
-fn write_price_override_file(
-    app_handle: tauri::AppHandle,
-    symbol: String,
-    content: String,
-) -> Result<(), String> {
-    let prices_dir = get_prices_dir(&app_handle)?;
-    let safe_symbol = symbol.replace(':', "_");
-    let file_path = prices_dir.join(format!("{}-override.csv", safe_symbol));
-
-    write(&file_path, content)
-        .map_err(|e| format!("Failed to write price override file for '{}': {}", symbol, e))
+#[derive(Serialize)]
+struct ImportRowError {
+    line: usize,
+    reason: String,
 }
 
-#[tauri::command]
-fn write_split_file(
-    app_handle: tauri::AppHandle,
-    symbol: String,
-    content: String,
-) -> Result<(), String> {
-    let splits_dir = get_splits_dir(&app_handle)?;
-    let safe_symbol = symbol.replace(':', "_");
-    let file_path = splits_dir.join(format!("{}.csv", safe_symbol));
+#[derive(Serialize)]
+struct PriceOverlap {
+    date: String,
+    existing_close: f64,
+    existing_source: String,
+    imported_close: f64,
+}
 
-    write(&file_path, content)
-        .map_err(|e| format!("Failed to write split file for '{}': {}", symbol, e))
+#[derive(Serialize)]
+struct ManualPriceImportPreview {
+    valid_rows: usize,
+    invalid_rows: Vec<ImportRowError>,
+    date_range: Option<(String, String)>,
+    overlaps_with_existing: Vec<PriceOverlap>,
+    committed: bool,
+    rows_written: usize,
 }
 
-#[tauri::command]
-fn read_split_file(app_handle: tauri::AppHandle, symbol: String) -> Result<String, String> {
-    let splits_dir = get_splits_dir(&app_handle)?;
-    let safe_symbol = symbol.replace(':', "_");
-    let file_path = splits_dir.join(format!("{}.csv", safe_symbol));
-
-    if !file_path.exists() {
-        return Ok(String::new());
-    }
-
-    read_to_string(&file_path)
-        .map_err(|e| format!("Failed to read split file for '{}': {}", symbol, e))
-}
-
-#[tauri::command]
-fn list_split_files(app_handle: tauri::AppHandle) -> Result<Vec<String>, String> {
-    let splits_dir = get_splits_dir(&app_handle)?;
-    let mut symbols = Vec::new();
+/// Date formats tried, in order, when the caller does not name one.
+const MANUAL_IMPORT_DATE_FORMATS: [&str; 5] =
+    ["%Y-%m-%d", "%Y/%m/%d", "%m/%d/%Y", "%d.%m.%Y", "%Y%m%d"];
 
-    if let Ok(entries) = std::fs::read_dir(&splits_dir) {
-        for entry in entries.flatten() {
-            if let Some(filename) = entry.file_name().to_str() {
-                if filename.ends_with(".csv") {
-                    let symbol = filename.trim_end_matches(".csv").replace('_', ":");
-                    symbols.push(symbol);
+/// Parses pasted price rows into manual entries. Tab-separated text is detected by
+/// its first line; otherwise commas are assumed. A header naming `date` and `close`
+/// (or `price`) places the columns, and `open`, `high`, `low` and `volume` are
+/// picked up when present. Without a header the first two columns are date, close.
+fn parse_manual_price_rows(
+    symbol: &str,
+    content: &str,
+    date_format: Option<&str>,
+) -> (Vec<PriceRecordEntry>, Vec<ImportRowError>) {
+    let delimiter = if content.lines().next().unwrap_or("").contains('\t') {
+        b'\t'
+    } else {
+        b','
+    };
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(content.as_bytes());
+    let mut records = reader.records().peekable();
+
+    let mut columns: HashMap<&str, usize> = HashMap::from([("date", 0), ("close", 1)]);
+    if let Some(Ok(first)) = records.peek() {
+        let headers: Vec<String> = first.iter().map(|h| h.to_lowercase()).collect();
+        let find = |aliases: &[&str]| headers.iter().position(|h| aliases.contains(&h.as_str()));
+        if let (Some(date), Some(close)) = (find(&["date"]), find(&["close", "price"])) {
+            columns = HashMap::from([("date", date), ("close", close)]);
+            for name in ["open", "high", "low", "volume"] {
+                if let Some(idx) = find(&[name]) {
+                    columns.insert(name, idx);
                 }
             }
+            records.next();
         }
     }
 
-    symbols.sort();
-    Ok(symbols)
+    let today = Utc::now().date_naive();
+    let formats: Vec<&str> = match date_format {
+        Some(format) => vec![format],
+        None => MANUAL_IMPORT_DATE_FORMATS.to_vec(),
+    };
+    let mut valid: Vec<PriceRecordEntry> = Vec::new();
+    let mut invalid = Vec::new();
+    for record in records {
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                let line = e.position().map(|p| p.line() as usize).unwrap_or(0);
+                invalid.push(ImportRowError {
+                    line,
+                    reason: e.to_string(),
+                });
+                continue;
+            }
+        };
+        let line = record.position().map(|p| p.line() as usize).unwrap_or(0);
+        if record.iter().all(|f| f.is_empty()) {
+            continue;
+        }
+        let field = |name: &str| {
+            columns
+                .get(name)
+                .and_then(|idx| record.get(*idx))
+                .unwrap_or("")
+        };
+        let number = |name: &str| parse_f64_str(field(name));
+        let mut reject = |reason: String| invalid.push(ImportRowError { line, reason });
+
+        let raw_date = field("date");
+        let Some(date) = formats
+            .iter()
+            .find_map(|format| NaiveDate::parse_from_str(raw_date, format).ok())
+        else {
+            reject(format!("Unrecognised date '{}'", raw_date));
+            continue;
+        };
+        if date > today {
+            reject(format!("{} is in the future", date));
+            continue;
+        }
+        let Some(close) = number("close").filter(|c| c.is_finite() && *c > 0.0) else {
+            reject(format!("Close must be positive, got '{}'", field("close")));
+            continue;
+        };
+        let (high, low) = (number("high"), number("low"));
+        if let (Some(high), Some(low)) = (high, low) {
+            if high < low {
+                reject(format!("High {} is below low {}", high, low));
+                continue;
+            }
+        }
+        if valid.iter().any(|r| r.date == date) {
+            reject(format!("Duplicate date {}", date));
+            continue;
+        }
+        valid.push(manual_price_entry(
+            symbol,
+            date,
+            close,
+            number("open"),
+            high,
+            low,
+            number("volume"),
+        ));
+    }
+    (valid, invalid)
 }
 
+/// Validates pasted price rows and reports what an import would do. With `commit`,
+/// the valid rows are written as manual prices. Dates that already have a
+/// non-manual row are listed in `overlaps_with_existing`; pass `keep_existing` to
+/// leave those rows alone instead of replacing them.
 #[tauri::command]
-fn write_dividend_file(
+fn import_manual_prices(
     app_handle: tauri::AppHandle,
     symbol: String,
     content: String,
-) -> Result<(), String> {
-    let dividends_dir = get_dividends_dir(&app_handle)?;
-    let safe_symbol = symbol.replace(':', "_");
-    let file_path = dividends_dir.join(format!("{}.csv", safe_symbol));
-
-    write(&file_path, content)
-        .map_err(|e| format!("Failed to write dividend file for '{}': {}", symbol, e))
-}
+    date_format: Option<String>,
+    commit: Option<bool>,
+    keep_existing: Option<bool>,
+) -> Result<ManualPriceImportPreview, AppError> {
+    let symbol = symbol.trim().to_string();
+    if symbol.is_empty() {
+        return Err(AppError::validation("symbol", "Symbol is required"));
+    }
+    let date_format = date_format.filter(|f| !f.trim().is_empty());
+    let (mut rows, invalid_rows) =
+        parse_manual_price_rows(&symbol, &content, date_format.as_deref());
 
-#[tauri::command]
-fn read_dividend_file(app_handle: tauri::AppHandle, symbol: String) -> Result<String, String> {
-    let dividends_dir = get_dividends_dir(&app_handle)?;
-    let safe_symbol = symbol.replace(':', "_");
-    let file_path = dividends_dir.join(format!("{}.csv", safe_symbol));
+    let existing: HashMap<NaiveDate, PriceRecordEntry> = price_file_entries(&app_handle, &symbol)?
+        .into_iter()
+        .map(|r| (r.date, r))
+        .collect();
+    let mut overlaps: Vec<PriceOverlap> = rows
+        .iter()
+        .filter_map(|row| {
+            let current = existing.get(&row.date)?;
+            (current.source != MANUAL_SOURCE).then(|| PriceOverlap {
+                date: row.date.format("%Y-%m-%d").to_string(),
+                existing_close: current.close,
+                existing_source: current.source.clone(),
+                imported_close: row.close,
+            })
+        })
+        .collect();
+    overlaps.sort_by(|a, b| a.date.cmp(&b.date));
 
-    if !file_path.exists() {
-        return Ok(String::new());
+    let date_range = rows
+        .iter()
+        .map(|r| r.date)
+        .min()
+        .zip(rows.iter().map(|r| r.date).max())
+        .map(|(start, end)| {
+            (
+                start.format("%Y-%m-%d").to_string(),
+                end.format("%Y-%m-%d").to_string(),
+            )
+        });
+    let valid_rows = rows.len();
+
+    let mut rows_written = 0;
+    let committed = commit.unwrap_or(false);
+    if committed {
+        if keep_existing.unwrap_or(false) {
+            rows.retain(|row| {
+                existing
+                    .get(&row.date)
+                    .is_none_or(|current| current.source == MANUAL_SOURCE)
+            });
+        }
+        rows_written = rows.len();
+        if rows_written > 0 {
+            upsert_manual_prices(&app_handle, &symbol, rows)?;
+            write_worker_log(
+                &app_handle,
+                &format!("Imported {} manual prices for {}", rows_written, symbol),
+            )?;
+        }
     }
 
-    read_to_string(&file_path)
-        .map_err(|e| format!("Failed to read dividend file for '{}': {}", symbol, e))
+    Ok(ManualPriceImportPreview {
+        valid_rows,
+        invalid_rows,
+        date_range,
+        overlaps_with_existing: overlaps,
+        committed,
+        rows_written,
+    })
 }
 
-#[tauri::command]
-fn list_dividend_files(app_handle: tauri::AppHandle) -> Result<Vec<String>, String> {
-    let dividends_dir = get_dividends_dir(&app_handle)?;
-    let mut symbols = Vec::new();
+fn dividend_file_path(app_handle: &tauri::AppHandle, symbol: &str) -> Result<PathBuf, String> {
+    Ok(get_dividends_dir(app_handle)?.join(format!("{}.csv", symbol_file_stem(symbol))))
+}
 
-    if let Ok(entries) = std::fs::read_dir(&dividends_dir) {
-        for entry in entries.flatten() {
-            if let Some(filename) = entry.file_name().to_str() {
-                if filename.ends_with(".csv") {
-                    let symbol = filename.trim_end_matches(".csv").replace('_', ":");
-                    symbols.push(symbol);
-                }
-            }
+/// Dividend rows padded to the `DIVIDEND_FILE_HEADER` columns; files written before
+/// the `source` column existed read back with an empty source.
+fn read_dividend_rows(path: &Path) -> Result<Vec<Vec<String>>, String> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let columns = DIVIDEND_FILE_HEADER.split(',').count();
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_path(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| format!("Invalid dividend row: {}", e))?;
+        let mut fields: Vec<String> = record.iter().map(|f| f.trim().to_string()).collect();
+        if fields.first().is_none_or(|date| date.is_empty()) {
+            continue;
         }
+        fields.resize(columns, String::new());
+        rows.push(fields);
     }
-
-    symbols.sort();
-    Ok(symbols)
+    Ok(rows)
 }
 
-fn persist_fx_rate_file(
+/// Writes dividend rows newest first.
+fn write_dividend_rows(
     app_handle: &tauri::AppHandle,
-    pair: &str,
-    content: &str,
+    path: &Path,
+    mut rows: Vec<Vec<String>>,
 ) -> Result<(), String> {
-    let fx_rates_dir = get_fx_rates_dir(app_handle)?;
-    let safe_pair = pair.replace('/', "_");
-    let file_path = fx_rates_dir.join(format!("{}.csv", safe_pair));
-
-    write(&file_path, content)
-        .map_err(|e| format!("Failed to write FX rate file for '{}': {}", pair, e))
+    rows.sort_by(|a, b| b[0].cmp(&a[0]));
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record(DIVIDEND_FILE_HEADER.split(','))
+        .map_err(|e| format!("Failed to write dividend header: {}", e))?;
+    for row in &rows {
+        writer
+            .write_record(row)
+            .map_err(|e| format!("Failed to write dividend row: {}", e))?;
+    }
+    let content = writer
+        .into_inner()
+        .map_err(|e| format!("Failed to build dividend file: {}", e))?;
+    atomic_write(app_handle, path, &content)
 }
 
-#[tauri::command]
-fn write_fx_rate_file(
-    app_handle: tauri::AppHandle,
-    pair: String,
-    content: String,
-) -> Result<(), String> {
-    persist_fx_rate_file(&app_handle, &pair, &content)
+fn parse_manual_date(field: &str, value: &str) -> Result<NaiveDate, AppError> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+        .map_err(|_| AppError::validation(field, format!("Invalid date {}", value)))
 }
 
+/// Inserts or replaces the dividend on `ex_date`, tagged as a manual entry so later
+/// syncs keep it.
 #[tauri::command]
-fn write_fx_rate_override_file(
+fn add_manual_dividend(
     app_handle: tauri::AppHandle,
-    pair: String,
-    content: String,
-) -> Result<(), String> {
-    let fx_rates_dir = get_fx_rates_dir(&app_handle)?;
-    let safe_pair = pair.replace('/', "_");
-    let file_path = fx_rates_dir.join(format!("{}-override.csv", safe_pair));
+    symbol: String,
+    ex_date: String,
+    amount: f64,
+    currency: String,
+) -> Result<(), AppError> {
+    let symbol = symbol.trim().to_string();
+    if symbol.is_empty() {
+        return Err(AppError::validation("symbol", "Symbol is required"));
+    }
+    let ex_date = parse_manual_date("ex_date", &ex_date)?;
+    if !amount.is_finite() || amount <= 0.0 {
+        return Err(AppError::validation(
+            "amount",
+            format!("Dividend amount must be positive, got {}", amount),
+        ));
+    }
+    let currency = currency.trim().to_uppercase();
+    if !KNOWN_CURRENCIES.contains(&currency.as_str()) {
+        return Err(AppError::validation(
+            "currency",
+            format!("Unknown currency code '{}'", currency),
+        ));
+    }
 
-    write(&file_path, content)
-        .map_err(|e| format!("Failed to write FX rate override file for '{}': {}", pair, e))
+    let path = dividend_file_path(&app_handle, &symbol)?;
+    let date = ex_date.format("%Y-%m-%d").to_string();
+    with_file_lock(&app_handle, &path, || -> Result<(), AppError> {
+        let mut rows = read_dividend_rows(&path)?;
+        rows.retain(|row| row[0] != date);
+        rows.push(vec![
+            date,
+            amount.to_string(),
+            currency.clone(),
+            Utc::now().to_rfc3339(),
+            MANUAL_SOURCE.to_string(),
+        ]);
+        write_dividend_rows(&app_handle, &path, rows)?;
+        Ok(())
+    })?;
+    invalidate_coverage_cache(&app_handle);
+
+    Ok(write_worker_log(
+        &app_handle,
+        &format!(
+            "Manual dividend for {} on {}: {} {}",
+            symbol, ex_date, amount, currency
+        ),
+    )?)
 }
 
 #[tauri::command]
-fn read_fx_rate_file(app_handle: tauri::AppHandle, pair: String) -> Result<String, String> {
-    let fx_rates_dir = get_fx_rates_dir(&app_handle)?;
-    let safe_pair = pair.replace('/', "_");
-    let file_path = fx_rates_dir.join(format!("{}.csv", safe_pair));
-
-    if !file_path.exists() {
-        return Ok(String::new());
-    }
+fn delete_dividend_row(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+    ex_date: String,
+) -> Result<(), AppError> {
+    let symbol = symbol.trim().to_string();
+    let date = parse_manual_date("ex_date", &ex_date)?
+        .format("%Y-%m-%d")
+        .to_string();
+    let path = dividend_file_path(&app_handle, &symbol)?;
+    with_file_lock(&app_handle, &path, || -> Result<(), AppError> {
+        let mut rows = read_dividend_rows(&path)?;
+        let before = rows.len();
+        rows.retain(|row| row[0] != date);
+        if rows.len() == before {
+            return Err(AppError::not_found(
+                "dividend_row",
+                format!("No dividend for {} on {}", symbol, date),
+            ));
+        }
+        write_dividend_rows(&app_handle, &path, rows)?;
+        Ok(())
+    })?;
+    invalidate_coverage_cache(&app_handle);
 
-    read_to_string(&file_path)
-        .map_err(|e| format!("Failed to read FX rate file for '{}': {}", pair, e))
+    Ok(write_worker_log(
+        &app_handle,
+        &format!("Deleted dividend row for {} on {}", symbol, date),
+    )?)
 }
 
-#[tauri::command]
-fn read_fx_rate_file_head(
-    app_handle: tauri::AppHandle,
-    pair: String,
-    lines: Option<usize>,
-) -> Result<String, String> {
-    let fx_rates_dir = get_fx_rates_dir(&app_handle)?;
-    let safe_pair = pair.replace('/', "_");
-    let file_path = fx_rates_dir.join(format!("{}.csv", safe_pair));
-    if !file_path.exists() {
-        return Ok(String::new());
+/// Split file rows (date, numerator, denominator, before_price, after_price),
+/// converting legacy single-ratio files on the way in.
+fn read_split_rows(path: &Path) -> Result<Vec<Vec<String>>, String> {
+    if !path.exists() {
+        return Ok(Vec::new());
     }
-    let max_lines = lines.unwrap_or(8).max(1);
-    read_file_head(&file_path, max_lines)
+    let existing =
+        read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let content = convert_legacy_split_content(&existing).unwrap_or(existing);
+    let columns = SPLIT_FILE_HEADER.trim_end().split(',').count();
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(content.as_bytes());
+    let mut rows = Vec::new();
+    for record in reader.records().flatten() {
+        let mut fields: Vec<String> = record.iter().map(|f| f.trim().to_string()).collect();
+        if fields.first().is_none_or(|date| date.is_empty())
+            || is_spinoff_marker(fields.iter().map(String::as_str))
+        {
+            continue;
+        }
+        fields.resize(columns, String::new());
+        rows.push(fields);
+    }
+    Ok(rows)
 }
 
-#[tauri::command]
-fn list_fx_rate_files(app_handle: tauri::AppHandle) -> Result<Vec<String>, String> {
-    let fx_rates_dir = get_fx_rates_dir(&app_handle)?;
-    let mut pairs = Vec::new();
-
-    if let Ok(entries) = std::fs::read_dir(&fx_rates_dir) {
-        for entry in entries.flatten() {
-            if let Some(filename) = entry.file_name().to_str() {
-                if filename.ends_with(".csv") {
-                    let pair = filename.trim_end_matches(".csv").replace('_', "/");
-                    pairs.push(pair);
-                }
-            }
+/// New shares per old share for a split file row. A missing, malformed or
+/// non-positive numerator or denominator is an error: treating it as 1:1 would
+/// re-adjust prices by the wrong factor.
+fn split_row_ratio(row: &[String]) -> Result<f64, String> {
+    match (parse_number(&row[1]), parse_number(&row[2])) {
+        (Ok(Some(numerator)), Ok(Some(denominator))) if numerator > 0.0 && denominator > 0.0 => {
+            Ok(numerator / denominator)
         }
+        _ => Err(format!(
+            "Split on {} has an invalid ratio '{}:{}'; fix the split file row first",
+            row[0], row[1], row[2]
+        )),
     }
-
-    pairs.sort();
-    Ok(pairs)
 }
 
-#[tauri::command]
-fn sync_history_once(app_handle: tauri::AppHandle) -> Result<(), String> {
-    sync_full_history(&app_handle)
+/// Rewrites the split file with `rows` (oldest first) and re-adjusts the symbol's
+/// price file by `ratio_change` for rows before `date`.
+fn apply_split_change(
+    app_handle: &tauri::AppHandle,
+    symbol: &str,
+    mut rows: Vec<Vec<String>>,
+    date: NaiveDate,
+    ratio_change: f64,
+) -> Result<(), String> {
+    let safe_symbol = symbol_file_stem(symbol);
+    rows.sort_by(|a, b| a[0].cmp(&b[0]));
+    let mut content = String::from(SPLIT_FILE_HEADER);
+    for row in &rows {
+        content.push_str(&row.join(","));
+        content.push('\n');
+    }
+    let split_path = get_splits_dir(app_handle)?.join(format!("{}.csv", safe_symbol));
+    atomic_write(app_handle, &split_path, content.as_bytes())
+        .map_err(|e| format!("Failed to write split file for '{}': {}", symbol, e))?;
+
+    let price_path = get_prices_dir(app_handle)?.join(format!("{}.csv", safe_symbol));
+    if price_path.exists() {
+        let split_events = load_split_events(app_handle, symbol)?;
+        rewrite_split_adjusted_prices(
+            app_handle,
+            &price_path,
+            &split_events,
+            Some((date, ratio_change)),
+        )?;
+    }
+    invalidate_coverage_cache(app_handle);
+    Ok(())
 }
 
+/// Records a `numerator:denominator` split on `date`, replacing any split already on
+/// that day. Like `apply_suggested_split`, prices before the date are assumed to be
+/// quoted pre-split and are re-adjusted, along with `split_unadjusted_close`.
 #[tauri::command]
-fn download_symbol_history(app_handle: tauri::AppHandle, symbol: String) -> Result<(), String> {
-    println!("[RUST] Received download request for: {}", symbol);
-
-    let fifteen_years_ago = Utc::now().date_naive() - ChronoDuration::days(15 * 365);
-    let mut price_map: HashMap<String, Vec<PriceRecordEntry>> = HashMap::new();
-
-    println!("[RUST] Calling ensure_history_for_symbol for: {}", symbol);
-    // Use the existing ensure_history_for_symbol logic
-    match ensure_history_for_symbol(&app_handle, &mut price_map, &symbol, fifteen_years_ago) {
-        Ok(_) => println!("[RUST] ✓ Successfully fetched data for: {}", symbol),
-        Err(e) => {
-            eprintln!("[RUST] ✗ Error fetching data for {}: {}", symbol, e);
-            return Err(e);
-        }
+fn add_manual_split(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+    date: String,
+    numerator: u32,
+    denominator: u32,
+) -> Result<(), AppError> {
+    let symbol = symbol.trim().to_string();
+    if symbol.is_empty() {
+        return Err(AppError::validation("symbol", "Symbol is required"));
     }
-
-    // Write the price file
-    if let Some(entries) = price_map.get(&symbol) {
-        println!(
-            "[RUST] Writing {} price entries for: {}",
-            entries.len(),
-            symbol
-        );
-        let csv_content = build_price_csv_content(entries);
-        persist_price_file_content(&app_handle, &symbol, &csv_content)?;
-        println!("[RUST] ✓ Successfully wrote price file for: {}", symbol);
-    } else {
-        eprintln!("[RUST] ⚠ No price data found for: {}", symbol);
+    let split_date = parse_manual_date("date", &date)?;
+    if numerator < 1 || denominator < 1 || numerator == denominator {
+        return Err(AppError::validation(
+            "ratio",
+            format!("Invalid split ratio {}:{}", numerator, denominator),
+        ));
     }
 
-    Ok(())
+    let split_path =
+        get_splits_dir(&app_handle)?.join(format!("{}.csv", symbol_file_stem(&symbol)));
+    let day = split_date.format("%Y-%m-%d").to_string();
+    with_file_lock(&app_handle, &split_path, || -> Result<(), AppError> {
+        let mut rows = read_split_rows(&split_path)?;
+        let previous_ratio = rows
+            .iter()
+            .find(|row| row[0] == day)
+            .map(|row| split_row_ratio(row))
+            .transpose()?
+            .unwrap_or(1.0);
+        rows.retain(|row| row[0] != day);
+
+        let series = load_ascending_price_series(&app_handle, &symbol).unwrap_or_default();
+        let price_near = |after: bool| {
+            let found = if after {
+                series.iter().find(|(d, _)| *d >= split_date)
+            } else {
+                series.iter().rev().find(|(d, _)| *d < split_date)
+            };
+            found.map(|(_, r)| r.close.to_string()).unwrap_or_default()
+        };
+        rows.push(vec![
+            day,
+            numerator.to_string(),
+            denominator.to_string(),
+            price_near(false),
+            price_near(true),
+        ]);
+
+        let ratio = numerator as f64 / denominator as f64;
+        apply_split_change(
+            &app_handle,
+            &symbol,
+            rows,
+            split_date,
+            ratio / previous_ratio,
+        )?;
+        Ok(())
+    })?;
+
+    Ok(write_worker_log(
+        &app_handle,
+        &format!(
+            "Manual {}:{} split for {} on {}",
+            numerator, denominator, symbol, split_date
+        ),
+    )?)
 }
 
+/// Removes the split on `date` and undoes its adjustment of earlier prices.
 #[tauri::command]
-fn start_history_worker(app_handle: tauri::AppHandle) -> Result<(), String> {
-    write_worker_log(&app_handle, "Starting background history worker")?;
-    let handle = app_handle.clone();
-    std::thread::spawn(move || {
-        if let Err(err) = sync_full_history(&handle) {
-            let _ = write_worker_log(&handle, &format!("History worker failed: {}", err));
-        }
-    });
-    Ok(())
+fn delete_split_row(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+    date: String,
+) -> Result<(), AppError> {
+    let symbol = symbol.trim().to_string();
+    let split_date = parse_manual_date("date", &date)?;
+    let split_path =
+        get_splits_dir(&app_handle)?.join(format!("{}.csv", symbol_file_stem(&symbol)));
+    let day = split_date.format("%Y-%m-%d").to_string();
+
+    with_file_lock(&app_handle, &split_path, || -> Result<(), AppError> {
+        let mut rows = read_split_rows(&split_path)?;
+        let Some(removed) = rows.iter().position(|row| row[0] == day) else {
+            return Err(AppError::not_found(
+                "split_row",
+                format!("No split for {} on {}", symbol, day),
+            ));
+        };
+        let ratio = split_row_ratio(&rows.remove(removed))?;
+        apply_split_change(&app_handle, &symbol, rows, split_date, 1.0 / ratio)?;
+        Ok(())
+    })?;
+
+    Ok(write_worker_log(
+        &app_handle,
+        &format!("Deleted split row for {} on {}", symbol, day),
+    )?)
 }
 
+/// The symbol's price file merged with its override file, rows in `order`
+/// (`"asc"`/`"desc"`, newest first by default) whatever the on-disk layout.
 #[tauri::command]
-fn get_history_log(app_handle: tauri::AppHandle) -> Result<String, String> {
-    let logs_dir = get_logs_dir(&app_handle)?;
-    let log_file = logs_dir.join("history_worker.log");
-    if !log_file.exists() {
-        return Ok(String::new());
+fn read_price_file(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+    order: Option<String>,
+) -> Result<String, AppError> {
+    let order = RowOrder::parse(order.as_deref())?;
+    let content = merged_price_file_content(&app_handle, &symbol)?;
+    if content.trim().is_empty() {
+        return Ok(content);
     }
-    read_to_string(&log_file).map_err(|e| format!("Failed to read history log: {}", e))
+    Ok(order_csv_rows(&content, order))
 }
 
-fn parse_f64_str(value: &str) -> Option<f64> {
-    let sanitized: String = value
-        .chars()
-        .filter(|c| c.is_ascii_digit() || *c == '.' || *c == '-' || *c == '+')
-        .collect();
-    if sanitized.is_empty() {
-        return None;
-    }
-    sanitized.parse::<f64>().ok()
-}
+fn merged_price_file_content(
+    app_handle: &tauri::AppHandle,
+    symbol: &str,
+) -> Result<String, String> {
+    let prices_dir = get_prices_dir(app_handle)?;
+    let safe_symbol = symbol_file_stem(symbol);
+    let base_path = prices_dir.join(format!("{}.csv", safe_symbol));
+    let override_path = prices_dir.join(format!("{}-override.csv", safe_symbol));
 
-fn sanitize_timestamp(value: &str) -> String {
-    value
-        .chars()
-        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
-        .collect()
-}
-
-fn load_all_transactions(app_handle: &tauri::AppHandle) -> Result<Vec<Transaction>, String> {
-    let json = read_csv(app_handle.clone())?;
-    serde_json::from_str(&json).map_err(|e| format!("Failed to parse transactions JSON: {}", e))
-}
-
-#[derive(Clone)]
-struct ProcessedTransaction {
-    date: NaiveDate,
-    txn_type: String,
-    quantity: f64,
-    split_ratio: f64,
-    currency: String,
-}
+    // Read base file
+    let base_content = if base_path.exists() {
+        read_to_string(&base_path)
+            .map_err(|e| format!("Failed to read price file for '{}': {}", symbol, e))?
+    } else {
+        String::new()
+    };
 
-fn load_symbol_transactions(
-    app_handle: &tauri::AppHandle,
-    symbol: &str,
-) -> Result<Vec<ProcessedTransaction>, String> {
-    let mut all = load_all_transactions(app_handle)?;
-    all.retain(|txn| txn.stock == symbol);
+    // Read override file
+    let override_content = if override_path.exists() {
+        read_to_string(&override_path)
+            .map_err(|e| format!("Failed to read price override file for '{}': {}", symbol, e))?
+    } else {
+        String::new()
+    };
 
-    if all.is_empty() {
-        return Err(format!("No transactions found for {}", symbol));
+    // If no override data, just return base
+    if override_content.trim().is_empty() || override_content.lines().count() <= 1 {
+        return Ok(base_content);
     }
 
-    let mut processed = Vec::new();
-    for txn in all {
-        let date = NaiveDate::parse_from_str(txn.date.trim(), "%Y-%m-%d")
-            .map_err(|e| format!("Invalid transaction date {}: {}", txn.date, e))?;
-        let quantity = parse_f64_str(&txn.quantity).unwrap_or(0.0);
-        let split_ratio = if txn.split_ratio.trim().is_empty() {
-            1.0
-        } else {
-            parse_f64_str(&txn.split_ratio).unwrap_or(1.0)
-        };
-
-        processed.push(ProcessedTransaction {
-            date,
-            txn_type: txn.transaction_type.to_lowercase(),
-            quantity,
-            split_ratio: if split_ratio > 0.0 { split_ratio } else { 1.0 },
-            currency: txn.currency.clone(),
-        });
+    // If no base data, just return override
+    if base_content.trim().is_empty() || base_content.lines().count() <= 1 {
+        return Ok(override_content);
     }
 
-    processed.sort_by_key(|t| t.date);
-    Ok(processed)
-}
-
-fn load_price_history_for_symbol(
-    app_handle: &tauri::AppHandle,
-    symbol: &str,
-) -> Result<Vec<PriceRecordEntry>, String> {
-    let prices_dir = get_prices_dir(app_handle)?;
-    let safe_symbol = symbol.replace(':', "_");
-    let path = prices_dir.join(format!("{}.csv", safe_symbol));
-
-    if !path.exists() {
-        return Err(format!("Price history not found for {}", symbol));
-    }
+    // Merge: parse both files and combine by date, with override taking precedence
+    use std::collections::HashMap;
 
-    let mut records = Vec::new();
-    let mut reader = csv::ReaderBuilder::new()
-        .has_headers(true)
-        .from_path(&path)
-        .map_err(|e| format!("Failed to read price file for {}: {}", symbol, e))?;
+    let mut records: HashMap<String, String> = HashMap::new();
+    let header = "date,close,open,high,low,volume,source,updated_at";
 
-    for result in reader.records() {
-        let record = result.map_err(|e| format!("Invalid price row: {}", e))?;
-        if record.len() < 2 {
+    // Parse base file (skip header) - convert old format to new format
+    for line in base_content.lines().skip(1) {
+        if line.trim().is_empty() {
             continue;
         }
-
-        let date = NaiveDate::parse_from_str(record.get(0).unwrap_or("").trim(), "%Y-%m-%d")
-            .map_err(|e| format!("Invalid price date for {}: {}", symbol, e))?;
-        let close = parse_f64_str(record.get(1).unwrap_or("").trim()).unwrap_or(0.0);
-        let open = record.get(2).and_then(|v| parse_f64_str(v.trim()));
-        let high = record.get(3).and_then(|v| parse_f64_str(v.trim()));
-        let low = record.get(4).and_then(|v| parse_f64_str(v.trim()));
-        let volume = record.get(5).and_then(|v| parse_f64_str(v.trim()));
-        let source = record.get(6).unwrap_or("manual").trim().to_string();
-
-        records.push(PriceRecordEntry {
-            symbol: symbol.to_string(),
-            date,
-            close,
-            open,
-            high,
-            low,
-            volume,
-            adjusted_close: None,
-            split_unadjusted_close: None,
-            source,
-        });
+        let fields: Vec<&str> = line.split(',').collect();
+        if fields.len() >= 10 {
+            // Old format: date,close,open,high,low,volume,adjusted_close,split_unadjusted_close,source,updated_at
+            // New format: date,close,open,high,low,volume,source,updated_at
+            let date = fields[0];
+            let close = fields[1];
+            let open = fields[2];
+            let high = fields[3];
+            let low = fields[4];
+            let volume = fields[5];
+            let source = fields[8];
+            let updated_at = fields[9];
+            let new_line = format!(
+                "{},{},{},{},{},{},{},{}",
+                date, close, open, high, low, volume, source, updated_at
+            );
+            records.insert(date.to_string(), new_line);
+        } else if fields.len() >= 8 {
+            // Already in new format
+            if let Some(date) = fields.first() {
+                records.insert(date.to_string(), line.to_string());
+            }
+        }
     }
 
-    if records.is_empty() {
-        return Err(format!("No closing prices available for {}", symbol));
+    // Parse override file and override base records (skip header)
+    for line in override_content.lines().skip(1) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(date) = line.split(',').next() {
+            records.insert(date.to_string(), line.to_string());
+        }
     }
 
-    records.sort_by_key(|r| r.date);
+    let mut sorted_dates: Vec<String> = records.keys().cloned().collect();
+    sorted_dates.sort_by(|a, b| CANONICAL_ROW_ORDER.compare(a, b));
 
-    if let Ok(split_events) = load_split_events(app_handle, symbol) {
-        if !split_events.is_empty() {
-            for record in records.iter_mut() {
-                let mut factor = 1.0f64;
-                for (split_date, ratio) in &split_events {
-                    if record.date < *split_date {
-                        factor *= *ratio;
-                    }
-                }
-                record.close *= factor;
-                if let Some(open) = record.open.as_mut() {
-                    *open *= factor;
-                }
-                if let Some(high) = record.high.as_mut() {
-                    *high *= factor;
-                }
-                if let Some(low) = record.low.as_mut() {
-                    *low *= factor;
-                }
-            }
+    // Build output
+    let mut output = String::from(header);
+    output.push('\n');
+    for date in sorted_dates {
+        if let Some(line) = records.get(&date) {
+            output.push_str(line);
+            output.push('\n');
         }
     }
 
-    Ok(records)
+    Ok(output)
 }
 
-fn load_split_events(
-    app_handle: &tauri::AppHandle,
-    symbol: &str,
-) -> Result<Vec<(NaiveDate, f64)>, String> {
-    let splits_dir = get_splits_dir(app_handle)?;
-    let safe_symbol = symbol.replace(':', "_");
-    let path = splits_dir.join(format!("{}.csv", safe_symbol));
-
-    if !path.exists() {
-        return Ok(Vec::new());
+/// Last `lines` rows of a symbol's base price file (plus its header) in on-disk order,
+/// which for canonical newest-first files are the oldest rows. Overrides are not
+/// merged; use `read_price_file_head` with an `order` for a sorted view.
+#[tauri::command]
+fn read_price_file_tail(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+    lines: Option<usize>,
+) -> Result<String, AppError> {
+    let prices_dir = get_prices_dir(&app_handle)?;
+    let safe_symbol = symbol_file_stem(&symbol);
+    let file_path = prices_dir.join(format!("{}.csv", safe_symbol));
+    if !file_path.exists() {
+        return Ok(String::new());
     }
+    Ok(read_file_tail(&file_path, lines.unwrap_or(8).max(1))?)
+}
 
-    let mut events = Vec::new();
-    let mut reader = csv::ReaderBuilder::new()
-        .has_headers(true)
-        .from_path(&path)
-        .map_err(|e| format!("Failed to read split file for {}: {}", symbol, e))?;
+/// Latest dated row with a parseable close in a price CSV snippet that starts
+/// with its header line.
+fn latest_close_in_csv(content: &str) -> Option<(NaiveDate, f64)> {
+    let mut lines = content.lines();
+    let header: Vec<&str> = lines.next()?.split(',').map(|h| h.trim()).collect();
+    let date_idx = header.iter().position(|h| *h == "date")?;
+    let close_idx = header.iter().position(|h| *h == "close")?;
+
+    lines
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(',').collect();
+            let date = NaiveDate::parse_from_str(fields.get(date_idx)?.trim(), "%Y-%m-%d").ok()?;
+            let close = parse_f64_str(fields.get(close_idx)?.trim())?;
+            Some((date, close))
+        })
+        .max_by_key(|(date, _)| *date)
+}
 
-    for result in reader.records() {
-        let record = result.map_err(|e| format!("Invalid split row: {}", e))?;
-        if record.len() < 3 {
-            continue;
+/// Most recent close per symbol, read from the first and last few rows of each price
+/// file (plus its override file) instead of the whole history. Watchlist symbols are
+/// always included alongside the requested ones.
+#[tauri::command]
+fn get_latest_prices(
+    app_handle: tauri::AppHandle,
+    mut symbols: Vec<String>,
+) -> Result<HashMap<String, Option<f64>>, AppError> {
+    let prices_dir = get_prices_dir(&app_handle)?;
+    let mut latest = HashMap::new();
+    for entry in load_watchlist(&app_handle)? {
+        if !symbols.contains(&entry.symbol) {
+            symbols.push(entry.symbol);
         }
+    }
 
-        let date = match NaiveDate::parse_from_str(record.get(0).unwrap_or("").trim(), "%Y-%m-%d") {
-            Ok(d) => d,
-            Err(_) => continue,
-        };
-
-        let numerator = record
-            .get(1)
-            .and_then(|v| v.trim().parse::<f64>().ok())
-            .unwrap_or(1.0)
-            .max(1.0);
-        let denominator = record
-            .get(2)
-            .and_then(|v| v.trim().parse::<f64>().ok())
-            .unwrap_or(1.0)
-            .max(1.0);
+    for symbol in symbols {
+        let safe_symbol = symbol_file_stem(&symbol);
+        let base_path = prices_dir.join(format!("{}.csv", safe_symbol));
+        let override_path = prices_dir.join(format!("{}-override.csv", safe_symbol));
 
-        if numerator > 0.0 && denominator > 0.0 {
-            events.push((date, numerator / denominator));
+        let mut best: Option<(NaiveDate, f64)> = None;
+        for path in [&base_path, &override_path] {
+            if !path.exists() {
+                continue;
+            }
+            // Newest rows sit at the top of canonical files and at the bottom of
+            // older ascending ones, so look at both ends.
+            let ends = format!("{}{}", read_file_head(path, 9)?, read_file_tail(path, 8)?);
+            if let Some(candidate) = latest_close_in_csv(&ends) {
+                // Overrides win ties, matching read_price_file's merge.
+                if best.is_none_or(|(date, _)| candidate.0 >= date) {
+                    best = Some(candidate);
+                }
+            }
         }
+        latest.insert(symbol, best.map(|(_, close)| close));
     }
 
-    events.sort_by_key(|(date, _)| *date);
-    Ok(events)
+    Ok(latest)
 }
 
-fn build_position_timeline(
-    prices: &[PriceRecordEntry],
-    transactions: &[ProcessedTransaction],
-) -> Vec<(String, f64, f64)> {
-    let mut results = Vec::new();
-    if prices.is_empty() {
-        return results;
+/// First `lines` lines (header included) of the merged price file in `order`, so
+/// the default returns the most recent prices even for an ascending file.
+#[tauri::command]
+fn read_price_file_head(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+    lines: Option<usize>,
+    order: Option<String>,
+) -> Result<String, AppError> {
+    let full_content = read_price_file(app_handle, symbol, order)?;
+    if full_content.is_empty() {
+        return Ok(String::new());
     }
 
-    let mut idx = 0usize;
-    let mut shares = 0.0f64;
-
-    for price in prices {
-        while idx < transactions.len() && transactions[idx].date <= price.date {
-            let txn = &transactions[idx];
-            match txn.txn_type.as_str() {
-                ty if ty.starts_with("buy") || ty == "purchase" => {
-                    shares += txn.quantity;
-                }
-                ty if ty.starts_with("sell") || ty == "sale" => {
-                    shares -= txn.quantity;
-                    if shares < 0.0 {
-                        shares = 0.0;
-                    }
-                }
-                ty if ty.contains("split") => {
-                    if txn.split_ratio > 0.0 {
-                        shares *= txn.split_ratio;
-                    }
-                }
-                _ => {}
-            }
-            idx += 1;
+    let max_lines = lines.unwrap_or(8).max(1);
+    let mut output = String::new();
+    for (idx, line) in full_content.lines().enumerate() {
+        if idx >= max_lines {
+            break;
         }
-
-        results.push((
-            price.date.format("%Y-%m-%d").to_string(),
-            price.close,
-            shares,
-        ));
+        output.push_str(line);
+        output.push('\n');
     }
+    Ok(output)
+}
 
-    results
+#[derive(Serialize)]
+struct DuplicatePriceRow {
+    line: usize,
+    close: Option<f64>,
+    source: String,
+    updated_at: String,
 }
 
-fn load_price_records(app_handle: &tauri::AppHandle) -> Result<Vec<PriceRecordEntry>, String> {
-    let mut records = Vec::new();
+#[derive(Serialize)]
+struct DuplicatePriceGroup {
+    symbol: String,
+    date: String,
+    rows: Vec<DuplicatePriceRow>,
+    suggested_keep_line: usize,
+}
 
-    let prices_dir = match get_prices_dir(app_handle) {
-        Ok(dir) => dir,
-        Err(_) => return Ok(records),
-    };
+struct RawPriceFile {
+    headers: csv::StringRecord,
+    rows: Vec<(usize, csv::StringRecord)>,
+}
 
-    let entries = match std::fs::read_dir(&prices_dir) {
-        Ok(e) => e,
-        Err(_) => return Ok(records),
-    };
+fn read_raw_price_file(path: &Path) -> Result<RawPriceFile, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .flexible(true)
+        .from_path(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let headers = reader
+        .headers()
+        .map_err(|e| format!("Failed to read header of {}: {}", path.display(), e))?
+        .clone();
 
-    for entry in entries.flatten() {
-        let path = entry.path();
-        if !path.is_file() || path.extension().and_then(|s| s.to_str()) != Some("csv") {
-            continue;
+    let mut rows = Vec::new();
+    for (idx, result) in reader.records().enumerate() {
+        let record = result.map_err(|e| format!("Invalid row in {}: {}", path.display(), e))?;
+        // Line numbers are 1-based and include the header.
+        rows.push((idx + 2, record));
+    }
+    Ok(RawPriceFile { headers, rows })
+}
+
+/// Groups rows sharing a date; the suggested survivor is the newest `updated_at`,
+/// with later rows winning ties.
+fn find_duplicates_in_file(symbol: &str, file: &RawPriceFile) -> Vec<DuplicatePriceGroup> {
+    let column = |name: &str| file.headers.iter().position(|h| h.trim() == name);
+    let date_idx = column("date").unwrap_or(0);
+    let close_idx = column("close").unwrap_or(1);
+    let source_idx = column("source");
+    let updated_idx = column("updated_at");
+
+    let mut by_date: HashMap<String, Vec<&(usize, csv::StringRecord)>> = HashMap::new();
+    for row in &file.rows {
+        let date = row.1.get(date_idx).unwrap_or("").trim().to_string();
+        if !date.is_empty() {
+            by_date.entry(date).or_default().push(row);
         }
+    }
 
-        let filename = match path.file_stem().and_then(|s| s.to_str()) {
-            Some(f) => f.replace('_', ":"),
-            None => continue,
-        };
+    let mut groups: Vec<DuplicatePriceGroup> = by_date
+        .into_iter()
+        .filter(|(_, rows)| rows.len() > 1)
+        .map(|(date, rows)| {
+            let field = |record: &csv::StringRecord, idx: Option<usize>| {
+                idx.and_then(|i| record.get(i))
+                    .unwrap_or("")
+                    .trim()
+                    .to_string()
+            };
+            let suggested_keep_line = rows
+                .iter()
+                .max_by_key(|(line, record)| {
+                    (
+                        parse_updated_at_timestamp(&field(record, updated_idx)),
+                        *line,
+                    )
+                })
+                .map(|(line, _)| *line)
+                .unwrap_or(0);
+            DuplicatePriceGroup {
+                symbol: symbol.to_string(),
+                date,
+                rows: rows
+                    .iter()
+                    .map(|(line, record)| DuplicatePriceRow {
+                        line: *line,
+                        close: record
+                            .get(close_idx)
+                            .and_then(|v| v.trim().parse::<f64>().ok()),
+                        source: field(record, source_idx),
+                        updated_at: field(record, updated_idx),
+                    })
+                    .collect(),
+                suggested_keep_line,
+            }
+        })
+        .collect();
+    groups.sort_by(|a, b| a.date.cmp(&b.date));
+    groups
+}
 
-        let mut reader = match csv::ReaderBuilder::new().has_headers(true).from_path(&path) {
-            Ok(r) => r,
-            Err(_) => continue,
+#[tauri::command]
+fn find_duplicate_price_rows(
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<DuplicatePriceGroup>, AppError> {
+    let prices_dir = get_prices_dir(&app_handle)?;
+    let mut groups = Vec::new();
+
+    let entries = std::fs::read_dir(&prices_dir)
+        .map_err(|e| format!("Failed to read prices directory: {}", e))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_file() || path.extension().is_none_or(|e| e != "csv") {
+            continue;
+        }
+        let symbol = match path.file_stem().and_then(|s| s.to_str()) {
+            Some(stem) => symbol_from_file_stem(stem),
+            None => continue,
         };
+        match read_raw_price_file(&path) {
+            Ok(file) => groups.extend(find_duplicates_in_file(&symbol, &file)),
+            Err(e) => eprintln!("[RUST] ⚠ Skipping {}: {}", path.display(), e),
+        }
+    }
 
-        for result in reader.records() {
-            let record = match result {
-                Ok(r) => r,
-                Err(_) => continue,
-            };
+    groups.sort_by(|a, b| a.symbol.cmp(&b.symbol).then(a.date.cmp(&b.date)));
+    Ok(groups)
+}
 
-            if record.len() < 3 {
-                continue;
-            }
+#[derive(Serialize)]
+struct RepairPriceFileResult {
+    symbol: String,
+    duplicates_removed: usize,
+    rows_remaining: usize,
+}
 
-            let date_str = record.get(0).unwrap_or("").trim();
-            let date = match NaiveDate::parse_from_str(date_str, "%Y-%m-%d") {
-                Ok(d) => d,
-                Err(_) => continue,
-            };
+#[tauri::command]
+fn repair_price_file(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+) -> Result<RepairPriceFileResult, AppError> {
+    let prices_dir = get_prices_dir(&app_handle)?;
+    let safe_symbol = symbol_file_stem(&symbol);
+    let path = prices_dir.join(format!("{}.csv", safe_symbol));
+    if !path.exists() {
+        return Err(AppError::not_found(
+            symbol.to_string(),
+            format!("Price history not found for {}", symbol),
+        ));
+    }
 
-            let close = parse_f64_str(record.get(1).unwrap_or("").trim()).unwrap_or(0.0);
-            let open = record.get(2).and_then(|v| parse_f64_str(v.trim()));
-            let high = record.get(3).and_then(|v| parse_f64_str(v.trim()));
-            let low = record.get(4).and_then(|v| parse_f64_str(v.trim()));
-            let volume = record.get(5).and_then(|v| parse_f64_str(v.trim()));
-            let source = record.get(6).unwrap_or("manual").trim().to_string();
+    with_file_lock(&app_handle, &path, || {
+        let file = read_raw_price_file(&path)?;
+        let groups = find_duplicates_in_file(&symbol, &file);
+        let dropped: std::collections::HashSet<usize> = groups
+            .iter()
+            .flat_map(|group| {
+                group
+                    .rows
+                    .iter()
+                    .map(|row| row.line)
+                    .filter(move |line| *line != group.suggested_keep_line)
+            })
+            .collect();
 
-            records.push(PriceRecordEntry {
-                symbol: filename.clone(),
-                date,
-                close,
-                open,
-                high,
-                low,
-                volume,
-                adjusted_close: None,
-                split_unadjusted_close: None,
-                source,
+        if dropped.is_empty() {
+            return Ok(RepairPriceFileResult {
+                symbol,
+                duplicates_removed: 0,
+                rows_remaining: file.rows.len(),
             });
         }
-    }
 
-    Ok(records)
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        writer
+            .write_record(&file.headers)
+            .map_err(|e| format!("Failed to write header: {}", e))?;
+        let mut rows_remaining = 0;
+        for (line, record) in &file.rows {
+            if dropped.contains(line) {
+                continue;
+            }
+            writer
+                .write_record(record)
+                .map_err(|e| format!("Failed to write row: {}", e))?;
+            rows_remaining += 1;
+        }
+        let content = writer
+            .into_inner()
+            .map_err(|e| format!("Failed to build price file: {}", e))?;
+        atomic_write(&app_handle, &path, &content)?;
+
+        Ok(RepairPriceFileResult {
+            symbol,
+            duplicates_removed: dropped.len(),
+            rows_remaining,
+        })
+    })
 }
 
-fn save_price_records(
-    app_handle: &tauri::AppHandle,
-    price_map: &HashMap<String, Vec<PriceRecordEntry>>,
-) -> Result<(), String> {
-    for (symbol, records) in price_map.iter() {
-        let mut entries = records.clone();
-        entries.sort_by(|a, b| b.date.cmp(&a.date));
+#[tauri::command]
+fn list_price_files(app_handle: tauri::AppHandle) -> Result<Vec<String>, AppError> {
+    let prices_dir = get_prices_dir(&app_handle)?;
+    let mut symbols = Vec::new();
 
-        let csv_content = build_price_csv_content(&entries);
-        persist_price_file_content(app_handle, symbol, &csv_content)?;
+    if let Ok(entries) = std::fs::read_dir(&prices_dir) {
+        for entry in entries.flatten() {
+            if let Some(filename) = entry.file_name().to_str() {
+                if filename.ends_with(".csv") {
+                    let symbol = symbol_from_file_stem(filename.trim_end_matches(".csv"));
+                    symbols.push(symbol);
+                }
+            }
+        }
     }
-    Ok(())
+
+    symbols.sort();
+    Ok(symbols)
 }
 
-fn sync_full_history(app_handle: &tauri::AppHandle) -> Result<(), String> {
-    write_worker_log(app_handle, "History worker started")?;
-    let transactions = load_all_transactions(app_handle)?;
-    if transactions.is_empty() {
-        write_worker_log(app_handle, "No transactions found; skipping history sync")?;
-        return Ok(());
-    }
+#[derive(Serialize)]
+struct PriceFileMetadata {
+    symbol: String,
+    file_size_bytes: u64,
+    row_count: u32,
+    earliest_date: Option<String>,
+    latest_date: Option<String>,
+    last_modified: String,
+    source_breakdown: HashMap<String, u32>,
+}
 
-    let mut earliest_by_symbol: HashMap<String, NaiveDate> = HashMap::new();
-    for txn in &transactions {
-        if txn.stock.trim().is_empty() {
+/// Size, row count, date range and per-source row counts for one price file. Only
+/// the date and source columns are looked at; rows without a date are not counted.
+fn price_file_metadata(path: &Path, symbol: String) -> Result<PriceFileMetadata, String> {
+    let meta = std::fs::metadata(path)
+        .map_err(|e| format!("Failed to read metadata for {}: {}", path.display(), e))?;
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_path(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let headers = reader
+        .headers()
+        .map_err(|e| format!("Failed to read header of {}: {}", path.display(), e))?
+        .clone();
+    let date_idx = headers.iter().position(|h| h.trim() == "date").unwrap_or(0);
+    let source_idx = headers.iter().position(|h| h.trim() == "source");
+
+    let mut row_count = 0u32;
+    let mut earliest: Option<String> = None;
+    let mut latest: Option<String> = None;
+    let mut source_breakdown: HashMap<String, u32> = HashMap::new();
+    let mut record = csv::StringRecord::new();
+    while reader
+        .read_record(&mut record)
+        .map_err(|e| format!("Invalid row in {}: {}", path.display(), e))?
+    {
+        let date = record.get(date_idx).unwrap_or("").trim();
+        if date.is_empty() {
             continue;
         }
-        let date = NaiveDate::parse_from_str(txn.date.trim(), "%Y-%m-%d")
-            .map_err(|e| format!("Invalid transaction date {}: {}", txn.date, e))?;
-        earliest_by_symbol
-            .entry(txn.stock.trim().to_string())
-            .and_modify(|d| {
-                if date < *d {
-                    *d = date;
-                }
-            })
-            .or_insert(date);
+        row_count += 1;
+        if earliest.as_deref().is_none_or(|d| date < d) {
+            earliest = Some(date.to_string());
+        }
+        if latest.as_deref().is_none_or(|d| date > d) {
+            latest = Some(date.to_string());
+        }
+        let source = source_idx
+            .and_then(|idx| record.get(idx))
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .unwrap_or("yahoo_finance");
+        *source_breakdown.entry(source.to_string()).or_insert(0) += 1;
     }
 
-    let mut price_records = load_price_records(app_handle)?;
-    let mut price_map: HashMap<String, Vec<PriceRecordEntry>> = HashMap::new();
-    for record in price_records.drain(..) {
-        price_map
-            .entry(record.symbol.clone())
-            .or_default()
-            .push(record);
-    }
+    Ok(PriceFileMetadata {
+        symbol,
+        file_size_bytes: meta.len(),
+        row_count,
+        earliest_date: earliest,
+        latest_date: latest,
+        last_modified: meta
+            .modified()
+            .map(|t| DateTime::<Utc>::from(t).to_rfc3339())
+            .unwrap_or_default(),
+        source_breakdown,
+    })
+}
 
-    for (symbol, date) in earliest_by_symbol.iter() {
-        write_worker_log(
-            app_handle,
-            &format!("Syncing history for {} from {}", symbol, date),
-        )?;
-        match ensure_history_for_symbol(app_handle, &mut price_map, symbol, *date) {
-            Ok(()) => {
-                write_worker_log(app_handle, &format!("Finished {}", symbol))?;
-            }
-            Err(err) => {
-                if err.contains("US tickers") {
-                    write_worker_log(app_handle, &format!("Skipped {}: {}", symbol, err))?;
-                } else {
-                    write_worker_log(app_handle, &format!("Failed to sync {}: {}", symbol, err))?;
-                }
-            }
+/// Metadata for every file in the prices directory, override files included,
+/// sorted by symbol.
+#[tauri::command]
+fn get_price_file_metadata(
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<PriceFileMetadata>, AppError> {
+    let prices_dir = get_prices_dir(&app_handle)?;
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(&prices_dir)?.flatten() {
+        let path = entry.path();
+        let Some(filename) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        if let Some(stem) = filename.strip_suffix(".csv") {
+            let symbol = symbol_from_file_stem(stem);
+            files.push(price_file_metadata(&path, symbol)?);
         }
     }
+    files.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+    Ok(files)
+}
+
+#[derive(Serialize)]
+struct DataSourceSummary {
+    symbol: String,
+    sources: HashMap<String, usize>,
+    primary_source: String,
+}
+
+/// Row counts per `source` value in one price file, streamed a record at a time
+/// without parsing the other columns. Files without a source column, and rows
+/// with it blank, count as `yahoo_finance` as in `price_file_metadata`.
+fn price_file_source_counts(path: &Path) -> Result<HashMap<String, usize>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_path(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let source_idx = reader
+        .headers()
+        .map_err(|e| format!("Failed to read header of {}: {}", path.display(), e))?
+        .iter()
+        .position(|h| h.trim() == "source");
 
-    for records in price_map.values_mut() {
-        records.sort_by(|a, b| b.date.cmp(&a.date));
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    let mut record = csv::StringRecord::new();
+    while reader
+        .read_record(&mut record)
+        .map_err(|e| format!("Invalid row in {}: {}", path.display(), e))?
+    {
+        let source = source_idx
+            .and_then(|idx| record.get(idx))
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .unwrap_or("yahoo_finance");
+        *counts.entry(source.to_string()).or_insert(0) += 1;
     }
-    let total_rows: usize = price_map.values().map(|v| v.len()).sum();
-    write_worker_log(app_handle, &format!("Saving {} price rows", total_rows))?;
-    save_price_records(app_handle, &price_map)?;
-    write_worker_log(app_handle, "History worker completed")?;
-    Ok(())
+    Ok(counts)
 }
 
+/// Which data sources back each symbol's price file, with the source holding the
+/// most rows as primary (ties go to the alphabetically first). Override files are
+/// skipped; a file with no rows has an empty primary source.
 #[tauri::command]
-fn proxy_get(url: String) -> Result<String, String> {
-    let parsed = url::Url::parse(&url).map_err(|e| format!("Invalid URL: {}", e))?;
-    let host = parsed.host_str().unwrap_or("").to_lowercase();
+fn get_data_sources(app_handle: tauri::AppHandle) -> Result<Vec<DataSourceSummary>, AppError> {
+    let prices_dir = get_prices_dir(&app_handle)?;
+    let mut summaries = Vec::new();
+    for entry in std::fs::read_dir(&prices_dir)?.flatten() {
+        let path = entry.path();
+        let Some(stem) = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .and_then(|n| n.strip_suffix(".csv"))
+        else {
+            continue;
+        };
+        if stem.ends_with("-override") {
+            continue;
+        }
+        let sources = price_file_source_counts(&path)?;
+        let primary_source = sources
+            .iter()
+            .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+            .map(|(source, _)| source.clone())
+            .unwrap_or_default();
+        summaries.push(DataSourceSummary {
+            symbol: symbol_from_file_stem(stem),
+            sources,
+            primary_source,
+        });
+    }
+    summaries.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+    Ok(summaries)
+}
 
-    let allowed_hosts = [
-        "query1.finance.yahoo.com",
-        "query2.finance.yahoo.com",
-        "finance.yahoo.com",
-        "yfapi.net",
-    ];
+#[tauri::command]
+fn read_price_override_file(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+) -> Result<String, AppError> {
+    let prices_dir = get_prices_dir(&app_handle)?;
+    let safe_symbol = symbol_file_stem(&symbol);
+    let file_path = prices_dir.join(format!("{}-override.csv", safe_symbol));
 
-    if !allowed_hosts.iter().any(|h| h.eq_ignore_ascii_case(&host)) {
-        return Err(format!("Host not allowed: {}", host));
+    if !file_path.exists() {
+        return Ok(String::new());
     }
 
-    let client = reqwest::blocking::Client::builder()
-        .timeout(Duration::from_secs(10))
-        .user_agent("portfolio-manager-desktop/1.0")
-        .build()
-        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+    Ok(read_to_string(&file_path)
+        .map_err(|e| format!("Failed to read price override file for '{}': {}", symbol, e))?)
+}
 
-    let response = client
-        .get(parsed)
-        .send()
-        .map_err(|e| format!("Request failed: {}", e))?;
+#[tauri::command]
+fn write_price_override_file(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+    content: String,
+) -> Result<(), AppError> {
+    let prices_dir = get_prices_dir(&app_handle)?;
+    let safe_symbol = symbol_file_stem(&symbol);
+    let file_path = prices_dir.join(format!("{}-override.csv", safe_symbol));
 
-    let status = response.status();
-    let body = response
-        .text()
-        .map_err(|e| format!("Failed to read response body: {}", e))?;
+    Ok(write(&file_path, content).map_err(|e| {
+        format!(
+            "Failed to write price override file for '{}': {}",
+            symbol, e
+        )
+    })?)
+}
 
-    if !status.is_success() {
-        return Err(format!("Upstream error {}: {}", status, body));
-    }
+#[tauri::command]
+fn write_split_file(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+    content: String,
+) -> Result<(), AppError> {
+    let splits_dir = get_splits_dir(&app_handle)?;
+    let safe_symbol = symbol_file_stem(&symbol);
+    let file_path = splits_dir.join(format!("{}.csv", safe_symbol));
 
-    Ok(body)
+    // Accept legacy single-ratio content but always persist the numerator/denominator schema.
+    let content = convert_legacy_split_content(&content).unwrap_or(content);
+    atomic_write(&app_handle, &file_path, content.as_bytes())
+        .map_err(|e| format!("Failed to write split file for '{}': {}", symbol, e))?;
+
+    // Prices are assumed adjusted already; only the quoted closes follow the new splits.
+    let price_path = get_prices_dir(&app_handle)?.join(format!("{}.csv", safe_symbol));
+    if price_path.exists() {
+        let split_events = load_split_events(&app_handle, &symbol)?;
+        rewrite_split_adjusted_prices(&app_handle, &price_path, &split_events, None)?;
+        invalidate_coverage_cache(&app_handle);
+    }
+    Ok(())
 }
 
-#[derive(Serialize, Deserialize)]
-struct StockDataCoverage {
-    ticker: String,
-    exchange: String,
-    currency: String,
-    earliest_transaction: String,
-    earliest_price: Option<String>,
-    latest_price: Option<String>,
-    total_days: i32,
-    missing_days: i32,
-    coverage_percent: f64,
-    split_count: i32,
-    last_split: Option<String>,
-    status: String,
-    delist_reason: Option<String>,
+/// Rows `record_spinoff_event` used to append to the parent's split file: a 1:1
+/// split with a sixth `spinoff` field. They are not splits and are skipped on read.
+fn is_spinoff_marker<'a>(mut fields: impl Iterator<Item = &'a str>) -> bool {
+    fields.nth(5).is_some_and(|kind| kind.trim() == "spinoff")
 }
 
-#[derive(Serialize, Deserialize)]
-struct SplitHistory {
-    ticker: String,
-    date: String,
-    numerator: i32,
-    denominator: i32,
-    ratio: String,
-    ratio_factor: f64,
-    before_price: Option<f64>,
-    after_price: Option<f64>,
+/// Split file content without spin-off marker rows, or `None` when there are none.
+fn strip_spinoff_markers(content: &str) -> Option<String> {
+    if !content
+        .lines()
+        .any(|line| is_spinoff_marker(line.split(',')))
+    {
+        return None;
+    }
+    Some(
+        content
+            .lines()
+            .filter(|line| !is_spinoff_marker(line.split(',')))
+            .map(|line| format!("{}\n", line))
+            .collect(),
+    )
 }
 
-fn parse_ratio_components(ratio: &str) -> (i32, i32) {
-    let trimmed = ratio.trim();
-    if trimmed.is_empty() {
-        return (1, 1);
+/// Converts a legacy `date,ratio[,before_price,after_price]` split file into the
+/// `SPLIT_FILE_HEADER` layout. Returns `None` when the content is already current.
+fn convert_legacy_split_content(content: &str) -> Option<String> {
+    let mut lines = content.lines();
+    let header = lines.next()?;
+    let has_fractional_header = header
+        .split(',')
+        .any(|col| col.trim().eq_ignore_ascii_case("numerator"));
+    if has_fractional_header {
+        return None;
     }
 
-    if let Some((num_str, den_str)) = trimmed.split_once(':') {
-        let numerator = num_str.trim().parse::<i32>().unwrap_or(1).max(1);
-        let denominator = den_str.trim().parse::<i32>().unwrap_or(1).max(1);
-        return (numerator, denominator);
-    }
+    // Headerless legacy files start directly with a data row.
+    let first_is_data =
+        NaiveDate::parse_from_str(header.split(',').next().unwrap_or("").trim(), "%Y-%m-%d")
+            .is_ok();
 
-    if let Ok(value) = trimmed.parse::<f64>() {
-        if value > 1.0 {
-            return (value.round() as i32, 1);
-        } else if value > 0.0 {
-            let denominator = (1.0 / value).round() as i32;
-            return (1, denominator.max(1));
+    let mut output = String::from(SPLIT_FILE_HEADER);
+    let data_lines = if first_is_data { Some(header) } else { None };
+    for line in data_lines.into_iter().chain(lines) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').collect();
+        let date = fields.first().map(|s| s.trim()).unwrap_or("");
+        if date.is_empty() {
+            continue;
         }
+        let (numerator, denominator) =
+            parse_ratio_components(fields.get(1).map(|s| s.trim()).unwrap_or(""));
+        let before_price = parse_price_field(fields.get(2))
+            .map(|v| v.to_string())
+            .unwrap_or_default();
+        let after_price = parse_price_field(fields.get(3))
+            .map(|v| v.to_string())
+            .unwrap_or_default();
+        output.push_str(&format!(
+            "{},{},{},{},{}\n",
+            date, numerator, denominator, before_price, after_price
+        ));
     }
 
-    (1, 1)
+    Some(output)
 }
 
-fn parse_price_field(field: Option<&&str>) -> Option<f64> {
-    field.and_then(|value| {
-        let trimmed = value.trim();
-        if trimmed.is_empty() {
-            None
-        } else {
-            trimmed.parse::<f64>().ok()
-        }
-    })
+#[derive(Serialize)]
+struct SplitMigrationResult {
+    files_migrated: usize,
 }
 
-#[derive(Serialize, Deserialize)]
-struct DataReadinessStats {
-    total_stocks: i32,
-    complete_data: i32,
-    partial_data: i32,
-    missing_data: i32,
-    total_price_records: i32,
-    oldest_date: Option<String>,
-    newest_date: Option<String>,
-}
+/// Rewrites legacy split files into the `SPLIT_FILE_HEADER` layout and drops the
+/// spin-off marker rows older versions appended.
+#[tauri::command]
+fn migrate_split_files(app_handle: tauri::AppHandle) -> Result<SplitMigrationResult, AppError> {
+    let splits_dir = get_splits_dir(&app_handle)?;
+    let mut files_migrated = 0;
 
-#[derive(Serialize, Deserialize)]
-struct NavSnapshotEntryPayload {
-    stock: String,
-    currency: String,
-    shares: f64,
-    average_cost: f64,
-    latest_price: f64,
-    market_value: f64,
-    market_value_usd: f64,
-    status: String,
-    last_transaction: Option<String>,
-}
+    let entries = std::fs::read_dir(&splits_dir)
+        .map_err(|e| format!("Failed to read splits directory: {}", e))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_file() || path.extension().is_none_or(|e| e != "csv") {
+            continue;
+        }
 
-#[derive(Serialize, Deserialize)]
-struct NavSnapshotPayload {
-    timestamp: String,
-    base_currency: String,
-    total_value_usd: f64,
-    entries: Vec<NavSnapshotEntryPayload>,
-}
+        let migrated = with_file_lock(&app_handle, &path, || -> Result<bool, String> {
+            let content =
+                read_to_string(&path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+            let converted = convert_legacy_split_content(&content);
+            let stripped = strip_spinoff_markers(converted.as_deref().unwrap_or(&content));
+            let Some(migrated) = stripped.or(converted) else {
+                return Ok(false);
+            };
+            atomic_write(&app_handle, &path, migrated.as_bytes())?;
+            Ok(true)
+        })?;
+        if migrated {
+            files_migrated += 1;
+        }
+    }
 
-#[derive(Serialize, Deserialize)]
-struct PositionSnapshotPayload {
-    timestamp: String,
-    stock: String,
-    currency: String,
-    shares: f64,
-    average_cost: f64,
-    latest_price: f64,
-    market_value: f64,
-    market_value_usd: f64,
-    status: String,
-    last_transaction: Option<String>,
+    Ok(SplitMigrationResult { files_migrated })
 }
 
-#[tauri::command]
-fn get_data_coverage(
-    app_handle: tauri::AppHandle,
-    include_completeness: Option<bool>,
-) -> Result<String, String> {
-    let include_completeness = include_completeness.unwrap_or(true);
-    let transactions = load_all_transactions(&app_handle)?;
-    let price_records = load_price_records(&app_handle)?;
-
-    let today = Utc::now().date_naive();
-    let fifteen_years_ago = today - ChronoDuration::days(15 * 365);
+/// Data layout version recorded in `schema_version`. Version 2 is the 10-column,
+/// newest-first price file schema.
+const DATA_SCHEMA_VERSION: u32 = 2;
 
-    let mut stock_map: HashMap<String, StockDataCoverage> = HashMap::new();
+fn read_schema_version(app_handle: &tauri::AppHandle) -> Result<Option<u32>, String> {
+    let path = get_data_dir(app_handle)?.join("schema_version");
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(read_to_string(&path)
+        .map_err(|e| format!("Failed to read schema_version: {}", e))?
+        .trim()
+        .parse()
+        .ok())
+}
 
-    for txn in &transactions {
-        if txn.stock.trim().is_empty() {
-            continue;
-        }
+fn write_schema_version(app_handle: &tauri::AppHandle, version: u32) -> Result<(), String> {
+    let path = get_data_dir(app_handle)?.join("schema_version");
+    write(&path, format!("{}\n", version))
+        .map_err(|e| format!("Failed to write schema_version: {}", e))
+}
 
-        let txn_date = match NaiveDate::parse_from_str(txn.date.trim(), "%Y-%m-%d") {
-            Ok(d) => d,
-            Err(_) => continue,
-        };
+/// True when a price file's header differs from `PRICE_FILE_HEADER` or its dates are
+/// not newest first.
+fn price_file_is_legacy(content: &str) -> bool {
+    let mut lines = content.lines();
+    let header = lines.next().unwrap_or("").trim();
+    if header != PRICE_FILE_HEADER {
+        return true;
+    }
+    let dates: Vec<&str> = lines
+        .filter_map(|line| line.split(',').next())
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .collect();
+    dates.windows(2).any(|pair| pair[0] < pair[1])
+}
 
-        if txn_date < fifteen_years_ago {
-            continue;
+/// Brings rows read from a legacy price file up to the current schema: rebuilds
+/// missing `split_unadjusted_close` values from `splits`, tags rows `legacy` when
+/// the file had no source column, and sorts newest first keeping one row per date.
+fn migrate_legacy_price_records(
+    content: &str,
+    mut records: Vec<PriceRecordEntry>,
+    splits: &[(NaiveDate, f64)],
+) -> Vec<PriceRecordEntry> {
+    let has_source = content
+        .lines()
+        .next()
+        .unwrap_or("")
+        .split(',')
+        .any(|h| h.trim() == "source");
+    for record in records.iter_mut() {
+        if record.split_unadjusted_close.is_none() && record.close > 0.0 {
+            record.split_unadjusted_close =
+                Some(record.close * split_unadjust_factor(record.date, splits));
+        }
+        if !has_source {
+            record.source = LEGACY_SOURCE.to_string();
         }
+    }
+    records.sort_by(|a, b| CANONICAL_ROW_ORDER.compare(&a.date, &b.date));
+    records.dedup_by_key(|r| r.date);
+    records
+}
 
-        let (exchange, _) = get_exchange_and_symbol(&txn.stock);
-        let exchange_str = exchange.unwrap_or_else(|| "UNKNOWN".to_string());
+#[derive(Serialize)]
+struct PriceMigrationResult {
+    files_migrated: usize,
+    files_unreadable: Vec<String>,
+    schema_version: u32,
+    already_current: bool,
+}
 
-        stock_map
-            .entry(txn.stock.clone())
-            .or_insert_with(|| StockDataCoverage {
-                ticker: txn.stock.clone(),
-                exchange: exchange_str.clone(),
-                currency: txn.currency.clone(),
-                earliest_transaction: txn.date.clone(),
-                earliest_price: None,
-                latest_price: None,
-                total_days: 0,
-                missing_days: 0,
-                coverage_percent: 0.0,
-                split_count: 0,
-                last_split: None,
-                status: "missing".to_string(),
-                delist_reason: None,
-            });
+/// Rewrites legacy price files (fewer columns, another column order, or oldest
+/// first) into the current schema, newest first. Columns are matched by header name;
+/// missing ones stay empty, except `split_unadjusted_close`, which is rebuilt from
+/// the split file. Rows without a source are tagged `legacy`. Runs once per data
+/// directory: `schema_version` records the result and later calls return early.
+#[tauri::command]
+fn migrate_price_files(app_handle: tauri::AppHandle) -> Result<PriceMigrationResult, AppError> {
+    if read_schema_version(&app_handle)?.is_some_and(|v| v >= DATA_SCHEMA_VERSION) {
+        return Ok(PriceMigrationResult {
+            files_migrated: 0,
+            files_unreadable: Vec::new(),
+            schema_version: DATA_SCHEMA_VERSION,
+            already_current: true,
+        });
+    }
 
-        if let Some(coverage) = stock_map.get_mut(&txn.stock) {
-            if txn.date < coverage.earliest_transaction {
-                coverage.earliest_transaction = txn.date.clone();
+    let prices_dir = get_prices_dir(&app_handle)?;
+    let mut files_migrated = 0;
+    let mut files_unreadable = Vec::new();
+    for entry in std::fs::read_dir(&prices_dir)
+        .map_err(|e| format!("Failed to read prices directory: {}", e))?
+        .flatten()
+    {
+        let path = entry.path();
+        if !path.is_file() || path.extension().is_none_or(|e| e != "csv") {
+            continue;
+        }
+        // None when the file is already current, Some(false) when it cannot be read.
+        let migrated = with_file_lock(&app_handle, &path, || -> Result<Option<bool>, String> {
+            let content =
+                read_to_string(&path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+            if !price_file_is_legacy(&content) {
+                return Ok(None);
             }
+            let records = match scan_price_file_records(&path) {
+                Ok(records)
+                    if !records.is_empty()
+                        || !content.lines().skip(1).any(|l| !l.trim().is_empty()) =>
+                {
+                    records
+                }
+                _ => return Ok(Some(false)),
+            };
+            let symbol = records
+                .first()
+                .map(|r| r.symbol.trim_end_matches("-override").to_string())
+                .unwrap_or_default();
+            let splits = load_split_events(&app_handle, &symbol).unwrap_or_default();
+            let records = migrate_legacy_price_records(&content, records, &splits);
+
+            let migrated = build_price_csv_content(&records)?;
+            atomic_write(&app_handle, &path, migrated.as_bytes())?;
+            Ok(Some(true))
+        })?;
+        match migrated {
+            Some(true) => files_migrated += 1,
+            Some(false) => files_unreadable.push(
+                path.file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+            ),
+            None => {}
         }
     }
 
-    for (symbol, prices) in price_records
+    if files_migrated > 0 {
+        invalidate_coverage_cache(&app_handle);
+    }
+    if files_unreadable.is_empty() {
+        write_schema_version(&app_handle, DATA_SCHEMA_VERSION)?;
+    }
+    write_worker_log(
+        &app_handle,
+        &format!(
+            "Migrated {} price files to schema {} ({} unreadable)",
+            files_migrated,
+            DATA_SCHEMA_VERSION,
+            files_unreadable.len()
+        ),
+    )?;
+
+    Ok(PriceMigrationResult {
+        files_migrated,
+        schema_version: if files_unreadable.is_empty() {
+            DATA_SCHEMA_VERSION
+        } else {
+            read_schema_version(&app_handle)?.unwrap_or(1)
+        },
+        files_unreadable,
+        already_current: false,
+    })
+}
+
+#[derive(Serialize)]
+struct SymbolFileMigrationResult {
+    files_renamed: usize,
+    /// Files left alone because one already exists under the new name.
+    conflicts: Vec<String>,
+    /// Old-scheme files matching no known symbol whose name has several '_', so
+    /// which of them stood for ':' cannot be told. They need renaming by hand.
+    undecodable: Vec<String>,
+}
+
+/// Renames per-symbol files written under the old `:` -> `_` scheme to
+/// `symbol_file_stem` names. The old names cannot be decoded on their own
+/// (`NYSE_BRK_B` could be `NYSE:BRK_B` or `NYSE:BRK:B`), so only symbols the
+/// portfolio knows about, from transactions and the watchlist, are matched; other
+/// ambiguous names are reported as undecodable. Safe to run repeatedly: a symbol
+/// whose name did not change is skipped.
+fn rename_legacy_symbol_files(
+    app_handle: &tauri::AppHandle,
+) -> Result<SymbolFileMigrationResult, String> {
+    let transactions = load_all_transactions(app_handle)?;
+    let mut spellings: Vec<String> = transactions.iter().map(|txn| txn.stock.clone()).collect();
+    spellings.extend(watch_only_symbols(app_handle, &transactions));
+
+    let locations = [
+        (get_prices_dir(app_handle)?, ".csv"),
+        (get_prices_dir(app_handle)?, "-override.csv"),
+        (get_splits_dir(app_handle)?, ".csv"),
+        (get_dividends_dir(app_handle)?, ".csv"),
+        (get_navs_dir(app_handle)?, ".csv"),
+        (get_yahoo_metas_dir(app_handle)?, ".json"),
+    ];
+    let (files_renamed, conflicts) =
+        rename_symbol_files(&locations, &legacy_symbol_stems(&spellings))?;
+    let known: std::collections::HashSet<String> = spellings
         .iter()
-        .fold(HashMap::new(), |mut acc, record| {
-            acc.entry(record.symbol.clone())
-                .or_insert_with(Vec::new)
-                .push(record.clone());
-            acc
-        })
-    {
-        if let Some(coverage) = stock_map.get_mut(&symbol) {
-            if let Some(earliest) = prices.iter().map(|p| p.date).min() {
-                coverage.earliest_price = Some(earliest.format("%Y-%m-%d").to_string());
-            }
-            if let Some(latest) = prices.iter().map(|p| p.date).max() {
-                coverage.latest_price = Some(latest.format("%Y-%m-%d").to_string());
-            }
-            if include_completeness {
-                let start_date = fifteen_years_ago;
-                let total_days = (today - start_date).num_days() as i32;
+        .map(|spelling| symbol_file_stem(&canonical_symbol(spelling)))
+        .collect();
+    let undecodable = undecodable_symbol_files(&locations, &known);
 
-                let price_dates: std::collections::HashSet<NaiveDate> =
-                    prices.iter().map(|p| p.date).collect();
-                let mut missing = 0;
-                let mut current = start_date;
-
-                while current <= today {
-                    let weekday = current.weekday();
-                    if weekday != chrono::Weekday::Sat && weekday != chrono::Weekday::Sun {
-                        if !price_dates.contains(&current) {
-                            missing += 1;
-                        }
-                    }
-                    current += ChronoDuration::days(1);
-                }
+    if !undecodable.is_empty() {
+        write_worker_log(
+            app_handle,
+            &format!(
+                "{} data files have ambiguous legacy names and were not renamed: {}",
+                undecodable.len(),
+                undecodable.join(", ")
+            ),
+        )?;
+    }
+    if files_renamed > 0 {
+        invalidate_coverage_cache(app_handle);
+        write_worker_log(
+            app_handle,
+            &format!(
+                "Renamed {} data files to the current symbol filename encoding",
+                files_renamed
+            ),
+        )?;
+    }
+    Ok(SymbolFileMigrationResult {
+        files_renamed,
+        conflicts,
+        undecodable,
+    })
+}
 
-                coverage.total_days = total_days;
-                coverage.missing_days = missing;
-                coverage.coverage_percent = if total_days > 0 {
-                    ((total_days - missing) as f64 / total_days as f64) * 100.0
-                } else {
-                    0.0
-                };
+/// Files in `locations` whose stem is not the current stem of a `known` symbol and
+/// only makes sense under the old scheme: no '%' escapes but more than one '_',
+/// which `symbol_file_stem` never writes for a canonical symbol.
+fn undecodable_symbol_files(
+    locations: &[(PathBuf, &str)],
+    known: &std::collections::HashSet<String>,
+) -> Vec<String> {
+    let mut undecodable = Vec::new();
+    for (dir, suffix) in locations {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            // The plain price file suffix also matches overrides, which have their own entry.
+            if *suffix == ".csv" && name.ends_with("-override.csv") {
+                continue;
+            }
+            let Some(stem) = name.strip_suffix(suffix) else {
+                continue;
+            };
+            if !stem.contains('%') && stem.matches('_').count() > 1 && !known.contains(stem) {
+                undecodable.push(entry.path().display().to_string());
+            }
+        }
+    }
+    undecodable.sort();
+    undecodable
+}
 
-                coverage.status = if coverage.coverage_percent >= 95.0 {
-                    "complete".to_string()
-                } else if coverage.coverage_percent >= 50.0 {
-                    "partial".to_string()
-                } else {
-                    "missing".to_string()
-                };
-            } else if coverage.latest_price.is_some() {
-                coverage.coverage_percent = 100.0;
-                coverage.status = "complete".to_string();
+/// Stems older versions may have used for each symbol, keyed by the current stem:
+/// the ':'-to-'_' encoding of the canonical symbol and of every spelling it was
+/// entered as, so files saved before canonicalisation (`0700.HK.csv`,
+/// `0700_HKEX.csv`) are found too.
+fn legacy_symbol_stems(
+    spellings: &[String],
+) -> std::collections::BTreeMap<String, std::collections::BTreeSet<String>> {
+    let mut stems: std::collections::BTreeMap<String, std::collections::BTreeSet<String>> =
+        std::collections::BTreeMap::new();
+    for spelling in spellings {
+        let spelling = spelling.trim();
+        let symbol = canonical_symbol(spelling);
+        if symbol.is_empty() {
+            continue;
+        }
+        let current = symbol_file_stem(&symbol);
+        let legacy = stems.entry(current.clone()).or_default();
+        for stem in [symbol.replace(':', "_"), spelling.replace(':', "_")] {
+            if stem != current {
+                legacy.insert(stem);
             }
         }
     }
+    stems.retain(|_, legacy| !legacy.is_empty());
+    stems
+}
 
-    // Count splits from split files
-    if let Ok(splits_dir) = get_splits_dir(&app_handle) {
-        if let Ok(entries) = std::fs::read_dir(&splits_dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if !path.is_file() || !path.extension().map_or(false, |e| e == "csv") {
+/// Moves each legacy stem's files in `locations` (directory and filename suffix)
+/// to the current stem. A file whose target already exists is left in place and
+/// returned as a conflict. Returns the number of files renamed and the conflicts.
+fn rename_symbol_files(
+    locations: &[(PathBuf, &str)],
+    stems: &std::collections::BTreeMap<String, std::collections::BTreeSet<String>>,
+) -> Result<(usize, Vec<String>), String> {
+    let mut files_renamed = 0;
+    let mut conflicts = Vec::new();
+    for (current, legacy_stems) in stems {
+        for legacy in legacy_stems {
+            for (dir, suffix) in locations {
+                let from = dir.join(format!("{}{}", legacy, suffix));
+                let to = dir.join(format!("{}{}", current, suffix));
+                if !from.is_file() {
+                    continue;
+                }
+                if to.exists() {
+                    conflicts.push(from.display().to_string());
                     continue;
                 }
+                std::fs::rename(&from, &to)
+                    .map_err(|e| format!("Failed to rename {:?} to {:?}: {}", from, to, e))?;
+                files_renamed += 1;
+            }
+        }
+    }
+    Ok((files_renamed, conflicts))
+}
 
-                let filename = match path.file_stem().and_then(|s| s.to_str()) {
-                    Some(f) => f.replace('_', ":"),
-                    None => continue,
-                };
+#[tauri::command]
+fn migrate_symbol_filenames(
+    app_handle: tauri::AppHandle,
+) -> Result<SymbolFileMigrationResult, AppError> {
+    Ok(rename_legacy_symbol_files(&app_handle)?)
+}
 
-                let content = match read_to_string(&path) {
-                    Ok(c) => c,
-                    Err(_) => continue,
-                };
+#[derive(Serialize, Clone)]
+struct SchemaStatus {
+    schema_version: u32,
+    latest_version: u32,
+    migration_pending: bool,
+}
 
-                let mut split_count = 0;
-                let mut last_split_date: Option<String> = None;
+/// Schema version of the data directory. A directory without a version file is
+/// stamped current when it has no price files yet, and treated as version 1
+/// otherwise.
+fn schema_status(app_handle: &tauri::AppHandle) -> Result<SchemaStatus, String> {
+    let version = match read_schema_version(app_handle)? {
+        Some(version) => version,
+        None => {
+            let has_prices = std::fs::read_dir(get_prices_dir(app_handle)?)
+                .map_err(|e| format!("Failed to read prices directory: {}", e))?
+                .flatten()
+                .any(|entry| entry.path().extension().is_some_and(|e| e == "csv"));
+            if has_prices {
+                1
+            } else {
+                write_schema_version(app_handle, DATA_SCHEMA_VERSION)?;
+                DATA_SCHEMA_VERSION
+            }
+        }
+    };
+    Ok(SchemaStatus {
+        schema_version: version,
+        latest_version: DATA_SCHEMA_VERSION,
+        migration_pending: version < DATA_SCHEMA_VERSION,
+    })
+}
 
-                for (idx, line) in content.lines().enumerate() {
-                    if idx == 0 || line.trim().is_empty() {
-                        continue;
-                    }
+#[tauri::command]
+fn get_schema_status(app_handle: tauri::AppHandle) -> Result<SchemaStatus, AppError> {
+    Ok(schema_status(&app_handle)?)
+}
 
-                    let fields: Vec<&str> = line.split(',').collect();
-                    if fields.len() >= 2 {
-                        split_count += 1;
-                        let date = fields[0].to_string();
-                        if last_split_date.is_none() || date > *last_split_date.as_ref().unwrap() {
-                            last_split_date = Some(date);
-                        }
-                    }
-                }
+#[tauri::command]
+fn read_split_file(app_handle: tauri::AppHandle, symbol: String) -> Result<String, AppError> {
+    let splits_dir = get_splits_dir(&app_handle)?;
+    let safe_symbol = symbol_file_stem(&symbol);
+    let file_path = splits_dir.join(format!("{}.csv", safe_symbol));
 
-                if let Some(coverage) = stock_map.get_mut(&filename) {
-                    coverage.split_count = split_count;
-                    coverage.last_split = last_split_date;
-                }
-            }
-        }
+    if !file_path.exists() {
+        return Ok(String::new());
     }
 
-    let coverage_list: Vec<StockDataCoverage> = stock_map.into_values().collect();
-    serde_json::to_string(&coverage_list)
-        .map_err(|e| format!("Failed to serialize coverage: {}", e))
+    Ok(read_to_string(&file_path)
+        .map_err(|e| format!("Failed to read split file for '{}': {}", symbol, e))?)
 }
 
 #[tauri::command]
-fn get_split_history(app_handle: tauri::AppHandle) -> Result<String, String> {
-    let mut splits: Vec<SplitHistory> = Vec::new();
-    let splits_dir = match get_splits_dir(&app_handle) {
-        Ok(dir) => dir,
-        Err(_) => return Ok(serde_json::to_string(&splits).unwrap()),
-    };
+fn list_split_files(app_handle: tauri::AppHandle) -> Result<Vec<String>, AppError> {
+    let splits_dir = get_splits_dir(&app_handle)?;
+    let mut symbols = Vec::new();
 
     if let Ok(entries) = std::fs::read_dir(&splits_dir) {
         for entry in entries.flatten() {
-            let path = entry.path();
-            if !path.is_file() || !path.extension().map_or(false, |e| e == "csv") {
-                continue;
+            if let Some(filename) = entry.file_name().to_str() {
+                if filename.ends_with(".csv") {
+                    let symbol = symbol_from_file_stem(filename.trim_end_matches(".csv"));
+                    symbols.push(symbol);
+                }
             }
+        }
+    }
 
-            let filename = match path.file_stem().and_then(|s| s.to_str()) {
-                Some(f) => f.replace('_', ":"),
-                None => continue,
-            };
+    symbols.sort();
+    Ok(symbols)
+}
 
-            let content = match read_to_string(&path) {
-                Ok(c) => c,
-                Err(_) => continue,
-            };
+#[tauri::command]
+fn write_dividend_file(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+    content: String,
+) -> Result<(), AppError> {
+    let dividends_dir = get_dividends_dir(&app_handle)?;
+    let safe_symbol = symbol_file_stem(&symbol);
+    let file_path = dividends_dir.join(format!("{}.csv", safe_symbol));
 
-            let mut lines = content.lines();
-            let header = lines.next().unwrap_or("");
-            let has_fractional_header = header
-                .split(',')
-                .any(|col| col.trim().eq_ignore_ascii_case("numerator"));
+    Ok(write(&file_path, content)
+        .map_err(|e| format!("Failed to write dividend file for '{}': {}", symbol, e))?)
+}
 
-            for line in lines {
-                if line.trim().is_empty() {
-                    continue;
-                }
-
-                let fields: Vec<&str> = line.split(',').collect();
-                if fields.is_empty() {
-                    continue;
-                }
+#[tauri::command]
+fn read_dividend_file(app_handle: tauri::AppHandle, symbol: String) -> Result<String, AppError> {
+    let dividends_dir = get_dividends_dir(&app_handle)?;
+    let safe_symbol = symbol_file_stem(&symbol);
+    let file_path = dividends_dir.join(format!("{}.csv", safe_symbol));
 
-                let date = fields.get(0).map(|s| s.trim()).unwrap_or("");
-                if date.is_empty() {
-                    continue;
-                }
+    if !file_path.exists() {
+        return Ok(String::new());
+    }
 
-                let (numerator, denominator, before_price, after_price) = if has_fractional_header {
-                    let numerator = fields
-                        .get(1)
-                        .and_then(|s| s.trim().parse::<i32>().ok())
-                        .unwrap_or(1)
-                        .max(1);
-                    let denominator = fields
-                        .get(2)
-                        .and_then(|s| s.trim().parse::<i32>().ok())
-                        .unwrap_or(1)
-                        .max(1);
-                    let before_price = parse_price_field(fields.get(3));
-                    let after_price = parse_price_field(fields.get(4));
-                    (numerator, denominator, before_price, after_price)
-                } else {
-                    let ratio_str = fields.get(1).map(|s| s.trim()).unwrap_or("");
-                    let (numerator, denominator) = parse_ratio_components(ratio_str);
-                    let before_price = parse_price_field(fields.get(2));
-                    let after_price = parse_price_field(fields.get(3));
-                    (numerator, denominator, before_price, after_price)
-                };
+    Ok(read_to_string(&file_path)
+        .map_err(|e| format!("Failed to read dividend file for '{}': {}", symbol, e))?)
+}
 
-                let ratio = format!("{}:{}", numerator, denominator);
-                let ratio_factor = numerator as f64 / denominator as f64;
+#[tauri::command]
+fn list_dividend_files(app_handle: tauri::AppHandle) -> Result<Vec<String>, AppError> {
+    let dividends_dir = get_dividends_dir(&app_handle)?;
+    let mut symbols = Vec::new();
 
-                splits.push(SplitHistory {
-                    ticker: filename.clone(),
-                    date: date.to_string(),
-                    numerator,
-                    denominator,
-                    ratio,
-                    ratio_factor,
-                    before_price,
-                    after_price,
-                });
+    if let Ok(entries) = std::fs::read_dir(&dividends_dir) {
+        for entry in entries.flatten() {
+            if let Some(filename) = entry.file_name().to_str() {
+                if filename.ends_with(".csv") {
+                    let symbol = symbol_from_file_stem(filename.trim_end_matches(".csv"));
+                    symbols.push(symbol);
+                }
             }
         }
     }
 
-    splits.sort_by(|a, b| b.date.cmp(&a.date));
+    symbols.sort();
+    Ok(symbols)
+}
+
+fn load_dividend_events(
+    app_handle: &tauri::AppHandle,
+    symbol: &str,
+) -> Result<Vec<(NaiveDate, f64, String)>, AppError> {
+    let dividends_dir = get_dividends_dir(app_handle)?;
+    let safe_symbol = symbol_file_stem(symbol);
+    let path = dividends_dir.join(format!("{}.csv", safe_symbol));
+
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let file = path.display().to_string();
+    let mut events = Vec::new();
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .from_path(&path)
+        .map_err(|e| {
+            AppError::csv(
+                &file,
+                &e,
+                format!("Failed to read dividend file for {}: {}", symbol, e),
+            )
+        })?;
+
+    for result in reader.records() {
+        let record =
+            result.map_err(|e| AppError::csv(&file, &e, format!("Invalid dividend row: {}", e)))?;
+        let date = match NaiveDate::parse_from_str(record.get(0).unwrap_or("").trim(), "%Y-%m-%d") {
+            Ok(d) => d,
+            Err(_) => continue,
+        };
+        let amount = match record.get(1).and_then(|v| v.trim().parse::<f64>().ok()) {
+            Some(a) => a,
+            None => continue,
+        };
+        let currency = match record.get(2).map(str::trim).filter(|c| !c.is_empty()) {
+            Some(c) => c.to_string(),
+            None => currency_for_exchange(get_exchange_and_symbol(symbol).0.as_deref()).to_string(),
+        };
+        events.push((date, amount, currency));
+    }
 
-    serde_json::to_string(&splits).map_err(|e| format!("Failed to serialize split history: {}", e))
+    events.sort_by_key(|(date, _, _)| *date);
+    Ok(events)
 }
 
-#[tauri::command]
-fn get_data_stats(app_handle: tauri::AppHandle) -> Result<String, String> {
-    let transactions = load_all_transactions(&app_handle)?;
-    let price_records = load_price_records(&app_handle)?;
+#[derive(Serialize)]
+struct DividendEntry {
+    symbol: String,
+    ex_date: String,
+    amount: f64,
+    currency: String,
+    amount_base: f64,
+    base_currency: String,
+}
 
-    let unique_stocks: std::collections::HashSet<String> =
-        transactions.iter().map(|t| t.stock.clone()).collect();
+/// Dividends for a company listed under several symbols, oldest first with amounts
+/// also converted to the base currency. A payment recorded under two symbols in the same currency is kept once;
+/// the same ex-date in different currencies is kept per currency.
+#[tauri::command]
+fn get_all_dividends_for_company(
+    app_handle: tauri::AppHandle,
+    symbols: Vec<String>,
+) -> Result<Vec<DividendEntry>, AppError> {
+    let base = configured_base_currency(&app_handle);
+    let mut fx = FxConverter::new(&app_handle);
+    let mut seen = std::collections::HashSet::new();
+    let mut entries = Vec::new();
+    for symbol in symbols.iter().map(|s| s.trim()).filter(|s| !s.is_empty()) {
+        for (date, amount, currency) in load_dividend_events(&app_handle, symbol)? {
+            if !seen.insert((date, currency.to_uppercase(), amount.to_bits())) {
+                continue;
+            }
+            entries.push(DividendEntry {
+                symbol: symbol.to_string(),
+                ex_date: date.to_string(),
+                amount,
+                amount_base: fx.convert(amount, &currency, &base, date)?,
+                currency,
+                base_currency: base.clone(),
+            });
+        }
+    }
+    entries.sort_by(|a, b| {
+        a.ex_date
+            .cmp(&b.ex_date)
+            .then_with(|| a.symbol.cmp(&b.symbol))
+    });
+    Ok(entries)
+}
 
-    let oldest_date = price_records
-        .iter()
-        .map(|p| p.date)
-        .min()
-        .map(|d| d.format("%Y-%m-%d").to_string());
+#[derive(Serialize, Deserialize, Clone)]
+struct PreferredHolding {
+    symbol: String,
+    par_value: f64,
+    coupon_rate: f64,
+    issue_date: NaiveDate,
+    maturity_date: Option<NaiveDate>,
+    current_price: f64,
+}
 
-    let newest_date = price_records
-        .iter()
-        .map(|p| p.date)
-        .max()
-        .map(|d| d.format("%Y-%m-%d").to_string());
+fn preferred_holdings_path(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+    Ok(get_data_dir(app_handle)?.join("preferred_holdings.csv"))
+}
 
-    let coverage = serde_json::from_str::<Vec<StockDataCoverage>>(&get_data_coverage(
-        app_handle.clone(),
-        None,
-    )?)
-    .unwrap_or_default();
+fn load_preferred_holdings(app_handle: &tauri::AppHandle) -> Result<Vec<PreferredHolding>, String> {
+    let path = preferred_holdings_path(app_handle)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
 
-    let complete_data = coverage.iter().filter(|c| c.status == "complete").count() as i32;
-    let partial_data = coverage.iter().filter(|c| c.status == "partial").count() as i32;
-    let missing_data = coverage.iter().filter(|c| c.status == "missing").count() as i32;
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .from_path(&path)
+        .map_err(|e| format!("Failed to read preferred_holdings.csv: {}", e))?;
 
-    let stats = DataReadinessStats {
-        total_stocks: unique_stocks.len() as i32,
-        complete_data,
-        partial_data,
-        missing_data,
-        total_price_records: price_records.len() as i32,
-        oldest_date,
-        newest_date,
-    };
+    reader
+        .deserialize::<PreferredHolding>()
+        .map(|result| result.map_err(|e| format!("Invalid row in preferred_holdings.csv: {}", e)))
+        .collect()
+}
 
-    serde_json::to_string(&stats).map_err(|e| format!("Failed to serialize stats: {}", e))
+fn find_preferred_holding(
+    app_handle: &tauri::AppHandle,
+    symbol: &str,
+) -> Result<PreferredHolding, String> {
+    load_preferred_holdings(app_handle)?
+        .into_iter()
+        .find(|h| h.symbol == symbol)
+        .ok_or_else(|| format!("No preferred holding found for {}", symbol))
 }
 
 #[tauri::command]
-fn save_nav_snapshot(
+fn write_preferred_holding(
     app_handle: tauri::AppHandle,
-    snapshot: NavSnapshotPayload,
-) -> Result<String, String> {
-    let navs_dir = get_navs_dir(&app_handle)?;
-    let safe_id = sanitize_timestamp(&snapshot.timestamp);
-    let file_path = navs_dir.join(format!("nav_{}.json", safe_id));
-    let content = serde_json::to_string_pretty(&snapshot)
-        .map_err(|e| format!("Failed to serialize NAV snapshot: {}", e))?;
-
-    write(&file_path, content).map_err(|e| format!("Failed to write NAV snapshot: {}", e))?;
+    holding: PreferredHolding,
+) -> Result<(), AppError> {
+    if holding.symbol.trim().is_empty() {
+        return Err(AppError::validation("symbol", "Symbol cannot be empty"));
+    }
+    if holding.par_value <= 0.0 {
+        return Err(AppError::validation(
+            "par_value",
+            "Par value must be positive",
+        ));
+    }
+    if holding.current_price <= 0.0 {
+        return Err(AppError::validation(
+            "current_price",
+            "Current price must be positive",
+        ));
+    }
+    if holding.coupon_rate < 0.0 {
+        return Err(AppError::validation(
+            "coupon_rate",
+            "Coupon rate cannot be negative",
+        ));
+    }
+    if let Some(maturity) = holding.maturity_date {
+        if maturity <= holding.issue_date {
+            return Err(AppError::validation(
+                "maturity_date",
+                "Maturity date must be after issue date",
+            ));
+        }
+    }
 
-    Ok(file_path.to_string_lossy().to_string())
+    let path = preferred_holdings_path(&app_handle)?;
+    Ok(with_file_lock(&app_handle, &path, || {
+        let mut holdings = load_preferred_holdings(&app_handle)?;
+        holdings.retain(|h| h.symbol != holding.symbol);
+        holdings.push(holding);
+        holdings.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        for h in &holdings {
+            writer
+                .serialize(h)
+                .map_err(|e| format!("Failed to serialize preferred holding: {}", e))?;
+        }
+        let content = writer
+            .into_inner()
+            .map_err(|e| format!("Failed to build preferred_holdings.csv: {}", e))?;
+        atomic_write(&app_handle, &path, &content)
+    })?)
 }
 
 #[tauri::command]
-fn save_position_snapshot(
+fn read_preferred_holdings(
     app_handle: tauri::AppHandle,
-    snapshot: PositionSnapshotPayload,
-) -> Result<String, String> {
-    let navs_dir = get_navs_dir(&app_handle)?;
-    let symbol = snapshot.stock;
+) -> Result<Vec<PreferredHolding>, AppError> {
+    Ok(load_preferred_holdings(&app_handle)?)
+}
 
-    let transactions = load_symbol_transactions(&app_handle, &symbol)?;
-    let currency = transactions
-        .first()
-        .map(|t| t.currency.clone())
-        .unwrap_or(snapshot.currency);
-    let mut prices = load_price_history_for_symbol(&app_handle, &symbol)?;
+#[tauri::command]
+fn calculate_current_yield(app_handle: tauri::AppHandle, symbol: String) -> Result<f64, AppError> {
+    let holding = find_preferred_holding(&app_handle, &symbol)?;
+    Ok(holding.coupon_rate * holding.par_value / holding.current_price)
+}
 
-    if let Some(first_txn_date) = transactions.first().map(|t| t.date) {
-        prices.retain(|record| record.date >= first_txn_date);
+/// Solves for the annually-compounded yield that prices the remaining coupons and
+/// par redemption at `current_price`. Perpetual preferreds fall back to current yield.
+fn yield_to_maturity(holding: &PreferredHolding, as_of: NaiveDate) -> Option<f64> {
+    let coupon = holding.coupon_rate * holding.par_value;
+    let maturity = match holding.maturity_date {
+        Some(m) => m,
+        None => return Some(coupon / holding.current_price),
+    };
+    if maturity <= as_of {
+        return None;
     }
 
-    if prices.is_empty() {
-        return Err(format!("No price history available for {}", symbol));
-    }
+    let years = (maturity - as_of).num_days() as f64 / 365.25;
+    let price_at = |y: f64| -> f64 {
+        let mut pv = holding.par_value / (1.0 + y).powf(years);
+        let mut t = years;
+        while t > 0.0 {
+            pv += coupon / (1.0 + y).powf(t);
+            t -= 1.0;
+        }
+        pv
+    };
 
-    let mut timeline = build_position_timeline(&prices, &transactions);
-    if timeline.is_empty() {
-        return Err(format!(
-            "Failed to calculate position history for {}",
-            symbol
-        ));
+    // Price is monotonically decreasing in yield, so bisect.
+    let (mut lo, mut hi) = (-0.99f64, 10.0f64);
+    if price_at(lo) < holding.current_price || price_at(hi) > holding.current_price {
+        return None;
     }
+    for _ in 0..200 {
+        let mid = (lo + hi) / 2.0;
+        if price_at(mid) > holding.current_price {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    Some((lo + hi) / 2.0)
+}
 
-    // Reverse to store latest rows first for faster partial reads.
-    timeline.reverse();
-
-    let dates: Vec<String> = timeline.iter().map(|(d, _, _)| d.clone()).collect();
-    let closes: Vec<f64> = timeline.iter().map(|(_, close, _)| *close).collect();
-    let shares_vec: Vec<f64> = timeline.iter().map(|(_, _, shares)| *shares).collect();
+#[tauri::command]
+fn calculate_yield_to_maturity(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+) -> Result<f64, AppError> {
+    let holding = find_preferred_holding(&app_handle, &symbol)?;
+    Ok(yield_to_maturity(&holding, Utc::now().date_naive())
+        .ok_or_else(|| format!("Unable to solve yield to maturity for {}", symbol))?)
+}
 
-    let base_df = DataFrame::new(vec![
-        Series::new("date", dates),
-        Series::new("close", closes),
-        Series::new("shares", shares_vec),
-    ])
-    .map_err(|e| format!("Failed to build dataframe: {}", e))?;
+#[derive(Serialize)]
+struct DividendIncomeEntry {
+    symbol: String,
+    ex_date: String,
+    amount_per_share: f64,
+    shares: f64,
+    total: f64,
+    currency: String,
+    kind: String,
+}
 
-    let mut calculated = base_df
-        .lazy()
-        .with_columns([(col("close") * col("shares")).alias("position_value")])
+/// Quarterly coupon dates from issue to maturity (or `until`), used for preferred
+/// holdings that have no dividend file of their own.
+fn preferred_coupon_dates(holding: &PreferredHolding, until: NaiveDate) -> Vec<NaiveDate> {
+    let end = holding.maturity_date.map_or(until, |m| m.min(until));
+    (1..)
+        .map_while(|quarter| {
+            holding
+                .issue_date
+                .checked_add_months(chrono::Months::new(quarter * 3))
+                .filter(|date| *date <= end)
+        })
         .collect()
-        .map_err(|e| format!("Failed to evaluate dataframe: {}", e))?;
-
-    calculated
-        .with_column(Series::new(
-            "currency",
-            vec![currency.clone(); calculated.height()],
-        ))
-        .map_err(|e| format!("Failed to append currency column: {}", e))?;
-    calculated
-        .with_column(Series::new(
-            "symbol",
-            vec![symbol.clone(); calculated.height()],
-        ))
-        .map_err(|e| format!("Failed to append symbol column: {}", e))?;
+}
 
-    let safe_symbol = symbol.replace(':', "_");
-    let file_path = navs_dir.join(format!("{}.csv", safe_symbol));
-    let mut file =
-        File::create(&file_path).map_err(|e| format!("Failed to create {:?}: {}", file_path, e))?;
+#[tauri::command]
+fn get_dividend_income(
+    app_handle: tauri::AppHandle,
+    year: Option<i32>,
+) -> Result<Vec<DividendIncomeEntry>, AppError> {
+    let transactions = load_all_transactions(&app_handle)?;
+    let preferred: HashMap<String, PreferredHolding> = load_preferred_holdings(&app_handle)?
+        .into_iter()
+        .map(|h| (h.symbol.clone(), h))
+        .collect();
 
-    CsvWriter::new(&mut file)
-        .include_header(true)
-        .finish(&mut calculated)
-        .map_err(|e| format!("Failed to write CSV: {}", e))?;
+    let mut by_symbol: HashMap<String, Vec<Transaction>> = HashMap::new();
+    for txn in transactions.iter() {
+        if txn.stock.trim().is_empty() {
+            continue;
+        }
+        by_symbol
+            .entry(txn.stock.clone())
+            .or_default()
+            .push(txn.clone());
+    }
 
-    Ok(file_path.to_string_lossy().to_string())
-}
+    let today = Utc::now().date_naive();
+    let mut income = Vec::new();
+
+    for (symbol, txns) in by_symbol {
+        let processed = process_complete_transactions(txns, lenient_parsing(&app_handle))?;
+        let currency = processed
+            .first()
+            .map(|t| t.currency.clone())
+            .unwrap_or_else(|| {
+                currency_for_exchange(get_exchange_and_symbol(&symbol).0.as_deref()).to_string()
+            });
+        let holding = preferred.get(&symbol);
+        let kind = if holding.is_some() {
+            "preferred"
+        } else {
+            "common"
+        };
 
-#[tauri::command]
-fn get_all_daily_prices(app_handle: tauri::AppHandle) -> Result<Vec<DailyPriceData>, String> {
-    let prices_dir = get_prices_dir(&app_handle)?;
-    let mut daily_prices = Vec::new();
+        let mut events = load_dividend_events(&app_handle, &symbol)?;
+        if events.is_empty() {
+            if let Some(holding) = holding {
+                let per_payment = holding.coupon_rate * holding.par_value / 4.0;
+                events = preferred_coupon_dates(holding, today)
+                    .into_iter()
+                    .map(|date| (date, per_payment, currency.clone()))
+                    .collect();
+            }
+        }
 
-    if let Ok(entries) = std::fs::read_dir(&prices_dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.extension().and_then(|s| s.to_str()) != Some("csv") {
+        for (ex_date, amount, event_currency) in events {
+            if year.is_some_and(|y| ex_date.year() != y) {
+                continue;
+            }
+            // Holders of record are those owning shares the day before the ex-date.
+            let shares = shares_held_on(&processed, ex_date - ChronoDuration::days(1));
+            if shares <= 0.0 {
                 continue;
             }
+            income.push(DividendIncomeEntry {
+                symbol: symbol.clone(),
+                ex_date: ex_date.format("%Y-%m-%d").to_string(),
+                amount_per_share: amount,
+                shares,
+                total: amount * shares,
+                currency: event_currency,
+                kind: kind.to_string(),
+            });
+        }
+    }
 
-            if let Some(filename) = path.file_name().and_then(|n| n.to_str()) {
-                let symbol = filename.trim_end_matches(".csv").replace('_', ":");
+    income.sort_by(|a, b| b.ex_date.cmp(&a.ex_date).then(a.symbol.cmp(&b.symbol)));
+    Ok(income)
+}
 
-                // Read only first 3 lines (header + latest 2 prices)
-                // Price files are sorted by date descending, so top 2 data rows are what we need
-                if let Ok(content) = read_file_head(&path, 3) {
-                    let lines: Vec<&str> = content.lines().collect();
-                    if lines.len() < 2 {
-                        continue; // Skip if no data (only header)
-                    }
+fn persist_fx_rate_file(
+    app_handle: &tauri::AppHandle,
+    pair: &str,
+    content: &str,
+) -> Result<(), String> {
+    let fx_rates_dir = get_fx_rates_dir(app_handle)?;
+    let safe_pair = pair.replace('/', "_");
+    let file_path = fx_rates_dir.join(format!("{}.csv", safe_pair));
 
-                    let latest_line = lines.get(1);
-                    let previous_line = lines.get(2);
+    write(&file_path, content)
+        .map_err(|e| format!("Failed to write FX rate file for '{}': {}", pair, e))
+}
 
-                    if let Some(latest_str) = latest_line {
-                        let fields: Vec<&str> = latest_str.split(',').collect();
-                        if fields.len() < 2 {
-                            continue;
-                        }
+#[tauri::command]
+fn write_fx_rate_file(
+    app_handle: tauri::AppHandle,
+    pair: String,
+    content: String,
+) -> Result<(), AppError> {
+    Ok(persist_fx_rate_file(&app_handle, &pair, &content)?)
+}
 
-                        if let (Ok(latest_date), Ok(latest_close)) = (
-                            NaiveDate::parse_from_str(fields[0].trim(), "%Y-%m-%d"),
-                            fields[1].trim().parse::<f64>(),
-                        ) {
-                            let mut previous_close: Option<f64> = None;
-                            let mut previous_date: Option<String> = None;
+#[tauri::command]
+fn write_fx_rate_override_file(
+    app_handle: tauri::AppHandle,
+    pair: String,
+    content: String,
+) -> Result<(), AppError> {
+    let fx_rates_dir = get_fx_rates_dir(&app_handle)?;
+    let safe_pair = pair.replace('/', "_");
+    let file_path = fx_rates_dir.join(format!("{}-override.csv", safe_pair));
 
-                            if let Some(prev_str) = previous_line {
-                                let prev_fields: Vec<&str> = prev_str.split(',').collect();
-                                if prev_fields.len() >= 2 {
-                                    if let (Ok(prev_date), Ok(prev_close_val)) = (
-                                        NaiveDate::parse_from_str(
-                                            prev_fields[0].trim(),
-                                            "%Y-%m-%d",
-                                        ),
-                                        prev_fields[1].trim().parse::<f64>(),
-                                    ) {
-                                        previous_date =
-                                            Some(prev_date.format("%Y-%m-%d").to_string());
-                                        previous_close = Some(prev_close_val);
-                                    }
-                                }
-                            }
+    Ok(write(&file_path, content).map_err(|e| {
+        format!(
+            "Failed to write FX rate override file for '{}': {}",
+            pair, e
+        )
+    })?)
+}
 
-                            daily_prices.push(DailyPriceData {
-                                symbol,
-                                latest_close,
-                                latest_date: latest_date.format("%Y-%m-%d").to_string(),
-                                previous_close,
-                                previous_date,
-                            });
-                        }
-                    }
-                }
-            }
-        }
+#[tauri::command]
+fn read_fx_rate_file(app_handle: tauri::AppHandle, pair: String) -> Result<String, AppError> {
+    let fx_rates_dir = get_fx_rates_dir(&app_handle)?;
+    let safe_pair = pair.replace('/', "_");
+    let file_path = fx_rates_dir.join(format!("{}.csv", safe_pair));
+
+    if !file_path.exists() {
+        return Ok(String::new());
     }
 
-    Ok(daily_prices)
+    Ok(read_to_string(&file_path)
+        .map_err(|e| format!("Failed to read FX rate file for '{}': {}", pair, e))?)
 }
 
 #[tauri::command]
-fn get_all_daily_fx_rates(app_handle: tauri::AppHandle) -> Result<Vec<DailyFxRateData>, String> {
+fn read_fx_rate_file_head(
+    app_handle: tauri::AppHandle,
+    pair: String,
+    lines: Option<usize>,
+) -> Result<String, AppError> {
     let fx_rates_dir = get_fx_rates_dir(&app_handle)?;
-    let mut daily_rates = Vec::new();
+    let safe_pair = pair.replace('/', "_");
+    let file_path = fx_rates_dir.join(format!("{}.csv", safe_pair));
+    if !file_path.exists() {
+        return Ok(String::new());
+    }
+    let max_lines = lines.unwrap_or(8).max(1);
+    Ok(read_file_head(&file_path, max_lines)?)
+}
+
+#[tauri::command]
+fn list_fx_rate_files(app_handle: tauri::AppHandle) -> Result<Vec<String>, AppError> {
+    let fx_rates_dir = get_fx_rates_dir(&app_handle)?;
+    let mut pairs = Vec::new();
 
     if let Ok(entries) = std::fs::read_dir(&fx_rates_dir) {
         for entry in entries.flatten() {
-            let path = entry.path();
-            if path.extension().and_then(|s| s.to_str()) != Some("csv") {
-                continue;
-            }
-
-            if let Some(filename) = path.file_name().and_then(|n| n.to_str()) {
-                let pair = filename.trim_end_matches(".csv").replace('_', "/");
-
-                // Read only first 3 lines (header + latest 2 rates)
-                // FX rate files are sorted by date descending
-                if let Ok(content) = read_file_head(&path, 3) {
-                    let lines: Vec<&str> = content.lines().collect();
-                    if lines.len() < 2 {
-                        continue; // Skip if no data (only header)
-                    }
-
-                    let latest_line = lines.get(1);
-                    let previous_line = lines.get(2);
-
-                    if let Some(latest_str) = latest_line {
-                        let fields: Vec<&str> = latest_str.split(',').collect();
-                        // FX CSV format: from_currency,to_currency,date,rate,source,updated_at
-                        if fields.len() < 4 {
-                            continue;
-                        }
-
-                        // Parse date (column 2) and rate (column 3)
-                        if let (Ok(latest_date), Ok(latest_rate)) = (
-                            NaiveDate::parse_from_str(fields[2].trim(), "%Y-%m-%d"),
-                            fields[3].trim().parse::<f64>(),
-                        ) {
-                            let mut previous_rate: Option<f64> = None;
-                            let mut previous_date: Option<String> = None;
-
-                            if let Some(prev_str) = previous_line {
-                                let prev_fields: Vec<&str> = prev_str.split(',').collect();
-                                if prev_fields.len() >= 4 {
-                                    if let (Ok(prev_date), Ok(prev_rate_val)) = (
-                                        NaiveDate::parse_from_str(
-                                            prev_fields[2].trim(),
-                                            "%Y-%m-%d",
-                                        ),
-                                        prev_fields[3].trim().parse::<f64>(),
-                                    ) {
-                                        previous_date =
-                                            Some(prev_date.format("%Y-%m-%d").to_string());
-                                        previous_rate = Some(prev_rate_val);
-                                    }
-                                }
-                            }
-
-                            daily_rates.push(DailyFxRateData {
-                                pair,
-                                latest_rate,
-                                latest_date: latest_date.format("%Y-%m-%d").to_string(),
-                                previous_rate,
-                                previous_date,
-                            });
-                        }
-                    }
+            if let Some(filename) = entry.file_name().to_str() {
+                if filename.ends_with(".csv") {
+                    let pair = filename.trim_end_matches(".csv").replace('_', "/");
+                    pairs.push(pair);
                 }
             }
         }
     }
 
-    Ok(daily_rates)
+    pairs.sort();
+    Ok(pairs)
 }
 
 #[tauri::command]
-fn read_fx_rates_polars(
-    app_handle: tauri::AppHandle,
-    #[allow(non_snake_case)]
-    fromCurrency: String,
-    #[allow(non_snake_case)]
-    toCurrency: String,
-    #[allow(non_snake_case)]
-    latestOnly: Option<bool>,
-    #[allow(non_snake_case)]
-    includeOverrides: Option<bool>,
-    limit: Option<usize>,
-) -> Result<Vec<FxRateRecordResponse>, String> {
-    let include_overrides = includeOverrides.unwrap_or(true);
-    let mut records =
-        load_fx_pair_with_polars(&app_handle, &fromCurrency, &toCurrency, include_overrides)?;
+fn sync_history_once(app_handle: tauri::AppHandle) -> Result<(), AppError> {
+    Ok(sync_full_history(&app_handle, &new_run_id())?)
+}
 
-    if records.is_empty() {
-        return Ok(records);
+#[tauri::command]
+fn download_symbol_history(app_handle: tauri::AppHandle, symbol: String) -> Result<(), AppError> {
+    println!("[RUST] Received download request for: {}", symbol);
+
+    let fifteen_years_ago = Utc::now().date_naive() - ChronoDuration::days(15 * 365);
+    let mut price_map: HashMap<String, Vec<PriceRecordEntry>> = HashMap::new();
+
+    println!("[RUST] Calling ensure_history_for_symbol for: {}", symbol);
+    // Use the existing ensure_history_for_symbol logic
+    let run_id = new_run_id();
+    write_run_log(
+        &app_handle,
+        &run_id,
+        LogLevel::Info,
+        &format!("Downloading history for {}", symbol),
+    )?;
+    let result = ensure_history_for_symbol(
+        &app_handle,
+        &run_id,
+        &mut price_map,
+        &symbol,
+        fifteen_years_ago,
+    );
+    if let Err(e) = record_sync_result(
+        &app_handle,
+        &symbol,
+        result.as_ref().err().map(AppError::message),
+    ) {
+        write_run_log(
+            &app_handle,
+            &run_id,
+            LogLevel::Warn,
+            &format!("Failed to record sync status for {}: {}", symbol, e),
+        )?;
+    }
+    match result {
+        Ok(_) => println!("[RUST] ✓ Successfully fetched data for: {}", symbol),
+        Err(e) => {
+            eprintln!("[RUST] ✗ Error fetching data for {}: {}", symbol, e);
+            return Err(e);
+        }
     }
 
-    let latest_only = latestOnly.unwrap_or(true);
-    if latest_only && records.len() > 1 {
-        records.truncate(1);
-    } else if let Some(limit) = limit {
-        if limit < records.len() {
-            records.truncate(limit);
+    // The map starts empty so a complete history is still refetched; merging the
+    // download into the file's rows keeps manual prices and older history.
+    if let Some(downloaded) = price_map.get(&symbol) {
+        let existing = price_file_entries(&app_handle, &symbol)?;
+        let entries = merge_price_entries(&symbol, &existing, downloaded)?;
+        println!(
+            "[RUST] Writing {} price entries for: {}",
+            entries.len(),
+            symbol
+        );
+        if write_price_entries_if_changed(&app_handle, &symbol, &entries)? {
+            println!("[RUST] ✓ Successfully wrote price file for: {}", symbol);
+        } else {
+            println!("[RUST] Price file for {} is already up to date", symbol);
         }
+    } else {
+        eprintln!("[RUST] ⚠ No price data found for: {}", symbol);
     }
 
-    Ok(records)
+    Ok(())
 }
 
-#[tauri::command]
-fn read_nav_file(app_handle: tauri::AppHandle, symbol: String) -> Result<String, String> {
-    let navs_dir = get_navs_dir(&app_handle)?;
-    let safe_symbol = symbol.replace(':', "_");
+#[derive(Serialize)]
+struct BulkDownloadFailure {
+    symbol: String,
+    error: String,
+}
 
-    let entries = std::fs::read_dir(&navs_dir)
-        .map_err(|e| format!("Failed to read navs directory: {}", e))?;
+#[derive(Serialize)]
+struct BulkDownloadResult {
+    success: Vec<String>,
+    failed: Vec<BulkDownloadFailure>,
+}
 
-    let mut matching_files: Vec<PathBuf> = entries
-        .filter_map(|entry| entry.ok())
-        .map(|entry| entry.path())
-        .filter(|path| {
-            path.file_name()
-                .and_then(|name| name.to_str())
-                .map(|name| name.starts_with(&safe_symbol) && name.ends_with(".csv"))
-                .unwrap_or(false)
+#[derive(Serialize, Clone)]
+struct BulkDownloadProgress {
+    symbol: String,
+    completed: usize,
+    total: usize,
+    error: Option<String>,
+}
+
+/// Downloads history for many symbols on a fixed pool of worker threads pulling
+/// from a shared queue, emitting `bulk_download_progress` after each symbol.
+/// Runs off the main thread so the window keeps rendering progress meanwhile.
+#[tauri::command(async)]
+fn bulk_download_history(
+    app_handle: tauri::AppHandle,
+    symbols: Vec<String>,
+    parallelism: Option<usize>,
+) -> Result<BulkDownloadResult, AppError> {
+    let mut unique: Vec<String> = Vec::new();
+    for symbol in symbols {
+        let symbol = symbol.trim().to_string();
+        if !symbol.is_empty() && !unique.contains(&symbol) {
+            unique.push(symbol);
+        }
+    }
+    let total = unique.len();
+    let workers = parallelism.unwrap_or(4).clamp(1, total.max(1));
+    let queue = Arc::new(Mutex::new(std::collections::VecDeque::from(unique)));
+    let (sender, receiver) = std::sync::mpsc::channel::<(String, Result<(), String>)>();
+
+    let handles: Vec<_> = (0..workers)
+        .map(|_| {
+            let queue = Arc::clone(&queue);
+            let sender = sender.clone();
+            let app_handle = app_handle.clone();
+            std::thread::spawn(move || loop {
+                let next = queue.lock().unwrap_or_else(|e| e.into_inner()).pop_front();
+                let Some(symbol) = next else {
+                    break;
+                };
+                let result = download_symbol_history(app_handle.clone(), symbol.clone())
+                    .map_err(String::from);
+                if sender.send((symbol, result)).is_err() {
+                    break;
+                }
+            })
         })
         .collect();
+    drop(sender);
 
-    if matching_files.is_empty() {
-        return Err(format!("No NAV file found for symbol '{}'", symbol));
+    let mut outcome = BulkDownloadResult {
+        success: Vec::new(),
+        failed: Vec::new(),
+    };
+    for (completed, (symbol, result)) in receiver.iter().enumerate() {
+        let _ = app_handle.emit_all(
+            "bulk_download_progress",
+            BulkDownloadProgress {
+                symbol: symbol.clone(),
+                completed: completed + 1,
+                total,
+                error: result.as_ref().err().cloned(),
+            },
+        );
+        match result {
+            Ok(()) => outcome.success.push(symbol),
+            Err(error) => outcome.failed.push(BulkDownloadFailure { symbol, error }),
+        }
     }
+    for handle in handles {
+        let _ = handle.join();
+    }
+
+    write_worker_log(
+        &app_handle,
+        &format!(
+            "Bulk download finished: {} succeeded, {} failed",
+            outcome.success.len(),
+            outcome.failed.len()
+        ),
+    )?;
+    Ok(outcome)
+}
 
-    matching_files.sort_by(|a, b| b.cmp(a));
-    let latest_file = &matching_files[0];
+const REALTIME_QUOTE_TTL: Duration = Duration::from_secs(60);
 
-    std::fs::read_to_string(latest_file)
-        .map_err(|e| format!("Failed to read NAV file for '{}': {}", symbol, e))
+#[derive(Serialize, Clone)]
+struct RealtimeQuote {
+    symbol: String,
+    current_price: f64,
+    change: Option<f64>,
+    change_pct: Option<f64>,
+    market_state: String,
+    currency: Option<String>,
+    exchange: Option<String>,
+    volume: Option<f64>,
+    market_cap: Option<f64>,
 }
 
-fn main() {
-    tauri::Builder::default()
-        .setup(|app| {
-            if let Err(e) = initialize_storage(&app.handle()) {
-                return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, e)));
-            }
-            Ok(())
-        })
-        .invoke_handler(tauri::generate_handler![
-            greet,
-            read_csv,
-            get_setting,
-            set_setting,
-            read_storage_csv,
-            write_storage_csv,
-            append_storage_csv,
-            read_data_csv,
+/// Recently fetched quotes, reused for `REALTIME_QUOTE_TTL` per symbol.
+#[derive(Default)]
+struct RealtimeQuoteCache {
+    quotes: Mutex<HashMap<String, (std::time::Instant, RealtimeQuote)>>,
+}
+
+/// Reads a quoteSummary number, which Yahoo wraps as `{ "raw": .., "fmt": .. }`.
+fn yahoo_raw_number(value: &serde_json::Value, key: &str) -> Option<f64> {
+    let field = value.get(key)?;
+    field
+        .get("raw")
+        .and_then(|raw| raw.as_f64())
+        .or_else(|| field.as_f64())
+}
+
+/// Collapses Yahoo's market states (PREPRE, POSTPOST, ...) to REGULAR, PRE, POST or CLOSED.
+fn normalize_market_state(state: &str) -> &'static str {
+    match state {
+        "REGULAR" => "REGULAR",
+        s if s.starts_with("PRE") => "PRE",
+        s if s.starts_with("POST") => "POST",
+        _ => "CLOSED",
+    }
+}
+
+/// Fetches Yahoo's v10 quoteSummary for `modules` (comma separated) and returns the
+/// first result object.
+fn fetch_yahoo_quote_summary(
+    yahoo_symbol: &str,
+    modules: &str,
+) -> Result<serde_json::Value, AppError> {
+    let mut url = url::Url::parse("https://query1.finance.yahoo.com/v10/finance/quoteSummary/")
+        .map_err(|e| format!("Invalid quote URL: {}", e))?;
+    url.path_segments_mut()
+        .map_err(|_| "Invalid quote URL".to_string())?
+        .pop_if_empty()
+        .push(yahoo_symbol);
+    url.query_pairs_mut().append_pair("modules", modules);
+
+    let client = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+    let response = client
+        .get(url)
+        .header("User-Agent", "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36")
+        .send()
+        .map_err(|e| format!("Yahoo quote request failed: {}", e))?;
+    let status = response.status();
+    let text = response
+        .text()
+        .map_err(|e| format!("Failed to read Yahoo quote response: {}", e))?;
+    if !status.is_success() {
+        return Err(AppError::from_status(
+            status,
+            format!("Yahoo quote error {}: {}", status, text),
+        ));
+    }
+
+    let mut parsed: serde_json::Value =
+        serde_json::from_str(&text).map_err(|e| format!("Invalid Yahoo quote JSON: {}", e))?;
+    match parsed.pointer_mut("/quoteSummary/result/0") {
+        Some(result) => Ok(result.take()),
+        None => {
+            let error = parsed
+                .pointer("/quoteSummary/error/description")
+                .and_then(|d| d.as_str())
+                .unwrap_or("no result");
+            Err(AppError::not_found(
+                "quote",
+                format!("Yahoo quote for {} failed: {}", yahoo_symbol, error),
+            ))
+        }
+    }
+}
+
+#[tauri::command]
+fn get_realtime_quote(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+) -> Result<RealtimeQuote, AppError> {
+    let symbol = symbol.trim().to_string();
+    if symbol.is_empty() {
+        return Err(AppError::validation("symbol", "Symbol is required"));
+    }
+    let cache = app_handle.try_state::<RealtimeQuoteCache>();
+    if let Some(cache) = cache.as_ref() {
+        let quotes = cache.quotes.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((fetched_at, quote)) = quotes.get(&symbol) {
+            if fetched_at.elapsed() < REALTIME_QUOTE_TTL {
+                return Ok(quote.clone());
+            }
+        }
+    }
+
+    let (yahoo_symbol, _) = resolve_yahoo_symbol(&app_handle, &symbol);
+    let summary = fetch_yahoo_quote_summary(&yahoo_symbol, "price")?;
+    let price = summary.get("price").ok_or_else(|| {
+        format!(
+            "Yahoo quote for {} failed: missing price module",
+            yahoo_symbol
+        )
+    })?;
+
+    let market_state = normalize_market_state(
+        price
+            .get("marketState")
+            .and_then(|s| s.as_str())
+            .unwrap_or(""),
+    );
+    // Outside regular hours prefer the extended-hours price when Yahoo has one.
+    let extended = match market_state {
+        "PRE" => Some("preMarket"),
+        "POST" => Some("postMarket"),
+        _ => None,
+    }
+    .and_then(|prefix| {
+        yahoo_raw_number(price, &format!("{}Price", prefix)).map(|p| {
+            (
+                p,
+                yahoo_raw_number(price, &format!("{}Change", prefix)),
+                yahoo_raw_number(price, &format!("{}ChangePercent", prefix)),
+            )
+        })
+    });
+    let (current_price, change, change_pct) = match extended {
+        Some(values) => values,
+        None => (
+            yahoo_raw_number(price, "regularMarketPrice")
+                .ok_or_else(|| format!("Yahoo quote for {} has no price", yahoo_symbol))?,
+            yahoo_raw_number(price, "regularMarketChange"),
+            yahoo_raw_number(price, "regularMarketChangePercent"),
+        ),
+    };
+
+    let quote = RealtimeQuote {
+        symbol: symbol.clone(),
+        current_price,
+        change,
+        // Yahoo reports the percentage as a fraction.
+        change_pct: change_pct.map(|pct| pct * 100.0),
+        market_state: market_state.to_string(),
+        currency: price
+            .get("currency")
+            .and_then(|c| c.as_str())
+            .map(str::to_string),
+        exchange: price
+            .get("exchangeName")
+            .and_then(|e| e.as_str())
+            .map(str::to_string),
+        volume: yahoo_raw_number(price, "regularMarketVolume"),
+        market_cap: yahoo_raw_number(price, "marketCap"),
+    };
+
+    if let Some(cache) = cache {
+        cache
+            .quotes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(symbol, (std::time::Instant::now(), quote.clone()));
+    }
+    Ok(quote)
+}
+
+#[tauri::command]
+fn start_history_worker(app_handle: tauri::AppHandle) -> Result<(), AppError> {
+    let (run_id, message) = match resumable_sync_checkpoint(&app_handle) {
+        Some(checkpoint) => (
+            checkpoint.run_id,
+            format!(
+                "Resuming interrupted history sync at {} of {} symbols",
+                checkpoint.completed,
+                checkpoint.symbols.len()
+            ),
+        ),
+        None => (
+            new_run_id(),
+            "Starting background history worker".to_string(),
+        ),
+    };
+    write_run_log(&app_handle, &run_id, LogLevel::Info, &message)?;
+    let handle = app_handle.clone();
+    std::thread::spawn(move || {
+        if let Err(err) = sync_full_history(&handle, &run_id) {
+            let _ = write_run_log(
+                &handle,
+                &run_id,
+                LogLevel::Error,
+                &format!("History worker failed: {}", err),
+            );
+        }
+    });
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct AppInfo {
+    app_version: String,
+    data_dir: String,
+    app_data_dir: Option<String>,
+    logs_dir: Option<String>,
+    backups_dir: Option<String>,
+    /// True while data lives under the crate's `data` directory (the only location
+    /// supported today) rather than a user-configured path.
+    legacy_data_dir: bool,
+    storage_backend: String,
+    price_files: usize,
+    split_files: usize,
+    dividend_files: usize,
+    fx_files: usize,
+    last_sync_at: Option<String>,
+}
+
+fn count_csv_files(dir: &Path) -> usize {
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| entry.path().extension().and_then(|e| e.to_str()) == Some("csv"))
+                .count()
+        })
+        .unwrap_or(0)
+}
+
+/// When the last sync finished. The in-memory state knows about syncs in this
+/// session; older ones come from the `last_sync_at` setting or, failing that, the
+/// per-symbol sync status file.
+fn last_sync_at(app_handle: &tauri::AppHandle) -> Option<String> {
+    app_handle
+        .try_state::<PortfolioState>()
+        .and_then(|state| {
+            state
+                .inner
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .last_sync_time
+                .clone()
+        })
+        .or_else(|| {
+            read_setting_value_internal(app_handle, "last_sync_at")
+                .ok()
+                .flatten()
+                .filter(|v| !v.trim().is_empty())
+        })
+        .or_else(|| {
+            load_sync_status(app_handle)
+                .unwrap_or_default()
+                .into_values()
+                .filter_map(|entry| entry.last_attempt_at)
+                .max()
+        })
+}
+
+/// Version, resolved paths and data file counts for the diagnostics page.
+#[tauri::command]
+fn get_app_info(app_handle: tauri::AppHandle) -> Result<AppInfo, AppError> {
+    let display = |path: Result<PathBuf, String>| path.ok().map(|p| p.display().to_string());
+    let data_dir = get_data_dir(&app_handle)?;
+    let count_in = |dir: Result<PathBuf, String>| dir.map(|d| count_csv_files(&d)).unwrap_or(0);
+
+    let last_sync_at = last_sync_at(&app_handle);
+
+    Ok(AppInfo {
+        app_version: app_handle.package_info().version.to_string(),
+        legacy_data_dir: data_dir.starts_with(env!("CARGO_MANIFEST_DIR")),
+        data_dir: data_dir.display().to_string(),
+        app_data_dir: app_handle
+            .path_resolver()
+            .app_data_dir()
+            .map(|p| p.display().to_string()),
+        logs_dir: display(get_logs_dir(&app_handle)),
+        backups_dir: display(get_backups_dir(&app_handle)),
+        storage_backend: "csv".to_string(),
+        price_files: count_in(get_prices_dir(&app_handle)),
+        split_files: count_in(get_splits_dir(&app_handle)),
+        dividend_files: count_in(get_dividends_dir(&app_handle)),
+        fx_files: count_in(get_fx_rates_dir(&app_handle)),
+        last_sync_at,
+    })
+}
+
+/// The history worker log, or only the lines of one sync run when `run_id` is given.
+#[tauri::command]
+fn get_history_log(
+    app_handle: tauri::AppHandle,
+    run_id: Option<String>,
+) -> Result<String, AppError> {
+    let logs_dir = get_logs_dir(&app_handle)?;
+    let log_file = logs_dir.join("history_worker.log");
+    if !log_file.exists() {
+        return Ok(String::new());
+    }
+    let content =
+        read_to_string(&log_file).map_err(|e| format!("Failed to read history log: {}", e))?;
+    let Some(run_id) = run_id
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+    else {
+        return Ok(content);
+    };
+    Ok(content
+        .lines()
+        .filter(|line| log_line_run_id(line) == Some(run_id.as_str()))
+        .map(|line| format!("{}\n", line))
+        .collect())
+}
+
+/// Lenient form of `parse_number`: empty and malformed values are both `None`.
+fn parse_f64_str(value: &str) -> Option<f64> {
+    parse_number(value).ok().flatten()
+}
+
+/// Parses a number as written in spreadsheets and broker exports: thousands
+/// separators (`1,234.50`, `1.234,50`, `1 234`, `1'234`), a decimal comma
+/// (`12,5`), a leading currency symbol and accounting negatives (`(12.50)`).
+/// Empty text is `Ok(None)`; anything that is not exactly one number, such as
+/// `N/A` or `12.34.56`, is an error rather than a guess.
+fn parse_number(raw: &str) -> Result<Option<f64>, String> {
+    let invalid = || format!("'{}' is not a number", raw.trim());
+    let mut text = raw.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+
+    let mut sign = 1.0;
+    if let Some(inner) = text.strip_prefix('(').and_then(|t| t.strip_suffix(')')) {
+        sign = -1.0;
+        text = inner.trim();
+    }
+    if let Some(rest) = text.strip_prefix('-') {
+        sign = -sign;
+        text = rest;
+    } else if let Some(rest) = text.strip_prefix('+') {
+        text = rest;
+    }
+    let text: String = text
+        .trim_start_matches(['$', '€', '£', '¥', '₩'])
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '\'' | '_' | '\u{a0}'))
+        .collect();
+
+    // Plain numbers, including exponents; the leading digit keeps out inf/NaN.
+    if text.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+        if let Ok(value) = text.parse::<f64>() {
+            return Ok(Some(sign * value));
+        }
+    }
+
+    let grouped = |part: &str, sep: char| {
+        let mut groups = part.split(sep);
+        groups
+            .next()
+            .is_some_and(|g| (1..=3).contains(&g.len()) && g.bytes().all(|b| b.is_ascii_digit()))
+            && groups.all(|g| g.len() == 3 && g.bytes().all(|b| b.is_ascii_digit()))
+    };
+    let decimal_sep = match (text.rfind(','), text.rfind('.')) {
+        (Some(c), Some(d)) => Some(if c > d { ',' } else { '.' }),
+        (Some(_), None) if grouped(&text, ',') => None,
+        (Some(_), None) => Some(','),
+        (None, Some(_)) if text.matches('.').count() > 1 && grouped(&text, '.') => None,
+        (None, Some(_)) => Some('.'),
+        (None, None) => None,
+    };
+    let thousands_sep = match decimal_sep {
+        Some('.') => ',',
+        Some(_) => '.',
+        None if text.contains(',') => ',',
+        None => '.',
+    };
+    let (int_part, frac_part) = match decimal_sep.and_then(|sep| text.rsplit_once(sep)) {
+        Some((int_part, frac_part)) => (int_part, frac_part),
+        None => (text.as_str(), ""),
+    };
+    if int_part.contains(thousands_sep) && !grouped(int_part, thousands_sep) {
+        return Err(invalid());
+    }
+    let int_digits = int_part.replace(thousands_sep, "");
+    let all_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if (int_digits.is_empty() && frac_part.is_empty())
+        || !all_digits(&int_digits)
+        || !all_digits(frac_part)
+    {
+        return Err(invalid());
+    }
+    format!(
+        "{}.{}",
+        if int_digits.is_empty() {
+            "0"
+        } else {
+            &int_digits
+        },
+        frac_part
+    )
+    .trim_end_matches('.')
+    .parse::<f64>()
+    .map(|value| Some(sign * value))
+    .map_err(|_| invalid())
+}
+
+/// A ratio written as `2:1`, `2/1`, `2-for-1` or a plain number. Empty is `Ok(None)`.
+fn parse_ratio(raw: &str) -> Result<Option<f64>, String> {
+    let text = raw.trim();
+    let Some((numerator, denominator)) = text
+        .split_once(':')
+        .or_else(|| text.split_once('/'))
+        .or_else(|| text.split_once("-for-"))
+    else {
+        return parse_number(text);
+    };
+    match (parse_number(numerator), parse_number(denominator)) {
+        (Ok(Some(n)), Ok(Some(d))) if n > 0.0 && d > 0.0 => Ok(Some(n / d)),
+        _ => Err(format!("'{}' is not a ratio", text)),
+    }
+}
+
+/// A value in a data file that could not be parsed. `line` is 1-based and counts
+/// the header.
+#[derive(Serialize, Clone)]
+struct ParseIssue {
+    file: String,
+    line: Option<usize>,
+    field: String,
+    raw_value: String,
+}
+
+impl std::fmt::Display for ParseIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: ", self.file, line)?,
+            None => write!(f, "{}: ", self.file)?,
+        }
+        write!(f, "invalid {} '{}'", self.field, self.raw_value)
+    }
+}
+
+/// Whether malformed numbers fall back to defaults (0 shares, 1:1 splits) instead
+/// of failing the load. Off unless the `lenient_parsing` setting is `true`.
+fn lenient_parsing(app_handle: &tauri::AppHandle) -> bool {
+    read_setting_value_internal(app_handle, "lenient_parsing")
+        .ok()
+        .flatten()
+        .is_some_and(|value| value.trim() == "true")
+}
+
+type NumberParser = fn(&str) -> Result<Option<f64>, String>;
+
+/// Parse issues in the named numeric columns of one CSV file. Columns in
+/// `required` are also reported when empty; absent columns are skipped.
+fn csv_parse_issues(
+    path: &Path,
+    columns: &[(&str, NumberParser)],
+    required: &[&str],
+) -> Result<Vec<ParseIssue>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_path(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let headers = reader
+        .headers()
+        .map_err(|e| format!("Failed to read header of {}: {}", path.display(), e))?
+        .clone();
+    let checks: Vec<(usize, &str, NumberParser)> = columns
+        .iter()
+        .filter_map(|(name, parse)| {
+            headers
+                .iter()
+                .position(|h| h.trim() == *name)
+                .map(|idx| (idx, *name, *parse))
+        })
+        .collect();
+
+    let mut issues = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| format!("Invalid row in {}: {}", path.display(), e))?;
+        if record.iter().all(|field| field.trim().is_empty()) {
+            continue;
+        }
+        for (idx, name, parse) in &checks {
+            let raw = record.get(*idx).unwrap_or("");
+            let missing = raw.trim().is_empty() && required.contains(name);
+            if missing || parse(raw).is_err() {
+                issues.push(ParseIssue {
+                    file: path.display().to_string(),
+                    line: record.position().map(|p| p.line() as usize),
+                    field: name.to_string(),
+                    raw_value: raw.to_string(),
+                });
+            }
+        }
+    }
+    Ok(issues)
+}
+
+/// Every malformed number across transactions and the price, dividend and split
+/// files, plus unreadable transaction dates, ordered by file and line. These are the values that fail a load unless
+/// `lenient_parsing` is set (price rows without a close are skipped instead).
+#[tauri::command]
+fn find_parse_issues(app_handle: tauri::AppHandle) -> Result<Vec<ParseIssue>, AppError> {
+    let mut issues: Vec<ParseIssue> = read_transaction_files(&app_handle)?
+        .iter()
+        .flat_map(transaction_parse_issues)
+        .collect();
+
+    let price_columns: Vec<(&str, NumberParser)> = [
+        "close",
+        "open",
+        "high",
+        "low",
+        "volume",
+        "adjusted_close",
+        "split_unadjusted_close",
+    ]
+    .into_iter()
+    .map(|name| (name, parse_number as NumberParser))
+    .collect();
+    let split_columns: [(&str, NumberParser); 4] = [
+        ("numerator", parse_number),
+        ("denominator", parse_number),
+        ("ratio", parse_ratio),
+        ("split_ratio", parse_ratio),
+    ];
+    let amount_column: [(&str, NumberParser); 1] = [("amount", parse_number)];
+    let mut scan_dir = |dir: PathBuf,
+                        columns: &[(&str, NumberParser)],
+                        required: &[&str]|
+     -> Result<(), AppError> {
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(&dir)?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|e| e == "csv"))
+            .collect();
+        paths.sort();
+        for path in paths {
+            issues.extend(csv_parse_issues(&path, columns, required)?);
+        }
+        Ok(())
+    };
+    scan_dir(get_prices_dir(&app_handle)?, &price_columns, &["close"])?;
+    scan_dir(get_dividends_dir(&app_handle)?, &amount_column, &["amount"])?;
+    scan_dir(get_splits_dir(&app_handle)?, &split_columns, &[])?;
+
+    issues.sort_by(|a, b| a.file.cmp(&b.file).then(a.line.cmp(&b.line)));
+    Ok(issues)
+}
+
+fn sanitize_timestamp(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+#[derive(Clone, Debug)]
+struct ProcessedTransaction {
+    date: NaiveDate,
+    txn_type: String,
+    quantity: f64,
+    price: f64,
+    fees: f64,
+    split_ratio: f64,
+    currency: String,
+    lot_id: Option<String>,
+}
+
+fn load_symbol_transactions(
+    app_handle: &tauri::AppHandle,
+    symbol: &str,
+) -> Result<Vec<ProcessedTransaction>, String> {
+    let all = load_all_transactions(app_handle)?;
+    let cache = app_handle.try_state::<TransactionCache>();
+    if let Some(cache) = cache.as_ref() {
+        let processed = cache.processed.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(txns) = processed.get(symbol) {
+            return Ok(txns.clone());
+        }
+    }
+
+    let symbol_txns: Vec<Transaction> = all
+        .iter()
+        .filter(|txn| txn.stock == symbol)
+        .cloned()
+        .collect();
+    if symbol_txns.is_empty() {
+        return Err(format!("No transactions found for {}", symbol));
+    }
+
+    let processed = process_complete_transactions(symbol_txns, lenient_parsing(app_handle))?;
+    if let Some(cache) = cache {
+        cache
+            .processed
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(symbol.to_string(), processed.clone());
+    }
+    Ok(processed)
+}
+
+/// Problems with a transaction's date and numeric fields; empty numbers are not
+/// issues.
+fn transaction_parse_issues(txn: &Transaction) -> Vec<ParseIssue> {
+    let (file, line) = match &txn.source {
+        Some((file, line)) => (file.clone(), Some(*line)),
+        None => (format!("{} transaction on {}", txn.stock, txn.date), None),
+    };
+    let date_issue = NaiveDate::parse_from_str(txn.date.trim(), "%Y-%m-%d")
+        .is_err()
+        .then(|| ParseIssue {
+            file: file.clone(),
+            line,
+            field: "date".to_string(),
+            raw_value: txn.date.clone(),
+        });
+    let number_issues = [
+        ("quantity", &txn.quantity, parse_number as fn(&str) -> _),
+        ("price", &txn.price, parse_number),
+        ("fees", &txn.fees, parse_number),
+        ("split_ratio", &txn.split_ratio, parse_ratio),
+    ]
+    .into_iter()
+    .filter(|(_, raw, parse)| parse(raw).is_err())
+    .map(|(field, raw, _)| ParseIssue {
+        file: file.clone(),
+        line,
+        field: field.to_string(),
+        raw_value: raw.clone(),
+    });
+    date_issue.into_iter().chain(number_issues).collect()
+}
+
+/// Parses and date-sorts transactions, returning every parse issue alongside the
+/// rows. A row without a valid date is always left out. A row with a malformed
+/// number is left out too unless `lenient`, in which case the field reads as its
+/// default (0, or 1 for a split ratio) and the row is kept.
+fn process_transactions(
+    transactions: Vec<Transaction>,
+    lenient: bool,
+) -> (Vec<ProcessedTransaction>, Vec<ParseIssue>) {
+    let mut processed = Vec::new();
+    let mut issues = Vec::new();
+    for txn in transactions {
+        let txn_issues = transaction_parse_issues(&txn);
+        let Ok(date) = NaiveDate::parse_from_str(txn.date.trim(), "%Y-%m-%d") else {
+            issues.extend(txn_issues);
+            continue;
+        };
+        let skip = !lenient && !txn_issues.is_empty();
+        issues.extend(txn_issues);
+        if skip {
+            continue;
+        }
+        let quantity = parse_f64_str(&txn.quantity).unwrap_or(0.0);
+        let split_ratio = parse_ratio(&txn.split_ratio).ok().flatten().unwrap_or(1.0);
+
+        processed.push(ProcessedTransaction {
+            date,
+            txn_type: txn.transaction_type.to_lowercase(),
+            quantity,
+            price: parse_f64_str(&txn.price).unwrap_or(0.0),
+            fees: parse_f64_str(&txn.fees).unwrap_or(0.0),
+            split_ratio: if split_ratio > 0.0 { split_ratio } else { 1.0 },
+            currency: txn.currency.clone(),
+            lot_id: Some(txn.lot_id.clone()).filter(|id| !id.is_empty()),
+        });
+    }
+
+    processed.sort_by_key(|t| t.date);
+    (processed, issues)
+}
+
+/// `process_transactions` for callers that need the whole history. It fails when a
+/// row was left out, listing every issue rather than the first; in lenient mode
+/// the defaulted fields are not errors here, `find_parse_issues` lists them.
+fn process_complete_transactions(
+    transactions: Vec<Transaction>,
+    lenient: bool,
+) -> Result<Vec<ProcessedTransaction>, String> {
+    let (processed, issues) = process_transactions(transactions, lenient);
+    let blocking: Vec<String> = issues
+        .iter()
+        .filter(|issue| !lenient || issue.field == "date")
+        .map(ToString::to_string)
+        .collect();
+    if !blocking.is_empty() {
+        return Err(blocking.join("; "));
+    }
+    Ok(processed)
+}
+
+fn load_price_history_for_symbol(
+    app_handle: &tauri::AppHandle,
+    symbol: &str,
+) -> Result<Vec<PriceRecordEntry>, String> {
+    let prices_dir = get_prices_dir(app_handle)?;
+    let safe_symbol = symbol_file_stem(symbol);
+    let path = prices_dir.join(format!("{}.csv", safe_symbol));
+
+    if !path.exists() {
+        return Err(format!("Price history not found for {}", symbol));
+    }
+
+    let mut records: Vec<PriceRecordEntry> = cached_price_series(app_handle, &path)?
+        .iter()
+        .cloned()
+        .map(|record| PriceRecordEntry {
+            symbol: symbol.to_string(),
+            ..record
+        })
+        .collect();
+
+    if records.is_empty() {
+        return Err(format!("No closing prices available for {}", symbol));
+    }
+
+    records.sort_by_key(|r| r.date);
+
+    if let Ok(split_events) = load_split_events(app_handle, symbol) {
+        if !split_events.is_empty() {
+            for record in records.iter_mut() {
+                let factor = split_unadjust_factor(record.date, &split_events);
+                record.close *= factor;
+                if let Some(open) = record.open.as_mut() {
+                    *open *= factor;
+                }
+                if let Some(high) = record.high.as_mut() {
+                    *high *= factor;
+                }
+                if let Some(low) = record.low.as_mut() {
+                    *low *= factor;
+                }
+            }
+        }
+    }
+
+    Ok(records)
+}
+
+fn load_split_events(
+    app_handle: &tauri::AppHandle,
+    symbol: &str,
+) -> Result<Vec<(NaiveDate, f64)>, String> {
+    let splits_dir = get_splits_dir(app_handle)?;
+    let safe_symbol = symbol_file_stem(symbol);
+    let path = splits_dir.join(format!("{}.csv", safe_symbol));
+
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let lenient = lenient_parsing(app_handle);
+    let mut events = Vec::new();
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .flexible(true)
+        .from_path(&path)
+        .map_err(|e| format!("Failed to read split file for {}: {}", symbol, e))?;
+    let has_fractional_header = reader
+        .headers()
+        .map(|h| {
+            h.iter()
+                .any(|col| col.trim().eq_ignore_ascii_case("numerator"))
+        })
+        .unwrap_or(false);
+
+    for result in reader.records() {
+        let record = result.map_err(|e| format!("Invalid split row: {}", e))?;
+        if record.len() < 2 || is_spinoff_marker(record.iter()) {
+            continue;
+        }
+
+        let date = match NaiveDate::parse_from_str(record.get(0).unwrap_or("").trim(), "%Y-%m-%d") {
+            Ok(d) => d,
+            Err(_) => continue,
+        };
+
+        // Ratios are new shares per old share: 2.0 for a 2:1 split, 0.1 for a
+        // 1-for-10 reverse split. Do not clamp either side to 1 or reverse splits
+        // stored as decimals collapse to 1:1.
+        let issue = |field: &str, raw: &str| {
+            ParseIssue {
+                file: path.display().to_string(),
+                line: record.position().map(|p| p.line() as usize),
+                field: field.to_string(),
+                raw_value: raw.to_string(),
+            }
+            .to_string()
+        };
+        let component = |idx: usize, field: &str| -> Result<f64, String> {
+            let raw = record.get(idx).unwrap_or("");
+            match parse_number(raw) {
+                Ok(value) => Ok(value.unwrap_or(1.0)),
+                Err(_) if lenient => Ok(1.0),
+                Err(_) => Err(issue(field, raw)),
+            }
+        };
+        let ratio = if has_fractional_header {
+            let numerator = component(1, "numerator")?;
+            let denominator = component(2, "denominator")?;
+            if numerator > 0.0 && denominator > 0.0 {
+                numerator / denominator
+            } else {
+                continue;
+            }
+        } else {
+            let raw = record.get(1).unwrap_or("");
+            match parse_ratio(raw) {
+                Ok(Some(ratio)) if ratio > 0.0 => ratio,
+                Err(_) if !lenient => return Err(issue("ratio", raw)),
+                _ => continue,
+            }
+        };
+
+        events.push((date, ratio));
+    }
+
+    events.sort_by_key(|(date, _)| *date);
+    Ok(events)
+}
+
+/// Factor that turns a split-adjusted price into the price actually quoted on
+/// `date`: the product of the ratios of every split after that date.
+///
+/// Stored closes are split-adjusted (Yahoo back-adjusts them) while position share
+/// counts are not, so the two only line up once prices are unadjusted. The same
+/// multiplication is right for reverse splits: a 1-for-10 split (ratio 0.1) means
+/// the adjusted pre-split close is 10x the quoted one, and multiplying by 0.1
+/// restores it.
+fn split_unadjust_factor(date: NaiveDate, split_events: &[(NaiveDate, f64)]) -> f64 {
+    split_events
+        .iter()
+        .filter(|(split_date, _)| date < *split_date)
+        .map(|(_, ratio)| *ratio)
+        .product()
+}
+
+/// Transaction types that add shares at a cost: plain buys plus subscribed rights.
+fn is_buy_type(ty: &str) -> bool {
+    ty.starts_with("buy") || ty == "purchase" || ty == "rights_subscribe"
+}
+
+/// ADR <-> ordinary share exchanges. Rows carry a signed quantity: negative on the
+/// security given up, positive on the one received.
+fn is_conversion_type(ty: &str) -> bool {
+    ty == "adr_convert_to_ordinary" || ty == "ordinary_convert_to_adr"
+}
+
+fn apply_transaction_to_shares(shares: f64, txn: &ProcessedTransaction) -> f64 {
+    match txn.txn_type.as_str() {
+        ty if is_buy_type(ty) => shares + txn.quantity,
+        ty if is_conversion_type(ty) => (shares + txn.quantity).max(0.0),
+        ty if ty.starts_with("sell") || ty == "sale" => (shares - txn.quantity).max(0.0),
+        ty if ty.contains("split") && txn.split_ratio > 0.0 => shares * txn.split_ratio,
+        _ => shares,
+    }
+}
+
+/// Replays transactions (sorted by date) up to and including `date`.
+fn shares_held_on(transactions: &[ProcessedTransaction], date: NaiveDate) -> f64 {
+    transactions
+        .iter()
+        .take_while(|txn| txn.date <= date)
+        .fold(0.0, apply_transaction_to_shares)
+}
+
+/// Replays transactions up to and including `date`, returning `(shares, total_cost)`
+/// under average-cost accounting. Sells release cost pro rata; `spinoff` rows carry
+/// the cost moved to the child as `quantity * price` (negative) without touching shares.
+fn position_cost_basis(transactions: &[ProcessedTransaction], date: NaiveDate) -> (f64, f64) {
+    let (shares, cost, _) = replay_average_cost(transactions, date);
+    (shares, cost)
+}
+
+/// Average-cost replay returning `(shares, total_cost, realized_pnl)`.
+fn replay_average_cost(transactions: &[ProcessedTransaction], date: NaiveDate) -> (f64, f64, f64) {
+    let mut shares = 0.0;
+    let mut cost = 0.0;
+    let mut realized = 0.0;
+    for txn in transactions.iter().take_while(|txn| txn.date <= date) {
+        let ty = txn.txn_type.as_str();
+        if is_buy_type(ty) {
+            cost += txn.quantity * txn.price + txn.fees;
+        } else if (ty.starts_with("sell") || ty == "sale") && shares > 0.0 {
+            let sold = txn.quantity.min(shares);
+            let released = cost * sold / shares;
+            realized += sold * txn.price - txn.fees - released;
+            cost -= released;
+        } else if is_conversion_type(ty) {
+            if txn.quantity < 0.0 && shares > 0.0 {
+                cost -= cost * (-txn.quantity).min(shares) / shares;
+            } else if txn.quantity > 0.0 {
+                cost += txn.quantity * txn.price;
+            }
+        } else if ty == "spinoff" {
+            cost = (cost + txn.quantity * txn.price).max(0.0);
+        }
+        shares = apply_transaction_to_shares(shares, txn);
+        if shares <= 0.0 {
+            cost = 0.0;
+        }
+    }
+    (shares, cost, realized)
+}
+
+#[derive(Clone, Debug)]
+struct OpenLot {
+    /// `<open_date>_<open_price>`, fixed when the lot is opened.
+    id: String,
+    date: NaiveDate,
+    quantity: f64,
+    cost_per_share: f64,
+}
+
+/// Part of a lot closed by a sale.
+#[derive(Clone, Debug)]
+struct RealizedLot {
+    sell_date: NaiveDate,
+    acquired_date: NaiveDate,
+    quantity: f64,
+    cost_basis: f64,
+    proceeds: f64,
+}
+
+fn lot_id_for(date: NaiveDate, price: f64) -> String {
+    format!("{}_{}", date.format("%Y-%m-%d"), price)
+}
+
+/// The open date a `lot_id_for` id starts with.
+fn lot_id_date(id: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(id.split('_').next()?, "%Y-%m-%d").ok()
+}
+
+/// Lots held more than a year are long-term; the sale must fall after the
+/// anniversary of the acquisition.
+fn is_long_term(acquired: NaiveDate, sold: NaiveDate) -> bool {
+    acquired
+        .checked_add_months(chrono::Months::new(12))
+        .is_some_and(|anniversary| sold > anniversary)
+}
+
+/// Open lots after replaying transactions first-in, first-out. Splits rescale every
+/// lot; spin-offs shave the moved cost off all lots pro rata.
+fn fifo_open_lots(transactions: &[ProcessedTransaction], date: NaiveDate) -> Vec<OpenLot> {
+    fifo_replay(transactions, date).0
+}
+
+/// FIFO replay returning the open lots and every lot portion closed by a sale.
+/// Sales carrying a lot id close that lot first and fall back to FIFO for any
+/// remainder. Sale fees reduce proceeds pro rata across the lots they close.
+fn fifo_replay(
+    transactions: &[ProcessedTransaction],
+    date: NaiveDate,
+) -> (Vec<OpenLot>, Vec<RealizedLot>) {
+    let mut lots: std::collections::VecDeque<OpenLot> = std::collections::VecDeque::new();
+    let mut realized = Vec::new();
+    let consume = |lots: &mut std::collections::VecDeque<OpenLot>, mut quantity: f64| {
+        let mut closed = Vec::new();
+        while quantity > 1e-9 {
+            let Some(front) = lots.front_mut() else {
+                break;
+            };
+            if front.quantity <= quantity + 1e-9 {
+                quantity -= front.quantity;
+                closed.push(front.clone());
+                lots.pop_front();
+            } else {
+                front.quantity -= quantity;
+                closed.push(OpenLot {
+                    quantity,
+                    ..front.clone()
+                });
+                quantity = 0.0;
+            }
+        }
+        closed
+    };
+
+    let consume_lot = |lots: &mut std::collections::VecDeque<OpenLot>, id: &str, quantity: f64| {
+        let Some(index) = lots.iter().position(|lot| lot.id == id) else {
+            return (Vec::new(), quantity);
+        };
+        let lot = &mut lots[index];
+        let taken = quantity.min(lot.quantity);
+        let closed = OpenLot {
+            quantity: taken,
+            ..lot.clone()
+        };
+        lot.quantity -= taken;
+        if lot.quantity <= 1e-9 {
+            lots.remove(index);
+        }
+        (vec![closed], quantity - taken)
+    };
+
+    for txn in transactions.iter().take_while(|txn| txn.date <= date) {
+        let ty = txn.txn_type.as_str();
+        if is_buy_type(ty) && txn.quantity > 0.0 {
+            lots.push_back(OpenLot {
+                id: lot_id_for(txn.date, txn.price),
+                date: txn.date,
+                quantity: txn.quantity,
+                cost_per_share: (txn.quantity * txn.price + txn.fees) / txn.quantity,
+            });
+        } else if ty.starts_with("sell") || ty == "sale" {
+            let (mut closed, remaining) = match txn.lot_id.as_deref() {
+                Some(id) => consume_lot(&mut lots, id, txn.quantity),
+                None => (Vec::new(), txn.quantity),
+            };
+            closed.extend(consume(&mut lots, remaining));
+            let sold: f64 = closed.iter().map(|lot| lot.quantity).sum();
+            for lot in closed {
+                let share = if sold > 0.0 { lot.quantity / sold } else { 0.0 };
+                realized.push(RealizedLot {
+                    sell_date: txn.date,
+                    acquired_date: lot.date,
+                    quantity: lot.quantity,
+                    cost_basis: lot.quantity * lot.cost_per_share,
+                    proceeds: lot.quantity * txn.price - txn.fees * share,
+                });
+            }
+        } else if is_conversion_type(ty) {
+            if txn.quantity < 0.0 {
+                consume(&mut lots, -txn.quantity);
+            } else if txn.quantity > 0.0 {
+                // Legs written by `record_adr_conversion` carry the id of the lot given
+                // up, so the received shares keep its acquisition date and queue in
+                // FIFO order by it. Older rows without an id open on the exchange date.
+                let date = txn
+                    .lot_id
+                    .as_deref()
+                    .and_then(lot_id_date)
+                    .unwrap_or(txn.date);
+                let index = lots
+                    .iter()
+                    .position(|lot| lot.date > date)
+                    .unwrap_or(lots.len());
+                lots.insert(
+                    index,
+                    OpenLot {
+                        id: txn
+                            .lot_id
+                            .clone()
+                            .unwrap_or_else(|| lot_id_for(txn.date, txn.price)),
+                        date,
+                        quantity: txn.quantity,
+                        cost_per_share: txn.price,
+                    },
+                );
+            }
+        } else if ty == "spinoff" {
+            let total: f64 = lots.iter().map(|l| l.quantity * l.cost_per_share).sum();
+            if total > 0.0 {
+                let keep = ((total + txn.quantity * txn.price) / total).max(0.0);
+                for lot in lots.iter_mut() {
+                    lot.cost_per_share *= keep;
+                }
+            }
+        } else if ty.contains("split") && txn.split_ratio > 0.0 {
+            for lot in lots.iter_mut() {
+                lot.quantity *= txn.split_ratio;
+                lot.cost_per_share /= txn.split_ratio;
+            }
+        }
+    }
+    (lots.into_iter().collect(), realized)
+}
+
+#[derive(Serialize)]
+struct InteropImportRow {
+    line: usize,
+    date: String,
+    kind: String,
+    symbol: String,
+    yahoo_symbol: String,
+    quantity: f64,
+    unit_price: f64,
+    fee: f64,
+    currency: String,
+    duplicate: bool,
+}
+
+#[derive(Serialize)]
+struct InteropImportPreview {
+    rows: Vec<InteropImportRow>,
+    invalid_rows: Vec<ImportRowError>,
+    new_transactions: usize,
+    new_dividends: usize,
+    duplicates: usize,
+    new_symbols: Vec<String>,
+    committed: bool,
+}
+
+fn json_number(value: &serde_json::Value) -> Option<f64> {
+    match value {
+        serde_json::Value::Number(n) => n.as_f64(),
+        serde_json::Value::String(s) => parse_f64_str(s),
+        _ => None,
+    }
+}
+
+/// Ghostfolio activities as import rows plus the rows that could not be read. A
+/// row is a duplicate when it matches a buy or sell in `existing`, a dividend
+/// `dividends_for` returns for its symbol, or an earlier row of the same import.
+fn interop_import_rows(
+    activities: &[serde_json::Value],
+    existing: &[Transaction],
+    reverse: &HashMap<String, String>,
+    mut dividends_for: impl FnMut(&str) -> Result<Vec<(NaiveDate, f64, String)>, AppError>,
+) -> Result<(Vec<InteropImportRow>, Vec<ImportRowError>), AppError> {
+    let same = |a: f64, b: f64| (a - b).abs() <= 1e-6 * a.abs().max(b.abs()).max(1.0);
+    let mut dividends: HashMap<String, Vec<(NaiveDate, f64, String)>> = HashMap::new();
+    let mut seen: Vec<(String, String, bool, f64, f64)> = existing
+        .iter()
+        .filter_map(|t| {
+            let ty = t.transaction_type.trim().to_lowercase();
+            let buy = is_buy_type(&ty);
+            (buy || ty.starts_with("sell") || ty == "sale").then(|| {
+                (
+                    t.date.trim().to_string(),
+                    t.stock.trim().to_string(),
+                    buy,
+                    parse_f64_str(&t.quantity).unwrap_or(0.0),
+                    parse_f64_str(&t.price).unwrap_or(0.0),
+                )
+            })
+        })
+        .collect();
+
+    let mut rows = Vec::new();
+    let mut invalid_rows = Vec::new();
+    for (index, activity) in activities.iter().enumerate() {
+        let line = index + 1;
+        let field = |name: &str| {
+            activity
+                .get(name)
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .trim()
+        };
+        let kind = field("type").to_uppercase();
+        if !matches!(kind.as_str(), "BUY" | "SELL" | "DIVIDEND" | "ITEM") {
+            invalid_rows.push(ImportRowError {
+                line,
+                reason: format!("Unsupported activity type '{}'", kind),
+            });
+            continue;
+        }
+        let date = field("date")
+            .get(..10)
+            .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+        let yahoo_symbol = field("symbol").to_string();
+        let quantity = activity.get("quantity").and_then(json_number);
+        let unit_price = activity.get("unitPrice").and_then(json_number);
+        let (Some(date), Some(quantity), Some(unit_price)) = (date, quantity, unit_price) else {
+            invalid_rows.push(ImportRowError {
+                line,
+                reason: "Missing or invalid date, quantity or unitPrice".to_string(),
+            });
+            continue;
+        };
+        if yahoo_symbol.is_empty() {
+            invalid_rows.push(ImportRowError {
+                line,
+                reason: "Missing symbol".to_string(),
+            });
+            continue;
+        }
+        let symbol = app_symbol_for_yahoo(&yahoo_symbol, reverse);
+        let currency = match field("currency").to_uppercase() {
+            c if c.is_empty() => {
+                currency_for_exchange(get_exchange_and_symbol(&symbol).0.as_deref()).to_string()
+            }
+            c => c,
+        };
+        let date_str = date.format("%Y-%m-%d").to_string();
+
+        let duplicate = if kind == "DIVIDEND" {
+            let events = match dividends.entry(symbol.clone()) {
+                Entry::Occupied(e) => e.into_mut(),
+                Entry::Vacant(e) => e.insert(dividends_for(&symbol)?),
+            };
+            let found = events
+                .iter()
+                .any(|(d, amount, _)| *d == date && same(*amount, unit_price));
+            if !found {
+                events.push((date, unit_price, currency.clone()));
+            }
+            found
+        } else {
+            if TRANSACTION_FILES
+                .iter()
+                .all(|(_, c)| !c.eq_ignore_ascii_case(&currency))
+            {
+                invalid_rows.push(ImportRowError {
+                    line,
+                    reason: format!("No transaction file for currency {}", currency),
+                });
+                continue;
+            }
+            let buy = kind != "SELL";
+            let found = seen.iter().any(|(d, s, b, q, p)| {
+                *d == date_str
+                    && *s == symbol
+                    && *b == buy
+                    && same(*q, quantity)
+                    && same(*p, unit_price)
+            });
+            if !found {
+                seen.push((date_str.clone(), symbol.clone(), buy, quantity, unit_price));
+            }
+            found
+        };
+
+        rows.push(InteropImportRow {
+            line,
+            date: date_str,
+            kind,
+            symbol,
+            yahoo_symbol,
+            quantity,
+            unit_price,
+            fee: activity.get("fee").and_then(json_number).unwrap_or(0.0),
+            currency,
+            duplicate,
+        });
+    }
+    Ok((rows, invalid_rows))
+}
+
+/// Imports a Ghostfolio activities export. BUY, SELL and ITEM become transactions in
+/// the market file for their currency; DIVIDEND becomes a manual dividend row (per
+/// share). Rows matching an existing transaction or dividend are flagged as
+/// duplicates and never written, so re-importing an `export_interop` file adds
+/// nothing. Nothing is written unless `commit` is set; symbols missing from
+/// securities.csv are added on commit.
+#[tauri::command]
+fn import_interop(
+    app_handle: tauri::AppHandle,
+    format: String,
+    path: String,
+    commit: Option<bool>,
+) -> Result<InteropImportPreview, AppError> {
+    if format.trim() != "ghostfolio" {
+        return Err(AppError::validation(
+            "format",
+            format!(
+                "Unsupported import format '{}'; expected ghostfolio",
+                format.trim()
+            ),
+        ));
+    }
+    let content = read_to_string(path.trim())
+        .map_err(|e| format!("Failed to read {}: {}", path.trim(), e))?;
+    let parsed: serde_json::Value =
+        serde_json::from_str(&content).map_err(|e| AppError::Parse {
+            file: path.trim().to_string(),
+            line: Some(e.line()),
+            message: format!("Invalid Ghostfolio export: {}", e),
+        })?;
+    let activities = parsed
+        .get("activities")
+        .unwrap_or(&parsed)
+        .as_array()
+        .ok_or_else(|| AppError::validation("path", "Export has no activities array"))?;
+
+    let existing = load_all_transactions(&app_handle)?;
+    let mut known: Vec<String> = existing
+        .iter()
+        .map(|t| t.stock.trim().to_string())
+        .collect();
+    let securities = load_security_currencies(&app_handle)?;
+    known.extend(securities.keys().cloned());
+    known.sort();
+    known.dedup();
+    let reverse = yahoo_reverse_map(&app_handle, &known);
+
+    let (rows, invalid_rows) = interop_import_rows(activities, &existing, &reverse, |symbol| {
+        load_dividend_events(&app_handle, symbol)
+    })?;
+
+    let fresh = || rows.iter().filter(|r| !r.duplicate);
+    let mut new_symbols: Vec<String> = fresh()
+        .map(|r| r.symbol.clone())
+        .filter(|s| !securities.contains_key(s))
+        .collect();
+    new_symbols.sort();
+    new_symbols.dedup();
+
+    let committed = commit.unwrap_or(false);
+    if committed {
+        let mut by_currency: std::collections::BTreeMap<String, Vec<Vec<String>>> =
+            std::collections::BTreeMap::new();
+        let mut dividend_rows: std::collections::BTreeMap<&str, Vec<&InteropImportRow>> =
+            std::collections::BTreeMap::new();
+        for row in fresh() {
+            if row.kind == "DIVIDEND" {
+                dividend_rows.entry(&row.symbol).or_default().push(row);
+                continue;
+            }
+            by_currency
+                .entry(row.currency.to_uppercase())
+                .or_default()
+                .push(vec![
+                    row.date.clone(),
+                    row.symbol.clone(),
+                    if row.kind == "SELL" { "sell" } else { "buy" }.to_string(),
+                    row.quantity.to_string(),
+                    row.unit_price.to_string(),
+                    row.fee.to_string(),
+                    String::new(),
+                ]);
+        }
+        for (currency, txn_rows) in &by_currency {
+            append_transaction_rows(&app_handle, currency, txn_rows)?;
+        }
+        for (symbol, imported) in dividend_rows {
+            let path = dividend_file_path(&app_handle, symbol)?;
+            with_file_lock(&app_handle, &path, || -> Result<(), String> {
+                let mut file_rows = read_dividend_rows(&path)?;
+                for row in imported {
+                    file_rows.retain(|r| r[0] != row.date);
+                    file_rows.push(vec![
+                        row.date.clone(),
+                        row.unit_price.to_string(),
+                        row.currency.clone(),
+                        Utc::now().to_rfc3339(),
+                        MANUAL_SOURCE.to_string(),
+                    ]);
+                }
+                write_dividend_rows(&app_handle, &path, file_rows)?;
+                Ok(())
+            })?;
+        }
+        for symbol in &new_symbols {
+            let Some(row) = rows.iter().find(|r| &r.symbol == symbol) else {
+                continue;
+            };
+            let (exchange, _) = get_exchange_and_symbol(symbol);
+            upsert_security(
+                &app_handle,
+                symbol,
+                &[
+                    ("name", symbol.clone()),
+                    ("exchange", exchange.unwrap_or_default()),
+                    ("currency", row.currency.clone()),
+                    ("type", "stock".to_string()),
+                    ("data_source", "yahoo_finance".to_string()),
+                    ("api_symbol", row.yahoo_symbol.clone()),
+                ],
+            )?;
+        }
+        invalidate_coverage_cache(&app_handle);
+        write_worker_log(
+            &app_handle,
+            &format!(
+                "Imported Ghostfolio activities from {}: {} new rows, {} new symbols",
+                path.trim(),
+                fresh().count(),
+                new_symbols.len()
+            ),
+        )?;
+    }
+
+    Ok(InteropImportPreview {
+        new_transactions: fresh().filter(|r| r.kind != "DIVIDEND").count(),
+        new_dividends: fresh().filter(|r| r.kind == "DIVIDEND").count(),
+        duplicates: rows.iter().filter(|r| r.duplicate).count(),
+        rows,
+        invalid_rows,
+        new_symbols,
+        committed,
+    })
+}
+
+#[derive(Serialize)]
+struct OpenLotResponse {
+    lot_id: String,
+    open_date: String,
+    quantity: f64,
+    cost_per_share: f64,
+}
+
+/// Lots still open today, with the ids `sell_from_lot` accepts.
+#[tauri::command]
+fn get_open_lots(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+) -> Result<Vec<OpenLotResponse>, AppError> {
+    let transactions = load_symbol_transactions(&app_handle, symbol.trim())?;
+    Ok(fifo_open_lots(&transactions, Utc::now().date_naive())
+        .into_iter()
+        .map(|lot| OpenLotResponse {
+            lot_id: lot.id,
+            open_date: lot.date.format("%Y-%m-%d").to_string(),
+            quantity: lot.quantity,
+            cost_per_share: lot.cost_per_share,
+        })
+        .collect())
+}
+
+#[derive(Serialize)]
+struct CostBasisPoint {
+    date: String,
+    avg_cost_per_share: f64,
+    total_shares: f64,
+    total_invested: f64,
+}
+
+/// Running average-cost position after each transaction date, for charting how the
+/// cost per share moved with buys, sells and splits. Transactions on the same day
+/// collapse into one point; `total_invested` is the cost still in the position.
+#[tauri::command]
+fn get_cost_basis_history(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+) -> Result<Vec<CostBasisPoint>, AppError> {
+    let transactions = load_symbol_transactions(&app_handle, symbol.trim())?;
+    let mut dates: Vec<NaiveDate> = transactions.iter().map(|t| t.date).collect();
+    dates.dedup();
+    Ok(dates
+        .into_iter()
+        .map(|date| {
+            let (shares, cost) = position_cost_basis(&transactions, date);
+            CostBasisPoint {
+                date: date.format("%Y-%m-%d").to_string(),
+                avg_cost_per_share: if shares > 1e-9 { cost / shares } else { 0.0 },
+                total_shares: shares,
+                total_invested: cost,
+            }
+        })
+        .collect())
+}
+
+#[derive(Serialize)]
+struct SpecificLotSaleResult {
+    symbol: String,
+    lot_id: String,
+    date: String,
+    quantity: f64,
+    price: f64,
+    fees: f64,
+    cost_basis: f64,
+    proceeds: f64,
+    realized_gain: f64,
+    remaining_in_lot: f64,
+}
+
+/// Records a sale from one specific lot (specific identification). The sell row
+/// carries the lot id so later FIFO replays close that lot instead of the oldest.
+/// `price` defaults to the latest close and `date` to today.
+#[tauri::command]
+fn sell_from_lot(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+    lot_id: String,
+    quantity: f64,
+    price: Option<f64>,
+    fees: Option<f64>,
+    date: Option<String>,
+) -> Result<SpecificLotSaleResult, AppError> {
+    let symbol = symbol.trim().to_string();
+    let lot_id = lot_id.trim().to_string();
+    if !(quantity.is_finite() && quantity > 0.0) {
+        return Err(AppError::validation(
+            "quantity",
+            "Quantity must be a positive number",
+        ));
+    }
+    let date = match date.as_deref().map(str::trim).filter(|d| !d.is_empty()) {
+        Some(raw) => NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+            .map_err(|e| AppError::validation("date", format!("Invalid date {}: {}", raw, e)))?,
+        None => Utc::now().date_naive(),
+    };
+
+    let transactions = load_symbol_transactions(&app_handle, &symbol)?;
+    let lot = fifo_open_lots(&transactions, date)
+        .into_iter()
+        .find(|lot| lot.id == lot_id)
+        .ok_or_else(|| {
+            AppError::not_found("lot", format!("No open lot {} for {}", lot_id, symbol))
+        })?;
+    if quantity > lot.quantity + 1e-9 {
+        return Err(AppError::validation(
+            "quantity",
+            format!(
+                "Lot {} only holds {} shares of {}",
+                lot_id, lot.quantity, symbol
+            ),
+        ));
+    }
+
+    let price = match price {
+        Some(price) => price,
+        None => load_price_history_for_symbol(&app_handle, &symbol)
+            .ok()
+            .and_then(|records| {
+                records
+                    .into_iter()
+                    .filter(|r| r.date <= date && r.close > 0.0)
+                    .max_by_key(|r| r.date)
+                    .map(|r| r.close)
+            })
+            .ok_or_else(|| {
+                AppError::validation("price", format!("No close available for {}", symbol))
+            })?,
+    };
+    let fees = fees.unwrap_or(0.0);
+    if !(price.is_finite() && price > 0.0) {
+        return Err(AppError::validation(
+            "price",
+            "Price must be a positive number",
+        ));
+    }
+    if !(fees.is_finite() && fees >= 0.0) {
+        return Err(AppError::validation("fees", "Fees cannot be negative"));
+    }
+
+    let currency = transactions
+        .last()
+        .map(|txn| txn.currency.clone())
+        .unwrap_or_else(|| {
+            currency_for_exchange(get_exchange_and_symbol(&symbol).0.as_deref()).to_string()
+        });
+    let transaction = Transaction {
+        date: date.format("%Y-%m-%d").to_string(),
+        stock: symbol.clone(),
+        transaction_type: "sell".to_string(),
+        quantity: quantity.to_string(),
+        price: price.to_string(),
+        fees: fees.to_string(),
+        split_ratio: String::new(),
+        currency: currency.clone(),
+        lot_id: lot_id.clone(),
+        source: None,
+    };
+    validate_transaction(app_handle.clone(), transaction.clone())?;
+    append_transaction_rows(&app_handle, &currency, &[transaction_row(&transaction)])?;
+
+    let cost_basis = quantity * lot.cost_per_share;
+    let proceeds = quantity * price - fees;
+    Ok(SpecificLotSaleResult {
+        symbol,
+        lot_id,
+        date: transaction.date,
+        quantity,
+        price,
+        fees,
+        cost_basis,
+        proceeds,
+        realized_gain: proceeds - cost_basis,
+        remaining_in_lot: (lot.quantity - quantity).max(0.0),
+    })
+}
+
+#[tauri::command]
+fn record_spinoff_event(
+    app_handle: tauri::AppHandle,
+    parent: String,
+    child: String,
+    spinoff_date: String,
+    cost_allocation_pct: f64,
+    shares_ratio: f64,
+) -> Result<(), AppError> {
+    let parent = parent.trim().to_string();
+    let child = child.trim().to_string();
+    if parent.is_empty() || child.is_empty() || parent == child {
+        return Err(AppError::validation(
+            "child",
+            "Parent and child symbols must be different and non-empty",
+        ));
+    }
+    if !(cost_allocation_pct > 0.0 && cost_allocation_pct < 1.0) {
+        return Err(AppError::validation(
+            "cost_allocation_pct",
+            format!(
+                "Cost allocation must be between 0 and 1, got {}",
+                cost_allocation_pct
+            ),
+        ));
+    }
+    if !shares_ratio.is_finite() || shares_ratio <= 0.0 {
+        return Err(AppError::validation(
+            "shares_ratio",
+            format!("Shares ratio must be positive, got {}", shares_ratio),
+        ));
+    }
+    let date = NaiveDate::parse_from_str(spinoff_date.trim(), "%Y-%m-%d")
+        .map_err(|e| format!("Invalid spin-off date {}: {}", spinoff_date, e))?;
+
+    // Holdings are measured the day before so same-day trades are not double counted.
+    let transactions = load_symbol_transactions(&app_handle, &parent)?;
+    let (parent_shares, parent_cost) =
+        position_cost_basis(&transactions, date - ChronoDuration::days(1));
+    if parent_shares <= 0.0 {
+        return Err(AppError::validation(
+            "parent",
+            format!("No {} shares held before {}", parent, date),
+        ));
+    }
+    let currency = transactions
+        .last()
+        .map(|txn| txn.currency.clone())
+        .unwrap_or_else(|| {
+            currency_for_exchange(get_exchange_and_symbol(&parent).0.as_deref()).to_string()
+        });
+
+    let allocated_cost = parent_cost * cost_allocation_pct;
+    let child_shares = parent_shares * shares_ratio;
+    let date_str = date.format("%Y-%m-%d").to_string();
+
+    // 1. Record the event with the other corporate actions. A spin-off leaves the
+    // parent's share count unchanged, so it has no place in the split file; the
+    // effect on the parent is the cost reduction written to the transaction file.
+    let actions_path = get_data_dir(&app_handle)?.join("corporate_actions.csv");
+    append_csv_rows(
+        &app_handle,
+        &actions_path,
+        CORPORATE_ACTIONS_HEADER,
+        &[vec![
+            date_str.clone(),
+            parent.clone(),
+            format!("spinoff:{}", child),
+            shares_ratio.to_string(),
+            (allocated_cost / child_shares).to_string(),
+            child_shares.to_string(),
+            Utc::now().to_rfc3339(),
+        ]],
+    )?;
+
+    // 2. Move cost from parent to child. The child buy is priced so that its cost
+    // basis equals the allocated amount; no cash changes hands.
+    let rows = [
+        vec![
+            date_str.clone(),
+            parent.clone(),
+            "spinoff".to_string(),
+            parent_shares.to_string(),
+            (-allocated_cost / parent_shares).to_string(),
+            "0".to_string(),
+            String::new(),
+        ],
+        vec![
+            date_str.clone(),
+            child.clone(),
+            "buy".to_string(),
+            child_shares.to_string(),
+            (allocated_cost / child_shares).to_string(),
+            "0".to_string(),
+            String::new(),
+        ],
+    ];
+    append_transaction_rows(&app_handle, &currency, &rows)?;
+
+    // 3. Log the event.
+    Ok(write_worker_log(
+        &app_handle,
+        &format!(
+            "Spin-off {} -> {} on {}: {:.4} child shares, {:.2} {} cost basis moved ({:.2}%)",
+            parent,
+            child,
+            date_str,
+            child_shares,
+            allocated_cost,
+            currency,
+            cost_allocation_pct * 100.0
+        ),
+    )?)
+}
+
+/// Latest split-unadjusted close on or before `date`, as quoted on that day.
+fn recent_market_close(
+    app_handle: &tauri::AppHandle,
+    symbol: &str,
+    date: NaiveDate,
+) -> Option<f64> {
+    load_price_history_for_symbol(app_handle, symbol)
+        .ok()?
+        .into_iter()
+        .rev()
+        .find(|record| record.date <= date && record.close > 0.0)
+        .map(|record| record.close)
+}
+
+#[tauri::command]
+fn validate_transaction(
+    app_handle: tauri::AppHandle,
+    transaction: Transaction,
+) -> Result<(), AppError> {
+    let date = NaiveDate::parse_from_str(transaction.date.trim(), "%Y-%m-%d")
+        .map_err(|e| format!("Invalid transaction date {}: {}", transaction.date, e))?;
+    let symbol = transaction.stock.trim();
+    if symbol.is_empty() {
+        return Err(AppError::validation(
+            "stock",
+            "Transaction symbol is required",
+        ));
+    }
+
+    let ty = transaction.transaction_type.trim().to_lowercase();
+    let quantity = parse_f64_str(&transaction.quantity);
+    let price = parse_f64_str(&transaction.price);
+    let is_sell = ty.starts_with("sell") || ty == "sale";
+
+    if is_buy_type(&ty) || is_sell {
+        if quantity.is_none_or(|q| q <= 0.0) {
+            return Err(AppError::validation(
+                "quantity",
+                format!("Quantity must be positive for {} transactions", ty),
+            ));
+        }
+        if price.is_none_or(|p| p < 0.0) {
+            return Err(AppError::validation(
+                "price",
+                format!("Price must be zero or positive for {} transactions", ty),
+            ));
+        }
+    } else if is_conversion_type(&ty) {
+        if quantity.is_none_or(|q| q == 0.0) {
+            return Err(AppError::validation(
+                "quantity",
+                format!("Quantity must be non-zero for {} transactions", ty),
+            ));
+        }
+    } else if ty.contains("split") {
+        if parse_f64_str(&transaction.split_ratio).is_none_or(|r| r <= 0.0) {
+            return Err(AppError::validation(
+                "split_ratio",
+                "Split transactions need a positive split_ratio",
+            ));
+        }
+    } else if !matches!(ty.as_str(), "dividend" | "div" | "spinoff") {
+        return Err(AppError::validation(
+            "transaction_type",
+            format!(
+                "Unrecognized transaction type '{}'",
+                transaction.transaction_type
+            ),
+        ));
+    }
+
+    if ty == "rights_subscribe" {
+        // Rights are offered below market; a subscription price above it is almost
+        // always a data entry mistake.
+        let price = price.unwrap_or(0.0);
+        if price <= 0.0 {
+            return Err(AppError::validation(
+                "price",
+                "Rights subscription price must be positive",
+            ));
+        }
+        if let Some(market) = recent_market_close(&app_handle, symbol, date) {
+            if price > market {
+                return Err(AppError::validation(
+                    "price",
+                    format!(
+                        "Rights subscription price {} for {} is above the recent market price {}",
+                        price, symbol, market
+                    ),
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Serialize, Clone)]
+struct TransactionsChanged {
+    file: String,
+    transactions: usize,
+}
+
+/// The `TRANSACTION_FILES` entry named `file`, matched case-insensitively.
+fn transaction_file_entry(file: &str) -> Result<(&'static str, &'static str), AppError> {
+    TRANSACTION_FILES
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(file.trim()))
+        .copied()
+        .ok_or_else(|| {
+            AppError::validation(
+                "file",
+                format!(
+                    "Unknown transaction file '{}'; expected one of {}",
+                    file.trim(),
+                    TRANSACTION_FILES
+                        .iter()
+                        .map(|(name, _)| *name)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            )
+        })
+}
+
+/// Re-reads a transaction file after a write and tells windows about it. Returns
+/// the number of transactions now in the file.
+fn notify_transactions_changed(
+    app_handle: &tauri::AppHandle,
+    filename: &str,
+    path: &Path,
+    currency: &str,
+) -> Result<usize, String> {
+    let transactions = read_csv_file(&path.to_string_lossy(), currency)?.len();
+    let _ = app_handle.emit_all(
+        "transactions_changed",
+        TransactionsChanged {
+            file: filename.to_string(),
+            transactions,
+        },
+    );
+    Ok(transactions)
+}
+
+/// Validates and appends one transaction to the file for its currency, or to
+/// `target_file` (e.g. `US_Trx.csv`) when given; rows in a file take that file's
+/// currency when read. A row matching an existing transaction's date, symbol, type,
+/// quantity and price is rejected. Returns the file's transaction count.
+#[tauri::command]
+fn add_transaction(
+    app_handle: tauri::AppHandle,
+    transaction: Transaction,
+    target_file: Option<String>,
+) -> Result<usize, AppError> {
+    validate_transaction(app_handle.clone(), transaction.clone())?;
+
+    let (filename, currency) = match target_file.filter(|f| !f.trim().is_empty()) {
+        Some(file) => transaction_file_entry(&file)?,
+        None => TRANSACTION_FILES
+            .iter()
+            .find(|(_, c)| c.eq_ignore_ascii_case(transaction.currency.trim()))
+            .copied()
+            .ok_or_else(|| {
+                AppError::validation(
+                    "currency",
+                    format!("No transaction file for currency {}", transaction.currency),
+                )
+            })?,
+    };
+
+    let same = |a: &str, b: &str| match (parse_f64_str(a), parse_f64_str(b)) {
+        (Some(a), Some(b)) => (a - b).abs() < 1e-9,
+        _ => a.trim() == b.trim(),
+    };
+    let duplicate = load_all_transactions(&app_handle)?.iter().any(|existing| {
+        existing.date.trim() == transaction.date.trim()
+            && existing.stock == canonical_symbol(&transaction.stock)
+            && existing
+                .transaction_type
+                .trim()
+                .eq_ignore_ascii_case(transaction.transaction_type.trim())
+            && same(&existing.quantity, &transaction.quantity)
+            && same(&existing.price, &transaction.price)
+    });
+    if duplicate {
+        return Err(AppError::validation(
+            "transaction",
+            format!(
+                "{} {} on {} is already recorded",
+                transaction.transaction_type.trim(),
+                transaction.stock.trim(),
+                transaction.date.trim()
+            ),
+        ));
+    }
+
+    let path = transaction_file_path(&app_handle, filename)?;
+    append_transaction_rows_to(&app_handle, &path, &[transaction_row(&transaction)])?;
+    write_worker_log(
+        &app_handle,
+        &format!(
+            "Added {} {} on {} to {}",
+            transaction.transaction_type.trim(),
+            transaction.stock.trim(),
+            transaction.date.trim(),
+            filename
+        ),
+    )?;
+    Ok(notify_transactions_changed(
+        &app_handle,
+        filename,
+        &path,
+        currency,
+    )?)
+}
+
+/// Removes every row of `file` with the given date, symbol and type (type compared
+/// case-insensitively). Other rows, including the header, are kept as they are.
+/// Returns the file's remaining transaction count.
+#[tauri::command]
+fn delete_transaction(
+    app_handle: tauri::AppHandle,
+    file: String,
+    date: String,
+    stock: String,
+    transaction_type: String,
+) -> Result<usize, AppError> {
+    let (filename, currency) = transaction_file_entry(&file)?;
+    let path = transaction_file_path(&app_handle, filename)?;
+    if !path.is_file() {
+        return Err(AppError::not_found(
+            filename,
+            format!("{} does not exist", filename),
+        ));
+    }
+
+    let removed = with_file_lock(&app_handle, &path, || -> Result<usize, AppError> {
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_path(&path)
+            .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        let mut kept = Vec::new();
+        let mut removed = 0usize;
+        for (idx, record) in reader.records().enumerate() {
+            let record = record.map_err(|e| AppError::Parse {
+                file: filename.to_string(),
+                line: Some(idx + 1),
+                message: e.to_string(),
+            })?;
+            let field = |i: usize| record.get(i).unwrap_or("").trim();
+            let header = idx == 0 && is_transaction_header(field(0));
+            if !header
+                && field(0) == date.trim()
+                && canonical_symbol(field(1)) == canonical_symbol(&stock)
+                && field(2).eq_ignore_ascii_case(transaction_type.trim())
+            {
+                removed += 1;
+                continue;
+            }
+            kept.push(record);
+        }
+        if removed == 0 {
+            return Err(AppError::not_found(
+                "transaction",
+                format!(
+                    "No {} {} on {} in {}",
+                    transaction_type.trim(),
+                    stock.trim(),
+                    date.trim(),
+                    filename
+                ),
+            ));
+        }
+
+        let mut writer = csv::WriterBuilder::new()
+            .flexible(true)
+            .from_writer(Vec::new());
+        for record in &kept {
+            writer
+                .write_record(record)
+                .map_err(|e| format!("Failed to write {}: {}", filename, e))?;
+        }
+        let content = writer
+            .into_inner()
+            .map_err(|e| format!("Failed to write {}: {}", filename, e))?;
+        atomic_write(&app_handle, &path, &content)?;
+        invalidate_transaction_cache(&app_handle);
+        Ok(removed)
+    })?;
+    invalidate_coverage_cache(&app_handle);
+
+    write_worker_log(
+        &app_handle,
+        &format!(
+            "Deleted {} {} row(s) for {} on {} from {}",
+            removed,
+            transaction_type.trim(),
+            stock.trim(),
+            date.trim(),
+            filename
+        ),
+    )?;
+    Ok(notify_transactions_changed(
+        &app_handle,
+        filename,
+        &path,
+        currency,
+    )?)
+}
+
+const CORPORATE_ACTIONS_HEADER: &str = "date,symbol,action,ratio,price,quantity,recorded_at\n";
+const CORPORATE_EVENTS_HEADER: &str =
+    "date,symbol,event_type,description,related_symbol,exchange_ratio\n";
+const CORPORATE_EVENT_TYPES: [&str; 6] = [
+    "merger",
+    "acquisition",
+    "delisting",
+    "bankruptcy",
+    "ticker_change",
+    "other",
+];
+
+#[derive(Serialize, Clone)]
+struct CorporateEvent {
+    date: String,
+    symbol: String,
+    event_type: String,
+    description: String,
+    related_symbol: Option<String>,
+    exchange_ratio: Option<f64>,
+}
+
+/// Event types after which the symbol no longer trades.
+fn ends_trading(event_type: &str) -> bool {
+    matches!(
+        event_type,
+        "merger" | "acquisition" | "delisting" | "bankruptcy"
+    )
+}
+
+fn load_corporate_events(app_handle: &tauri::AppHandle) -> Result<Vec<CorporateEvent>, String> {
+    let path = get_data_dir(app_handle)?.join("corporate_events.csv");
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_path(&path)
+        .map_err(|e| format!("Failed to read corporate_events.csv: {}", e))?;
+
+    let mut events = Vec::new();
+    for record in reader.records().flatten() {
+        let field = |idx: usize| record.get(idx).unwrap_or("").trim().to_string();
+        let (date, symbol) = (field(0), field(1));
+        if date.is_empty() || symbol.is_empty() {
+            continue;
+        }
+        events.push(CorporateEvent {
+            date,
+            symbol,
+            event_type: field(2).to_lowercase(),
+            description: field(3),
+            related_symbol: Some(field(4)).filter(|s| !s.is_empty()),
+            exchange_ratio: parse_f64_str(&field(5)),
+        });
+    }
+    events.sort_by(|a, b| a.date.cmp(&b.date));
+    Ok(events)
+}
+
+const WATCHLIST_HEADER: &str = "symbol,added_at\n";
+/// How far back price history is fetched for symbols that are only watched.
+const WATCHLIST_HISTORY_DAYS: i64 = 5 * 365;
+
+#[derive(Serialize, Clone)]
+struct WatchlistEntry {
+    symbol: String,
+    added_at: Option<String>,
+}
+
+/// Reads data/watchlist.csv. The file is meant to be hand-edited: `#` comments,
+/// blank lines, a missing header and extra columns are all tolerated, and repeated
+/// symbols keep their first entry.
+fn load_watchlist(app_handle: &tauri::AppHandle) -> Result<Vec<WatchlistEntry>, String> {
+    let path = get_data_dir(app_handle)?.join("watchlist.csv");
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .comment(Some(b'#'))
+        .trim(csv::Trim::All)
+        .from_path(&path)
+        .map_err(|e| format!("Failed to read watchlist.csv: {}", e))?;
+
+    let mut entries: Vec<WatchlistEntry> = Vec::new();
+    for record in reader.records().flatten() {
+        let symbol = record.get(0).unwrap_or("").to_uppercase();
+        if symbol.is_empty() || symbol == "SYMBOL" || entries.iter().any(|e| e.symbol == symbol) {
+            continue;
+        }
+        entries.push(WatchlistEntry {
+            symbol,
+            added_at: record.get(1).filter(|s| !s.is_empty()).map(str::to_string),
+        });
+    }
+    Ok(entries)
+}
+
+/// Watched symbols that have no transactions.
+fn watch_only_symbols(app_handle: &tauri::AppHandle, transactions: &[Transaction]) -> Vec<String> {
+    let held: std::collections::HashSet<&str> =
+        transactions.iter().map(|t| t.stock.trim()).collect();
+    load_watchlist(app_handle)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|entry| entry.symbol)
+        .filter(|symbol| !held.contains(symbol.as_str()))
+        .collect()
+}
+
+#[tauri::command]
+fn get_watchlist(app_handle: tauri::AppHandle) -> Result<Vec<WatchlistEntry>, AppError> {
+    Ok(load_watchlist(&app_handle)?)
+}
+
+#[tauri::command]
+fn add_watch_symbol(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+) -> Result<Vec<WatchlistEntry>, AppError> {
+    let symbol = symbol.trim().to_uppercase();
+    if symbol.is_empty() || symbol.contains(',') || symbol.starts_with('#') {
+        return Err(AppError::validation(
+            "symbol",
+            format!("Invalid watchlist symbol '{}'", symbol),
+        ));
+    }
+    if !load_watchlist(&app_handle)?
+        .iter()
+        .any(|entry| entry.symbol == symbol)
+    {
+        let path = get_data_dir(&app_handle)?.join("watchlist.csv");
+        append_csv_rows(
+            &app_handle,
+            &path,
+            WATCHLIST_HEADER,
+            &[vec![symbol, Utc::now().format("%Y-%m-%d").to_string()]],
+        )?;
+    }
+    Ok(load_watchlist(&app_handle)?)
+}
+
+/// Drops every line for `symbol`, leaving comments and other rows as written.
+#[tauri::command]
+fn remove_watch_symbol(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+) -> Result<Vec<WatchlistEntry>, AppError> {
+    let symbol = symbol.trim().to_uppercase();
+    let path = get_data_dir(&app_handle)?.join("watchlist.csv");
+    if !path.exists() {
+        return Err(AppError::not_found(
+            "watchlist",
+            format!("{} is not on the watchlist", symbol),
+        ));
+    }
+    with_file_lock(&app_handle, &path, || -> Result<(), AppError> {
+        let content = read_to_string(&path)?;
+        let mut removed = false;
+        let kept: Vec<&str> = content
+            .lines()
+            .filter(|line| {
+                let first = line.split(',').next().unwrap_or("").trim();
+                let matches =
+                    !line.trim_start().starts_with('#') && first.eq_ignore_ascii_case(&symbol);
+                removed |= matches;
+                !matches
+            })
+            .collect();
+        if !removed {
+            return Err(AppError::not_found(
+                "watchlist",
+                format!("{} is not on the watchlist", symbol),
+            ));
+        }
+        let mut updated = kept.join("\n");
+        updated.push('\n');
+        atomic_write(&app_handle, &path, updated.as_bytes())?;
+        Ok(())
+    })?;
+    Ok(load_watchlist(&app_handle)?)
+}
+
+/// The earliest trading-ending event on or before `as_of` per symbol.
+fn delisting_events(
+    app_handle: &tauri::AppHandle,
+    as_of: NaiveDate,
+) -> HashMap<String, CorporateEvent> {
+    let mut delisted: HashMap<String, CorporateEvent> = HashMap::new();
+    for event in load_corporate_events(app_handle).unwrap_or_default() {
+        let on_or_before = NaiveDate::parse_from_str(&event.date, "%Y-%m-%d")
+            .map(|date| date <= as_of)
+            .unwrap_or(false);
+        if on_or_before && ends_trading(&event.event_type) {
+            delisted.entry(event.symbol.clone()).or_insert(event);
+        }
+    }
+    delisted
+}
+
+/// Days without a regular-market trade after which a Yahoo meta counts as stale.
+const DELISTING_STALE_DAYS: i64 = 30;
+
+/// Reads a Yahoo chart meta (and the previously saved one, when there is one) for
+/// signs that the symbol stopped trading. A missing `regularMarketPrice` is enough
+/// on its own; otherwise two of: `exchangeName` or `quoteType` changed since the last
+/// meta, a `marketState` other than REGULAR, or no trade for
+/// `DELISTING_STALE_DAYS`. Returns the last trade date and the reasons.
+fn detect_delisting(
+    previous: Option<&serde_json::Value>,
+    current: &serde_json::Value,
+    today: NaiveDate,
+) -> Option<(NaiveDate, String)> {
+    let text = |meta: &serde_json::Value, key: &str| {
+        meta.get(key)
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+            .filter(|v| !v.is_empty())
+    };
+    let last_trade = current
+        .get("regularMarketTime")
+        .and_then(|v| v.as_i64())
+        .and_then(|ts| DateTime::from_timestamp(ts, 0))
+        .map(|dt| dt.date_naive());
+
+    let mut reasons = Vec::new();
+    let price_missing = current
+        .get("regularMarketPrice")
+        .and_then(|v| v.as_f64())
+        .is_none();
+    if price_missing {
+        reasons.push("regularMarketPrice missing".to_string());
+    }
+    for key in ["exchangeName", "quoteType"] {
+        if let (Some(before), Some(after)) = (
+            previous.and_then(|meta| text(meta, key)),
+            text(current, key),
+        ) {
+            if before != after {
+                reasons.push(format!("{} changed from {} to {}", key, before, after));
+            }
+        }
+    }
+    if let Some(state) = text(current, "marketState").filter(|s| s != "REGULAR") {
+        reasons.push(format!("marketState is {}", state));
+    }
+    if let Some(date) = last_trade.filter(|d| (today - *d).num_days() > DELISTING_STALE_DAYS) {
+        reasons.push(format!("no trade since {}", date));
+    }
+
+    (price_missing || reasons.len() >= 2).then(|| (last_trade.unwrap_or(today), reasons.join("; ")))
+}
+
+/// Appends an auto-detected `delisting` corporate event unless the symbol already
+/// has a trading-ending event. The sync skips the symbol once that date has passed.
+fn record_detected_delisting(
+    app_handle: &tauri::AppHandle,
+    symbol: &str,
+    date: NaiveDate,
+    reason: &str,
+) -> Result<bool, String> {
+    let already = load_corporate_events(app_handle)?
+        .iter()
+        .any(|event| event.symbol == symbol && ends_trading(&event.event_type));
+    if already {
+        return Ok(false);
+    }
+    let path = get_data_dir(app_handle)?.join("corporate_events.csv");
+    append_csv_rows(
+        app_handle,
+        &path,
+        CORPORATE_EVENTS_HEADER,
+        &[vec![
+            date.format("%Y-%m-%d").to_string(),
+            symbol.to_string(),
+            "delisting".to_string(),
+            format!("Detected from Yahoo meta: {}", reason),
+            String::new(),
+            String::new(),
+        ]],
+    )?;
+    invalidate_coverage_cache(app_handle);
+    write_worker_log(
+        app_handle,
+        &format!("Marked {} as delisted on {}: {}", symbol, date, reason),
+    )?;
+    Ok(true)
+}
+
+/// Runs the delisting check against every saved Yahoo meta for traded and watched
+/// symbols, recording new detections. Returns the symbols that look delisted.
+/// Without an earlier meta to compare, only the current meta's signals apply.
+#[tauri::command]
+fn check_for_delistings(app_handle: tauri::AppHandle) -> Result<Vec<String>, AppError> {
+    let today = Utc::now().date_naive();
+    let transactions = load_all_transactions(&app_handle)?;
+    let mut symbols = traded_symbols(&app_handle)?;
+    symbols.extend(watch_only_symbols(&app_handle, &transactions));
+
+    let mut delisted = Vec::new();
+    for symbol in symbols {
+        let Some(meta) = load_yahoo_meta(&app_handle, &symbol) else {
+            continue;
+        };
+        if let Some((date, reason)) = detect_delisting(None, &meta, today) {
+            record_detected_delisting(&app_handle, &symbol, date, &reason)?;
+            delisted.push(symbol);
+        }
+    }
+    Ok(delisted)
+}
+
+#[tauri::command]
+fn record_corporate_event(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+    date: String,
+    event_type: String,
+    description: String,
+    related_symbol: Option<String>,
+    exchange_ratio: Option<f64>,
+) -> Result<(), AppError> {
+    let symbol = symbol.trim().to_string();
+    if symbol.is_empty() {
+        return Err(AppError::validation("symbol", "Symbol is required"));
+    }
+    let date = NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+        .map_err(|e| format!("Invalid event date {}: {}", date, e))?;
+    let event_type = event_type.trim().to_lowercase();
+    if !CORPORATE_EVENT_TYPES.contains(&event_type.as_str()) {
+        return Err(AppError::validation(
+            "event_type",
+            format!(
+                "Unknown event type '{}'; expected one of {}",
+                event_type,
+                CORPORATE_EVENT_TYPES.join(", ")
+            ),
+        ));
+    }
+    if let Some(ratio) = exchange_ratio {
+        if !ratio.is_finite() || ratio <= 0.0 {
+            return Err(AppError::validation(
+                "exchange_ratio",
+                format!("Exchange ratio must be positive, got {}", ratio),
+            ));
+        }
+    }
+
+    let path = get_data_dir(&app_handle)?.join("corporate_events.csv");
+    append_csv_rows(
+        &app_handle,
+        &path,
+        CORPORATE_EVENTS_HEADER,
+        &[vec![
+            date.format("%Y-%m-%d").to_string(),
+            symbol.clone(),
+            event_type.clone(),
+            description.trim().to_string(),
+            related_symbol.unwrap_or_default().trim().to_string(),
+            exchange_ratio.map(|r| r.to_string()).unwrap_or_default(),
+        ]],
+    )?;
+    invalidate_coverage_cache(&app_handle);
+
+    Ok(write_worker_log(
+        &app_handle,
+        &format!("Recorded {} for {} on {}", event_type, symbol, date),
+    )?)
+}
+
+#[tauri::command]
+fn get_corporate_events(
+    app_handle: tauri::AppHandle,
+    symbol: Option<String>,
+) -> Result<Vec<CorporateEvent>, AppError> {
+    let mut events = load_corporate_events(&app_handle)?;
+    if let Some(symbol) = symbol.map(|s| s.trim().to_string()) {
+        events.retain(|event| {
+            event.symbol == symbol || event.related_symbol.as_deref() == Some(symbol.as_str())
+        });
+    }
+    Ok(events)
+}
+
+#[tauri::command]
+fn record_rights_issue(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+    subscription_ratio: f64,
+    subscription_price: f64,
+    ex_rights_date: String,
+) -> Result<(), AppError> {
+    let symbol = symbol.trim().to_string();
+    if !subscription_ratio.is_finite() || subscription_ratio <= 0.0 {
+        return Err(AppError::validation(
+            "subscription_ratio",
+            format!(
+                "Subscription ratio must be positive, got {}",
+                subscription_ratio
+            ),
+        ));
+    }
+    let date = NaiveDate::parse_from_str(ex_rights_date.trim(), "%Y-%m-%d")
+        .map_err(|e| format!("Invalid ex-rights date {}: {}", ex_rights_date, e))?;
+
+    // Entitlement is based on shares held going into the ex-rights date; rights
+    // are only issued for whole new shares.
+    let transactions = load_symbol_transactions(&app_handle, &symbol)?;
+    let held = shares_held_on(&transactions, date - ChronoDuration::days(1));
+    let quantity = (held * subscription_ratio).floor();
+    if quantity <= 0.0 {
+        return Err(AppError::validation(
+            "subscription_ratio",
+            format!(
+                "{} shares of {} held before {} do not entitle any new shares",
+                held, symbol, date
+            ),
+        ));
+    }
+    let currency = transactions
+        .last()
+        .map(|txn| txn.currency.clone())
+        .unwrap_or_else(|| {
+            currency_for_exchange(get_exchange_and_symbol(&symbol).0.as_deref()).to_string()
+        });
+
+    let date_str = date.format("%Y-%m-%d").to_string();
+    let transaction = Transaction {
+        date: date_str.clone(),
+        stock: symbol.clone(),
+        transaction_type: "rights_subscribe".to_string(),
+        quantity: quantity.to_string(),
+        price: subscription_price.to_string(),
+        fees: "0".to_string(),
+        split_ratio: String::new(),
+        currency: currency.clone(),
+        lot_id: String::new(),
+        source: None,
+    };
+    validate_transaction(app_handle.clone(), transaction.clone())?;
+
+    let actions_path = get_data_dir(&app_handle)?.join("corporate_actions.csv");
+    append_csv_rows(
+        &app_handle,
+        &actions_path,
+        CORPORATE_ACTIONS_HEADER,
+        &[vec![
+            date_str,
+            symbol.clone(),
+            "rights_issue".to_string(),
+            subscription_ratio.to_string(),
+            subscription_price.to_string(),
+            quantity.to_string(),
+            Utc::now().to_rfc3339(),
+        ]],
+    )?;
+
+    Ok(append_transaction_rows(
+        &app_handle,
+        &currency,
+        &[vec![
+            transaction.date,
+            transaction.stock,
+            transaction.transaction_type,
+            transaction.quantity,
+            transaction.price,
+            transaction.fees,
+            transaction.split_ratio,
+        ]],
+    )?)
+}
+
+/// Currency of the transaction file a symbol's trades are kept in.
+fn symbol_currency(app_handle: &tauri::AppHandle, symbol: &str) -> Result<String, String> {
+    if let Some(txn) = load_all_transactions(app_handle)?
+        .iter()
+        .find(|txn| txn.stock == symbol)
+    {
+        return Ok(txn.currency.clone());
+    }
+    Ok(currency_for_exchange(get_exchange_and_symbol(symbol).0.as_deref()).to_string())
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+fn record_adr_conversion(
+    app_handle: tauri::AppHandle,
+    adr_symbol: String,
+    ordinary_symbol: String,
+    date: String,
+    adr_quantity: f64,
+    ordinary_quantity: f64,
+    conversion_ratio: f64,
+    to_adr: Option<bool>,
+) -> Result<(), AppError> {
+    let adr_symbol = adr_symbol.trim().to_string();
+    let ordinary_symbol = ordinary_symbol.trim().to_string();
+    if adr_symbol.is_empty() || ordinary_symbol.is_empty() || adr_symbol == ordinary_symbol {
+        return Err(AppError::validation(
+            "ordinary_symbol",
+            "ADR and ordinary symbols must be different and non-empty",
+        ));
+    }
+    if adr_quantity <= 0.0 || ordinary_quantity <= 0.0 || conversion_ratio <= 0.0 {
+        return Err(AppError::validation(
+            "adr_quantity",
+            "Quantities and conversion ratio must be positive",
+        ));
+    }
+    // conversion_ratio is ordinary shares per ADR.
+    let expected = adr_quantity * conversion_ratio;
+    if (expected - ordinary_quantity).abs() > expected * 1e-6 {
+        return Err(AppError::validation(
+            "ordinary_quantity",
+            format!(
+                "{} ADRs at {} ordinary shares each is {} shares, not {}",
+                adr_quantity, conversion_ratio, expected, ordinary_quantity
+            ),
+        ));
+    }
+    let date = NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+        .map_err(|e| format!("Invalid conversion date {}: {}", date, e))?;
+
+    let to_adr = to_adr.unwrap_or(false);
+    let (txn_type, source, source_qty, target, target_qty) = if to_adr {
+        (
+            "ordinary_convert_to_adr",
+            &ordinary_symbol,
+            ordinary_quantity,
+            &adr_symbol,
+            adr_quantity,
+        )
+    } else {
+        (
+            "adr_convert_to_ordinary",
+            &adr_symbol,
+            adr_quantity,
+            &ordinary_symbol,
+            ordinary_quantity,
+        )
+    };
+
+    let source_transactions = load_symbol_transactions(&app_handle, source)?;
+    let held = shares_held_on(&source_transactions, date);
+    if held + 1e-9 < source_qty {
+        return Err(AppError::validation(
+            "adr_quantity",
+            format!(
+                "Only {} shares of {} held on {}, cannot convert {}",
+                held, source, date, source_qty
+            ),
+        ));
+    }
+    // The exchange is not a disposal: each lot given up, taken first-in first-out,
+    // crosses over with its acquisition date and total cost, its quantity scaled by
+    // the ratio. The lot id on the received leg carries the date.
+    let carried = conversion_carried_lots(
+        &fifo_open_lots(&source_transactions, date),
+        source_qty,
+        target_qty / source_qty,
+    );
+    let moved_cost: f64 = carried
+        .iter()
+        .map(|lot| lot.quantity * lot.cost_per_share)
+        .sum();
+    let date_str = date.format("%Y-%m-%d").to_string();
+    let row = |symbol: &String, quantity: f64, price: f64, lot_id: &str| {
+        let mut row = vec![
+            date_str.clone(),
+            symbol.clone(),
+            txn_type.to_string(),
+            quantity.to_string(),
+            price.to_string(),
+            "0".to_string(),
+            String::new(),
+        ];
+        if !lot_id.is_empty() {
+            row.push(lot_id.to_string());
+        }
+        row
+    };
+
+    append_transaction_rows(
+        &app_handle,
+        &symbol_currency(&app_handle, source)?,
+        &[row(source, -source_qty, moved_cost / source_qty, "")],
+    )?;
+    let received: Vec<Vec<String>> = carried
+        .iter()
+        .map(|lot| row(target, lot.quantity, lot.cost_per_share, &lot.id))
+        .collect();
+    append_transaction_rows(
+        &app_handle,
+        &symbol_currency(&app_handle, target)?,
+        &received,
+    )?;
+
+    let actions_path = get_data_dir(&app_handle)?.join("corporate_actions.csv");
+    Ok(append_csv_rows(
+        &app_handle,
+        &actions_path,
+        CORPORATE_ACTIONS_HEADER,
+        &[vec![
+            date_str,
+            source.clone(),
+            format!("{}:{}", txn_type, target),
+            conversion_ratio.to_string(),
+            (moved_cost / target_qty).to_string(),
+            target_qty.to_string(),
+            Utc::now().to_rfc3339(),
+        ]],
+    )?)
+}
+
+/// The lots received for `quantity` shares given up from `lots` (FIFO order), each
+/// keeping its id, date and total cost with its quantity multiplied by `ratio`.
+fn conversion_carried_lots(lots: &[OpenLot], quantity: f64, ratio: f64) -> Vec<OpenLot> {
+    let mut remaining = quantity;
+    let mut carried = Vec::new();
+    for lot in lots {
+        if remaining <= 1e-9 {
+            break;
+        }
+        let taken = lot.quantity.min(remaining);
+        remaining -= taken;
+        carried.push(OpenLot {
+            quantity: taken * ratio,
+            cost_per_share: lot.cost_per_share / ratio,
+            ..lot.clone()
+        });
+    }
+    carried
+}
+
+/// `(date, close, shares)` per price date. Trades before the first price row are
+/// applied on that row; trades after the last one add a final point on the last
+/// trade date at the last known close. Both cases are reported in the returned
+/// warnings, since the row dates no longer match the trade dates.
+fn build_position_timeline(
+    prices: &[PriceRecordEntry],
+    transactions: &[ProcessedTransaction],
+) -> (Vec<(String, f64, f64)>, Vec<String>) {
+    let mut results = Vec::new();
+    let mut warnings = Vec::new();
+    let (Some(first_price), Some(last_price)) = (prices.first(), prices.last()) else {
+        return (results, warnings);
+    };
+
+    for txn in transactions.iter().filter(|t| t.date < first_price.date) {
+        warnings.push(format!(
+            "{} on {} predates price history (starts {}); applied on that day",
+            txn.txn_type, txn.date, first_price.date
+        ));
+    }
+
+    let mut idx = 0usize;
+    let mut shares = 0.0f64;
+
+    for price in prices {
+        while idx < transactions.len() && transactions[idx].date <= price.date {
+            shares = apply_transaction_to_shares(shares, &transactions[idx]);
+            idx += 1;
+        }
+
+        results.push((
+            price.date.format("%Y-%m-%d").to_string(),
+            price.close,
+            shares,
+        ));
+    }
+
+    if let Some(last_txn) = transactions.last().filter(|_| idx < transactions.len()) {
+        for txn in &transactions[idx..] {
+            shares = apply_transaction_to_shares(shares, txn);
+            warnings.push(format!(
+                "{} on {} is after the last price ({}); valued at that close",
+                txn.txn_type, txn.date, last_price.date
+            ));
+        }
+        results.push((
+            last_txn.date.format("%Y-%m-%d").to_string(),
+            last_price.close,
+            shares,
+        ));
+    }
+
+    (results, warnings)
+}
+
+/// Price frames keyed by the symbol their file stem decodes to. Override files
+/// appear under their `-override` symbol.
+type PriceFrames = std::collections::BTreeMap<String, DataFrame>;
+
+/// Every price file as a per-symbol frame, so coverage and stats can read dates
+/// and row counts without building a `PriceRecordEntry` for each row.
+fn load_price_frames(app_handle: &tauri::AppHandle) -> Result<PriceFrames, AppError> {
+    let prices_dir = match get_prices_dir(app_handle) {
+        Ok(dir) => dir,
+        Err(_) => return Ok(PriceFrames::new()),
+    };
+
+    let entries = match std::fs::read_dir(&prices_dir) {
+        Ok(e) => e,
+        Err(_) => return Ok(PriceFrames::new()),
+    };
+
+    let paths: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("csv"))
+        .collect();
+    scan_price_frames(&paths)
+}
+
+/// Scans `paths` in parallel on rayon's pool; files are independent. Fails with
+/// the first file that cannot be read.
+fn scan_price_frames(paths: &[PathBuf]) -> Result<PriceFrames, AppError> {
+    paths
+        .par_iter()
+        .filter_map(|path| {
+            let symbol = symbol_from_file_stem(path.file_stem()?.to_str()?);
+            Some(scan_price_frame(path).map(|frame| (symbol, frame)))
+        })
+        .collect()
+}
+
+/// Dates in a price frame's `day` column, in frame order.
+fn price_frame_dates(frame: &DataFrame) -> Vec<NaiveDate> {
+    frame
+        .column("day")
+        .ok()
+        .and_then(|days| days.i32().ok())
+        .map(|days| {
+            days.into_iter()
+                .flatten()
+                .filter_map(NaiveDate::from_num_days_from_ce_opt)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Earliest and latest date in a price frame, or `None` when it has no rows.
+fn price_frame_span(frame: &DataFrame) -> Option<(NaiveDate, NaiveDate)> {
+    let days = frame.column("day").ok()?.i32().ok()?;
+    Some((
+        NaiveDate::from_num_days_from_ce_opt(days.min()?)?,
+        NaiveDate::from_num_days_from_ce_opt(days.max()?)?,
+    ))
+}
+
+/// Days from the common era to 1970-01-01, the epoch polars counts `Date` from.
+const UNIX_EPOCH_DAYS_FROM_CE: i32 = 719_163;
+
+/// One price CSV as a frame in the `price_entries_frame` layout, in file order.
+/// Columns are found by header name, so the ten-column layout written by
+/// `build_price_csv_content` and older files with fewer or extra columns all load;
+/// absent columns come back null. The price columns are first read with an
+/// explicit Float64 schema; a file polars cannot parse that way (thousands
+/// separators, decimal commas) is read again as text and its numbers go through
+/// `parse_f64_str`. Rows without a valid date or close are dropped. Zero and
+/// negative closes are kept so `scan_price_anomalies` can flag them as
+/// `non_positive_close`.
+fn scan_price_frame(path: &Path) -> Result<DataFrame, AppError> {
+    let header = LazyCsvReader::new(path)
+        .has_header(true)
+        .with_infer_schema_length(Some(0))
+        .finish()
+        .and_then(|lazy| lazy.schema())
+        .map_err(|e| {
+            AppError::polars(
+                path,
+                &e,
+                format!("Failed to read header of {}: {}", path.display(), e),
+            )
+        })?;
+    if !header.contains("date") || !header.contains("close") {
+        return Ok(price_entries_frame(&[])?);
+    }
+    scan_price_frame_with(path, &header, true)
+        .or_else(|_| scan_price_frame_with(path, &header, false))
+}
+
+/// Reads a price CSV whose columns are `header`, with the price columns typed as
+/// Float64 when `typed`, or as text parsed by `parse_number_column` otherwise.
+fn scan_price_frame_with(path: &Path, header: &Schema, typed: bool) -> Result<DataFrame, AppError> {
+    let mut explicit: Schema = PRICE_VALUE_COLUMNS
+        .iter()
+        .filter(|name| typed && header.contains(name))
+        .map(|name| Field::new(name, DataType::Float64))
+        .collect();
+    if typed {
+        explicit.with_column("date".into(), DataType::Date);
+    }
+    let lazy = LazyCsvReader::new(path)
+        .has_header(true)
+        .with_infer_schema_length(Some(0))
+        .with_dtype_overwrite(Some(&explicit))
+        .finish()
+        .map_err(|e| {
+            AppError::polars(
+                path,
+                &e,
+                format!("Failed to read {}: {}", path.display(), e),
+            )
+        })?;
+
+    let trimmed = |name: &str| col(name).str().strip_chars(lit(Null {}));
+    let date = if typed {
+        col("date")
+    } else {
+        trimmed("date").str().to_date(StrptimeOptions {
+            format: Some("%Y-%m-%d".into()),
+            strict: false,
+            exact: true,
+            cache: false,
+        })
+    };
+    let mut columns =
+        vec![(date.cast(DataType::Int32) + lit(UNIX_EPOCH_DAYS_FROM_CE)).alias("day")];
+    for name in PRICE_VALUE_COLUMNS {
+        columns.push(if !header.contains(name) {
+            lit(Null {}).cast(DataType::Float64).alias(name)
+        } else if typed {
+            when(col(name).is_finite())
+                .then(col(name))
+                .otherwise(lit(Null {}))
+                .alias(name)
+        } else {
+            col(name).map(parse_number_column, GetOutput::from_type(DataType::Float64))
+        });
+    }
+    columns.push(if header.contains("source") {
+        let source = trimmed("source");
+        when(source.clone().is_null().or(source.clone().eq(lit(""))))
+            .then(lit(LEGACY_SOURCE))
+            .otherwise(source)
+            .alias("source")
+    } else {
+        lit(LEGACY_SOURCE).alias("source")
+    });
+
+    lazy.select(columns)
+        .filter(col("day").is_not_null().and(col("close").is_not_null()))
+        .collect()
+        .map_err(|e| {
+            AppError::polars(
+                path,
+                &e,
+                format!("Failed to read {}: {}", path.display(), e),
+            )
+        })
+}
+
+/// Text column as Float64: polars' own cast first, then `parse_f64_str` for the
+/// values it cannot read. Non-finite values become null.
+fn parse_number_column(text: Series) -> PolarsResult<Option<Series>> {
+    let cast = text.cast(&DataType::Float64)?;
+    let values: Float64Chunked = cast
+        .f64()?
+        .into_iter()
+        .zip(text.str()?)
+        .map(|(number, raw)| {
+            number
+                .or_else(|| raw.and_then(parse_f64_str))
+                .filter(|v| v.is_finite())
+        })
+        .collect();
+    Ok(Some(values.with_name(text.name()).into_series()))
+}
+
+/// Rows of one price CSV, read through `scan_price_frame`.
+fn scan_price_file_records(path: &Path) -> Result<Vec<PriceRecordEntry>, AppError> {
+    let symbol = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .map(symbol_from_file_stem)
+        .ok_or_else(|| format!("Invalid price file name {}", path.display()))?;
+    let records = price_entries_from_frame(&symbol, &scan_price_frame(path)?)?;
+    warn_on_duplicate_dates(&symbol, records.iter().map(|r| r.date));
+    Ok(records)
+}
+
+const DEFAULT_PRICE_CACHE_CAPACITY: usize = 256;
+
+struct CachedPriceSeries {
+    fingerprint: (Option<std::time::SystemTime>, u64),
+    records: Arc<Vec<PriceRecordEntry>>,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct PriceStoreInner {
+    series: HashMap<PathBuf, CachedPriceSeries>,
+    clock: u64,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+}
+
+/// Parsed price files keyed by path. An entry is reused while the file's mtime and
+/// size match; `atomic_write` drops the entry for any path it replaces. The least
+/// recently used series are evicted once the `priceCacheCapacity` setting is exceeded.
+#[derive(Default)]
+struct PriceStore {
+    inner: Mutex<PriceStoreInner>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PriceCacheStats {
+    symbols: usize,
+    capacity: usize,
+    cached_rows: usize,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+}
+
+fn file_fingerprint(path: &Path) -> (Option<std::time::SystemTime>, u64) {
+    match std::fs::metadata(path) {
+        Ok(meta) => (meta.modified().ok(), meta.len()),
+        Err(_) => (None, 0),
+    }
+}
+
+impl PriceStore {
+    fn lookup(&self, path: &Path) -> Option<Arc<Vec<PriceRecordEntry>>> {
+        let fingerprint = file_fingerprint(path);
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        inner.clock += 1;
+        let now = inner.clock;
+        let records = match inner.series.get_mut(path) {
+            Some(entry) if entry.fingerprint == fingerprint => {
+                entry.last_used = now;
+                Some(Arc::clone(&entry.records))
+            }
+            _ => None,
+        };
+        if records.is_some() {
+            inner.hits += 1;
+        } else {
+            inner.misses += 1;
+        }
+        records
+    }
+
+    fn insert(
+        &self,
+        path: &Path,
+        fingerprint: (Option<std::time::SystemTime>, u64),
+        records: Arc<Vec<PriceRecordEntry>>,
+        capacity: usize,
+    ) {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        inner.clock += 1;
+        let last_used = inner.clock;
+        inner.series.insert(
+            path.to_path_buf(),
+            CachedPriceSeries {
+                fingerprint,
+                records,
+                last_used,
+            },
+        );
+        while inner.series.len() > capacity.max(1) {
+            let oldest = inner
+                .series
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(path, _)| path.clone());
+            match oldest {
+                Some(path) => {
+                    inner.series.remove(&path);
+                    inner.evictions += 1;
+                }
+                None => break,
+            }
+        }
+    }
+
+    fn invalidate(&self, path: &Path) {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        inner.series.remove(path);
+    }
+
+    fn clear(&self) {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        inner.series.clear();
+    }
+}
+
+fn price_cache_capacity(app_handle: &tauri::AppHandle) -> usize {
+    read_setting_value_internal(app_handle, "priceCacheCapacity")
+        .ok()
+        .flatten()
+        .and_then(|value| value.trim().parse::<usize>().ok())
+        .filter(|capacity| *capacity > 0)
+        .unwrap_or(DEFAULT_PRICE_CACHE_CAPACITY)
+}
+
+/// Parsed rows of one price file, served from `PriceStore` when the file is
+/// unchanged since it was last read. A missing file has no rows.
+fn cached_price_series(
+    app_handle: &tauri::AppHandle,
+    path: &Path,
+) -> Result<Arc<Vec<PriceRecordEntry>>, AppError> {
+    if !path.exists() {
+        return Ok(Arc::new(Vec::new()));
+    }
+    let store = app_handle.try_state::<PriceStore>();
+    if let Some(records) = store.as_ref().and_then(|store| store.lookup(path)) {
+        return Ok(records);
+    }
+
+    let fingerprint = file_fingerprint(path);
+    let records = Arc::new(scan_price_file_records(path)?);
+    if let Some(store) = store {
+        store.insert(
+            path,
+            fingerprint,
+            Arc::clone(&records),
+            price_cache_capacity(app_handle),
+        );
+    }
+    Ok(records)
+}
+
+#[tauri::command]
+fn clear_price_cache(app_handle: tauri::AppHandle) {
+    if let Some(store) = app_handle.try_state::<PriceStore>() {
+        store.clear();
+    }
+}
+
+#[tauri::command]
+fn get_price_cache_stats(app_handle: tauri::AppHandle) -> PriceCacheStats {
+    let capacity = price_cache_capacity(&app_handle);
+    let store = app_handle.state::<PriceStore>();
+    let inner = store.inner.lock().unwrap_or_else(|e| e.into_inner());
+    PriceCacheStats {
+        symbols: inner.series.len(),
+        capacity,
+        cached_rows: inner.series.values().map(|entry| entry.records.len()).sum(),
+        hits: inner.hits,
+        misses: inner.misses,
+        evictions: inner.evictions,
+    }
+}
+
+fn warn_on_duplicate_dates(symbol: &str, dates: impl Iterator<Item = NaiveDate>) {
+    let mut counts: HashMap<NaiveDate, usize> = HashMap::new();
+    for date in dates {
+        *counts.entry(date).or_default() += 1;
+    }
+    let duplicated = counts.values().filter(|count| **count > 1).count();
+    if duplicated > 0 {
+        eprintln!(
+            "[RUST] ⚠ {} has {} dates with duplicate price rows; run find_duplicate_price_rows",
+            symbol, duplicated
+        );
+    }
+}
+
+/// Compares two price CSVs ignoring the trailing `updated_at` field of each line,
+/// which changes on every rebuild.
+fn price_content_matches(existing: &str, new: &str) -> bool {
+    let strip = |line: &str| match line.rfind(',') {
+        Some(idx) => line[..idx].to_string(),
+        None => line.to_string(),
+    };
+    let existing_lines = existing.lines().filter(|l| !l.trim().is_empty());
+    let new_lines = new.lines().filter(|l| !l.trim().is_empty());
+    existing_lines.map(strip).eq(new_lines.map(strip))
+}
+
+/// Writes a symbol's price file unless the existing file already holds the same
+/// rows. Returns whether the file was written.
+fn write_price_entries_if_changed(
+    app_handle: &tauri::AppHandle,
+    symbol: &str,
+    records: &[PriceRecordEntry],
+) -> Result<bool, String> {
+    let mut entries = records.to_vec();
+    entries.sort_by_key(|r| std::cmp::Reverse(r.date));
+    let csv_content = build_price_csv_content(&entries)?;
+
+    let path = get_prices_dir(app_handle)?.join(format!("{}.csv", symbol_file_stem(symbol)));
+    if let Ok(existing) = read_to_string(&path) {
+        if price_content_matches(&existing, &csv_content) {
+            return Ok(false);
+        }
+    }
+
+    persist_price_file_content(app_handle, symbol, &csv_content)?;
+    Ok(true)
+}
+
+/// How long an interrupted sync stays eligible for resumption.
+const SYNC_RESUME_WINDOW_HOURS: i64 = 24;
+
+/// Progress of one history sync run, saved after every symbol so a run cut short
+/// by the app quitting can pick up where it stopped.
+#[derive(Serialize, Deserialize, Clone)]
+struct SyncCheckpoint {
+    run_id: String,
+    started_at: String,
+    /// Symbols in the order the run visits them.
+    symbols: Vec<String>,
+    /// How many of `symbols` have been attempted.
+    completed: usize,
+    finished: bool,
+}
+
+fn sync_checkpoint_path(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+    Ok(get_data_dir(app_handle)?.join("sync_checkpoint.json"))
+}
+
+fn load_sync_checkpoint(app_handle: &tauri::AppHandle) -> Result<Option<SyncCheckpoint>, String> {
+    let path = sync_checkpoint_path(app_handle)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content =
+        read_to_string(&path).map_err(|e| format!("Failed to read sync_checkpoint.json: {}", e))?;
+    match serde_json::from_str(&content) {
+        Ok(checkpoint) => Ok(Some(checkpoint)),
+        Err(e) => {
+            let _ = write_worker_log(
+                app_handle,
+                &format!("Ignoring unreadable sync_checkpoint.json: {}", e),
+            );
+            Ok(None)
+        }
+    }
+}
+
+fn save_sync_checkpoint(
+    app_handle: &tauri::AppHandle,
+    checkpoint: &SyncCheckpoint,
+) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(checkpoint)
+        .map_err(|e| format!("Failed to serialize sync checkpoint: {}", e))?;
+    atomic_write(
+        app_handle,
+        &sync_checkpoint_path(app_handle)?,
+        content.as_bytes(),
+    )
+}
+
+/// The last run, if it stopped partway through less than a day ago.
+fn resumable_sync_checkpoint(app_handle: &tauri::AppHandle) -> Option<SyncCheckpoint> {
+    let checkpoint = load_sync_checkpoint(app_handle).ok().flatten()?;
+    let started_at = DateTime::parse_from_rfc3339(&checkpoint.started_at).ok()?;
+    let age = Utc::now().signed_duration_since(started_at);
+    (!checkpoint.finished
+        && checkpoint.completed < checkpoint.symbols.len()
+        && age < ChronoDuration::hours(SYNC_RESUME_WINDOW_HOURS))
+    .then_some(checkpoint)
+}
+
+/// Checkpoint for `run_id` over `targets`, which are in priority order. Resuming
+/// keeps the interrupted run's order: symbols it already attempted come first and
+/// count as completed, followed by the rest of its list and then any symbols added
+/// since.
+fn sync_checkpoint_for_run(
+    app_handle: &tauri::AppHandle,
+    run_id: &str,
+    targets: &[String],
+) -> Result<SyncCheckpoint, String> {
+    let previous = load_sync_checkpoint(app_handle)?
+        .filter(|checkpoint| checkpoint.run_id == run_id && !checkpoint.finished);
+    let mut symbols: Vec<String> = Vec::new();
+    let mut completed = 0;
+    let mut started_at = Utc::now().to_rfc3339();
+    if let Some(previous) = previous {
+        started_at = previous.started_at;
+        for (index, symbol) in previous.symbols.into_iter().enumerate() {
+            if targets.contains(&symbol) && !symbols.contains(&symbol) {
+                if index < previous.completed {
+                    completed += 1;
+                }
+                symbols.push(symbol);
+            }
+        }
+    }
+    let added: Vec<String> = targets
+        .iter()
+        .filter(|symbol| !symbols.contains(symbol))
+        .cloned()
+        .collect();
+    symbols.extend(added);
+    Ok(SyncCheckpoint {
+        run_id: run_id.to_string(),
+        started_at,
+        symbols,
+        completed,
+        finished: false,
+    })
+}
+
+fn finish_sync_checkpoint(app_handle: &tauri::AppHandle, run_id: &str) -> Result<(), String> {
+    match load_sync_checkpoint(app_handle)? {
+        Some(mut checkpoint) if checkpoint.run_id == run_id => {
+            checkpoint.finished = true;
+            save_sync_checkpoint(app_handle, &checkpoint)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The interrupted sync `start_history_worker` would resume, if any.
+#[tauri::command]
+fn get_incomplete_run(app_handle: tauri::AppHandle) -> Option<SyncCheckpoint> {
+    resumable_sync_checkpoint(&app_handle)
+}
+
+/// Drops the interrupted sync's checkpoint so the next worker start runs a fresh
+/// full sync. Returns whether there was one to discard.
+#[tauri::command]
+fn discard_incomplete_run(app_handle: tauri::AppHandle) -> Result<bool, AppError> {
+    let Some(checkpoint) = resumable_sync_checkpoint(&app_handle) else {
+        return Ok(false);
+    };
+    std::fs::remove_file(sync_checkpoint_path(&app_handle)?)
+        .map_err(|e| format!("Failed to remove sync_checkpoint.json: {}", e))?;
+    write_run_log(
+        &app_handle,
+        &checkpoint.run_id,
+        LogLevel::Info,
+        &format!(
+            "Discarded incomplete run after {} of {} symbols",
+            checkpoint.completed,
+            checkpoint.symbols.len()
+        ),
+    )?;
+    Ok(true)
+}
+
+/// Symbols with transactions this recent sync right after open positions.
+const SYNC_RECENT_ACTIVITY_DAYS: i64 = 90;
+/// Default for the `sync_closed_positions_years` setting; 0 never skips.
+const DEFAULT_SYNC_CLOSED_POSITIONS_YEARS: i64 = 5;
+/// Missing trading days in a row that count as a gap rather than an unlisted holiday.
+const PRICE_GAP_TRADING_DAYS: usize = 3;
+
+/// Whether `records` miss a run of trading days between `start` and `end`.
+fn has_price_gap(
+    symbol: &str,
+    records: Option<&Vec<PriceRecordEntry>>,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> bool {
+    let dates: std::collections::HashSet<NaiveDate> = records
+        .map(|records| records.iter().map(|r| r.date).collect())
+        .unwrap_or_default();
+    let (exchange, _) = get_exchange_and_symbol(symbol);
+    let calendar = TradingCalendar::new(exchange.as_deref(), start, end);
+    let missing = calendar.missing_trading_days(&dates, start, end);
+    merge_missing_ranges(&calendar, &missing)
+        .iter()
+        .any(|range| range.days > PRICE_GAP_TRADING_DAYS)
+}
+
+/// Sync order for `targets`: open positions, then symbols traded in the last 90
+/// days, then the rest, each bucket stalest stored price first. Positions closed
+/// more than `sync_closed_positions_years` ago are returned separately as skipped
+/// unless their prices have a gap inside the holding period.
+fn prioritize_sync_symbols(
+    app_handle: &tauri::AppHandle,
+    transactions: &[Transaction],
+    targets: &HashMap<String, NaiveDate>,
+    price_map: &HashMap<String, Vec<PriceRecordEntry>>,
+    today: NaiveDate,
+) -> (Vec<String>, Vec<String>) {
+    let closed_years = read_setting_value_internal(app_handle, "sync_closed_positions_years")
+        .ok()
+        .flatten()
+        .and_then(|v| v.trim().parse::<i64>().ok())
+        .unwrap_or(DEFAULT_SYNC_CLOSED_POSITIONS_YEARS);
+    let lenient = lenient_parsing(app_handle);
+    let mut by_symbol: HashMap<String, Vec<Transaction>> = HashMap::new();
+    for txn in transactions {
+        if !txn.stock.trim().is_empty() {
+            by_symbol
+                .entry(canonical_symbol(&txn.stock))
+                .or_default()
+                .push(txn.clone());
+        }
+    }
+
+    let mut ranked: Vec<(u8, Option<NaiveDate>, String)> = Vec::new();
+    let mut skipped = Vec::new();
+    for symbol in targets.keys() {
+        let latest_price = price_map
+            .get(symbol)
+            .and_then(|records| records.iter().map(|r| r.date).max());
+        let Some(txns) = by_symbol.get(symbol) else {
+            ranked.push((2, latest_price, symbol.clone()));
+            continue;
+        };
+        let dates: Vec<NaiveDate> = txns
+            .iter()
+            .filter_map(|t| NaiveDate::parse_from_str(t.date.trim(), "%Y-%m-%d").ok())
+            .collect();
+        let first_activity = dates.iter().min().copied().unwrap_or(today);
+        let last_activity = dates.iter().max().copied().unwrap_or(today);
+        // A history that fails to replay is treated as open so it still syncs.
+        let open = process_complete_transactions(txns.clone(), lenient)
+            .map(|processed| replay_average_cost(&processed, today).0 > 1e-9)
+            .unwrap_or(true);
+
+        let bucket = if open {
+            0
+        } else if last_activity >= today - ChronoDuration::days(SYNC_RECENT_ACTIVITY_DAYS) {
+            1
+        } else {
+            2
+        };
+        let long_closed = !open
+            && closed_years > 0
+            && last_activity < today - ChronoDuration::days(closed_years * 365);
+        if long_closed
+            && !has_price_gap(symbol, price_map.get(symbol), first_activity, last_activity)
+        {
+            skipped.push(symbol.clone());
+            continue;
+        }
+        ranked.push((bucket, latest_price, symbol.clone()));
+    }
+
+    // `None` (no stored prices) sorts before any date, so it goes first.
+    ranked.sort();
+    skipped.sort();
+    (
+        ranked.into_iter().map(|(_, _, symbol)| symbol).collect(),
+        skipped,
+    )
+}
+
+/// Runs a full history sync, publishing its progress through `PortfolioState`.
+fn sync_full_history(app_handle: &tauri::AppHandle, run_id: &str) -> Result<(), String> {
+    update_portfolio_state(app_handle, |state| {
+        state.sync_active = true;
+        true
+    });
+    let result = run_full_history_sync(app_handle, run_id);
+    if result.is_ok() {
+        let _ = finish_sync_checkpoint(app_handle, run_id);
+        let _ = set_setting(
+            app_handle.clone(),
+            "last_sync_at".to_string(),
+            Utc::now().to_rfc3339(),
+        );
+    }
+    update_portfolio_state(app_handle, |state| {
+        state.sync_active = false;
+        if result.is_ok() {
+            state.last_sync_time = Some(Utc::now().to_rfc3339());
+            state.dirty = DirtyFlags::default();
+        }
+        true
+    });
+    result
+}
+
+fn run_full_history_sync(app_handle: &tauri::AppHandle, run_id: &str) -> Result<(), String> {
+    write_run_log(app_handle, run_id, LogLevel::Info, "History worker started")?;
+    if let Err(err) = maybe_auto_backup(app_handle) {
+        write_run_log(
+            app_handle,
+            run_id,
+            LogLevel::Warn,
+            &format!("Pre-sync backup failed: {}", err),
+        )?;
+    }
+    let transactions = load_all_transactions(app_handle)?;
+    let watch_only = watch_only_symbols(app_handle, &transactions);
+    if transactions.is_empty() && watch_only.is_empty() {
+        write_run_log(
+            app_handle,
+            run_id,
+            LogLevel::Info,
+            "No transactions found; skipping history sync",
+        )?;
+        return Ok(());
+    }
+
+    let today = Utc::now().date_naive();
+    let mut earliest_by_symbol: HashMap<String, NaiveDate> = HashMap::new();
+    for txn in transactions.iter() {
+        if txn.stock.trim().is_empty() {
+            continue;
+        }
+        let date = NaiveDate::parse_from_str(txn.date.trim(), "%Y-%m-%d")
+            .map_err(|e| format!("Invalid transaction date {}: {}", txn.date, e))?;
+        earliest_by_symbol
+            .entry(canonical_symbol(&txn.stock))
+            .and_modify(|d| {
+                if date < *d {
+                    *d = date;
+                }
+            })
+            .or_insert(date);
+    }
+    for symbol in watch_only {
+        earliest_by_symbol.insert(symbol, today - ChronoDuration::days(WATCHLIST_HISTORY_DAYS));
+    }
+
+    // Demo symbols are synthetic; Yahoo has nothing for them.
+    for symbol in load_demo_manifest(app_handle)?.symbols {
+        earliest_by_symbol.remove(&symbol);
+    }
+
+    // Symbols that stopped trading before today have no new prices to fetch.
+    for (symbol, event) in delisting_events(app_handle, today - ChronoDuration::days(1)) {
+        if earliest_by_symbol.remove(&symbol).is_some() {
+            write_run_log(
+                app_handle,
+                run_id,
+                LogLevel::Info,
+                &format!(
+                    "Skipping {}: {} on {}",
+                    symbol, event.event_type, event.date
+                ),
+            )?;
+        }
+    }
+
+    let mut price_map: HashMap<String, Vec<PriceRecordEntry>> = HashMap::new();
+    for (symbol, frame) in load_price_frames(app_handle)? {
+        price_map.insert(symbol.clone(), price_entries_from_frame(&symbol, &frame)?);
+    }
+
+    let stale_before = find_stale_symbols(&price_map, earliest_by_symbol.keys(), today);
+
+    let (order, skipped) = prioritize_sync_symbols(
+        app_handle,
+        &transactions,
+        &earliest_by_symbol,
+        &price_map,
+        today,
+    );
+    if !skipped.is_empty() {
+        write_run_log(
+            app_handle,
+            run_id,
+            LogLevel::Info,
+            &format!(
+                "Skipping {} long-closed positions: {}",
+                skipped.len(),
+                skipped.join(", ")
+            ),
+        )?;
+        for symbol in &skipped {
+            earliest_by_symbol.remove(symbol);
+        }
+    }
+
+    let mut checkpoint = sync_checkpoint_for_run(app_handle, run_id, &order)?;
+    write_run_log(
+        app_handle,
+        run_id,
+        LogLevel::Info,
+        &format!("Sync order: {}", checkpoint.symbols.join(", ")),
+    )?;
+    if checkpoint.completed > 0 {
+        write_run_log(
+            app_handle,
+            run_id,
+            LogLevel::Info,
+            &format!(
+                "Resuming after {} of {} symbols",
+                checkpoint.completed,
+                checkpoint.symbols.len()
+            ),
+        )?;
+    }
+    save_sync_checkpoint(app_handle, &checkpoint)?;
+
+    let mut written = 0;
+    while checkpoint.completed < checkpoint.symbols.len() {
+        let symbol = checkpoint.symbols[checkpoint.completed].clone();
+        let symbol = &symbol;
+        let date = &earliest_by_symbol[symbol];
+        write_run_log(
+            app_handle,
+            run_id,
+            LogLevel::Info,
+            &format!("Syncing history for {} from {}", symbol, date),
+        )?;
+        let result = ensure_history_for_symbol(app_handle, run_id, &mut price_map, symbol, *date);
+        if let Err(e) = record_sync_result(
+            app_handle,
+            symbol,
+            result.as_ref().err().map(AppError::message),
+        ) {
+            write_run_log(
+                app_handle,
+                run_id,
+                LogLevel::Warn,
+                &format!("Failed to record sync status for {}: {}", symbol, e),
+            )?;
+        }
+        match result {
+            Ok(changed) => {
+                // Written per symbol so the checkpoint never runs ahead of the files.
+                if changed
+                    && write_price_entries_if_changed(app_handle, symbol, &price_map[symbol])?
+                {
+                    written += 1;
+                }
+                write_run_log(
+                    app_handle,
+                    run_id,
+                    LogLevel::Info,
+                    &format!("Finished {}", symbol),
+                )?;
+            }
+            Err(err @ AppError::NotFound { .. }) => {
+                write_run_log(
+                    app_handle,
+                    run_id,
+                    LogLevel::Warn,
+                    &format!("Skipped {}: {}", symbol, err),
+                )?;
+            }
+            Err(err) => {
+                write_run_log(
+                    app_handle,
+                    run_id,
+                    LogLevel::Error,
+                    &format!("Failed to sync {}: {}", symbol, err),
+                )?;
+            }
+        }
+        checkpoint.completed += 1;
+        save_sync_checkpoint(app_handle, &checkpoint)?;
+    }
+
+    write_run_log(
+        app_handle,
+        run_id,
+        LogLevel::Info,
+        &format!("Wrote {} changed price files", written),
+    )?;
+    if written > 0 {
+        invalidate_coverage_cache(app_handle);
+    }
+
+    let sync_status = load_sync_status(app_handle).unwrap_or_default();
+    let mut newly_stale: Vec<StaleSymbol> =
+        find_stale_symbols(&price_map, earliest_by_symbol.keys(), today)
+            .into_iter()
+            .filter(|(symbol, _)| !stale_before.contains_key(symbol))
+            .map(|(symbol, (latest, missed))| StaleSymbol {
+                last_error: sync_status
+                    .get(&symbol)
+                    .and_then(|entry| entry.last_error.clone()),
+                latest_price: latest.format("%Y-%m-%d").to_string(),
+                days_since_latest_price: missed,
+                symbol,
+            })
+            .collect();
+    if !newly_stale.is_empty() {
+        newly_stale.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        let names: Vec<&str> = newly_stale.iter().map(|s| s.symbol.as_str()).collect();
+        write_run_log(
+            app_handle,
+            run_id,
+            LogLevel::Warn,
+            &format!("Prices went stale for: {}", names.join(", ")),
+        )?;
+        let _ = app_handle.emit_all("stale-prices-detected", &newly_stale);
+    }
+
+    let rebuild_navs_after_sync =
+        read_setting_value_internal(app_handle, "rebuild_navs_after_sync")?
+            .map(|v| v.trim().eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+    if rebuild_navs_after_sync {
+        let outcomes = rebuild_navs(app_handle)?;
+        let rebuilt = outcomes.iter().filter(|o| o.status == "rebuilt").count();
+        write_run_log(
+            app_handle,
+            run_id,
+            LogLevel::Info,
+            &format!(
+                "Rebuilt {} of {} NAV files after sync",
+                rebuilt,
+                outcomes.len()
+            ),
+        )?;
+    }
+
+    write_run_log(
+        app_handle,
+        run_id,
+        LogLevel::Info,
+        "History worker completed",
+    )?;
+    Ok(())
+}
+
+#[tauri::command]
+fn proxy_get(url: String) -> Result<String, AppError> {
+    let parsed = url::Url::parse(&url).map_err(|e| format!("Invalid URL: {}", e))?;
+    let host = parsed.host_str().unwrap_or("").to_lowercase();
+
+    let allowed_hosts = [
+        "query1.finance.yahoo.com",
+        "query2.finance.yahoo.com",
+        "finance.yahoo.com",
+        "yfapi.net",
+    ];
+
+    if !allowed_hosts.iter().any(|h| h.eq_ignore_ascii_case(&host)) {
+        return Err(AppError::validation(
+            "url",
+            format!("Host not allowed: {}", host),
+        ));
+    }
+
+    let client = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .user_agent("portfolio-manager-desktop/1.0")
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+
+    let response = client
+        .get(parsed)
+        .send()
+        .map_err(|e| format!("Request failed: {}", e))?;
+
+    let status = response.status();
+    let body = response
+        .text()
+        .map_err(|e| format!("Failed to read response body: {}", e))?;
+
+    if !status.is_success() {
+        return Err(AppError::from_status(
+            status,
+            format!("Upstream error {}: {}", status, body),
+        ));
+    }
+
+    Ok(body)
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct StockDataCoverage {
+    ticker: String,
+    exchange: String,
+    currency: String,
+    earliest_transaction: String,
+    earliest_price: Option<String>,
+    latest_price: Option<String>,
+    total_days: i32,
+    missing_days: i32,
+    coverage_percent: f64,
+    split_count: i32,
+    last_split: Option<String>,
+    status: String,
+    delist_reason: Option<String>,
+    #[serde(default)]
+    days_since_latest_price: Option<i64>,
+    #[serde(default)]
+    stale: bool,
+    #[serde(default)]
+    last_sync_error: Option<String>,
+    #[serde(default)]
+    first_trade_date: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SplitHistory {
+    ticker: String,
+    date: String,
+    numerator: i32,
+    denominator: i32,
+    ratio: String,
+    ratio_factor: f64,
+    before_price: Option<f64>,
+    after_price: Option<f64>,
+}
+
+fn parse_ratio_components(ratio: &str) -> (i32, i32) {
+    let trimmed = ratio.trim();
+    if trimmed.is_empty() {
+        return (1, 1);
+    }
+
+    if let Some((num_str, den_str)) = trimmed
+        .split_once(':')
+        .or_else(|| trimmed.split_once('/'))
+        .or_else(|| trimmed.split_once("-for-"))
+    {
+        let numerator = num_str.trim().parse::<i32>().unwrap_or(1).max(1);
+        let denominator = den_str.trim().parse::<i32>().unwrap_or(1).max(1);
+        return (numerator, denominator);
+    }
+
+    if let Ok(value) = trimmed.parse::<f64>() {
+        if value > 0.0 {
+            return ratio_to_fraction(value);
+        }
+    }
+
+    (1, 1)
+}
+
+/// Best rational approximation with a small denominator, so 1.5 becomes 3:2
+/// and 0.1 becomes 1:10 instead of being rounded to a whole ratio.
+fn ratio_to_fraction(value: f64) -> (i32, i32) {
+    let mut best = (value.round().max(1.0) as i32, 1);
+    let mut best_err = (value - best.0 as f64).abs();
+    for denominator in 1..=1000 {
+        let numerator = (value * denominator as f64).round();
+        if numerator < 1.0 {
+            continue;
+        }
+        let err = (value - numerator / denominator as f64).abs();
+        if err + 1e-9 < best_err {
+            best = (numerator as i32, denominator);
+            best_err = err;
+        }
+        if best_err < 1e-6 {
+            break;
+        }
+    }
+    best
+}
+
+fn parse_price_field(field: Option<&&str>) -> Option<f64> {
+    field.and_then(|value| {
+        let trimmed = value.trim();
+        if trimmed.is_empty() {
+            None
+        } else {
+            trimmed.parse::<f64>().ok()
+        }
+    })
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct DataReadinessStats {
+    total_stocks: i32,
+    complete_data: i32,
+    partial_data: i32,
+    missing_data: i32,
+    #[serde(default)]
+    stale_data: i32,
+    total_price_records: i32,
+    oldest_date: Option<String>,
+    newest_date: Option<String>,
+    #[serde(default)]
+    markets: std::collections::BTreeMap<String, MarketReadinessStats>,
+    #[serde(default)]
+    last_sync_at: Option<String>,
+    #[serde(default)]
+    sync_in_progress: bool,
+    /// Symbols whose latest price is more than two calendar days old.
+    #[serde(default)]
+    stale_symbols: usize,
+    /// Symbols with at least one missing trading day in their coverage.
+    #[serde(default)]
+    symbols_with_gaps: usize,
+    #[serde(default)]
+    total_dividend_files: usize,
+    #[serde(default)]
+    total_split_files: usize,
+    #[serde(default)]
+    disk_usage_bytes: u64,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone)]
+struct MarketReadinessStats {
+    total_stocks: i32,
+    complete_data: i32,
+    partial_data: i32,
+    missing_data: i32,
+    stale_data: i32,
+    total_price_records: i32,
+    oldest_date: Option<String>,
+    newest_date: Option<String>,
+    dividend_files: i32,
+    split_count: i32,
+    fx_pairs: i32,
+}
+
+/// Market a symbol belongs to for reporting: US, TW, JP, HK or OTHER.
+fn market_group(symbol: &str) -> &'static str {
+    match get_exchange_and_symbol(symbol).0.as_deref() {
+        None | Some("NASDAQ") | Some("NYSE") | Some("NYSEARCA") | Some("NYSEAMERICAN")
+        | Some("OTCMKTS") => "US",
+        Some("TWSE") | Some("TPE") | Some("TPEX") => "TW",
+        Some("JPX") | Some("TYO") => "JP",
+        Some("HKEX") => "HK",
+        _ => "OTHER",
+    }
+}
+
+fn market_breakdown(
+    app_handle: &tauri::AppHandle,
+    coverage: &[StockDataCoverage],
+    price_frames: &PriceFrames,
+) -> Result<std::collections::BTreeMap<String, MarketReadinessStats>, String> {
+    let mut markets: std::collections::BTreeMap<String, MarketReadinessStats> =
+        ["US", "TW", "JP", "HK", "OTHER"]
+            .iter()
+            .map(|m| (m.to_string(), MarketReadinessStats::default()))
+            .collect();
+    for item in coverage {
+        let stats = markets
+            .entry(market_group(&item.ticker).to_string())
+            .or_default();
+        stats.total_stocks += 1;
+        match item.status.as_str() {
+            "complete" => stats.complete_data += 1,
+            "partial" => stats.partial_data += 1,
+            "missing" => stats.missing_data += 1,
+            _ => {}
+        }
+        if item.stale {
+            stats.stale_data += 1;
+        }
+    }
+
+    let format_date = |d: NaiveDate| d.format("%Y-%m-%d").to_string();
+    for (symbol, frame) in price_frames {
+        let stats = markets.entry(market_group(symbol).to_string()).or_default();
+        stats.total_price_records += frame.height() as i32;
+        let Some((earliest, latest)) = price_frame_span(frame) else {
+            continue;
+        };
+        let (earliest, latest) = (format_date(earliest), format_date(latest));
+        if stats.oldest_date.as_ref().is_none_or(|d| earliest < *d) {
+            stats.oldest_date = Some(earliest);
+        }
+        if stats.newest_date.as_ref().is_none_or(|d| latest > *d) {
+            stats.newest_date = Some(latest);
+        }
+    }
+
+    let csv_stems = |dir: PathBuf| -> Vec<String> {
+        std::fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|e| e.path())
+                    .filter(|p| p.is_file() && p.extension().is_some_and(|e| e == "csv"))
+                    .filter_map(|p| p.file_stem().and_then(|s| s.to_str()).map(str::to_string))
+                    .filter(|stem| !stem.ends_with("-override"))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    for stem in csv_stems(get_dividends_dir(app_handle)?) {
+        let symbol = symbol_from_file_stem(&stem);
+        markets
+            .entry(market_group(&symbol).to_string())
+            .or_default()
+            .dividend_files += 1;
+    }
+    for stem in csv_stems(get_splits_dir(app_handle)?) {
+        let symbol = symbol_from_file_stem(&stem);
+        let splits = load_split_events(app_handle, &symbol)?;
+        let count = splits
+            .iter()
+            .filter(|(_, r)| (r - 1.0).abs() > 1e-9)
+            .count() as i32;
+        markets
+            .entry(market_group(&symbol).to_string())
+            .or_default()
+            .split_count += count;
+    }
+
+    // FX pairs are attributed to each market whose trading currency they involve.
+    let fx_pairs = csv_stems(get_fx_rates_dir(app_handle)?);
+    for (name, currency) in [("US", "USD"), ("TW", "TWD"), ("JP", "JPY"), ("HK", "HKD")] {
+        let count = fx_pairs
+            .iter()
+            .filter(|pair| pair.split('_').any(|c| c == currency))
+            .count() as i32;
+        markets.entry(name.to_string()).or_default().fx_pairs = count;
+    }
+
+    Ok(markets)
+}
+
+#[derive(Serialize, Deserialize)]
+struct NavSnapshotEntryPayload {
+    stock: String,
+    currency: String,
+    shares: f64,
+    average_cost: f64,
+    latest_price: f64,
+    market_value: f64,
+    market_value_usd: f64,
+    status: String,
+    last_transaction: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct NavSnapshotPayload {
+    timestamp: String,
+    base_currency: String,
+    total_value_usd: f64,
+    entries: Vec<NavSnapshotEntryPayload>,
+    #[serde(default)]
+    cash_positions: Vec<CashPosition>,
+}
+
+/// Uninvested cash in one currency, optionally per account, from
+/// data/cash_positions.csv.
+#[derive(Serialize, Deserialize, Clone)]
+struct CashPosition {
+    currency: String,
+    amount: f64,
+    account: Option<String>,
+}
+
+/// A position as the frontend sees it. Only `stock` and `currency` drive the NAV
+/// rebuild; the figures are optional and only cross-checked against the values
+/// `write_position_nav` derives.
+#[derive(Serialize, Deserialize)]
+struct PositionSnapshotPayload {
+    timestamp: String,
+    stock: String,
+    currency: String,
+    #[serde(default)]
+    shares: Option<f64>,
+    #[serde(default)]
+    average_cost: Option<f64>,
+    #[serde(default)]
+    latest_price: Option<f64>,
+    #[serde(default)]
+    market_value: Option<f64>,
+    #[serde(default)]
+    market_value_usd: Option<f64>,
+    #[serde(default)]
+    status: Option<String>,
+    last_transaction: Option<String>,
+}
+
+/// Exchange trading calendar: weekends plus the main recurring market holidays.
+/// Lunar-calendar holidays (Lunar New Year, Golden Week variations) are not modelled,
+/// so gap reports for TW/HK/JP may still list a few genuine closures.
+struct TradingCalendar {
+    holidays: std::collections::HashSet<NaiveDate>,
+}
+
+fn observed_holiday(date: NaiveDate) -> NaiveDate {
+    match date.weekday() {
+        chrono::Weekday::Sat => date - ChronoDuration::days(1),
+        chrono::Weekday::Sun => date + ChronoDuration::days(1),
+        _ => date,
+    }
+}
+
+fn easter_sunday(year: i32) -> Option<NaiveDate> {
+    // Anonymous Gregorian algorithm
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = ((h + l - 7 * m + 114) % 31) + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+}
+
+fn market_holidays(exchange: Option<&str>, year: i32) -> Vec<NaiveDate> {
+    use chrono::Weekday::{Mon, Thu};
+
+    let ymd = |m: u32, d: u32| NaiveDate::from_ymd_opt(year, m, d);
+    let nth =
+        |m: u32, wd: chrono::Weekday, n: u8| NaiveDate::from_weekday_of_month_opt(year, m, wd, n);
+    let last_monday_of_may = (1..=5)
+        .rev()
+        .find_map(|n| NaiveDate::from_weekday_of_month_opt(year, 5, Mon, n));
+    let good_friday = easter_sunday(year).map(|d| d - ChronoDuration::days(2));
+    let easter_monday = easter_sunday(year).map(|d| d + ChronoDuration::days(1));
+
+    let days: Vec<Option<NaiveDate>> = match exchange {
+        Some("NYSE") | Some("NASDAQ") | Some("NYSEARCA") | Some("NYSEAMERICAN")
+        | Some("OTCMKTS") | None | Some("UNKNOWN") => vec![
+            ymd(1, 1).map(observed_holiday),
+            nth(1, Mon, 3),
+            nth(2, Mon, 3),
+            good_friday,
+            last_monday_of_may,
+            if year >= 2022 {
+                ymd(6, 19).map(observed_holiday)
+            } else {
+                None
+            },
+            ymd(7, 4).map(observed_holiday),
+            nth(9, Mon, 1),
+            nth(11, Thu, 4),
+            ymd(12, 25).map(observed_holiday),
+        ],
+        Some("HKEX") => vec![
+            ymd(1, 1),
+            good_friday,
+            easter_monday,
+            ymd(5, 1),
+            ymd(7, 1),
+            ymd(10, 1),
+            ymd(12, 25),
+            ymd(12, 26),
+        ],
+        Some("TWSE") | Some("TPE") | Some("TPEX") => {
+            vec![ymd(1, 1), ymd(2, 28), ymd(4, 4), ymd(10, 10)]
+        }
+        Some("JPX") | Some("TYO") => vec![
+            ymd(1, 1),
+            ymd(1, 2),
+            ymd(1, 3),
+            nth(1, Mon, 2),
+            ymd(2, 11),
+            ymd(4, 29),
+            ymd(5, 3),
+            ymd(5, 4),
+            ymd(5, 5),
+            ymd(11, 3),
+            ymd(11, 23),
+            ymd(12, 31),
+        ],
+        Some("LSE") => vec![
+            ymd(1, 1).map(observed_holiday),
+            good_friday,
+            easter_monday,
+            ymd(12, 25).map(observed_holiday),
+            ymd(12, 26),
+        ],
+        _ => vec![ymd(1, 1), ymd(12, 25)],
+    };
+
+    days.into_iter().flatten().collect()
+}
+
+impl TradingCalendar {
+    fn new(exchange: Option<&str>, start: NaiveDate, end: NaiveDate) -> Self {
+        let holidays = (start.year()..=end.year())
+            .flat_map(|year| market_holidays(exchange, year))
+            .collect();
+        TradingCalendar { holidays }
+    }
+
+    fn is_trading_day(&self, date: NaiveDate) -> bool {
+        let weekday = date.weekday();
+        weekday != chrono::Weekday::Sat
+            && weekday != chrono::Weekday::Sun
+            && !self.holidays.contains(&date)
+    }
+
+    fn trading_days(&self, start: NaiveDate, end: NaiveDate) -> Vec<NaiveDate> {
+        start
+            .iter_days()
+            .take_while(|d| *d <= end)
+            .filter(|d| self.is_trading_day(*d))
+            .collect()
+    }
+
+    fn count_trading_days(&self, start: NaiveDate, end: NaiveDate) -> usize {
+        self.trading_days(start, end).len()
+    }
+
+    fn missing_trading_days(
+        &self,
+        price_dates: &std::collections::HashSet<NaiveDate>,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Vec<NaiveDate> {
+        self.trading_days(start, end)
+            .into_iter()
+            .filter(|d| !price_dates.contains(d))
+            .collect()
+    }
+}
+
+/// A symbol whose latest close is more than this many trading sessions old is stale.
+const STALE_PRICE_TRADING_DAYS: i64 = 5;
+
+/// Completed trading sessions after `latest`, excluding today whose close may not be
+/// published yet.
+fn trading_days_since(exchange: Option<&str>, latest: NaiveDate, today: NaiveDate) -> i64 {
+    let yesterday = today - ChronoDuration::days(1);
+    if latest >= yesterday {
+        return 0;
+    }
+    let start = latest + ChronoDuration::days(1);
+    TradingCalendar::new(exchange, start, yesterday).count_trading_days(start, yesterday) as i64
+}
+
+#[derive(Serialize, Clone)]
+struct StaleSymbol {
+    symbol: String,
+    latest_price: String,
+    days_since_latest_price: i64,
+    last_error: Option<String>,
+}
+
+fn find_stale_symbols<'a>(
+    price_map: &HashMap<String, Vec<PriceRecordEntry>>,
+    symbols: impl Iterator<Item = &'a String>,
+    today: NaiveDate,
+) -> HashMap<String, (NaiveDate, i64)> {
+    let mut stale = HashMap::new();
+    for symbol in symbols {
+        let latest = match price_map
+            .get(symbol)
+            .and_then(|records| records.iter().map(|r| r.date).max())
+        {
+            Some(d) => d,
+            None => continue,
+        };
+        let (exchange, _) = get_exchange_and_symbol(symbol);
+        let missed = trading_days_since(exchange.as_deref(), latest, today);
+        if missed > STALE_PRICE_TRADING_DAYS {
+            stale.insert(symbol.clone(), (latest, missed));
+        }
+    }
+    stale
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct MissingDateRange {
+    from: String,
+    to: String,
+    days: usize,
+}
+
+/// Merges missing trading days into spans; two missing days belong to the same span
+/// when no trading day with data lies between them.
+fn merge_missing_ranges(
+    calendar: &TradingCalendar,
+    missing: &[NaiveDate],
+) -> Vec<MissingDateRange> {
+    let mut ranges: Vec<(NaiveDate, NaiveDate, usize)> = Vec::new();
+    for date in missing {
+        if let Some(last) = ranges.last_mut() {
+            let next_trading = last
+                .1
+                .iter_days()
+                .skip(1)
+                .find(|d| calendar.is_trading_day(*d));
+            if next_trading == Some(*date) {
+                last.1 = *date;
+                last.2 += 1;
+                continue;
+            }
+        }
+        ranges.push((*date, *date, 1));
+    }
+
+    ranges
+        .into_iter()
+        .map(|(from, to, days)| MissingDateRange {
+            from: from.format("%Y-%m-%d").to_string(),
+            to: to.format("%Y-%m-%d").to_string(),
+            days,
+        })
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct SyncStatusEntry {
+    symbol: String,
+    last_attempt_at: Option<String>,
+    last_success_at: Option<String>,
+    last_error: Option<String>,
+}
+
+fn sync_status_path(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+    Ok(get_data_dir(app_handle)?.join("sync_status.json"))
+}
+
+fn load_sync_status(
+    app_handle: &tauri::AppHandle,
+) -> Result<HashMap<String, SyncStatusEntry>, AppError> {
+    read_sync_status_file(&sync_status_path(app_handle)?)
+}
+
+/// Entries in a sync status file; none when it does not exist. Content that is not
+/// a status map is a `Parse` error rather than an empty map.
+fn read_sync_status_file(path: &Path) -> Result<HashMap<String, SyncStatusEntry>, AppError> {
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+    let content = read_to_string(path)
+        .map_err(|e| AppError::io(&e, format!("Failed to read sync_status.json: {}", e)))?;
+    serde_json::from_str(&content).map_err(|e| AppError::Parse {
+        file: path.display().to_string(),
+        line: Some(e.line()),
+        message: format!("Invalid sync_status.json: {}", e),
+    })
+}
+
+fn record_sync_result(
+    app_handle: &tauri::AppHandle,
+    symbol: &str,
+    error: Option<&str>,
+) -> Result<(), String> {
+    // Parallel downloads update this file concurrently; hold its lock across the
+    // read-modify-write so entries are not lost.
+    let path = sync_status_path(app_handle)?;
+    with_file_lock(app_handle, &path, || {
+        // A corrupt file is moved aside, not overwritten, so the history it held
+        // can still be recovered by hand.
+        let mut statuses = match read_sync_status_file(&path) {
+            Err(AppError::Parse { message, .. }) => {
+                let aside = path.with_extension(format!(
+                    "json.corrupt-{}",
+                    Utc::now().format("%Y%m%d_%H%M%S")
+                ));
+                std::fs::rename(&path, &aside)
+                    .map_err(|e| format!("Failed to move aside sync_status.json: {}", e))?;
+                write_worker_log(
+                    app_handle,
+                    &format!("{}; moved to {} and reset", message, aside.display()),
+                )?;
+                HashMap::new()
+            }
+            result => result?,
+        };
+        let now = Utc::now().to_rfc3339();
+        let entry = statuses
+            .entry(symbol.to_string())
+            .or_insert_with(|| SyncStatusEntry {
+                symbol: symbol.to_string(),
+                ..Default::default()
+            });
+        entry.last_attempt_at = Some(now.clone());
+        match error {
+            None => {
+                entry.last_success_at = Some(now);
+                entry.last_error = None;
+            }
+            Some(err) => entry.last_error = Some(err.to_string()),
+        }
+
+        let content = serde_json::to_string_pretty(&statuses)
+            .map_err(|e| format!("Failed to serialize sync status: {}", e))?;
+        atomic_write(app_handle, &path, content.as_bytes())
+            .map_err(|e| format!("Failed to write sync_status.json: {}", e))
+    })
+}
+
+#[tauri::command]
+fn get_sync_status(app_handle: tauri::AppHandle) -> Result<Vec<SyncStatusEntry>, AppError> {
+    let mut entries: Vec<SyncStatusEntry> = load_sync_status(&app_handle)?.into_values().collect();
+    entries.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+    Ok(entries)
+}
+
+#[derive(Serialize)]
+struct SymbolCoverage {
+    symbol: String,
+    exchange: String,
+    window_start: String,
+    window_end: String,
+    earliest_price: Option<String>,
+    latest_price: Option<String>,
+    expected_days: usize,
+    missing_days: usize,
+    coverage_percent: f64,
+    missing_ranges: Vec<MissingDateRange>,
+    real_rows: usize,
+    forward_filled_rows: usize,
+    dividend_count: usize,
+    first_dividend: Option<String>,
+    last_dividend: Option<String>,
+    split_count: usize,
+    last_split: Option<String>,
+    first_trade_date: Option<String>,
+    sync_status: Option<SyncStatusEntry>,
+    tags: Vec<String>,
+}
+
+fn load_yahoo_meta(app_handle: &tauri::AppHandle, symbol: &str) -> Option<serde_json::Value> {
+    let path = get_yahoo_metas_dir(app_handle)
+        .ok()?
+        .join(format!("{}.json", symbol_file_stem(symbol)));
+    let content = read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// First day the symbol traded, from the saved Yahoo meta (`firstTradeDate`, unix
+/// seconds) or, when the meta lacks it, the earliest price row. Symbols without a
+/// meta file return `None` so their coverage window is left alone.
+fn first_trade_date(
+    app_handle: &tauri::AppHandle,
+    symbol: &str,
+    earliest_price: Option<NaiveDate>,
+) -> Option<NaiveDate> {
+    let meta = load_yahoo_meta(app_handle, symbol)?;
+    meta_first_trade_date(&meta).or(earliest_price)
+}
+
+fn meta_first_trade_date(meta: &serde_json::Value) -> Option<NaiveDate> {
+    meta.get("firstTradeDate")
+        .and_then(|v| v.as_i64())
+        .and_then(|ts| DateTime::from_timestamp(ts, 0))
+        .map(|dt| dt.date_naive())
+}
+
+/// Start of the window coverage is measured over: `bound`, moved up to the first
+/// trade date when the symbol listed later.
+fn coverage_window_start(bound: NaiveDate, first_trade: Option<NaiveDate>) -> NaiveDate {
+    bound.max(first_trade.unwrap_or(bound))
+}
+
+#[derive(Serialize)]
+struct MarketCap {
+    symbol: String,
+    market_cap_base: f64,
+    base_currency: String,
+    /// Size bucket by USD market cap, whatever the base currency.
+    market_cap_category: String,
+    #[serde(skip)]
+    market_cap_usd: f64,
+}
+
+fn market_cap_category(market_cap_usd: f64) -> &'static str {
+    const BILLION: f64 = 1_000_000_000.0;
+    if market_cap_usd > 200.0 * BILLION {
+        "mega"
+    } else if market_cap_usd >= 10.0 * BILLION {
+        "large"
+    } else if market_cap_usd >= 2.0 * BILLION {
+        "mid"
+    } else if market_cap_usd >= 0.3 * BILLION {
+        "small"
+    } else {
+        "micro"
+    }
+}
+
+/// Market capitalisation per symbol from the saved Yahoo metas (`regularMarketCap`),
+/// converted to the base currency at the latest stored FX rate. Symbols whose meta has no market
+/// cap, or whose currency has no FX history, are left out.
+#[tauri::command]
+fn get_market_caps(app_handle: tauri::AppHandle) -> Result<Vec<MarketCap>, AppError> {
+    let metas_dir = get_yahoo_metas_dir(&app_handle)?;
+    let today = Utc::now().date_naive();
+    let base = configured_base_currency(&app_handle);
+    let base_rate = usd_rate_on(&usd_rate_series(&app_handle, &base)?, today);
+    let mut usd_rates: HashMap<String, Option<f64>> = HashMap::new();
+    let mut caps = Vec::new();
+
+    for entry in std::fs::read_dir(&metas_dir)?.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let Some(symbol) = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .map(symbol_from_file_stem)
+        else {
+            continue;
+        };
+        let Some(meta) = load_yahoo_meta(&app_handle, &symbol) else {
+            continue;
+        };
+        let Some(market_cap) = meta
+            .get("regularMarketCap")
+            .or_else(|| meta.get("marketCap"))
+            .and_then(|v| v.as_f64())
+            .filter(|cap| *cap > 0.0)
+        else {
+            continue;
+        };
+
+        // Uppercasing maps Yahoo's pence quote currency "GBp" to its FX series, GBP.
+        let currency = meta
+            .get("currency")
+            .and_then(|c| c.as_str())
+            .map(str::to_uppercase)
+            .unwrap_or_else(|| {
+                currency_for_exchange(get_exchange_and_symbol(&symbol).0.as_deref()).to_string()
+            });
+        let rate = *usd_rates.entry(currency.clone()).or_insert_with(|| {
+            usd_rate_series(&app_handle, &currency)
+                .ok()
+                .map(|series| usd_rate_on(&series, today))
+        });
+        let Some(rate) = rate else {
+            continue;
+        };
+
+        let market_cap_usd = market_cap * rate;
+        caps.push(MarketCap {
+            symbol,
+            market_cap_base: market_cap_usd / base_rate,
+            base_currency: base.clone(),
+            market_cap_category: market_cap_category(market_cap_usd).to_string(),
+            market_cap_usd,
+        });
+    }
+
+    caps.sort_by(|a, b| b.market_cap_usd.total_cmp(&a.market_cap_usd));
+    Ok(caps)
+}
+
+/// Non-empty `sector` per ticker from securities.csv.
+fn load_security_sectors(app_handle: &tauri::AppHandle) -> Result<HashMap<String, String>, String> {
+    let (headers, rows) = read_securities_rows(app_handle)?;
+    let column = |name: &str| headers.iter().position(|h| h == name);
+    let (Some(ticker_idx), Some(sector_idx)) = (column("ticker"), column("sector")) else {
+        return Ok(HashMap::new());
+    };
+    Ok(rows
+        .into_iter()
+        .filter_map(|(_, fields)| {
+            let ticker = fields.get(ticker_idx)?.trim().to_string();
+            let sector = fields.get(sector_idx)?.trim().to_string();
+            (!ticker.is_empty() && !sector.is_empty()).then_some((ticker, sector))
+        })
+        .collect())
+}
+
+/// The `type` column of securities.csv, which decides how a symbol's history is
+/// fetched and how it is grouped in reports.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SecurityType {
+    Stock,
+    Etf,
+    Fund,
+    Bond,
+}
+
+impl SecurityType {
+    /// Reads a configured type; blank and unrecognised values are stocks.
+    fn parse(raw: &str) -> Self {
+        match raw
+            .trim()
+            .to_lowercase()
+            .replace(['_', '-', ' '], "")
+            .as_str()
+        {
+            "etf" => SecurityType::Etf,
+            "fund" | "mutualfund" => SecurityType::Fund,
+            "bond" => SecurityType::Bond,
+            _ => SecurityType::Stock,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            SecurityType::Stock => "Stock",
+            SecurityType::Etf => "ETF",
+            SecurityType::Fund => "Fund",
+            SecurityType::Bond => "Bond",
+        }
+    }
+
+    /// Yahoo `instrumentType` values consistent with this type.
+    fn yahoo_instrument_types(self) -> &'static [&'static str] {
+        match self {
+            SecurityType::Stock => &["EQUITY"],
+            SecurityType::Etf => &["ETF"],
+            SecurityType::Fund => &["MUTUALFUND", "MONEYMARKET"],
+            SecurityType::Bond => &["BOND"],
+        }
+    }
+
+    /// Every per-type difference in how history is fetched lives here.
+    fn fetch_profile(self) -> FetchProfile {
+        match self {
+            SecurityType::Stock | SecurityType::Etf => FetchProfile {
+                interval: "1d",
+                keep_ohlcv: true,
+                corporate_actions: true,
+            },
+            // Funds price once a day at NAV; any OHLC or volume Yahoo sends is filler.
+            SecurityType::Fund => FetchProfile {
+                interval: "1d",
+                keep_ohlcv: false,
+                corporate_actions: true,
+            },
+            SecurityType::Bond => FetchProfile {
+                interval: "1d",
+                keep_ohlcv: true,
+                corporate_actions: false,
+            },
+        }
+    }
+}
+
+struct FetchProfile {
+    interval: &'static str,
+    /// Keep open/high/low/volume; otherwise only closes are stored.
+    keep_ohlcv: bool,
+    /// Take splits and dividends from the source.
+    corporate_actions: bool,
+}
+
+impl FetchProfile {
+    /// Drops what this profile does not store from freshly fetched rows.
+    fn apply(&self, records: &mut [PriceRecordEntry], dividends: &mut Vec<(NaiveDate, f64)>) {
+        for record in records.iter_mut() {
+            if !self.keep_ohlcv {
+                record.open = None;
+                record.high = None;
+                record.low = None;
+                record.volume = None;
+            }
+            if !self.corporate_actions {
+                record.split_unadjusted_close = Some(record.close);
+            }
+        }
+        if !self.corporate_actions {
+            dividends.clear();
+        }
+    }
+}
+
+/// Configured `type` per ticker from securities.csv; only rows with a value.
+fn load_security_types(
+    app_handle: &tauri::AppHandle,
+) -> Result<HashMap<String, SecurityType>, String> {
+    let (headers, rows) = read_securities_rows(app_handle)?;
+    let column = |name: &str| headers.iter().position(|h| h == name);
+    let (Some(ticker_idx), Some(type_idx)) = (column("ticker"), column("type")) else {
+        return Ok(HashMap::new());
+    };
+    Ok(rows
+        .into_iter()
+        .filter_map(|(_, fields)| {
+            let ticker = canonical_symbol(fields.get(ticker_idx)?);
+            let kind = fields.get(type_idx)?.trim();
+            (!ticker.is_empty() && !kind.is_empty()).then(|| (ticker, SecurityType::parse(kind)))
+        })
+        .collect())
+}
+
+fn security_type(app_handle: &tauri::AppHandle, symbol: &str) -> Option<SecurityType> {
+    load_security_types(app_handle)
+        .ok()?
+        .remove(&canonical_symbol(symbol))
+}
+
+#[derive(Serialize)]
+struct SectorRotationSignal {
+    sector: String,
+    holdings: usize,
+    return_3m: f64,
+    return_1m: f64,
+    momentum: f64,
+    signal: String,
+}
+
+/// Equal-weighted 3-month and 1-month returns of current holdings per securities.csv
+/// sector, from split-adjusted local closes. `momentum` is `return_3m - return_1m`;
+/// a sector is "overweight" when the 3-month return and momentum are both positive,
+/// "underweight" when both are negative and "neutral" otherwise. Holdings without a
+/// sector are grouped as "Unclassified".
+#[tauri::command]
+fn get_sector_rotation_signals(
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<SectorRotationSignal>, AppError> {
+    let today = Utc::now().date_naive();
+    let sectors = load_security_sectors(&app_handle)?;
+
+    let mut returns: std::collections::BTreeMap<String, Vec<(f64, f64)>> =
+        std::collections::BTreeMap::new();
+    for symbol in traded_symbols(&app_handle)? {
+        let transactions = load_symbol_transactions(&app_handle, &symbol)?;
+        if shares_held_on(&transactions, today) <= 1e-9 {
+            continue;
+        }
+        let Ok(series) = load_ascending_price_series(&app_handle, &symbol) else {
+            continue;
+        };
+        let closes: Vec<(NaiveDate, f64)> = series.into_iter().map(|(d, r)| (d, r.close)).collect();
+        let return_since = |days: i64| {
+            let base = close_on_or_before(&closes, today - ChronoDuration::days(days))?;
+            close_on_or_before(&closes, today).map(|latest| latest / base - 1.0)
+        };
+        let (Some(return_3m), Some(return_1m)) = (return_since(91), return_since(30)) else {
+            continue;
+        };
+        let sector = sectors
+            .get(&symbol)
+            .cloned()
+            .unwrap_or_else(|| "Unclassified".to_string());
+        returns
+            .entry(sector)
+            .or_default()
+            .push((return_3m, return_1m));
+    }
+
+    Ok(returns
+        .into_iter()
+        .map(|(sector, members)| {
+            let n = members.len() as f64;
+            let return_3m = members.iter().map(|(r3, _)| r3).sum::<f64>() / n;
+            let return_1m = members.iter().map(|(_, r1)| r1).sum::<f64>() / n;
+            let momentum = return_3m - return_1m;
+            let signal = if return_3m > 0.0 && momentum > 0.0 {
+                "overweight"
+            } else if return_3m < 0.0 && momentum < 0.0 {
+                "underweight"
+            } else {
+                "neutral"
+            };
+            SectorRotationSignal {
+                sector,
+                holdings: members.len(),
+                return_3m,
+                return_1m,
+                momentum,
+                signal: signal.to_string(),
+            }
+        })
+        .collect())
+}
+
+/// Meta keys written by `refresh_symbol_fundamentals`, with the quoteSummary module
+/// each is read from.
+const FUNDAMENTAL_FIELDS: [(&str, &str); 5] = [
+    ("trailingPE", "summaryDetail"),
+    ("forwardPE", "summaryDetail"),
+    ("priceToBook", "defaultKeyStatistics"),
+    ("returnOnEquity", "financialData"),
+    ("debtToEquity", "financialData"),
+];
+const FUNDAMENTAL_META_KEYS: [&str; 6] = [
+    "trailingPE",
+    "forwardPE",
+    "priceToBook",
+    "returnOnEquity",
+    "debtToEquity",
+    "fundamentalsUpdatedAt",
+];
+
+#[derive(Serialize)]
+struct FundamentalData {
+    symbol: String,
+    trailing_pe: Option<f64>,
+    forward_pe: Option<f64>,
+    price_to_book: Option<f64>,
+    return_on_equity: Option<f64>,
+    debt_to_equity: Option<f64>,
+    updated_at: Option<String>,
+}
+
+#[derive(Serialize)]
+struct PortfolioFundamentals {
+    symbol: String,
+    pe: Option<f64>,
+    pb: Option<f64>,
+    roe: Option<f64>,
+    debt_to_equity: Option<f64>,
+}
+
+fn fundamentals_from_meta(symbol: &str, meta: Option<&serde_json::Value>) -> FundamentalData {
+    let number = |key: &str| meta.and_then(|m| yahoo_raw_number(m, key));
+    FundamentalData {
+        symbol: symbol.to_string(),
+        trailing_pe: number("trailingPE"),
+        forward_pe: number("forwardPE"),
+        price_to_book: number("priceToBook"),
+        return_on_equity: number("returnOnEquity"),
+        debt_to_equity: number("debtToEquity"),
+        updated_at: meta
+            .and_then(|m| m.get("fundamentalsUpdatedAt"))
+            .and_then(|v| v.as_str())
+            .map(str::to_string),
+    }
+}
+
+/// Valuation fields from the symbol's saved Yahoo meta. Fields are `None` until
+/// `refresh_symbol_fundamentals` has stored them.
+#[tauri::command]
+fn get_fundamental_data(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+) -> Result<FundamentalData, AppError> {
+    let symbol = symbol.trim().to_string();
+    let meta = load_yahoo_meta(&app_handle, &symbol).ok_or_else(|| {
+        AppError::not_found("yahoo_meta", format!("No Yahoo meta saved for {}", symbol))
+    })?;
+    Ok(fundamentals_from_meta(&symbol, Some(&meta)))
+}
+
+/// Fundamentals for every traded symbol; P/E is trailing, falling back to forward.
+#[tauri::command]
+fn get_portfolio_fundamentals(
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<PortfolioFundamentals>, AppError> {
+    let mut symbols: Vec<String> = load_all_transactions(&app_handle)?
+        .iter()
+        .map(|txn| txn.stock.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    symbols.sort();
+    symbols.dedup();
+
+    Ok(symbols
+        .into_iter()
+        .map(|symbol| {
+            let meta = load_yahoo_meta(&app_handle, &symbol);
+            let data = fundamentals_from_meta(&symbol, meta.as_ref());
+            PortfolioFundamentals {
+                symbol,
+                pe: data.trailing_pe.or(data.forward_pe),
+                pb: data.price_to_book,
+                roe: data.return_on_equity,
+                debt_to_equity: data.debt_to_equity,
+            }
+        })
+        .collect())
+}
+
+/// Re-fetches valuation fields from Yahoo's quoteSummary and stores them in the
+/// symbol's meta file alongside the chart meta.
+#[tauri::command]
+fn refresh_symbol_fundamentals(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+) -> Result<FundamentalData, AppError> {
+    let symbol = symbol.trim().to_string();
+    if symbol.is_empty() {
+        return Err(AppError::validation("symbol", "Symbol is required"));
+    }
+    let (yahoo_symbol, _) = resolve_yahoo_symbol(&app_handle, &symbol);
+    let summary = fetch_yahoo_quote_summary(
+        &yahoo_symbol,
+        "summaryDetail,defaultKeyStatistics,financialData",
+    )?;
+
+    let path =
+        get_yahoo_metas_dir(&app_handle)?.join(format!("{}.json", symbol_file_stem(&symbol)));
+    let meta = with_file_lock(&app_handle, &path, || -> Result<_, AppError> {
+        let mut meta = load_yahoo_meta(&app_handle, &symbol)
+            .filter(|m| m.is_object())
+            .unwrap_or_else(|| serde_json::json!({}));
+        if let Some(object) = meta.as_object_mut() {
+            for (key, module) in FUNDAMENTAL_FIELDS {
+                let value = summary
+                    .get(module)
+                    .and_then(|m| yahoo_raw_number(m, key))
+                    .map_or(serde_json::Value::Null, serde_json::Value::from);
+                object.insert(key.to_string(), value);
+            }
+            object.insert(
+                "fundamentalsUpdatedAt".to_string(),
+                serde_json::Value::from(Utc::now().to_rfc3339()),
+            );
+        }
+
+        let content = serde_json::to_string_pretty(&meta)
+            .map_err(|e| format!("Failed to serialize meta JSON: {}", e))?;
+        atomic_write(&app_handle, &path, content.as_bytes())?;
+        Ok(meta)
+    })?;
+    Ok(fundamentals_from_meta(&symbol, Some(&meta)))
+}
+
+fn is_forward_filled_source(source: &str) -> bool {
+    let source = source.to_lowercase();
+    source.contains("ffill") || source.contains("forward")
+}
+
+#[tauri::command]
+fn get_symbol_coverage(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+) -> Result<SymbolCoverage, AppError> {
+    let prices_dir = get_prices_dir(&app_handle)?;
+    let safe_symbol = symbol_file_stem(&symbol);
+    let prices = cached_price_series(
+        &app_handle,
+        &prices_dir.join(format!("{}.csv", safe_symbol)),
+    )?;
+
+    let price_dates: std::collections::HashSet<NaiveDate> = prices.iter().map(|p| p.date).collect();
+
+    let today = Utc::now().date_naive();
+    let first_trade = first_trade_date(&app_handle, &symbol, price_dates.iter().min().copied());
+    // Days before the listing cannot have prices, so they do not count against coverage.
+    // A configured history start replaces the fifteen-year lookback.
+    let window_start = coverage_window_start(
+        history_start_override(&app_handle, &symbol)
+            .unwrap_or(today - ChronoDuration::days(15 * 365)),
+        first_trade,
+    );
+    let (exchange, _) = get_exchange_and_symbol(&symbol);
+    let calendar = TradingCalendar::new(exchange.as_deref(), window_start, today);
+    let expected_days = calendar.count_trading_days(window_start, today);
+    let missing = calendar.missing_trading_days(&price_dates, window_start, today);
+
+    let forward_filled_rows = prices
+        .iter()
+        .filter(|p| is_forward_filled_source(&p.source))
+        .count();
+
+    let dividends = load_dividend_events(&app_handle, &symbol)?;
+    let splits = load_split_events(&app_handle, &symbol)?;
+    let format_date = |d: &NaiveDate| d.format("%Y-%m-%d").to_string();
+
+    Ok(SymbolCoverage {
+        exchange: exchange.unwrap_or_else(|| "UNKNOWN".to_string()),
+        window_start: format_date(&window_start),
+        window_end: format_date(&today),
+        earliest_price: price_dates.iter().min().map(format_date),
+        latest_price: price_dates.iter().max().map(format_date),
+        expected_days,
+        missing_days: missing.len(),
+        coverage_percent: if expected_days > 0 {
+            (expected_days - missing.len()) as f64 / expected_days as f64 * 100.0
+        } else {
+            0.0
+        },
+        missing_ranges: merge_missing_ranges(&calendar, &missing),
+        real_rows: prices.len() - forward_filled_rows,
+        forward_filled_rows,
+        dividend_count: dividends.len(),
+        first_dividend: dividends.first().map(|(d, _, _)| format_date(d)),
+        last_dividend: dividends.last().map(|(d, _, _)| format_date(d)),
+        split_count: splits.len(),
+        last_split: splits.last().map(|(d, _)| format_date(d)),
+        first_trade_date: first_trade.as_ref().map(format_date),
+        sync_status: load_sync_status(&app_handle)?.remove(&symbol),
+        tags: security_tags(&app_handle, &symbol),
+        symbol,
+    })
+}
+
+/// Widest window `backfill_price_range` fetches in one call.
+const MAX_BACKFILL_DAYS: i64 = 2 * 366;
+
+#[derive(Serialize)]
+struct BackfillResult {
+    symbol: String,
+    from: String,
+    to: String,
+    source: Option<String>,
+    /// Weekdays in the range that had no price before the backfill.
+    missing_weekdays: usize,
+    filled: usize,
+    /// Still without a price afterwards, usually market holidays.
+    still_missing: Vec<String>,
+}
+
+/// Fetches only `from..=to` for `symbol` and merges it into the price file the way
+/// a sync does, so manual rows are kept, then re-derives the split-unadjusted
+/// closes. Reports how many of the range's missing weekdays were filled. Ranges are
+/// capped at two years; split longer gaps into several calls.
+#[tauri::command]
+fn backfill_price_range(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+    from: String,
+    to: String,
+) -> Result<BackfillResult, AppError> {
+    let symbol = symbol.trim().to_string();
+    if symbol.is_empty() {
+        return Err(AppError::validation("symbol", "Symbol is required"));
+    }
+    let from = parse_manual_date("from", &from)?;
+    let to = parse_manual_date("to", &to)?;
+    if from > to {
+        return Err(AppError::validation(
+            "from",
+            "Start date is after the end date",
+        ));
+    }
+    let span = (to - from).num_days() + 1;
+    if span > MAX_BACKFILL_DAYS {
+        return Err(AppError::validation(
+            "to",
+            format!(
+                "Range covers {} days; backfill at most {} days per call and split longer gaps into chunks",
+                span, MAX_BACKFILL_DAYS
+            ),
+        ));
+    }
+
+    let existing = price_file_entries(&app_handle, &symbol)?;
+    let missing_dates = |entries: &[PriceRecordEntry]| -> Vec<NaiveDate> {
+        let dates: std::collections::HashSet<NaiveDate> = entries.iter().map(|r| r.date).collect();
+        from.iter_days()
+            .take_while(|d| *d <= to)
+            .filter(|d| {
+                !matches!(d.weekday(), chrono::Weekday::Sat | chrono::Weekday::Sun)
+                    && !dates.contains(d)
+            })
+            .collect()
+    };
+    let missing_before = missing_dates(&existing);
+
+    let (yahoo_symbol, _) = resolve_yahoo_symbol(&app_handle, &symbol);
+    let fetcher = data_fetcher(&app_handle);
+    let profile = security_type(&app_handle, &symbol)
+        .unwrap_or(SecurityType::Stock)
+        .fetch_profile();
+    let mut incoming = match fetch_yahoo_chunk(
+        fetcher.as_ref(),
+        &yahoo_symbol,
+        &symbol,
+        from,
+        to,
+        profile.interval,
+    ) {
+        Ok((records, _, _)) if !records.is_empty() => records,
+        yahoo if !offline_mode_enabled() => {
+            match fallback_history(
+                &app_handle,
+                &new_run_id(),
+                &symbol,
+                from,
+                to,
+                &FALLBACK_SOURCES,
+            )? {
+                Some((records, _)) => records,
+                None => yahoo?.0,
+            }
+        }
+        yahoo => yahoo?.0,
+    };
+    incoming.retain(|r| r.date >= from && r.date <= to);
+    profile.apply(&mut incoming, &mut Vec::new());
+    let source = incoming.first().map(|r| r.source.clone());
+
+    let merged = merge_price_entries(&symbol, &existing, &incoming)?;
+    let still_missing = missing_dates(&merged);
+    if write_price_entries_if_changed(&app_handle, &symbol, &merged)? {
+        let path = get_prices_dir(&app_handle)?.join(format!("{}.csv", symbol_file_stem(&symbol)));
+        let split_events = load_split_events(&app_handle, &symbol)?;
+        rewrite_split_adjusted_prices(&app_handle, &path, &split_events, None)?;
+        invalidate_coverage_cache(&app_handle);
+    }
+
+    let filled = missing_before.len() - still_missing.len();
+    write_worker_log(
+        &app_handle,
+        &format!(
+            "Backfilled {} {}..{}: filled {} of {} missing weekdays",
+            symbol,
+            from,
+            to,
+            filled,
+            missing_before.len()
+        ),
+    )?;
+    Ok(BackfillResult {
+        symbol,
+        from: from.format("%Y-%m-%d").to_string(),
+        to: to.format("%Y-%m-%d").to_string(),
+        source,
+        missing_weekdays: missing_before.len(),
+        filled,
+        still_missing: still_missing
+            .iter()
+            .map(|d| d.format("%Y-%m-%d").to_string())
+            .collect(),
+    })
+}
+
+/// File count and newest modification time across the inputs coverage is built
+/// from. Cheap to compute (metadata only) compared with re-reading every price file.
+#[derive(PartialEq, Clone, Copy, Debug)]
+struct DataFingerprint {
+    file_count: usize,
+    latest_mtime: Option<std::time::SystemTime>,
+}
+
+fn data_fingerprint(app_handle: &tauri::AppHandle) -> Result<DataFingerprint, String> {
+    let mut paths = Vec::new();
+    for (filename, _) in TRANSACTION_FILES {
+        if let Some(path) = transaction_file_candidates(app_handle, filename)?
+            .into_iter()
+            .find(|path| path.is_file())
+        {
+            paths.push(path);
+        }
+    }
+    Ok(fingerprint_files(
+        &[get_prices_dir(app_handle)?, get_splits_dir(app_handle)?],
+        paths,
+    ))
+}
+
+/// File count and newest mtime over every entry of `dirs` plus `files`. Missing
+/// directories and files without metadata are skipped.
+fn fingerprint_files(dirs: &[PathBuf], files: Vec<PathBuf>) -> DataFingerprint {
+    let mut fingerprint = DataFingerprint {
+        file_count: 0,
+        latest_mtime: None,
+    };
+    let dir_entries = dirs
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten().map(|entry| entry.path()));
+    for path in dir_entries.chain(files) {
+        if let Ok(modified) = std::fs::metadata(&path).and_then(|m| m.modified()) {
+            fingerprint.file_count += 1;
+            fingerprint.latest_mtime = fingerprint.latest_mtime.max(Some(modified));
+        }
+    }
+    fingerprint
+}
+
+/// Coverage and readiness stats from the last computation, reused while the data
+/// fingerprint is unchanged. Writers call `invalidate` so same-second edits that the
+/// mtime cannot distinguish are still picked up.
+#[derive(Default)]
+struct CoverageCache {
+    coverage: Mutex<HashMap<bool, (DataFingerprint, Vec<StockDataCoverage>)>>,
+    stats: Mutex<Option<(DataFingerprint, DataReadinessStats)>>,
+}
+
+impl CoverageCache {
+    fn invalidate(&self) {
+        self.coverage
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+        *self.stats.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+fn invalidate_coverage_cache(app_handle: &tauri::AppHandle) {
+    if let Some(cache) = app_handle.try_state::<CoverageCache>() {
+        cache.invalidate();
+    }
+}
+
+fn cached_data_coverage(
+    app_handle: &tauri::AppHandle,
+    include_completeness: bool,
+    force_refresh: bool,
+) -> Result<Vec<StockDataCoverage>, String> {
+    let fingerprint = data_fingerprint(app_handle)?;
+    let cache = app_handle.try_state::<CoverageCache>();
+    if let Some(cache) = cache.as_ref().filter(|_| !force_refresh) {
+        let entries = cache.coverage.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((cached_fingerprint, coverage)) = entries.get(&include_completeness) {
+            if *cached_fingerprint == fingerprint {
+                return Ok(coverage.clone());
+            }
+        }
+    }
+
+    let transactions = load_all_transactions(app_handle)?;
+    let price_frames = load_price_frames(app_handle)?;
+    let coverage = build_data_coverage(
+        app_handle,
+        &transactions,
+        &price_frames,
+        include_completeness,
+    )?;
+    if let Some(cache) = cache {
+        cache
+            .coverage
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(include_completeness, (fingerprint, coverage.clone()));
+    }
+    Ok(coverage)
+}
+
+#[tauri::command]
+fn get_data_coverage(
+    app_handle: tauri::AppHandle,
+    include_completeness: Option<bool>,
+    force_refresh: Option<bool>,
+) -> Result<Vec<StockDataCoverage>, AppError> {
+    Ok(cached_data_coverage(
+        &app_handle,
+        include_completeness.unwrap_or(true),
+        force_refresh.unwrap_or(false),
+    )?)
+}
+
+/// Pre-serialized form of `get_data_coverage`, kept for one release for older frontends.
+#[tauri::command]
+fn get_data_coverage_json(
+    app_handle: tauri::AppHandle,
+    include_completeness: Option<bool>,
+    force_refresh: Option<bool>,
+) -> Result<String, AppError> {
+    let coverage_list = get_data_coverage(app_handle, include_completeness, force_refresh)?;
+    Ok(serde_json::to_string(&coverage_list)
+        .map_err(|e| format!("Failed to serialize coverage: {}", e))?)
+}
+
+fn build_data_coverage(
+    app_handle: &tauri::AppHandle,
+    transactions: &[Transaction],
+    price_frames: &PriceFrames,
+    include_completeness: bool,
+) -> Result<Vec<StockDataCoverage>, String> {
+    let today = Utc::now().date_naive();
+    let fifteen_years_ago = today - ChronoDuration::days(15 * 365);
+    let history_starts = history_start_overrides(app_handle).unwrap_or_default();
+    let tags = load_security_tags(app_handle).unwrap_or_default();
+
+    let mut stock_map: HashMap<String, StockDataCoverage> = HashMap::new();
+
+    for txn in transactions {
+        if txn.stock.trim().is_empty() {
+            continue;
+        }
+
+        let txn_date = match NaiveDate::parse_from_str(txn.date.trim(), "%Y-%m-%d") {
+            Ok(d) => d,
+            Err(_) => continue,
+        };
+
+        if txn_date < fifteen_years_ago {
+            continue;
+        }
+
+        let ticker = canonical_symbol(&txn.stock);
+        let (exchange, _) = get_exchange_and_symbol(&ticker);
+        let exchange_str = exchange.unwrap_or_else(|| "UNKNOWN".to_string());
+
+        stock_map
+            .entry(ticker.clone())
+            .or_insert_with(|| StockDataCoverage {
+                ticker: ticker.clone(),
+                exchange: exchange_str.clone(),
+                currency: txn.currency.clone(),
+                earliest_transaction: txn.date.clone(),
+                earliest_price: None,
+                latest_price: None,
+                total_days: 0,
+                missing_days: 0,
+                coverage_percent: 0.0,
+                split_count: 0,
+                last_split: None,
+                status: "missing".to_string(),
+                delist_reason: None,
+                days_since_latest_price: None,
+                stale: false,
+                last_sync_error: None,
+                first_trade_date: None,
+                tags: tags.get(&ticker).cloned().unwrap_or_default(),
+            });
+
+        if let Some(coverage) = stock_map.get_mut(&ticker) {
+            if txn.date < coverage.earliest_transaction {
+                coverage.earliest_transaction = txn.date.clone();
+            }
+        }
+    }
+
+    let mut dates_by_symbol: HashMap<String, Vec<NaiveDate>> = HashMap::new();
+    for (symbol, frame) in price_frames {
+        dates_by_symbol
+            .entry(canonical_symbol(symbol))
+            .or_default()
+            .extend(price_frame_dates(frame));
+    }
+    for (symbol, dates) in dates_by_symbol {
+        if let Some(coverage) = stock_map.get_mut(&symbol) {
+            if let Some(earliest) = dates.iter().min() {
+                coverage.earliest_price = Some(earliest.format("%Y-%m-%d").to_string());
+            }
+            if let Some(latest) = dates.iter().max().copied() {
+                coverage.latest_price = Some(latest.format("%Y-%m-%d").to_string());
+                let missed = trading_days_since(Some(coverage.exchange.as_str()), latest, today);
+                coverage.days_since_latest_price = Some(missed);
+                coverage.stale = missed > STALE_PRICE_TRADING_DAYS
+                    && coverage.status != "delisted"
+                    && coverage.delist_reason.is_none();
+            }
+            let first_trade = first_trade_date(app_handle, &symbol, dates.iter().min().copied());
+            coverage.first_trade_date = first_trade.map(|d| d.format("%Y-%m-%d").to_string());
+            if include_completeness {
+                let bound = history_starts
+                    .get(&symbol)
+                    .copied()
+                    .unwrap_or(fifteen_years_ago);
+                let window_start = coverage_window_start(bound, first_trade);
+                let calendar =
+                    TradingCalendar::new(Some(coverage.exchange.as_str()), window_start, today);
+                let total_days = calendar.count_trading_days(window_start, today) as i32;
+
+                let price_dates: std::collections::HashSet<NaiveDate> =
+                    dates.iter().copied().collect();
+                let missing = calendar
+                    .missing_trading_days(&price_dates, window_start, today)
+                    .len() as i32;
+
+                coverage.total_days = total_days;
+                coverage.missing_days = missing;
+                coverage.coverage_percent = if total_days > 0 {
+                    ((total_days - missing) as f64 / total_days as f64) * 100.0
+                } else {
+                    0.0
+                };
+
+                coverage.status = if coverage.coverage_percent >= 95.0 {
+                    "complete".to_string()
+                } else if coverage.coverage_percent >= 50.0 {
+                    "partial".to_string()
+                } else {
+                    "missing".to_string()
+                };
+            } else if coverage.latest_price.is_some() {
+                coverage.coverage_percent = 100.0;
+                coverage.status = "complete".to_string();
+            }
+        }
+    }
+
+    for (symbol, event) in delisting_events(app_handle, today) {
+        if let Some(coverage) = stock_map.get_mut(&symbol) {
+            let mut reason = format!("{} on {}", event.event_type, event.date);
+            if !event.description.is_empty() {
+                reason.push_str(&format!(": {}", event.description));
+            }
+            coverage.delist_reason = Some(reason);
+            coverage.status = "delisted".to_string();
+            coverage.stale = false;
+        }
+    }
+
+    // Count splits from split files
+    if let Ok(splits_dir) = get_splits_dir(app_handle) {
+        if let Ok(entries) = std::fs::read_dir(&splits_dir) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if !path.is_file() || path.extension().is_none_or(|e| e != "csv") {
+                    continue;
+                }
+
+                let filename = match path.file_stem().and_then(|s| s.to_str()) {
+                    Some(f) => symbol_from_file_stem(f),
+                    None => continue,
+                };
+
+                let content = match read_to_string(&path) {
+                    Ok(c) => c,
+                    Err(_) => continue,
+                };
+
+                let mut split_count = 0;
+                let mut last_split_date: Option<String> = None;
+
+                for (idx, line) in content.lines().enumerate() {
+                    if idx == 0 || line.trim().is_empty() {
+                        continue;
+                    }
+
+                    let fields: Vec<&str> = line.split(',').collect();
+                    if fields.len() >= 2 && !is_spinoff_marker(fields.iter().copied()) {
+                        split_count += 1;
+                        let date = fields[0].to_string();
+                        if last_split_date.is_none() || date > *last_split_date.as_ref().unwrap() {
+                            last_split_date = Some(date);
+                        }
+                    }
+                }
+
+                if let Some(coverage) = stock_map.get_mut(&filename) {
+                    coverage.split_count = split_count;
+                    coverage.last_split = last_split_date;
+                }
+            }
+        }
+    }
+
+    let sync_status = load_sync_status(app_handle).unwrap_or_default();
+    for coverage in stock_map.values_mut() {
+        coverage.last_sync_error = sync_status
+            .get(&coverage.ticker)
+            .and_then(|entry| entry.last_error.clone());
+    }
+
+    Ok(stock_map.into_values().collect())
+}
+
+#[tauri::command]
+fn get_split_history(app_handle: tauri::AppHandle) -> Result<Vec<SplitHistory>, AppError> {
+    let mut splits: Vec<SplitHistory> = Vec::new();
+    let splits_dir = match get_splits_dir(&app_handle) {
+        Ok(dir) => dir,
+        Err(_) => return Ok(splits),
+    };
+
+    if let Ok(entries) = std::fs::read_dir(&splits_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_file() || path.extension().is_none_or(|e| e != "csv") {
+                continue;
+            }
+
+            let filename = match path.file_stem().and_then(|s| s.to_str()) {
+                Some(f) => symbol_from_file_stem(f),
+                None => continue,
+            };
+
+            let content = match read_to_string(&path) {
+                Ok(c) => c,
+                Err(_) => continue,
+            };
+
+            let mut lines = content.lines();
+            let header = lines.next().unwrap_or("");
+            let has_fractional_header = header
+                .split(',')
+                .any(|col| col.trim().eq_ignore_ascii_case("numerator"));
+
+            for line in lines {
+                if line.trim().is_empty() {
+                    continue;
+                }
+
+                let fields: Vec<&str> = line.split(',').collect();
+                if fields.is_empty() {
+                    continue;
+                }
+
+                let date = fields.first().map(|s| s.trim()).unwrap_or("");
+                if date.is_empty() {
+                    continue;
+                }
+                // Spin-offs are recorded alongside splits but do not change share counts.
+                if has_fractional_header && fields.get(5).is_some_and(|s| !s.trim().is_empty()) {
+                    continue;
+                }
+
+                let (numerator, denominator, before_price, after_price) = if has_fractional_header {
+                    let numerator = fields
+                        .get(1)
+                        .and_then(|s| s.trim().parse::<i32>().ok())
+                        .unwrap_or(1)
+                        .max(1);
+                    let denominator = fields
+                        .get(2)
+                        .and_then(|s| s.trim().parse::<i32>().ok())
+                        .unwrap_or(1)
+                        .max(1);
+                    let before_price = parse_price_field(fields.get(3));
+                    let after_price = parse_price_field(fields.get(4));
+                    (numerator, denominator, before_price, after_price)
+                } else {
+                    let ratio_str = fields.get(1).map(|s| s.trim()).unwrap_or("");
+                    let (numerator, denominator) = parse_ratio_components(ratio_str);
+                    let before_price = parse_price_field(fields.get(2));
+                    let after_price = parse_price_field(fields.get(3));
+                    (numerator, denominator, before_price, after_price)
+                };
+
+                let ratio = format!("{}:{}", numerator, denominator);
+                let ratio_factor = numerator as f64 / denominator as f64;
+
+                splits.push(SplitHistory {
+                    ticker: filename.clone(),
+                    date: date.to_string(),
+                    numerator,
+                    denominator,
+                    ratio,
+                    ratio_factor,
+                    before_price,
+                    after_price,
+                });
+            }
+        }
+    }
+
+    splits.sort_by(|a, b| b.date.cmp(&a.date));
+
+    Ok(splits)
+}
+
+/// Pre-serialized form of `get_split_history`, kept for one release for older frontends.
+#[tauri::command]
+fn get_split_history_json(app_handle: tauri::AppHandle) -> Result<String, AppError> {
+    let splits = get_split_history(app_handle)?;
+    Ok(serde_json::to_string(&splits)
+        .map_err(|e| format!("Failed to serialize split history: {}", e))?)
+}
+
+#[derive(Serialize)]
+struct UpcomingDividend {
+    ex_date: String,
+    amount: f64,
+    estimated: bool,
+}
+
+#[derive(Serialize)]
+struct PositionDetail {
+    symbol: String,
+    currency: String,
+    shares: f64,
+    average_cost: f64,
+    cost_basis_average: f64,
+    cost_basis_fifo: f64,
+    oldest_lot_date: Option<String>,
+    latest_price: Option<f64>,
+    latest_price_date: Option<String>,
+    market_value: Option<f64>,
+    unrealized_pnl: Option<f64>,
+    realized_pnl: f64,
+    dividend_income: f64,
+    beta_1y: Option<f64>,
+    sharpe_1y: Option<f64>,
+    max_drawdown_1y: Option<f64>,
+    high_52w: Option<f64>,
+    low_52w: Option<f64>,
+    ytd_return: Option<f64>,
+    return_1y: Option<f64>,
+    cagr_3y: Option<f64>,
+    upcoming_dividend: Option<UpcomingDividend>,
+    coverage_percent: Option<f64>,
+    tags: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct PositionReportRow {
+    symbol: String,
+    currency: String,
+    shares: f64,
+    average_cost: f64,
+    cost_basis: f64,
+    market_value: Option<f64>,
+    unrealized_pnl: Option<f64>,
+    realized_pnl: f64,
+    base_currency: String,
+}
+
+#[derive(Serialize)]
+struct RealizedGainReportRow {
+    symbol: String,
+    sell_date: String,
+    acquired_date: String,
+    quantity: f64,
+    currency: String,
+    cost_basis: f64,
+    proceeds: f64,
+    gain: f64,
+    base_currency: String,
+}
+
+#[derive(Serialize)]
+struct ExportReportResult {
+    path: String,
+    rows: usize,
+}
+
+/// Converts between currencies through their USD rates on `date`, caching each
+/// currency's FX series.
+struct FxConverter<'a> {
+    app_handle: &'a tauri::AppHandle,
+    series: HashMap<String, Vec<(NaiveDate, f64)>>,
+}
+
+impl<'a> FxConverter<'a> {
+    fn new(app_handle: &'a tauri::AppHandle) -> Self {
+        FxConverter {
+            app_handle,
+            series: HashMap::new(),
+        }
+    }
+
+    fn usd_rate(&mut self, currency: &str, date: NaiveDate) -> Result<f64, String> {
+        let currency = currency.trim().to_uppercase();
+        if !self.series.contains_key(&currency) {
+            let series = usd_rate_series(self.app_handle, &currency)?;
+            self.series.insert(currency.clone(), series);
+        }
+        Ok(usd_rate_on(&self.series[&currency], date))
+    }
+
+    fn convert(
+        &mut self,
+        amount: f64,
+        from: &str,
+        to: &str,
+        date: NaiveDate,
+    ) -> Result<f64, String> {
+        if from.eq_ignore_ascii_case(to) {
+            return Ok(amount);
+        }
+        Ok(amount * self.usd_rate(from, date)? / self.usd_rate(to, date)?)
+    }
+}
+
+fn traded_symbols(app_handle: &tauri::AppHandle) -> Result<Vec<String>, String> {
+    let mut symbols: Vec<String> = load_all_transactions(app_handle)?
+        .iter()
+        .map(|txn| txn.stock.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    symbols.sort();
+    symbols.dedup();
+    Ok(symbols)
+}
+
+/// Writes `rows` as JSON when the path ends in `.json`, CSV otherwise.
+fn write_report_rows<T: Serialize>(path: &Path, rows: &[T]) -> Result<(), AppError> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        create_dir_all(parent)?;
+    }
+    let is_json = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("json"));
+    let content = if is_json {
+        serde_json::to_vec_pretty(rows).map_err(|e| format!("Failed to serialize report: {}", e))?
+    } else {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        for row in rows {
+            writer
+                .serialize(row)
+                .map_err(|e| format!("Failed to write report row: {}", e))?;
+        }
+        writer
+            .into_inner()
+            .map_err(|e| format!("Failed to build report: {}", e))?
+    };
+    write(path, content)?;
+    Ok(())
+}
+
+/// Current holdings from the position detail analytics, valued in `base` at the
+/// latest FX rate.
+fn position_report_rows(
+    app_handle: &tauri::AppHandle,
+    base: &str,
+    fx: &mut FxConverter,
+) -> Result<Vec<PositionReportRow>, AppError> {
+    let today = Utc::now().date_naive();
+    let mut rows = Vec::new();
+    for symbol in traded_symbols(app_handle)? {
+        let detail = get_position_detail(app_handle.clone(), symbol, None)?;
+        if detail.shares <= 1e-9 {
+            continue;
+        }
+        let currency = detail.currency.clone();
+        let mut to_base = |value: f64| fx.convert(value, &currency, base, today);
+        rows.push(PositionReportRow {
+            average_cost: to_base(detail.average_cost)?,
+            cost_basis: to_base(detail.cost_basis_average)?,
+            market_value: detail.market_value.map(&mut to_base).transpose()?,
+            unrealized_pnl: detail.unrealized_pnl.map(&mut to_base).transpose()?,
+            realized_pnl: to_base(detail.realized_pnl)?,
+            symbol: detail.symbol,
+            currency: detail.currency,
+            shares: detail.shares,
+            base_currency: base.to_string(),
+        });
+    }
+    Ok(rows)
+}
+
+/// FIFO lot sales in `base` at the sale-date FX rate, oldest first.
+fn realized_gain_rows(
+    app_handle: &tauri::AppHandle,
+    base: &str,
+    year: Option<i32>,
+    fx: &mut FxConverter,
+) -> Result<Vec<RealizedGainReportRow>, AppError> {
+    let today = Utc::now().date_naive();
+    let mut rows = Vec::new();
+    for symbol in traded_symbols(app_handle)? {
+        let transactions = load_symbol_transactions(app_handle, &symbol)?;
+        let currency = transactions
+            .first()
+            .map(|t| t.currency.clone())
+            .unwrap_or_else(|| "USD".to_string());
+        let (_, realized) = fifo_replay(&transactions, today);
+        for lot in realized
+            .into_iter()
+            .filter(|lot| year.is_none_or(|y| lot.sell_date.year() == y))
+        {
+            let mut to_base = |value: f64| fx.convert(value, &currency, base, lot.sell_date);
+            let cost_basis = to_base(lot.cost_basis)?;
+            let proceeds = to_base(lot.proceeds)?;
+            rows.push(RealizedGainReportRow {
+                symbol: symbol.clone(),
+                sell_date: lot.sell_date.format("%Y-%m-%d").to_string(),
+                acquired_date: lot.acquired_date.format("%Y-%m-%d").to_string(),
+                quantity: lot.quantity,
+                currency: currency.clone(),
+                cost_basis,
+                proceeds,
+                gain: proceeds - cost_basis,
+                base_currency: base.to_string(),
+            });
+        }
+    }
+    rows.sort_by(|a, b| a.sell_date.cmp(&b.sell_date).then(a.symbol.cmp(&b.symbol)));
+    Ok(rows)
+}
+
+/// Exports `positions` (current holdings, from the position detail analytics) or
+/// `realized_gains` (FIFO lot sales, optionally limited to `year`) in
+/// `base_currency`, or the configured base currency when omitted. Values convert at the latest FX rate for positions and at the
+/// sale date for gains. Existing files are kept unless `overwrite` is set.
+#[tauri::command]
+fn export_report(
+    app_handle: tauri::AppHandle,
+    kind: String,
+    path: String,
+    base_currency: Option<String>,
+    year: Option<i32>,
+    overwrite: Option<bool>,
+) -> Result<ExportReportResult, AppError> {
+    let path = PathBuf::from(path.trim());
+    if path.as_os_str().is_empty() {
+        return Err(AppError::validation("path", "An export path is required"));
+    }
+    if path.exists() && !overwrite.unwrap_or(false) {
+        return Err(AppError::validation(
+            "path",
+            format!("{} already exists", path.display()),
+        ));
+    }
+    let base = match base_currency {
+        Some(code) if !code.trim().is_empty() => validate_currency_code("base_currency", &code)?,
+        _ => configured_base_currency(&app_handle),
+    };
+
+    let mut fx = FxConverter::new(&app_handle);
+    let rows = match kind.trim() {
+        "positions" => {
+            let rows = position_report_rows(&app_handle, &base, &mut fx)?;
+            if rows.is_empty() {
+                return Err(AppError::not_found(
+                    "positions",
+                    "No open positions to export",
+                ));
+            }
+            write_report_rows(&path, &rows)?;
+            rows.len()
+        }
+        "realized_gains" => {
+            let rows = realized_gain_rows(&app_handle, &base, year, &mut fx)?;
+            if rows.is_empty() {
+                let scope = year.map(|y| format!(" in {}", y)).unwrap_or_default();
+                return Err(AppError::not_found(
+                    "realized_gains",
+                    format!("No realized gains{}", scope),
+                ));
+            }
+            write_report_rows(&path, &rows)?;
+            rows.len()
+        }
+        other => {
+            return Err(AppError::validation(
+                "kind",
+                format!(
+                    "Unknown report '{}'; expected positions or realized_gains",
+                    other
+                ),
+            ))
+        }
+    };
+
+    Ok(ExportReportResult {
+        path: path.display().to_string(),
+        rows,
+    })
+}
+
+#[derive(Serialize)]
+struct TaxLotRow {
+    #[serde(rename = "Security")]
+    security: String,
+    #[serde(rename = "CUSIP/ISIN")]
+    identifier: String,
+    #[serde(rename = "Date Acquired")]
+    date_acquired: String,
+    #[serde(rename = "Date Sold")]
+    date_sold: String,
+    #[serde(rename = "Holding Period")]
+    holding_period: String,
+    #[serde(rename = "Shares")]
+    shares: f64,
+    #[serde(rename = "Cost Per Share")]
+    cost_per_share: f64,
+    #[serde(rename = "Total Cost")]
+    total_cost: f64,
+    #[serde(rename = "Proceeds Per Share")]
+    proceeds_per_share: f64,
+    #[serde(rename = "Total Proceeds")]
+    total_proceeds: f64,
+    #[serde(rename = "Gross Gain/Loss")]
+    gross_gain: f64,
+    #[serde(rename = "Wash Sale Adj")]
+    wash_sale_adjustment: f64,
+    #[serde(rename = "Net Gain/Loss")]
+    net_gain: f64,
+    #[serde(rename = "Term")]
+    term: String,
+}
+
+/// ISIN, or CUSIP when there is no ISIN, per ticker from securities.csv. Either
+/// column is optional.
+fn security_identifiers(app_handle: &tauri::AppHandle) -> Result<HashMap<String, String>, String> {
+    let (headers, rows) = read_securities_rows(app_handle)?;
+    let column = |name: &str| headers.iter().position(|h| h.eq_ignore_ascii_case(name));
+    let ticker_idx = column("ticker").unwrap_or(0);
+    let id_columns: Vec<usize> = ["isin", "cusip"]
+        .iter()
+        .filter_map(|name| column(name))
+        .collect();
+    Ok(rows
+        .into_iter()
+        .filter_map(|(_, fields)| {
+            let ticker = canonical_symbol(fields.get(ticker_idx)?);
+            let identifier = id_columns
+                .iter()
+                .filter_map(|idx| fields.get(*idx))
+                .map(|value| value.trim())
+                .find(|value| !value.is_empty())?;
+            Some((ticker, identifier.to_string()))
+        })
+        .collect())
+}
+
+/// Per-ticker `history_start` dates from securities.csv. The column is optional;
+/// blank or malformed values mean the history depth follows the transactions.
+fn history_start_overrides(
+    app_handle: &tauri::AppHandle,
+) -> Result<HashMap<String, NaiveDate>, String> {
+    let (headers, rows) = read_securities_rows(app_handle)?;
+    let column = |name: &str| headers.iter().position(|h| h.eq_ignore_ascii_case(name));
+    let ticker_idx = column("ticker").unwrap_or(0);
+    let Some(start_idx) = column("history_start") else {
+        return Ok(HashMap::new());
+    };
+    Ok(rows
+        .into_iter()
+        .filter_map(|(_, fields)| {
+            let ticker = canonical_symbol(fields.get(ticker_idx)?);
+            let date = NaiveDate::parse_from_str(fields.get(start_idx)?.trim(), "%Y-%m-%d").ok()?;
+            Some((ticker, date))
+        })
+        .collect())
+}
+
+fn history_start_override(app_handle: &tauri::AppHandle, symbol: &str) -> Option<NaiveDate> {
+    history_start_overrides(app_handle)
+        .ok()?
+        .remove(&canonical_symbol(symbol))
+}
+
+/// Sets how far back price history for `symbol` should go, overriding its earliest
+/// transaction, in securities.csv's `history_start` column (added when missing).
+/// An empty `date` clears the override so the next sync derives it again.
+#[tauri::command]
+fn set_history_start(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+    date: Option<String>,
+) -> Result<(), AppError> {
+    let symbol = canonical_symbol(symbol.trim());
+    if symbol.is_empty() {
+        return Err(AppError::validation("symbol", "Symbol is required"));
+    }
+    let date = date
+        .filter(|d| !d.trim().is_empty())
+        .map(|d| parse_manual_date("date", &d))
+        .transpose()?;
+
+    with_securities_lock(&app_handle, || {
+        let (mut headers, rows) = read_securities_rows(&app_handle)?;
+        let ticker_idx = headers
+            .iter()
+            .position(|h| h.eq_ignore_ascii_case("ticker"))
+            .unwrap_or(0);
+        let start_idx = match headers
+            .iter()
+            .position(|h| h.eq_ignore_ascii_case("history_start"))
+        {
+            Some(idx) => idx,
+            None if date.is_none() => return Ok(()),
+            None => {
+                headers.push("history_start".to_string());
+                headers.len() - 1
+            }
+        };
+        let mut rows: Vec<Vec<String>> = rows
+            .into_iter()
+            .map(|(_, mut fields)| {
+                fields.resize(headers.len(), String::new());
+                fields
+            })
+            .collect();
+        let value = date
+            .map(|d| d.format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+        match rows
+            .iter_mut()
+            .find(|fields| canonical_symbol(&fields[ticker_idx]) == symbol)
+        {
+            Some(fields) => fields[start_idx] = value.clone(),
+            None if date.is_none() => return Ok(()),
+            None => {
+                let mut fields = vec![String::new(); headers.len()];
+                fields[ticker_idx] = symbol.clone();
+                fields[start_idx] = value.clone();
+                rows.push(fields);
+            }
+        }
+        write_securities_rows(&app_handle, &headers, &rows)?;
+        invalidate_coverage_cache(&app_handle);
+        Ok(write_worker_log(
+            &app_handle,
+            &if value.is_empty() {
+                format!("Cleared history start for {}", symbol)
+            } else {
+                format!("History start for {} set to {}", symbol, value)
+            },
+        )?)
+    })
+}
+
+/// Separator between tags in securities.csv's `tags` column. The field is written
+/// with CSV quoting, so tags may contain commas but not this character.
+const TAG_SEPARATOR: char = ';';
+
+/// Trimmed, non-empty tags with case-insensitive duplicates dropped, first spelling kept.
+fn normalize_tags<I, S>(tags: I) -> Vec<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut seen = std::collections::HashSet::new();
+    tags.into_iter()
+        .map(|tag| tag.as_ref().trim().to_string())
+        .filter(|tag| !tag.is_empty() && seen.insert(tag.to_lowercase()))
+        .collect()
+}
+
+/// Tags per ticker from securities.csv; only rows with at least one tag.
+fn load_security_tags(
+    app_handle: &tauri::AppHandle,
+) -> Result<HashMap<String, Vec<String>>, String> {
+    let (headers, rows) = read_securities_rows(app_handle)?;
+    let column = |name: &str| headers.iter().position(|h| h.eq_ignore_ascii_case(name));
+    let ticker_idx = column("ticker").unwrap_or(0);
+    let Some(tags_idx) = column("tags") else {
+        return Ok(HashMap::new());
+    };
+    Ok(rows
+        .into_iter()
+        .filter_map(|(_, fields)| {
+            let ticker = canonical_symbol(fields.get(ticker_idx)?);
+            let tags = normalize_tags(fields.get(tags_idx)?.split(TAG_SEPARATOR));
+            (!ticker.is_empty() && !tags.is_empty()).then_some((ticker, tags))
+        })
+        .collect())
+}
+
+fn security_tags(app_handle: &tauri::AppHandle, symbol: &str) -> Vec<String> {
+    load_security_tags(app_handle)
+        .ok()
+        .and_then(|mut tags| tags.remove(&canonical_symbol(symbol)))
+        .unwrap_or_default()
+}
+
+/// Appends columns added to `SECURITIES_HEADER` since the file was created, padding
+/// each row with empty fields. Existing columns and their order are left alone, and
+/// rows keep any length mismatch they already had so `validate_securities_csv` still
+/// reports them.
+fn migrate_securities_columns(app_handle: &tauri::AppHandle) -> Result<(), String> {
+    let path = get_data_dir(app_handle)?.join("securities.csv");
+    if !path.exists() {
+        return Ok(());
+    }
+    with_securities_lock(app_handle, || {
+        let (mut headers, rows) = read_securities_rows(app_handle)?;
+        let missing: Vec<String> = SECURITIES_HEADER
+            .trim_end()
+            .split(',')
+            .filter(|name| !headers.iter().any(|h| h.eq_ignore_ascii_case(name)))
+            .map(|name| name.to_string())
+            .collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        headers.extend(missing.iter().cloned());
+        let rows: Vec<Vec<String>> = rows
+            .into_iter()
+            .map(|(_, mut fields)| {
+                fields.extend(missing.iter().map(|_| String::new()));
+                fields
+            })
+            .collect();
+        write_securities_rows(app_handle, &headers, &rows)?;
+        write_worker_log(
+            app_handle,
+            &format!("Added {} to securities.csv", missing.join(", ")),
+        )
+    })
+}
+
+/// Replaces the tags of `ticker` in securities.csv, adding a row when the ticker is
+/// not listed yet. Tags are trimmed and de-duplicated; an empty list clears them.
+/// Returns the tags as stored.
+#[tauri::command]
+fn set_security_tags(
+    app_handle: tauri::AppHandle,
+    ticker: String,
+    tags: Vec<String>,
+) -> Result<Vec<String>, AppError> {
+    let ticker = canonical_symbol(ticker.trim());
+    if ticker.is_empty() {
+        return Err(AppError::validation("ticker", "Ticker is required"));
+    }
+    let tags = normalize_tags(&tags);
+    if let Some(tag) = tags.iter().find(|tag| tag.contains(TAG_SEPARATOR)) {
+        return Err(AppError::validation(
+            "tags",
+            format!("Tag '{}' must not contain '{}'", tag, TAG_SEPARATOR),
+        ));
+    }
+
+    with_securities_lock(&app_handle, || {
+        migrate_securities_columns(&app_handle)?;
+        let (headers, rows) = read_securities_rows(&app_handle)?;
+        let column = |name: &str| headers.iter().position(|h| h.eq_ignore_ascii_case(name));
+        let ticker_idx = column("ticker").unwrap_or(0);
+        let tags_idx = column("tags").ok_or("securities.csv has no tags column")?;
+        let mut rows: Vec<Vec<String>> = rows
+            .into_iter()
+            .map(|(_, mut fields)| {
+                fields.resize(headers.len(), String::new());
+                fields
+            })
+            .collect();
+        let value = tags.join(&TAG_SEPARATOR.to_string());
+        match rows
+            .iter_mut()
+            .find(|fields| canonical_symbol(&fields[ticker_idx]) == ticker)
+        {
+            Some(fields) => fields[tags_idx] = value,
+            None if tags.is_empty() => return Ok(tags),
+            None => {
+                let mut fields = vec![String::new(); headers.len()];
+                fields[ticker_idx] = ticker.clone();
+                fields[tags_idx] = value;
+                rows.push(fields);
+            }
+        }
+        write_securities_rows(&app_handle, &headers, &rows)?;
+        invalidate_coverage_cache(&app_handle);
+        Ok(tags)
+    })
+}
+
+#[derive(Serialize)]
+struct TaggedSecurity {
+    ticker: String,
+    name: String,
+    tags: Vec<String>,
+    notes: String,
+}
+
+/// Securities carrying `tag`, matched case-insensitively, sorted by ticker.
+#[tauri::command]
+fn get_securities_by_tag(
+    app_handle: tauri::AppHandle,
+    tag: String,
+) -> Result<Vec<TaggedSecurity>, AppError> {
+    let tag = tag.trim().to_lowercase();
+    if tag.is_empty() {
+        return Err(AppError::validation("tag", "Tag is required"));
+    }
+    let (headers, rows) = read_securities_rows(&app_handle)?;
+    let column = |name: &str| headers.iter().position(|h| h.eq_ignore_ascii_case(name));
+    let ticker_idx = column("ticker").unwrap_or(0);
+    let Some(tags_idx) = column("tags") else {
+        return Ok(Vec::new());
+    };
+    let field = |fields: &[String], idx: Option<usize>| {
+        idx.and_then(|idx| fields.get(idx))
+            .map(|value| value.trim().to_string())
+            .unwrap_or_default()
+    };
+    let mut securities: Vec<TaggedSecurity> = rows
+        .into_iter()
+        .filter_map(|(_, fields)| {
+            let tags = normalize_tags(fields.get(tags_idx)?.split(TAG_SEPARATOR));
+            tags.iter()
+                .any(|t| t.to_lowercase() == tag)
+                .then(|| TaggedSecurity {
+                    ticker: canonical_symbol(&field(&fields, Some(ticker_idx))),
+                    name: field(&fields, column("name")),
+                    notes: field(&fields, column("notes")),
+                    tags,
+                })
+        })
+        .filter(|security| !security.ticker.is_empty())
+        .collect();
+    securities.sort_by(|a, b| a.ticker.cmp(&b.ticker));
+    Ok(securities)
+}
+
+/// FIFO lot sales in `tax_year` with per-share detail, in `base` at the sale-date
+/// FX rate. A loss is treated as a wash sale when shares of the same security were
+/// bought within 30 days either side of the sale, other than the lot being sold;
+/// the disallowed part is the loss times the replacement shares over the shares
+/// sold, capped at the whole loss. The disallowed loss is reported, not carried
+/// into the replacement lot's basis.
+fn tax_lot_rows(
+    app_handle: &tauri::AppHandle,
+    base: &str,
+    tax_year: i32,
+    fx: &mut FxConverter,
+) -> Result<Vec<TaxLotRow>, AppError> {
+    let today = Utc::now().date_naive();
+    let identifiers = security_identifiers(app_handle)?;
+    let mut rows = Vec::new();
+    for symbol in traded_symbols(app_handle)? {
+        let transactions = load_symbol_transactions(app_handle, &symbol)?;
+        let currency = transactions
+            .first()
+            .map(|t| t.currency.clone())
+            .unwrap_or_else(|| "USD".to_string());
+        let buys: Vec<(NaiveDate, f64)> = transactions
+            .iter()
+            .filter(|t| is_buy_type(&t.txn_type) && t.quantity > 0.0)
+            .map(|t| (t.date, t.quantity))
+            .collect();
+        let (_, realized) = fifo_replay(&transactions, today);
+        for lot in realized
+            .into_iter()
+            .filter(|lot| lot.sell_date.year() == tax_year && lot.quantity > 1e-9)
+        {
+            let mut to_base = |value: f64| fx.convert(value, &currency, base, lot.sell_date);
+            let total_cost = to_base(lot.cost_basis)?;
+            let total_proceeds = to_base(lot.proceeds)?;
+            let gross_gain = total_proceeds - total_cost;
+
+            let wash_sale_adjustment = if gross_gain < 0.0 {
+                let window = ChronoDuration::days(30);
+                let replacement: f64 = buys
+                    .iter()
+                    .filter(|(date, _)| {
+                        *date != lot.acquired_date
+                            && *date >= lot.sell_date - window
+                            && *date <= lot.sell_date + window
+                    })
+                    .map(|(_, quantity)| quantity)
+                    .sum();
+                -gross_gain * (replacement / lot.quantity).min(1.0)
+            } else {
+                0.0
+            };
+
+            let held_days = (lot.sell_date - lot.acquired_date).num_days();
+            let long_term = is_long_term(lot.acquired_date, lot.sell_date);
+            rows.push(TaxLotRow {
+                identifier: identifiers.get(&symbol).cloned().unwrap_or_default(),
+                security: symbol.clone(),
+                date_acquired: lot.acquired_date.format("%Y-%m-%d").to_string(),
+                date_sold: lot.sell_date.format("%Y-%m-%d").to_string(),
+                holding_period: format!("{} days", held_days),
+                shares: lot.quantity,
+                cost_per_share: total_cost / lot.quantity,
+                total_cost,
+                proceeds_per_share: total_proceeds / lot.quantity,
+                total_proceeds,
+                gross_gain,
+                wash_sale_adjustment,
+                net_gain: gross_gain + wash_sale_adjustment,
+                term: if long_term { "Long" } else { "Short" }.to_string(),
+            });
+        }
+    }
+    rows.sort_by(|a, b| {
+        a.date_sold
+            .cmp(&b.date_sold)
+            .then(a.security.cmp(&b.security))
+            .then(a.date_acquired.cmp(&b.date_acquired))
+    });
+    Ok(rows)
+}
+
+/// Writes every lot sold in `tax_year` to `output_path` as CSV with the columns an
+/// accountant or tax software expects, in the configured base currency. Unlike the
+/// realized gains report it carries per-share figures, the holding period, the
+/// term and a wash sale adjustment. Returns the path written.
+#[tauri::command]
+fn export_tax_lots_csv(
+    app_handle: tauri::AppHandle,
+    tax_year: i32,
+    output_path: String,
+) -> Result<String, AppError> {
+    let path = PathBuf::from(output_path.trim());
+    if path.as_os_str().is_empty() {
+        return Err(AppError::validation(
+            "output_path",
+            "An export path is required",
+        ));
+    }
+    let base = configured_base_currency(&app_handle);
+    let mut fx = FxConverter::new(&app_handle);
+    let rows = tax_lot_rows(&app_handle, &base, tax_year, &mut fx)?;
+    if rows.is_empty() {
+        return Err(AppError::not_found(
+            "tax_lots",
+            format!("No lots sold in {}", tax_year),
+        ));
+    }
+
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        create_dir_all(parent)?;
+    }
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for row in &rows {
+        writer
+            .serialize(row)
+            .map_err(|e| format!("Failed to write tax lot row: {}", e))?;
+    }
+    let content = writer
+        .into_inner()
+        .map_err(|e| format!("Failed to build tax lot export: {}", e))?;
+    write(&path, content)?;
+    write_worker_log(
+        &app_handle,
+        &format!(
+            "Exported {} tax lots for {} to {}",
+            rows.len(),
+            tax_year,
+            path.display()
+        ),
+    )?;
+    Ok(path.display().to_string())
+}
+
+#[derive(Serialize)]
+struct CurrencyPnlResult {
+    symbol: String,
+    currency: String,
+    security_gain_usd: f64,
+    currency_gain_usd: f64,
+    total_gain_usd: f64,
+    methodology: String,
+}
+
+const CURRENCY_PNL_METHODOLOGY: &str = "Per FIFO lot, in USD: cost = native cost x FX on the \
+purchase date; value = native proceeds (sold lots) or shares x latest close (open lots) x FX \
+on the sale date or today. Security gain = (native value - native cost) x end FX; currency \
+gain = native cost x (end FX - purchase FX). Dividends are excluded.";
+
+/// Splits a position's gain in USD into the part from the security's price in its
+/// own currency and the part from that currency moving against the dollar, summed
+/// over sold and still-open FIFO lots.
+#[tauri::command]
+fn calculate_currency_pnl(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+) -> Result<CurrencyPnlResult, AppError> {
+    let symbol = symbol.trim().to_string();
+    let transactions = load_symbol_transactions(&app_handle, &symbol)?;
+    let currency = transactions
+        .first()
+        .map(|t| t.currency.trim().to_uppercase())
+        .filter(|c| !c.is_empty())
+        .unwrap_or_else(|| "USD".to_string());
+    let today = Utc::now().date_naive();
+    let (open_lots, realized) = fifo_replay(&transactions, today);
+    let mut fx = FxConverter::new(&app_handle);
+
+    // (acquired, native cost, native value, valued on)
+    let mut lots: Vec<(NaiveDate, f64, f64, NaiveDate)> = realized
+        .iter()
+        .filter(|lot| lot.quantity > 1e-9)
+        .map(|lot| {
+            (
+                lot.acquired_date,
+                lot.cost_basis,
+                lot.proceeds,
+                lot.sell_date,
+            )
+        })
+        .collect();
+    if open_lots.iter().any(|lot| lot.quantity > 1e-9) {
+        let latest_close = load_price_history_for_symbol(&app_handle, &symbol)
+            .ok()
+            .and_then(|records| {
+                records
+                    .into_iter()
+                    .filter(|r| r.close > 0.0)
+                    .max_by_key(|r| r.date)
+                    .map(|r| r.close)
+            })
+            .ok_or_else(|| {
+                AppError::not_found("prices", format!("No close available for {}", symbol))
+            })?;
+        lots.extend(
+            open_lots
+                .iter()
+                .filter(|lot| lot.quantity > 1e-9)
+                .map(|lot| {
+                    (
+                        lot.date,
+                        lot.quantity * lot.cost_per_share,
+                        lot.quantity * latest_close,
+                        today,
+                    )
+                }),
+        );
+    }
+
+    let mut security_gain_usd = 0.0;
+    let mut currency_gain_usd = 0.0;
+    for (acquired, cost, value, valued_on) in lots {
+        let purchase_fx = fx.usd_rate(&currency, acquired)?;
+        let end_fx = fx.usd_rate(&currency, valued_on)?;
+        security_gain_usd += (value - cost) * end_fx;
+        currency_gain_usd += cost * (end_fx - purchase_fx);
+    }
+
+    Ok(CurrencyPnlResult {
+        symbol,
+        currency,
+        security_gain_usd,
+        currency_gain_usd,
+        total_gain_usd: security_gain_usd + currency_gain_usd,
+        methodology: CURRENCY_PNL_METHODOLOGY.to_string(),
+    })
+}
+
+/// One buy, sell or dividend in the shape shared by the interop exporters.
+struct InteropActivity {
+    date: NaiveDate,
+    kind: &'static str,
+    symbol: String,
+    yahoo_symbol: String,
+    quantity: f64,
+    unit_price: f64,
+    fee: f64,
+    currency: String,
+}
+
+#[derive(Serialize)]
+struct InteropExportResult {
+    path: String,
+    rows: usize,
+    skipped_transactions: usize,
+}
+
+/// Buys and sells from the transaction files plus dividends synthesised from the
+/// dividend files, across every traded symbol in date order.
+fn interop_activities(
+    app_handle: &tauri::AppHandle,
+) -> Result<(Vec<InteropActivity>, usize), String> {
+    let mut activities = Vec::new();
+    let mut skipped = 0;
+    for symbol in traded_symbols(app_handle)? {
+        let transactions = load_symbol_transactions(app_handle, &symbol)?;
+        let (yahoo_symbol, _) = resolve_yahoo_symbol(app_handle, &symbol);
+        let dividends = load_dividend_events(app_handle, &symbol)?;
+        let (symbol_activities, symbol_skipped) =
+            symbol_interop_activities(&symbol, &yahoo_symbol, &transactions, &dividends);
+        activities.extend(symbol_activities);
+        skipped += symbol_skipped;
+    }
+    activities.sort_by(|a, b| a.date.cmp(&b.date).then(a.symbol.cmp(&b.symbol)));
+    Ok((activities, skipped))
+}
+
+/// One symbol's buys and sells, plus a dividend per ex-date worth the per-share
+/// amount times the shares held the day before. Other transaction types have no
+/// counterpart and are counted as skipped; splits are already reflected in the
+/// share counts.
+fn symbol_interop_activities(
+    symbol: &str,
+    yahoo_symbol: &str,
+    transactions: &[ProcessedTransaction],
+    dividends: &[(NaiveDate, f64, String)],
+) -> (Vec<InteropActivity>, usize) {
+    let mut activities = Vec::new();
+    let mut skipped = 0;
+    let fallback_currency =
+        currency_for_exchange(get_exchange_and_symbol(symbol).0.as_deref()).to_string();
+
+    for txn in transactions {
+        let ty = txn.txn_type.as_str();
+        let kind = if is_buy_type(ty) {
+            "BUY"
+        } else if ty.starts_with("sell") || ty == "sale" {
+            "SELL"
+        } else {
+            if !ty.contains("split") {
+                skipped += 1;
+            }
+            continue;
+        };
+        activities.push(InteropActivity {
+            date: txn.date,
+            kind,
+            symbol: symbol.to_string(),
+            yahoo_symbol: yahoo_symbol.to_string(),
+            quantity: txn.quantity,
+            unit_price: txn.price,
+            fee: txn.fees,
+            currency: if txn.currency.trim().is_empty() {
+                fallback_currency.clone()
+            } else {
+                txn.currency.clone()
+            },
+        });
+    }
+
+    for (ex_date, amount, currency) in dividends {
+        let held = shares_held_on(transactions, *ex_date - ChronoDuration::days(1));
+        if held <= 1e-9 {
+            continue;
+        }
+        activities.push(InteropActivity {
+            date: *ex_date,
+            kind: "DIVIDEND",
+            symbol: symbol.to_string(),
+            yahoo_symbol: yahoo_symbol.to_string(),
+            quantity: held,
+            unit_price: *amount,
+            fee: 0.0,
+            currency: currency.clone(),
+        });
+    }
+    (activities, skipped)
+}
+
+/// Ghostfolio's activities import JSON.
+fn ghostfolio_export(activities: &[InteropActivity]) -> Result<Vec<u8>, String> {
+    let rows: Vec<serde_json::Value> = activities
+        .iter()
+        .map(|a| {
+            serde_json::json!({
+                "accountId": null,
+                "comment": null,
+                "currency": a.currency,
+                "dataSource": "YAHOO",
+                "date": format!("{}T00:00:00.000Z", a.date.format("%Y-%m-%d")),
+                "fee": a.fee,
+                "quantity": a.quantity,
+                "symbol": a.yahoo_symbol,
+                "type": a.kind,
+                "unitPrice": a.unit_price,
+            })
+        })
+        .collect();
+    serde_json::to_vec_pretty(&serde_json::json!({
+        "meta": { "date": Utc::now().to_rfc3339(), "version": "dev" },
+        "activities": rows,
+    }))
+    .map_err(|e| format!("Failed to serialize Ghostfolio export: {}", e))
+}
+
+/// Portfolio Performance's CSV import template for portfolio transactions. `Value`
+/// is the cash moved: gross plus fees on buys, minus fees on sells.
+fn portfolio_performance_export(activities: &[InteropActivity]) -> Result<Vec<u8>, String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record([
+            "Date",
+            "Type",
+            "Security Name",
+            "Ticker Symbol",
+            "Shares",
+            "Value",
+            "Fees",
+            "Transaction Currency",
+            "Note",
+        ])
+        .map_err(|e| format!("Failed to write header: {}", e))?;
+    for a in activities {
+        let gross = a.quantity * a.unit_price;
+        let (kind, value) = match a.kind {
+            "BUY" => ("Buy", gross + a.fee),
+            "SELL" => ("Sell", gross - a.fee),
+            _ => ("Dividend", gross),
+        };
+        writer
+            .write_record([
+                a.date.format("%Y-%m-%d").to_string(),
+                kind.to_string(),
+                a.symbol.clone(),
+                a.yahoo_symbol.clone(),
+                a.quantity.to_string(),
+                format!("{:.2}", value),
+                format!("{:.2}", a.fee),
+                a.currency.clone(),
+                String::new(),
+            ])
+            .map_err(|e| format!("Failed to write row: {}", e))?;
+    }
+    writer
+        .into_inner()
+        .map_err(|e| format!("Failed to build Portfolio Performance export: {}", e))
+}
+
+/// Exports transactions and synthesised dividends for Ghostfolio (`ghostfolio`,
+/// activities JSON) or Portfolio Performance (`portfolio_performance`, CSV).
+/// Symbols use their Yahoo form so both tools can resolve quotes.
+#[tauri::command]
+fn export_interop(
+    app_handle: tauri::AppHandle,
+    format: String,
+    path: String,
+    overwrite: Option<bool>,
+) -> Result<InteropExportResult, AppError> {
+    let path = PathBuf::from(path.trim());
+    if path.as_os_str().is_empty() {
+        return Err(AppError::validation("path", "An export path is required"));
+    }
+    if path.exists() && !overwrite.unwrap_or(false) {
+        return Err(AppError::validation(
+            "path",
+            format!("{} already exists", path.display()),
+        ));
+    }
+
+    let (activities, skipped_transactions) = interop_activities(&app_handle)?;
+    if activities.is_empty() {
+        return Err(AppError::not_found(
+            "transactions",
+            "No activities to export",
+        ));
+    }
+    let content = match format.trim() {
+        "ghostfolio" => ghostfolio_export(&activities)?,
+        "portfolio_performance" => portfolio_performance_export(&activities)?,
+        other => {
+            return Err(AppError::validation(
+                "format",
+                format!(
+                    "Unknown format '{}'; expected ghostfolio or portfolio_performance",
+                    other
+                ),
+            ))
+        }
+    };
+
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        create_dir_all(parent)?;
+    }
+    write(&path, content)?;
+    Ok(InteropExportResult {
+        path: path.display().to_string(),
+        rows: activities.len(),
+        skipped_transactions,
+    })
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Inline SVG line chart of `points`; empty when there are fewer than two.
+fn svg_line_chart(points: &[(NaiveDate, f64)]) -> String {
+    let (width, height, pad) = (800.0, 240.0, 24.0);
+    if points.len() < 2 {
+        return String::new();
+    }
+    let min = points.iter().map(|(_, v)| *v).fold(f64::INFINITY, f64::min);
+    let max = points
+        .iter()
+        .map(|(_, v)| *v)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let span = if max > min { max - min } else { 1.0 };
+    let last = (points.len() - 1) as f64;
+    let coords: Vec<String> = points
+        .iter()
+        .enumerate()
+        .map(|(i, (_, v))| {
+            let x = pad + (width - 2.0 * pad) * i as f64 / last;
+            let y = height - pad - (height - 2.0 * pad) * (v - min) / span;
+            format!("{:.1},{:.1}", x, y)
+        })
+        .collect();
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {w} {h}\" width=\"{w}\" height=\"{h}\">\
+<polyline fill=\"none\" stroke=\"#2563eb\" stroke-width=\"2\" points=\"{pts}\"/>\
+<text x=\"{pad}\" y=\"16\" font-size=\"12\">{max:.2}</text>\
+<text x=\"{pad}\" y=\"{bottom}\" font-size=\"12\">{min:.2}</text>\
+<text x=\"{pad}\" y=\"{h}\" font-size=\"11\">{start}</text>\
+<text x=\"{end_x}\" y=\"{h}\" font-size=\"11\" text-anchor=\"end\">{end}</text></svg>",
+        w = width,
+        h = height,
+        pad = pad,
+        pts = coords.join(" "),
+        max = max,
+        min = min,
+        bottom = height - pad - 4.0,
+        start = points[0].0,
+        end_x = width - pad,
+        end = points[points.len() - 1].0,
+    )
+}
+
+fn html_table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut html = String::from("<table><thead><tr>");
+    for header in headers {
+        html.push_str(&format!("<th>{}</th>", html_escape(header)));
+    }
+    html.push_str("</tr></thead><tbody>");
+    for row in rows {
+        html.push_str("<tr>");
+        for cell in row {
+            html.push_str(&format!("<td>{}</td>", html_escape(cell)));
+        }
+        html.push_str("</tr>");
+    }
+    html.push_str("</tbody></table>");
+    html
+}
+
+/// Weekly portfolio market value in `base` from the first transaction to today,
+/// using quoted closes and the FX rate on each sample date.
+fn portfolio_value_series(
+    app_handle: &tauri::AppHandle,
+    base: &str,
+    fx: &mut FxConverter,
+    prices: &HashMap<String, Vec<PriceRecordEntry>>,
+) -> Result<Vec<(NaiveDate, f64)>, AppError> {
+    let today = Utc::now().date_naive();
+    let mut positions = Vec::new();
+    for symbol in traded_symbols(app_handle)? {
+        let transactions = load_symbol_transactions(app_handle, &symbol)?;
+        let Some(records) = prices.get(&symbol) else {
+            continue;
+        };
+        let mut closes: Vec<(NaiveDate, f64)> = records.iter().map(|r| (r.date, r.close)).collect();
+        closes.sort_by_key(|(d, _)| *d);
+        positions.push((transactions, closes));
+    }
+    let Some(start) = positions
+        .iter()
+        .filter_map(|(txns, _)| txns.first().map(|t| t.date))
+        .min()
+    else {
+        return Ok(Vec::new());
+    };
+
+    let mut series = Vec::new();
+    let mut date = start;
+    loop {
+        let mut total = 0.0;
+        for (transactions, closes) in &positions {
+            let shares = shares_held_on(transactions, date);
+            if shares <= 1e-9 {
+                continue;
+            }
+            if let Some(close) = close_on_or_before(closes, date) {
+                let currency = &transactions[0].currency;
+                total += fx.convert(shares * close, currency, base, date)?;
+            }
+        }
+        series.push((date, total));
+        if date >= today {
+            break;
+        }
+        date = (date + ChronoDuration::days(7)).min(today);
+    }
+    Ok(series)
+}
+
+/// Everything `generate_report_html` puts in the report, already in the base
+/// currency and formatted for display.
+struct ReportHtml {
+    generated_at: String,
+    base: String,
+    total_value: f64,
+    cash_value: f64,
+    holding_rows: Vec<Vec<String>>,
+    /// Allocation tables by the column they group on.
+    allocations: [(&'static str, Vec<Vec<String>>); 4],
+    series: Vec<(NaiveDate, f64)>,
+    dividend_rows: Vec<Vec<String>>,
+    gain_rows: Vec<Vec<String>>,
+    caveats: Vec<String>,
+}
+
+fn render_report_html(report: &ReportHtml) -> String {
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html lang=\"en\"><head><meta charset=\"utf-8\">\
+<title>Portfolio report</title><style>\
+body{font-family:system-ui,sans-serif;margin:2rem;color:#111}\
+table{border-collapse:collapse;margin-bottom:1rem}\
+th,td{border:1px solid #ddd;padding:4px 8px;text-align:right}\
+th:first-child,td:first-child{text-align:left}\
+.caveat{color:#b45309}</style></head><body>\n",
+    );
+    html.push_str(&format!(
+        "<h1>Portfolio report</h1><p>Generated {} &middot; values in {} &middot; total value {:.2} (cash {:.2})</p>\n",
+        html_escape(&report.generated_at),
+        html_escape(&report.base),
+        report.total_value,
+        report.cash_value
+    ));
+    html.push_str("<section id=\"holdings\"><h2>Holdings</h2>");
+    html.push_str(&html_table(
+        &[
+            "Symbol",
+            "Currency",
+            "Shares",
+            "Avg cost",
+            "Cost basis",
+            "Market value",
+            "Unrealized",
+        ],
+        &report.holding_rows,
+    ));
+    html.push_str("</section>\n<section id=\"allocation\"><h2>Allocation</h2>");
+    for (group, rows) in &report.allocations {
+        html.push_str(&format!("<h3>By {}</h3>", group.to_lowercase()));
+        html.push_str(&html_table(&[group, "Value", "Weight"], rows));
+    }
+    html.push_str("</section>\n<section id=\"nav\"><h2>Portfolio value</h2>");
+    html.push_str(&svg_line_chart(&report.series));
+    html.push_str("</section>\n<section id=\"dividends\"><h2>Dividend income by year</h2>");
+    html.push_str(&html_table(&["Year", "Income"], &report.dividend_rows));
+    html.push_str("</section>\n<section id=\"realized-gains\"><h2>Top realized gains</h2>");
+    html.push_str(&html_table(
+        &["Symbol", "Sold", "Acquired", "Quantity", "Gain"],
+        &report.gain_rows,
+    ));
+    html.push_str("</section>\n<section id=\"data-caveats\"><h2>Data caveats</h2>");
+    if report.caveats.is_empty() {
+        html.push_str("<p>All held symbols have current price data.</p>");
+    } else {
+        html.push_str("<ul>");
+        for caveat in &report.caveats {
+            html.push_str(&format!(
+                "<li class=\"caveat\">{}</li>",
+                html_escape(caveat)
+            ));
+        }
+        html.push_str("</ul>");
+    }
+    html.push_str("</section>\n</body></html>\n");
+    html
+}
+
+/// Renders a self-contained HTML snapshot (holdings, allocation, value chart,
+/// yearly dividends, top realized gains) in `base_currency`, defaulting to the
+/// configured one. No external assets
+/// are referenced, so the file can be shared as is. Stale or missing price data is
+/// listed at the end.
+#[tauri::command]
+fn generate_report_html(
+    app_handle: tauri::AppHandle,
+    path: String,
+    base_currency: Option<String>,
+    overwrite: Option<bool>,
+) -> Result<ExportReportResult, AppError> {
+    let path = PathBuf::from(path.trim());
+    if path.as_os_str().is_empty() {
+        return Err(AppError::validation("path", "A report path is required"));
+    }
+    if path.exists() && !overwrite.unwrap_or(false) {
+        return Err(AppError::validation(
+            "path",
+            format!("{} already exists", path.display()),
+        ));
+    }
+    let base = match base_currency {
+        Some(code) if !code.trim().is_empty() => validate_currency_code("base_currency", &code)?,
+        _ => configured_base_currency(&app_handle),
+    };
+
+    let today = Utc::now().date_naive();
+    let mut fx = FxConverter::new(&app_handle);
+    let positions = position_report_rows(&app_handle, &base, &mut fx)?;
+    let realized = realized_gain_rows(&app_handle, &base, None, &mut fx)?;
+
+    let mut prices: HashMap<String, Vec<PriceRecordEntry>> = HashMap::new();
+    let mut missing_prices = Vec::new();
+    for symbol in traded_symbols(&app_handle)? {
+        match load_price_history_for_symbol(&app_handle, &symbol) {
+            Ok(records) if !records.is_empty() => {
+                prices.insert(symbol, records);
+            }
+            _ => missing_prices.push(symbol),
+        }
+    }
+    let series = portfolio_value_series(&app_handle, &base, &mut fx, &prices)?;
+
+    let holding_rows: Vec<Vec<String>> = positions
+        .iter()
+        .map(|p| {
+            let optional = |v: Option<f64>| v.map(|v| format!("{:.2}", v)).unwrap_or_default();
+            vec![
+                p.symbol.clone(),
+                p.currency.clone(),
+                format!("{}", p.shares),
+                format!("{:.2}", p.average_cost),
+                format!("{:.2}", p.cost_basis),
+                optional(p.market_value),
+                optional(p.unrealized_pnl),
+            ]
+        })
+        .collect();
+
+    let cash_value = cash_total(&app_handle, &base, today, &mut fx)?;
+    let total_value: f64 =
+        positions.iter().filter_map(|p| p.market_value).sum::<f64>() + cash_value;
+    let sectors = load_security_sectors(&app_handle)?;
+    let types = load_security_types(&app_handle)?;
+    let tags = load_security_tags(&app_handle)?;
+    let mut by_currency: std::collections::BTreeMap<String, f64> =
+        std::collections::BTreeMap::new();
+    let mut by_sector: std::collections::BTreeMap<String, f64> = std::collections::BTreeMap::new();
+    let mut by_type: std::collections::BTreeMap<String, f64> = std::collections::BTreeMap::new();
+    let mut by_tag: std::collections::BTreeMap<String, f64> = std::collections::BTreeMap::new();
+    for p in &positions {
+        let value = p.market_value.unwrap_or(0.0);
+        *by_currency.entry(p.currency.clone()).or_default() += value;
+        let kind = types
+            .get(&canonical_symbol(&p.symbol))
+            .copied()
+            .unwrap_or(SecurityType::Stock);
+        *by_type.entry(kind.label().to_string()).or_default() += value;
+        // A position counts in full under each of its tags, so tag weights can
+        // add up to more than 100%.
+        match tags.get(&canonical_symbol(&p.symbol)) {
+            Some(position_tags) => {
+                for tag in position_tags {
+                    *by_tag.entry(tag.clone()).or_default() += value;
+                }
+            }
+            None => *by_tag.entry("Untagged".to_string()).or_default() += value,
+        }
+        let sector = sectors
+            .get(&p.symbol)
+            .cloned()
+            .unwrap_or_else(|| "Unclassified".to_string());
+        *by_sector.entry(sector).or_default() += value;
+    }
+    // Cash is its own asset class, and counts towards its currency.
+    for cash in load_cash_positions(&app_handle)? {
+        let value = fx.convert(cash.amount, &cash.currency, &base, today)?;
+        *by_currency.entry(cash.currency).or_default() += value;
+        *by_type.entry("Cash".to_string()).or_default() += value;
+        *by_sector.entry("Cash".to_string()).or_default() += value;
+    }
+    let allocation_rows = |groups: std::collections::BTreeMap<String, f64>| -> Vec<Vec<String>> {
+        groups
+            .into_iter()
+            .map(|(name, value)| {
+                let weight = if total_value > 0.0 {
+                    value / total_value * 100.0
+                } else {
+                    0.0
+                };
+                vec![name, format!("{:.2}", value), format!("{:.1}%", weight)]
+            })
+            .collect()
+    };
+
+    let mut dividends_by_year: std::collections::BTreeMap<i32, f64> =
+        std::collections::BTreeMap::new();
+    for entry in get_dividend_income(app_handle.clone(), None)? {
+        let Ok(ex_date) = NaiveDate::parse_from_str(&entry.ex_date, "%Y-%m-%d") else {
+            continue;
+        };
+        *dividends_by_year.entry(ex_date.year()).or_default() +=
+            fx.convert(entry.total, &entry.currency, &base, ex_date)?;
+    }
+    let dividend_rows: Vec<Vec<String>> = dividends_by_year
+        .into_iter()
+        .rev()
+        .map(|(year, total)| vec![year.to_string(), format!("{:.2}", total)])
+        .collect();
+
+    let mut top_gains: Vec<&RealizedGainReportRow> = realized.iter().collect();
+    top_gains.sort_by(|a, b| b.gain.total_cmp(&a.gain));
+    let gain_rows: Vec<Vec<String>> = top_gains
+        .into_iter()
+        .take(10)
+        .map(|g| {
+            vec![
+                g.symbol.clone(),
+                g.sell_date.clone(),
+                g.acquired_date.clone(),
+                format!("{}", g.quantity),
+                format!("{:.2}", g.gain),
+            ]
+        })
+        .collect();
+
+    let held: Vec<String> = positions.iter().map(|p| p.symbol.clone()).collect();
+    let mut stale: Vec<(String, NaiveDate, i64)> = find_stale_symbols(&prices, held.iter(), today)
+        .into_iter()
+        .map(|(symbol, (latest, missed))| (symbol, latest, missed))
+        .collect();
+    stale.sort();
+    let mut caveats: Vec<String> = stale
+        .into_iter()
+        .map(|(symbol, latest, missed)| {
+            format!(
+                "{}: latest price {} ({} trading days old)",
+                symbol, latest, missed
+            )
+        })
+        .collect();
+    caveats.extend(
+        missing_prices
+            .iter()
+            .map(|symbol| format!("{}: no local price history", symbol)),
+    );
+
+    let html = render_report_html(&ReportHtml {
+        generated_at: Utc::now().to_rfc3339(),
+        base,
+        total_value,
+        cash_value,
+        holding_rows,
+        allocations: [
+            ("Currency", allocation_rows(by_currency)),
+            ("Sector", allocation_rows(by_sector)),
+            ("Type", allocation_rows(by_type)),
+            ("Tag", allocation_rows(by_tag)),
+        ],
+        series,
+        dividend_rows,
+        gain_rows,
+        caveats,
+    });
+
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        create_dir_all(parent)?;
+    }
+    write(&path, html)?;
+    Ok(ExportReportResult {
+        path: path.display().to_string(),
+        rows: positions.len(),
+    })
+}
+
+fn close_on_or_before(series: &[(NaiveDate, f64)], date: NaiveDate) -> Option<f64> {
+    series
+        .iter()
+        .rev()
+        .find(|(d, close)| *d <= date && *close > 0.0)
+        .map(|(_, close)| *close)
+}
+
+fn daily_returns_since(series: &[(NaiveDate, f64)], start: NaiveDate) -> Vec<(NaiveDate, f64)> {
+    series
+        .windows(2)
+        .filter(|pair| pair[1].0 > start && pair[0].1 > 0.0)
+        .map(|pair| (pair[1].0, pair[1].1 / pair[0].1 - 1.0))
+        .collect()
+}
+
+/// Largest peak-to-trough decline, as a negative fraction.
+fn max_drawdown(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let mut peak = f64::NEG_INFINITY;
+    let mut worst: Option<f64> = None;
+    for value in values.filter(|v| *v > 0.0) {
+        peak = peak.max(value);
+        let drawdown = value / peak - 1.0;
+        worst = Some(worst.map_or(drawdown, |w: f64| w.min(drawdown)));
+    }
+    worst
+}
+
+#[derive(Serialize)]
+struct DrawdownPoint {
+    date: String,
+    drawdown_pct: f64,
+}
+
+/// Drawdown on every date of `symbol`'s split-adjusted closes or, without a symbol,
+/// of the weekly portfolio value in the base currency: `value / running peak - 1`
+/// as a percentage, where the peak is the highest value from the start of the
+/// window up to and including that date. Portfolio values include contributions,
+/// so new money lifts the peak just as gains do.
+#[tauri::command]
+fn get_drawdown_series(
+    app_handle: tauri::AppHandle,
+    symbol: Option<String>,
+    start_date: Option<String>,
+    end_date: Option<String>,
+) -> Result<Vec<DrawdownPoint>, AppError> {
+    let optional_date = |field: &str, value: Option<String>| {
+        value
+            .filter(|v| !v.trim().is_empty())
+            .map(|v| parse_manual_date(field, &v))
+            .transpose()
+    };
+    let start = optional_date("start_date", start_date)?;
+    let end = optional_date("end_date", end_date)?;
+    if let (Some(start), Some(end)) = (start, end) {
+        if start > end {
+            return Err(AppError::validation(
+                "start_date",
+                "Start date must not be after the end date",
+            ));
+        }
+    }
+
+    let series: Vec<(NaiveDate, f64)> = match symbol
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+    {
+        Some(symbol) => load_ascending_price_series(&app_handle, &symbol)?
+            .into_iter()
+            .map(|(date, r)| (date, r.close))
+            .collect(),
+        None => {
+            let base = configured_base_currency(&app_handle);
+            let mut fx = FxConverter::new(&app_handle);
+            let mut prices: HashMap<String, Vec<PriceRecordEntry>> = HashMap::new();
+            for symbol in traded_symbols(&app_handle)? {
+                if let Ok(records) = load_price_history_for_symbol(&app_handle, &symbol) {
+                    prices.insert(symbol, records);
+                }
+            }
+            portfolio_value_series(&app_handle, &base, &mut fx, &prices)?
+        }
+    };
+
+    let mut peak = f64::NEG_INFINITY;
+    Ok(series
+        .into_iter()
+        .filter(|(date, value)| {
+            *value > 0.0
+                && start.is_none_or(|start| *date >= start)
+                && end.is_none_or(|end| *date <= end)
+        })
+        .map(|(date, value)| {
+            peak = peak.max(value);
+            DrawdownPoint {
+                date: date.format("%Y-%m-%d").to_string(),
+                drawdown_pct: (value / peak - 1.0) * 100.0,
+            }
+        })
+        .collect())
+}
+
+fn beta_against(
+    returns: &[(NaiveDate, f64)],
+    benchmark_returns: &[(NaiveDate, f64)],
+) -> Option<f64> {
+    let benchmark: HashMap<NaiveDate, f64> = benchmark_returns.iter().copied().collect();
+    let pairs: Vec<(f64, f64)> = returns
+        .iter()
+        .filter_map(|(date, r)| benchmark.get(date).map(|b| (*r, *b)))
+        .collect();
+    if pairs.len() < 20 {
+        return None;
+    }
+    let n = pairs.len() as f64;
+    let mean_r = pairs.iter().map(|(r, _)| r).sum::<f64>() / n;
+    let mean_b = pairs.iter().map(|(_, b)| b).sum::<f64>() / n;
+    let covariance = pairs
+        .iter()
+        .map(|(r, b)| (r - mean_r) * (b - mean_b))
+        .sum::<f64>()
+        / (n - 1.0);
+    let variance = pairs.iter().map(|(_, b)| (b - mean_b).powi(2)).sum::<f64>() / (n - 1.0);
+    (variance > 0.0).then(|| covariance / variance)
+}
+
+/// Annualised Sharpe ratio of daily returns with a zero risk-free rate.
+fn sharpe_ratio(returns: &[(NaiveDate, f64)]) -> Option<f64> {
+    if returns.len() < 20 {
+        return None;
+    }
+    let n = returns.len() as f64;
+    let mean = returns.iter().map(|(_, r)| r).sum::<f64>() / n;
+    let variance = returns.iter().map(|(_, r)| (r - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    let std_dev = variance.sqrt();
+    (std_dev > 0.0).then(|| mean / std_dev * 252f64.sqrt())
+}
+
+/// Projects the next ex-date from the median gap between recent payments. Returns
+/// `None` once the projection is more than one gap overdue (the payer likely stopped).
+fn estimate_next_dividend(
+    events: &[(NaiveDate, f64, String)],
+    today: NaiveDate,
+) -> Option<UpcomingDividend> {
+    let (last_date, last_amount, _) = events.last()?;
+    let mut gaps: Vec<i64> = events
+        .windows(2)
+        .rev()
+        .take(4)
+        .map(|pair| (pair[1].0 - pair[0].0).num_days())
+        .filter(|gap| *gap > 0)
+        .collect();
+    if gaps.is_empty() {
+        return None;
+    }
+    gaps.sort_unstable();
+    let gap = ChronoDuration::days(gaps[gaps.len() / 2]);
+    let mut next = *last_date + gap;
+    if next + gap < today {
+        return None;
+    }
+    while next < today {
+        next += gap;
+    }
+    Some(UpcomingDividend {
+        ex_date: next.format("%Y-%m-%d").to_string(),
+        amount: *last_amount,
+        estimated: true,
+    })
+}
+
+/// USD per unit of `currency`, ascending by date, from the stored USD_XXX (or
+/// XXX_USD) FX files. USD itself yields an empty series; use `usd_rate_on`.
+fn usd_rate_series(
+    app_handle: &tauri::AppHandle,
+    currency: &str,
+) -> Result<Vec<(NaiveDate, f64)>, String> {
+    if currency.eq_ignore_ascii_case("USD") {
+        return Ok(Vec::new());
+    }
+    let to_series = |records: Vec<FxRateRecordResponse>, invert: bool| {
+        let mut series: Vec<(NaiveDate, f64)> = records
+            .into_iter()
+            .filter(|r| r.rate > 0.0)
+            .filter_map(|r| {
+                let date = NaiveDate::parse_from_str(r.date.trim(), "%Y-%m-%d").ok()?;
+                Some((date, if invert { 1.0 / r.rate } else { r.rate }))
+            })
+            .collect();
+        series.sort_by_key(|(date, _)| *date);
+        series
+    };
+
+    let quoted = to_series(
+        load_fx_pair_with_polars(app_handle, "USD", currency, true)?,
+        true,
+    );
+    if !quoted.is_empty() {
+        return Ok(quoted);
+    }
+    let direct = to_series(
+        load_fx_pair_with_polars(app_handle, currency, "USD", true)?,
+        false,
+    );
+    if direct.is_empty() {
+        return Err(format!("No FX rates stored for USD/{}", currency));
+    }
+    Ok(direct)
+}
+
+/// Rate on or before `date`, falling back to the earliest known rate.
+fn usd_rate_on(series: &[(NaiveDate, f64)], date: NaiveDate) -> f64 {
+    if series.is_empty() {
+        return 1.0;
+    }
+    close_on_or_before(series, date).unwrap_or(series[0].1)
+}
+
+/// Money moved into (+) or out of (-) a position by a transaction, in its currency.
+/// Spin-off and conversion rows carry moved cost as a signed `quantity * price`.
+fn transaction_cash_flow(txn: &ProcessedTransaction) -> f64 {
+    let ty = txn.txn_type.as_str();
+    if is_buy_type(ty) {
+        txn.quantity * txn.price + txn.fees
+    } else if ty.starts_with("sell") || ty == "sale" {
+        -(txn.quantity * txn.price) + txn.fees
+    } else if is_conversion_type(ty) || ty == "spinoff" {
+        txn.quantity * txn.price
+    } else {
+        0.0
+    }
+}
+
+#[derive(Serialize)]
+struct PeriodPnl {
+    period_label: String,
+    start_value_base: f64,
+    end_value_base: f64,
+    cash_flows_base: f64,
+    pnl_base: f64,
+    pnl_pct: f64,
+    base_currency: String,
+}
+
+/// Consecutive `(label, first_day, last_day)` periods covering `from..=to`.
+fn period_ranges(
+    period: &str,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<(String, NaiveDate, NaiveDate)>, String> {
+    let months_per_period = match period {
+        "year" => 12,
+        "quarter" => 3,
+        "month" => 1,
+        other => {
+            return Err(format!(
+                "Unknown period '{}'; expected year, quarter or month",
+                other
+            ))
+        }
+    };
+    let first_month = (from.month0() / months_per_period) * months_per_period + 1;
+    let mut start = NaiveDate::from_ymd_opt(from.year(), first_month, 1)
+        .ok_or_else(|| format!("Invalid start date {}", from))?;
+
+    let mut ranges = Vec::new();
+    while start <= to {
+        let next = start
+            .checked_add_months(chrono::Months::new(months_per_period))
+            .ok_or_else(|| format!("Date overflow after {}", start))?;
+        let label = match period {
+            "year" => start.format("%Y").to_string(),
+            "quarter" => format!("{}-Q{}", start.year(), start.month0() / 3 + 1),
+            _ => start.format("%Y-%m").to_string(),
+        };
+        ranges.push((label, start, (next - ChronoDuration::days(1)).min(to)));
+        start = next;
+    }
+    Ok(ranges)
+}
+
+/// P&L per calendar period in the base currency. Returns use Modified Dietz: each cash flow is
+/// weighted by the fraction of the period it was invested, so large mid-period buys
+/// or sells do not distort the percentage. Dividends paid count as outflows.
+#[tauri::command]
+fn get_pnl_by_period(
+    app_handle: tauri::AppHandle,
+    period: String,
+    symbol: Option<String>,
+) -> Result<Vec<PeriodPnl>, AppError> {
+    let today = Utc::now().date_naive();
+    let symbols: Vec<String> = match symbol.map(|s| s.trim().to_string()) {
+        Some(symbol) if !symbol.is_empty() => vec![symbol],
+        _ => {
+            let mut symbols: Vec<String> = load_all_transactions(&app_handle)?
+                .iter()
+                .map(|txn| txn.stock.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+            symbols.sort();
+            symbols.dedup();
+            symbols
+        }
+    };
+
+    struct SymbolSeries {
+        transactions: Vec<ProcessedTransaction>,
+        closes: Vec<(NaiveDate, f64)>,
+        usd_rates: Vec<(NaiveDate, f64)>,
+        dividends: Vec<(NaiveDate, f64)>,
+    }
+    let mut series = Vec::new();
+    for symbol in &symbols {
+        let transactions = load_symbol_transactions(&app_handle, symbol)?;
+        let currency = transactions
+            .first()
+            .map(|t| t.currency.clone())
+            .unwrap_or_else(|| "USD".to_string());
+        series.push(SymbolSeries {
+            closes: load_price_history_for_symbol(&app_handle, symbol)
+                .map(|records| records.into_iter().map(|r| (r.date, r.close)).collect())
+                .unwrap_or_default(),
+            usd_rates: usd_rate_series(&app_handle, &currency)?,
+            dividends: load_dividend_events(&app_handle, symbol)?
+                .into_iter()
+                .map(|(date, amount, _)| (date, amount))
+                .collect(),
+            transactions,
+        });
+    }
+
+    let Some(first_date) = series
+        .iter()
+        .filter_map(|s| s.transactions.first().map(|t| t.date))
+        .min()
+    else {
+        return Ok(Vec::new());
+    };
+    let base = configured_base_currency(&app_handle);
+    let base_rates = usd_rate_series(&app_handle, &base)?;
+    let to_base = |usd: f64, date: NaiveDate| usd / usd_rate_on(&base_rates, date);
+
+    let mut results = Vec::new();
+    for (label, start, end) in period_ranges(period.trim(), first_date, today)? {
+        let opening = start - ChronoDuration::days(1);
+        let period_days = (end - opening).num_days().max(1) as f64;
+        let (mut start_value, mut end_value, mut flows, mut weighted_flows) = (0.0, 0.0, 0.0, 0.0);
+
+        for s in &series {
+            let value_on = |date: NaiveDate| {
+                shares_held_on(&s.transactions, date)
+                    * close_on_or_before(&s.closes, date).unwrap_or(0.0)
+                    * usd_rate_on(&s.usd_rates, date)
+            };
+            start_value += to_base(value_on(opening), opening);
+            end_value += to_base(value_on(end), end);
+
+            let txn_flows = s
+                .transactions
+                .iter()
+                .filter(|t| t.date > opening && t.date <= end)
+                .map(|t| (t.date, transaction_cash_flow(t)));
+            let dividend_flows = s
+                .dividends
+                .iter()
+                .filter(|(date, _)| *date > opening && *date <= end)
+                .map(|(date, amount)| {
+                    let held = shares_held_on(&s.transactions, *date - ChronoDuration::days(1));
+                    (*date, -amount * held)
+                });
+            for (date, flow) in txn_flows.chain(dividend_flows) {
+                let flow_base = to_base(flow * usd_rate_on(&s.usd_rates, date), date);
+                let weight = (end - date).num_days() as f64 / period_days;
+                flows += flow_base;
+                weighted_flows += weight * flow_base;
+            }
+        }
+
+        let pnl = end_value - start_value - flows;
+        let invested = start_value + weighted_flows;
+        results.push(PeriodPnl {
+            period_label: label,
+            start_value_base: start_value,
+            end_value_base: end_value,
+            cash_flows_base: flows,
+            pnl_base: pnl,
+            pnl_pct: if invested.abs() > f64::EPSILON {
+                pnl / invested * 100.0
+            } else {
+                0.0
+            },
+            base_currency: base.clone(),
+        });
+    }
+
+    Ok(results)
+}
+
+#[derive(Serialize, Default)]
+struct TransactionSummary {
+    total_buy_transactions: usize,
+    total_sell_transactions: usize,
+    total_split_events: usize,
+    total_invested_base: f64,
+    total_proceeds_base: f64,
+    net_invested_base: f64,
+    unique_symbols_bought: usize,
+    average_transaction_size_base: f64,
+    busiest_month: String,
+    most_traded_symbol: String,
+    base_currency: String,
+}
+
+/// Trading activity totals, optionally limited to one calendar year. Amounts are
+/// converted to the base currency at the rate on each transaction date.
+#[tauri::command]
+fn get_transaction_summary(
+    app_handle: tauri::AppHandle,
+    year: Option<i32>,
+) -> Result<TransactionSummary, AppError> {
+    let mut symbols: Vec<String> = load_all_transactions(&app_handle)?
+        .iter()
+        .map(|txn| txn.stock.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    symbols.sort();
+    symbols.dedup();
+
+    let mut summary = TransactionSummary {
+        base_currency: configured_base_currency(&app_handle),
+        ..Default::default()
+    };
+    let base_rates = usd_rate_series(&app_handle, &summary.base_currency)?;
+    let mut usd_rates: HashMap<String, Vec<(NaiveDate, f64)>> = HashMap::new();
+    let mut month_counts: std::collections::BTreeMap<String, usize> = Default::default();
+    let mut symbol_counts: std::collections::BTreeMap<String, usize> = Default::default();
+
+    for symbol in &symbols {
+        let transactions = load_symbol_transactions(&app_handle, symbol)?;
+        let mut bought = false;
+        for txn in transactions
+            .iter()
+            .filter(|t| year.is_none_or(|y| t.date.year() == y))
+        {
+            let ty = txn.txn_type.as_str();
+            let is_sell = ty.starts_with("sell") || ty == "sale";
+            if ty.contains("split") {
+                summary.total_split_events += 1;
+            }
+            if !is_buy_type(ty) && !is_sell {
+                continue;
+            }
+
+            let currency = if txn.currency.trim().is_empty() {
+                "USD".to_string()
+            } else {
+                txn.currency.trim().to_uppercase()
+            };
+            if !usd_rates.contains_key(&currency) {
+                let series = usd_rate_series(&app_handle, &currency)?;
+                usd_rates.insert(currency.clone(), series);
+            }
+            let rate =
+                usd_rate_on(&usd_rates[&currency], txn.date) / usd_rate_on(&base_rates, txn.date);
+
+            if is_sell {
+                summary.total_sell_transactions += 1;
+                summary.total_proceeds_base += (txn.quantity * txn.price - txn.fees) * rate;
+            } else {
+                summary.total_buy_transactions += 1;
+                summary.total_invested_base += (txn.quantity * txn.price + txn.fees) * rate;
+                bought = true;
+            }
+            *month_counts
+                .entry(txn.date.format("%Y-%m").to_string())
+                .or_default() += 1;
+            *symbol_counts.entry(symbol.clone()).or_default() += 1;
+        }
+        if bought {
+            summary.unique_symbols_bought += 1;
+        }
+    }
+
+    summary.net_invested_base = summary.total_invested_base - summary.total_proceeds_base;
+    let trades = summary.total_buy_transactions + summary.total_sell_transactions;
+    if trades > 0 {
+        summary.average_transaction_size_base =
+            (summary.total_invested_base + summary.total_proceeds_base) / trades as f64;
+    }
+    // Ties go to the earliest month and alphabetically first symbol.
+    let busiest = |counts: std::collections::BTreeMap<String, usize>| {
+        counts
+            .into_iter()
+            .fold((String::new(), 0), |best, (key, count)| {
+                if count > best.1 {
+                    (key, count)
+                } else {
+                    best
+                }
+            })
+            .0
+    };
+    summary.busiest_month = busiest(month_counts);
+    summary.most_traded_symbol = busiest(symbol_counts);
+
+    Ok(summary)
+}
+
+#[derive(Serialize)]
+struct AverageDownSimulation {
+    current_avg_cost: f64,
+    new_avg_cost: f64,
+    current_shares: f64,
+    new_shares: f64,
+    breakeven_price: f64,
+    additional_investment_base: f64,
+    base_currency: String,
+}
+
+#[derive(Serialize)]
+struct AverageDownStep {
+    price: f64,
+    cumulative_avg_cost: f64,
+    cumulative_shares: f64,
+}
+
+/// Current `(shares, total cost, currency)` for a held position, replayed with the
+/// average cost method.
+fn current_position_cost(
+    app_handle: &tauri::AppHandle,
+    symbol: &str,
+) -> Result<(f64, f64, String), AppError> {
+    let transactions = load_symbol_transactions(app_handle, symbol)?;
+    let currency = transactions
+        .first()
+        .map(|t| t.currency.clone())
+        .unwrap_or_else(|| "USD".to_string());
+    let (shares, cost, _) = replay_average_cost(&transactions, Utc::now().date_naive());
+    Ok((shares, cost, currency))
+}
+
+fn validate_purchase(
+    shares_field: &str,
+    shares: f64,
+    price_field: &str,
+    price: f64,
+) -> Result<(), AppError> {
+    if !shares.is_finite() || shares <= 0.0 {
+        return Err(AppError::validation(
+            shares_field,
+            format!("Share count must be positive, got {}", shares),
+        ));
+    }
+    if !price.is_finite() || price <= 0.0 {
+        return Err(AppError::validation(
+            price_field,
+            format!("Price must be positive, got {}", price),
+        ));
+    }
+    Ok(())
+}
+
+/// Average cost after buying `additional_shares` more at `at_price` (in the
+/// position's currency). The breakeven price is the new average cost.
+#[tauri::command]
+fn simulate_average_down(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+    additional_shares: f64,
+    at_price: f64,
+) -> Result<AverageDownSimulation, AppError> {
+    validate_purchase("additional_shares", additional_shares, "at_price", at_price)?;
+    let (shares, cost, currency) = current_position_cost(&app_handle, symbol.trim())?;
+    let today = Utc::now().date_naive();
+    let base = configured_base_currency(&app_handle);
+    let base_rate = usd_rate_on(&usd_rate_series(&app_handle, &currency)?, today)
+        / usd_rate_on(&usd_rate_series(&app_handle, &base)?, today);
+
+    let new_shares = shares + additional_shares;
+    let new_avg_cost = (cost + additional_shares * at_price) / new_shares;
+    Ok(AverageDownSimulation {
+        current_avg_cost: if shares > 0.0 { cost / shares } else { 0.0 },
+        new_avg_cost,
+        current_shares: shares,
+        new_shares,
+        breakeven_price: new_avg_cost,
+        additional_investment_base: additional_shares * at_price * base_rate,
+        base_currency: base,
+    })
+}
+
+/// Cumulative average cost after buying `shares_per_target` at each price in turn.
+#[tauri::command]
+fn simulate_average_down_ladder(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+    price_targets: Vec<f64>,
+    shares_per_target: f64,
+) -> Result<Vec<AverageDownStep>, AppError> {
+    for price in &price_targets {
+        validate_purchase(
+            "shares_per_target",
+            shares_per_target,
+            "price_targets",
+            *price,
+        )?;
+    }
+    let (mut shares, mut cost, _) = current_position_cost(&app_handle, symbol.trim())?;
+
+    Ok(price_targets
+        .into_iter()
+        .map(|price| {
+            shares += shares_per_target;
+            cost += shares_per_target * price;
+            AverageDownStep {
+                price,
+                cumulative_avg_cost: cost / shares,
+                cumulative_shares: shares,
+            }
+        })
+        .collect())
+}
+
+#[derive(Serialize)]
+struct LotTiming {
+    open_date: String,
+    close_date: String,
+    quantity: f64,
+    actual_return: f64,
+    max_possible_return_1y: Option<f64>,
+    timing_efficiency: Option<f64>,
+}
+
+#[derive(Serialize)]
+struct TimingAnalysis {
+    symbol: String,
+    lots: Vec<LotTiming>,
+    avg_timing_efficiency: Option<f64>,
+    buy_and_hold_return: Option<f64>,
+}
+
+/// Post-mortem of each FIFO-closed lot: the return achieved against the best
+/// split-adjusted close within a year of opening (measured from the open-date
+/// close). Efficiency is undefined when that best return is not positive.
+/// `buy_and_hold_return` spans the first to the latest local close.
+#[tauri::command]
+fn get_timing_analysis(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+) -> Result<TimingAnalysis, AppError> {
+    let symbol = symbol.trim().to_string();
+    let today = Utc::now().date_naive();
+    let transactions = load_symbol_transactions(&app_handle, &symbol)?;
+    let closes: Vec<(NaiveDate, f64)> = load_ascending_price_series(&app_handle, &symbol)
+        .map(|series| series.into_iter().map(|(d, r)| (d, r.close)).collect())
+        .unwrap_or_default();
+
+    let (_, realized) = fifo_replay(&transactions, today);
+    let lots: Vec<LotTiming> = realized
+        .into_iter()
+        .filter(|lot| lot.cost_basis > 0.0)
+        .map(|lot| {
+            let actual_return = lot.proceeds / lot.cost_basis - 1.0;
+            let window_end = lot.acquired_date + ChronoDuration::days(365);
+            let max_possible_return_1y = close_on_or_before(&closes, lot.acquired_date)
+                .zip(
+                    closes
+                        .iter()
+                        .filter(|(d, c)| *d >= lot.acquired_date && *d <= window_end && *c > 0.0)
+                        .map(|(_, c)| *c)
+                        .reduce(f64::max),
+                )
+                .map(|(entry, best)| best / entry - 1.0);
+            let timing_efficiency = max_possible_return_1y
+                .filter(|best| *best > 0.0)
+                .map(|best| actual_return / best);
+            LotTiming {
+                open_date: lot.acquired_date.format("%Y-%m-%d").to_string(),
+                close_date: lot.sell_date.format("%Y-%m-%d").to_string(),
+                quantity: lot.quantity,
+                actual_return,
+                max_possible_return_1y,
+                timing_efficiency,
+            }
+        })
+        .collect();
+
+    let efficiencies: Vec<f64> = lots.iter().filter_map(|l| l.timing_efficiency).collect();
+    let avg_timing_efficiency = (!efficiencies.is_empty())
+        .then(|| efficiencies.iter().sum::<f64>() / efficiencies.len() as f64);
+    let buy_and_hold_return = closes
+        .iter()
+        .find(|(_, c)| *c > 0.0)
+        .zip(closes.iter().rev().find(|(_, c)| *c > 0.0))
+        .map(|((_, first), (_, last))| last / first - 1.0);
+
+    Ok(TimingAnalysis {
+        symbol,
+        lots,
+        avg_timing_efficiency,
+        buy_and_hold_return,
+    })
+}
+
+#[tauri::command]
+fn get_position_detail(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+    benchmark: Option<String>,
+) -> Result<PositionDetail, AppError> {
+    let today = Utc::now().date_naive();
+    let transactions = load_symbol_transactions(&app_handle, &symbol)?;
+    let currency = transactions
+        .first()
+        .map(|t| t.currency.clone())
+        .unwrap_or_else(|| {
+            currency_for_exchange(get_exchange_and_symbol(&symbol).0.as_deref()).to_string()
+        });
+
+    let (shares, cost_basis_average, realized_pnl) = replay_average_cost(&transactions, today);
+    let open_lots = fifo_open_lots(&transactions, today);
+    let cost_basis_fifo = open_lots
+        .iter()
+        .map(|lot| lot.quantity * lot.cost_per_share)
+        .sum();
+
+    // Quoted (split-unadjusted) prices value the position; split-adjusted closes
+    // drive the return statistics.
+    let quoted: Vec<(NaiveDate, f64)> = load_price_history_for_symbol(&app_handle, &symbol)
+        .map(|records| records.into_iter().map(|r| (r.date, r.close)).collect())
+        .unwrap_or_default();
+    let adjusted: Vec<(NaiveDate, f64)> = load_ascending_price_series(&app_handle, &symbol)
+        .map(|series| series.into_iter().map(|(d, r)| (d, r.close)).collect())
+        .unwrap_or_default();
+
+    let latest = quoted.iter().rev().find(|(_, close)| *close > 0.0).copied();
+    let market_value = latest.map(|(_, close)| close * shares);
+    let one_year_ago = today - ChronoDuration::days(365);
+    let year_window = quoted.iter().filter(|(d, c)| *d > one_year_ago && *c > 0.0);
+    let high_52w = year_window.clone().map(|(_, c)| *c).reduce(f64::max);
+    let low_52w = year_window.map(|(_, c)| *c).reduce(f64::min);
+
+    let latest_adjusted = close_on_or_before(&adjusted, today);
+    let return_since = |start: NaiveDate| {
+        let base = close_on_or_before(&adjusted, start)?;
+        latest_adjusted.map(|latest| latest / base - 1.0)
+    };
+    let year_start = NaiveDate::from_ymd_opt(today.year() - 1, 12, 31).unwrap_or(today);
+    let cagr_3y = return_since(today - ChronoDuration::days(3 * 365))
+        .map(|total| (1.0 + total).powf(1.0 / 3.0) - 1.0);
+
+    let returns_1y = daily_returns_since(&adjusted, one_year_ago);
+    let benchmark = benchmark.unwrap_or_else(|| "SPY".to_string());
+    let beta_1y = load_ascending_price_series(&app_handle, &benchmark)
+        .ok()
+        .map(|series| {
+            let closes: Vec<(NaiveDate, f64)> =
+                series.into_iter().map(|(d, r)| (d, r.close)).collect();
+            daily_returns_since(&closes, one_year_ago)
+        })
+        .and_then(|benchmark_returns| beta_against(&returns_1y, &benchmark_returns));
+
+    let dividend_events = load_dividend_events(&app_handle, &symbol)?;
+    let dividend_income = dividend_events
+        .iter()
+        .map(|(ex_date, amount, _)| {
+            amount * shares_held_on(&transactions, *ex_date - ChronoDuration::days(1))
+        })
+        .sum();
+
+    Ok(PositionDetail {
+        currency,
+        shares,
+        average_cost: if shares > 0.0 {
+            cost_basis_average / shares
+        } else {
+            0.0
+        },
+        cost_basis_average,
+        cost_basis_fifo,
+        oldest_lot_date: open_lots
+            .first()
+            .map(|lot| lot.date.format("%Y-%m-%d").to_string()),
+        latest_price: latest.map(|(_, close)| close),
+        latest_price_date: latest.map(|(date, _)| date.format("%Y-%m-%d").to_string()),
+        market_value,
+        unrealized_pnl: market_value.map(|value| value - cost_basis_average),
+        realized_pnl,
+        dividend_income,
+        beta_1y,
+        sharpe_1y: sharpe_ratio(&returns_1y),
+        max_drawdown_1y: max_drawdown(
+            adjusted
+                .iter()
+                .filter(|(d, _)| *d > one_year_ago)
+                .map(|(_, c)| *c),
+        ),
+        high_52w,
+        low_52w,
+        ytd_return: return_since(year_start),
+        return_1y: return_since(one_year_ago),
+        cagr_3y,
+        upcoming_dividend: estimate_next_dividend(&dividend_events, today),
+        coverage_percent: get_symbol_coverage(app_handle.clone(), symbol.clone())
+            .ok()
+            .map(|coverage| coverage.coverage_percent),
+        tags: security_tags(&app_handle, &symbol),
+        symbol,
+    })
+}
+
+#[derive(Serialize, Clone)]
+struct FrontierPortfolio {
+    weights: HashMap<String, f64>,
+    expected_return: f64,
+    volatility: f64,
+    sharpe: f64,
+}
+
+#[derive(Serialize)]
+struct EfficientFrontierResult {
+    portfolios: Vec<FrontierPortfolio>,
+    minimum_variance_portfolio: FrontierPortfolio,
+    maximum_sharpe_portfolio: FrontierPortfolio,
+}
+
+const FRONTIER_LOOKBACK_DAYS: usize = 252;
+
+/// Monte Carlo efficient frontier over the current holdings. Daily returns of the
+/// split-adjusted closes over the last 252 trading days that every holding has a
+/// price for give the mean vector and covariance matrix; `num_portfolios` (default
+/// 1000) weight vectors are then drawn uniformly from the simplex (a flat Dirichlet,
+/// sampled as normalised exponentials) and scored. Returns and volatility are
+/// annualised over 252 days and the Sharpe ratio assumes a zero risk-free rate, as
+/// in the position analytics.
+#[tauri::command]
+fn calculate_efficient_frontier(
+    app_handle: tauri::AppHandle,
+    num_portfolios: Option<u32>,
+) -> Result<EfficientFrontierResult, AppError> {
+    use rand::Rng;
+
+    let num_portfolios = num_portfolios.unwrap_or(1000);
+    if !(1..=100_000).contains(&num_portfolios) {
+        return Err(AppError::validation(
+            "num_portfolios",
+            "Must be between 1 and 100000",
+        ));
+    }
+
+    let today = Utc::now().date_naive();
+    let mut symbols = Vec::new();
+    let mut closes: Vec<HashMap<NaiveDate, f64>> = Vec::new();
+    for symbol in traded_symbols(&app_handle)? {
+        let transactions = load_symbol_transactions(&app_handle, &symbol)?;
+        if replay_average_cost(&transactions, today).0 <= 1e-9 {
+            continue;
+        }
+        let series: HashMap<NaiveDate, f64> = load_ascending_price_series(&app_handle, &symbol)?
+            .into_iter()
+            .filter(|(_, r)| r.close > 0.0)
+            .map(|(d, r)| (d, r.close))
+            .collect();
+        symbols.push(symbol);
+        closes.push(series);
+    }
+    if symbols.is_empty() {
+        return Err(AppError::not_found("holdings", "No open positions"));
+    }
+
+    let mut dates: Vec<NaiveDate> = closes[0]
+        .keys()
+        .copied()
+        .filter(|date| closes.iter().all(|series| series.contains_key(date)))
+        .collect();
+    dates.sort();
+    let dates = &dates[dates.len().saturating_sub(FRONTIER_LOOKBACK_DAYS + 1)..];
+    if dates.len() < 21 {
+        return Err(AppError::validation(
+            "prices",
+            format!(
+                "Only {} days have prices for every holding; at least 21 are needed",
+                dates.len()
+            ),
+        ));
+    }
+
+    let returns: Vec<Vec<f64>> = closes
+        .iter()
+        .map(|series| {
+            dates
+                .windows(2)
+                .map(|pair| series[&pair[1]] / series[&pair[0]] - 1.0)
+                .collect()
+        })
+        .collect();
+    let n = symbols.len();
+    let observations = (dates.len() - 1) as f64;
+    let means: Vec<f64> = returns
+        .iter()
+        .map(|r| r.iter().sum::<f64>() / observations)
+        .collect();
+    let mut covariance = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        for j in i..n {
+            let value = returns[i]
+                .iter()
+                .zip(&returns[j])
+                .map(|(a, b)| (a - means[i]) * (b - means[j]))
+                .sum::<f64>()
+                / (observations - 1.0);
+            covariance[i][j] = value;
+            covariance[j][i] = value;
+        }
+    }
+
+    let mut rng = rand::thread_rng();
+    let portfolios: Vec<FrontierPortfolio> = (0..num_portfolios)
+        .map(|_| {
+            let draws: Vec<f64> = (0..n)
+                .map(|_| -rng.gen_range(f64::EPSILON..1.0).ln())
+                .collect();
+            let total: f64 = draws.iter().sum();
+            let weights: Vec<f64> = draws.iter().map(|d| d / total).collect();
+
+            let daily_return: f64 = weights.iter().zip(&means).map(|(w, m)| w * m).sum();
+            let daily_variance: f64 = (0..n)
+                .map(|i| {
+                    (0..n)
+                        .map(|j| weights[i] * weights[j] * covariance[i][j])
+                        .sum::<f64>()
+                })
+                .sum();
+            let expected_return = daily_return * FRONTIER_LOOKBACK_DAYS as f64;
+            let volatility = (daily_variance.max(0.0) * FRONTIER_LOOKBACK_DAYS as f64).sqrt();
+            FrontierPortfolio {
+                weights: symbols.iter().cloned().zip(weights).collect(),
+                expected_return,
+                volatility,
+                sharpe: if volatility > 0.0 {
+                    expected_return / volatility
+                } else {
+                    0.0
+                },
+            }
+        })
+        .collect();
+
+    let minimum_variance_portfolio = portfolios
+        .iter()
+        .min_by(|a, b| a.volatility.total_cmp(&b.volatility))
+        .cloned()
+        .ok_or_else(|| "No portfolios simulated".to_string())?;
+    let maximum_sharpe_portfolio = portfolios
+        .iter()
+        .max_by(|a, b| a.sharpe.total_cmp(&b.sharpe))
+        .cloned()
+        .ok_or_else(|| "No portfolios simulated".to_string())?;
+    Ok(EfficientFrontierResult {
+        portfolios,
+        minimum_variance_portfolio,
+        maximum_sharpe_portfolio,
+    })
+}
+
+#[derive(Serialize, Clone)]
+struct CurrencySource {
+    source: String,
+    currency: String,
+}
+
+#[derive(Serialize)]
+struct CurrencyFinding {
+    symbol: Option<String>,
+    severity: String,
+    kind: String,
+    message: String,
+    sources: Vec<CurrencySource>,
+}
+
+/// `(currency, exchange)` per ticker from securities.csv, read by header name.
+fn load_security_currencies(
+    app_handle: &tauri::AppHandle,
+) -> Result<HashMap<String, (String, String)>, String> {
+    let path = get_data_dir(app_handle)?.join("securities.csv");
+    let mut securities = HashMap::new();
+    if !path.exists() {
+        return Ok(securities);
+    }
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_path(&path)
+        .map_err(|e| format!("Failed to read securities.csv: {}", e))?;
+    let headers = reader
+        .headers()
+        .map_err(|e| format!("Failed to read securities.csv header: {}", e))?
+        .clone();
+    let column = |name: &str| headers.iter().position(|h| h.trim() == name);
+    let (Some(ticker_idx), Some(currency_idx)) = (column("ticker"), column("currency")) else {
+        return Ok(securities);
+    };
+    let exchange_idx = column("exchange");
+    for record in reader.records().flatten() {
+        let ticker = record.get(ticker_idx).unwrap_or("").trim();
+        if ticker.is_empty() {
+            continue;
+        }
+        let currency = record.get(currency_idx).unwrap_or("").trim().to_uppercase();
+        let exchange = exchange_idx
+            .and_then(|idx| record.get(idx))
+            .unwrap_or("")
+            .trim()
+            .to_string();
+        securities.insert(ticker.to_string(), (currency, exchange));
+    }
+    Ok(securities)
+}
+
+#[derive(Serialize)]
+struct SecurityIssue {
+    line: usize,
+    ticker: String,
+    kind: String,
+    message: String,
+}
+
+#[derive(Serialize)]
+struct SecurityValidationReport {
+    rows_checked: usize,
+    issues: Vec<SecurityIssue>,
+    missing_tickers: Vec<String>,
+}
+
+type SecuritiesRows = (Vec<String>, Vec<(usize, Vec<String>)>);
+
+/// Reads securities.csv as RFC 4180 CSV, returning the header and each row with its
+/// 1-based line number. Rows are not length-checked so callers can report them.
+fn read_securities_rows(app_handle: &tauri::AppHandle) -> Result<SecuritiesRows, String> {
+    let path = get_data_dir(app_handle)?.join("securities.csv");
+    let default_header: Vec<String> = SECURITIES_HEADER
+        .trim_end()
+        .split(',')
+        .map(|h| h.to_string())
+        .collect();
+    if !path.exists() {
+        return Ok((default_header, Vec::new()));
+    }
+
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_path(&path)
+        .map_err(|e| format!("Failed to read securities.csv: {}", e))?;
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| format!("Failed to read securities.csv header: {}", e))?
+        .iter()
+        .map(|h| h.trim().to_string())
+        .collect();
+    let headers = if headers.iter().all(|h| h.is_empty()) {
+        default_header
+    } else {
+        headers
+    };
+
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| format!("Failed to read securities.csv: {}", e))?;
+        let line = record.position().map(|p| p.line() as usize).unwrap_or(0);
+        let fields: Vec<String> = record.iter().map(|f| f.to_string()).collect();
+        if fields.iter().all(|f| f.trim().is_empty()) {
+            continue;
+        }
+        rows.push((line, fields));
+    }
+    Ok((headers, rows))
+}
+
+/// Tickers that have a base price file but no securities.csv row.
+fn tickers_missing_from_securities(
+    app_handle: &tauri::AppHandle,
+    listed: &std::collections::HashSet<String>,
+) -> Result<Vec<String>, String> {
+    Ok(list_price_files(app_handle.clone())?
+        .into_iter()
+        .filter(|symbol| !symbol.ends_with("-override") && !listed.contains(symbol))
+        .collect())
+}
+
+/// Runs `f` holding the securities.csv lock, for read-modify-write updates.
+fn with_securities_lock<T, E: From<String>>(
+    app_handle: &tauri::AppHandle,
+    f: impl FnOnce() -> Result<T, E>,
+) -> Result<T, E> {
+    let path = get_data_dir(app_handle)?.join("securities.csv");
+    with_file_lock(app_handle, &path, f)
+}
+
+fn write_securities_rows(
+    app_handle: &tauri::AppHandle,
+    headers: &[String],
+    rows: &[Vec<String>],
+) -> Result<(), String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record(headers)
+        .map_err(|e| format!("Failed to write securities.csv header: {}", e))?;
+    for fields in rows {
+        writer
+            .write_record(fields)
+            .map_err(|e| format!("Failed to write securities.csv row: {}", e))?;
+    }
+    let content = writer
+        .into_inner()
+        .map_err(|e| format!("Failed to write securities.csv: {}", e))?;
+    let path = get_data_dir(app_handle)?.join("securities.csv");
+    atomic_write(app_handle, &path, &content)
+}
+
+/// Inserts or updates the securities.csv row for `ticker`. Only non-empty values
+/// overwrite existing fields; `last_updated` is always refreshed.
+fn upsert_security(
+    app_handle: &tauri::AppHandle,
+    ticker: &str,
+    values: &[(&str, String)],
+) -> Result<(), String> {
+    with_securities_lock(app_handle, || {
+        let (headers, rows) = read_securities_rows(app_handle)?;
+        let column = |name: &str| headers.iter().position(|h| h == name);
+        let ticker_idx = column("ticker").unwrap_or(0);
+
+        let mut rows: Vec<Vec<String>> = rows
+            .into_iter()
+            .map(|(_, mut fields)| {
+                fields.resize(headers.len(), String::new());
+                fields
+            })
+            .collect();
+        let row_idx = match rows
+            .iter()
+            .position(|fields| fields[ticker_idx].trim() == ticker)
+        {
+            Some(idx) => idx,
+            None => {
+                let mut fields = vec![String::new(); headers.len()];
+                fields[ticker_idx] = ticker.to_string();
+                rows.push(fields);
+                rows.len() - 1
+            }
+        };
+
+        let row = &mut rows[row_idx];
+        for (name, value) in values {
+            if let Some(idx) = column(name).filter(|_| !value.trim().is_empty()) {
+                row[idx] = value.trim().to_string();
+            }
+        }
+        if let Some(idx) = column("last_updated") {
+            row[idx] = Utc::now().to_rfc3339();
+        }
+
+        write_securities_rows(app_handle, &headers, &rows)
+    })
+}
+
+#[tauri::command]
+fn validate_securities_csv(
+    app_handle: tauri::AppHandle,
+) -> Result<SecurityValidationReport, AppError> {
+    let (headers, rows) = read_securities_rows(&app_handle)?;
+    let column = |name: &str| headers.iter().position(|h| h == name);
+    let ticker_idx = column("ticker").unwrap_or(0);
+    let exchange_idx = column("exchange");
+    let currency_idx = column("currency");
+
+    let mut issues = Vec::new();
+    let mut listed = std::collections::HashSet::new();
+    for (line, fields) in &rows {
+        let ticker = fields
+            .get(ticker_idx)
+            .map(|t| t.trim().to_string())
+            .unwrap_or_default();
+        let mut issue = |kind: &str, message: String| {
+            issues.push(SecurityIssue {
+                line: *line,
+                ticker: ticker.clone(),
+                kind: kind.to_string(),
+                message,
+            })
+        };
+
+        if fields.len() != headers.len() {
+            issue(
+                "column_count",
+                format!(
+                    "Expected {} columns but found {}; a name containing commas may be unquoted",
+                    headers.len(),
+                    fields.len()
+                ),
+            );
+            // Later columns are shifted, so exchange/currency checks would be noise.
+            continue;
+        }
+        if ticker.is_empty() {
+            issue("missing_ticker", "Row has no ticker".to_string());
+            continue;
+        }
+        if !listed.insert(ticker.clone()) {
+            issue(
+                "duplicate_ticker",
+                format!("{} is listed more than once", ticker),
+            );
+        }
+
+        let exchange = exchange_idx
+            .and_then(|idx| fields.get(idx))
+            .map(|e| e.trim().to_uppercase())
+            .unwrap_or_default();
+        if !exchange.is_empty() && !KNOWN_EXCHANGES.contains(&exchange.as_str()) {
+            issue(
+                "unknown_exchange",
+                format!("Unknown exchange identifier '{}'", exchange),
+            );
+        }
+
+        let currency = currency_idx
+            .and_then(|idx| fields.get(idx))
+            .map(|c| c.trim().to_uppercase())
+            .unwrap_or_default();
+        if currency.is_empty() {
+            issue("missing_currency", "Currency is empty".to_string());
+        } else if !KNOWN_CURRENCIES.contains(&currency.as_str()) {
+            issue(
+                "unknown_currency",
+                format!("Unknown currency code '{}'", currency),
+            );
+        }
+    }
+
+    let missing_tickers = tickers_missing_from_securities(&app_handle, &listed)?;
+    Ok(SecurityValidationReport {
+        rows_checked: rows.len(),
+        issues,
+        missing_tickers,
+    })
+}
+
+/// Rewrites securities.csv with RFC 4180 quoting. Rows with surplus columns have the
+/// extra fields folded back into `name` (the usual unquoted-comma case), short rows are
+/// padded, and tickers with price files but no row get placeholder entries.
+/// Returns the tickers that were added.
+#[tauri::command]
+fn repair_securities_csv(app_handle: tauri::AppHandle) -> Result<Vec<String>, AppError> {
+    with_securities_lock(&app_handle, || {
+        let (headers, rows) = read_securities_rows(&app_handle)?;
+        let column = |name: &str| headers.iter().position(|h| h == name);
+        let ticker_idx = column("ticker").unwrap_or(0);
+        let name_idx = column("name");
+
+        let mut repaired: Vec<Vec<String>> = Vec::with_capacity(rows.len());
+        let mut listed = std::collections::HashSet::new();
+        for (_, mut fields) in rows {
+            if fields.len() > headers.len() {
+                if let Some(name_idx) = name_idx {
+                    let surplus = fields.len() - headers.len();
+                    let merged = fields[name_idx..=name_idx + surplus].join(",");
+                    fields.splice(name_idx..=name_idx + surplus, [merged]);
+                } else {
+                    fields.truncate(headers.len());
+                }
+            }
+            fields.resize(headers.len(), String::new());
+            listed.insert(fields[ticker_idx].trim().to_string());
+            repaired.push(fields);
+        }
+
+        let missing = tickers_missing_from_securities(&app_handle, &listed)?;
+        let now = Utc::now().to_rfc3339();
+        for ticker in &missing {
+            let (exchange, _) = get_exchange_and_symbol(ticker);
+            let mut fields = vec![String::new(); headers.len()];
+            let mut set = |name: &str, value: String| {
+                if let Some(idx) = column(name) {
+                    fields[idx] = value;
+                }
+            };
+            set("ticker", ticker.clone());
+            set("name", ticker.clone());
+            set("exchange", exchange.clone().unwrap_or_default());
+            set(
+                "currency",
+                currency_for_exchange(exchange.as_deref()).to_string(),
+            );
+            set("type", "stock".to_string());
+            set("data_source", "yahoo_finance".to_string());
+            set("api_symbol", resolve_yahoo_symbol(&app_handle, ticker).0);
+            set("last_updated", now.clone());
+            repaired.push(fields);
+        }
+
+        write_securities_rows(&app_handle, &headers, &repaired)?;
+
+        write_worker_log(
+            &app_handle,
+            &format!(
+                "Repaired securities.csv: {} rows, {} placeholders added",
+                repaired.len(),
+                missing.len()
+            ),
+        )?;
+        Ok(missing)
+    })
+}
+
+#[derive(Serialize)]
+struct DeduplicateSecuritiesResult {
+    removed_count: usize,
+}
+
+/// Sort key for a `last_updated` value: RFC 3339 timestamps and plain dates compare
+/// chronologically, anything unparseable sorts first.
+fn security_updated_key(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .map(|dt| dt.and_utc())
+        })
+}
+
+/// Tickers listed more than once in securities.csv, with their row counts.
+#[tauri::command]
+fn count_duplicate_securities(
+    app_handle: tauri::AppHandle,
+) -> Result<HashMap<String, usize>, AppError> {
+    let (headers, rows) = read_securities_rows(&app_handle)?;
+    let ticker_idx = headers.iter().position(|h| h == "ticker").unwrap_or(0);
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for (_, fields) in &rows {
+        let ticker = fields.get(ticker_idx).map(|t| t.trim()).unwrap_or("");
+        if !ticker.is_empty() {
+            *counts.entry(ticker.to_string()).or_default() += 1;
+        }
+    }
+    counts.retain(|_, count| *count > 1);
+    Ok(counts)
+}
+
+/// Keeps one securities.csv row per ticker: the one with the newest `last_updated`,
+/// or the later row in the file on a tie. Survivors stay at the position of the
+/// ticker's first row.
+#[tauri::command]
+fn deduplicate_securities(
+    app_handle: tauri::AppHandle,
+) -> Result<DeduplicateSecuritiesResult, AppError> {
+    with_securities_lock(&app_handle, || {
+        let (headers, rows) = read_securities_rows(&app_handle)?;
+        let column = |name: &str| headers.iter().position(|h| h == name);
+        let ticker_idx = column("ticker").unwrap_or(0);
+        let updated_idx = column("last_updated");
+        let total = rows.len();
+
+        let mut kept: Vec<Vec<String>> = Vec::with_capacity(total);
+        let mut position_by_ticker: HashMap<String, usize> = HashMap::new();
+        for (_, fields) in rows {
+            let ticker = fields
+                .get(ticker_idx)
+                .map(|t| t.trim().to_string())
+                .unwrap_or_default();
+            if ticker.is_empty() {
+                kept.push(fields);
+                continue;
+            }
+            match position_by_ticker.entry(ticker) {
+                Entry::Vacant(slot) => {
+                    slot.insert(kept.len());
+                    kept.push(fields);
+                }
+                Entry::Occupied(slot) => {
+                    let updated = |row: &[String]| {
+                        updated_idx
+                            .and_then(|idx| row.get(idx))
+                            .and_then(|v| security_updated_key(v))
+                    };
+                    let existing = &mut kept[*slot.get()];
+                    if updated(&fields) >= updated(existing) {
+                        *existing = fields;
+                    }
+                }
+            }
+        }
+
+        let removed_count = total - kept.len();
+        if removed_count > 0 {
+            write_securities_rows(&app_handle, &headers, &kept)?;
+            write_worker_log(
+                &app_handle,
+                &format!(
+                    "Deduplicated securities.csv: removed {} rows",
+                    removed_count
+                ),
+            )?;
+        }
+        Ok(DeduplicateSecuritiesResult { removed_count })
+    })
+}
+
+const OPENFIGI_MAPPING_URL: &str = "https://api.openfigi.com/v3/mapping";
+const OPENFIGI_REQUESTS_PER_MINUTE: usize = 25;
+
+/// Start times of recent OpenFIGI requests, used to stay under the free-tier limit.
+#[derive(Default)]
+struct OpenFigiRateLimiter {
+    recent: Mutex<std::collections::VecDeque<std::time::Instant>>,
+}
+
+impl OpenFigiRateLimiter {
+    /// Blocks until another request fits in the rolling one-minute window.
+    fn acquire(&self) {
+        let window = Duration::from_secs(60);
+        let mut recent = self.recent.lock().unwrap_or_else(|e| e.into_inner());
+        loop {
+            let now = std::time::Instant::now();
+            while recent
+                .front()
+                .is_some_and(|started| now.duration_since(*started) >= window)
+            {
+                recent.pop_front();
+            }
+            if recent.len() < OPENFIGI_REQUESTS_PER_MINUTE {
+                recent.push_back(now);
+                return;
+            }
+            let wait = recent
+                .front()
+                .map(|started| window.saturating_sub(now.duration_since(*started)))
+                .unwrap_or_default();
+            std::thread::sleep(wait);
+        }
+    }
+}
+
+#[derive(Serialize, Clone)]
+struct SecurityInfo {
+    ticker: String,
+    name: String,
+    exchange: String,
+    currency: String,
+    #[serde(rename = "type")]
+    type_: String,
+    sector: String,
+    figi: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OpenFigiInstrument {
+    figi: Option<String>,
+    name: Option<String>,
+    ticker: Option<String>,
+    exch_code: Option<String>,
+    security_type: Option<String>,
+    security_type2: Option<String>,
+    market_sector: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct OpenFigiMappingResult {
+    data: Option<Vec<OpenFigiInstrument>>,
+    warning: Option<String>,
+    error: Option<String>,
+}
+
+/// Maps an OpenFIGI/Bloomberg exchange code onto the identifiers used in symbols.
+fn exchange_for_figi_code(exch_code: &str) -> Option<&'static str> {
+    match exch_code {
+        "UW" | "UQ" | "UR" => Some("NASDAQ"),
+        "UN" => Some("NYSE"),
+        "UP" => Some("NYSEARCA"),
+        "UA" => Some("NYSEAMERICAN"),
+        "UV" | "PQ" => Some("OTCMKTS"),
+        "TT" => Some("TWSE"),
+        "JT" | "JP" => Some("JPX"),
+        "HK" => Some("HKEX"),
+        "LN" => Some("LSE"),
+        "AU" | "AT" => Some("ASX"),
+        "CN" | "CT" => Some("TSX"),
+        "GR" | "GY" | "GF" => Some("FRA"),
+        "FP" => Some("PAR"),
+        "NA" => Some("AMS"),
+        "SS" => Some("STO"),
+        "KS" => Some("KRX"),
+        "KQ" => Some("KOSDAQ"),
+        _ => None,
+    }
+}
+
+/// Looks up a security on OpenFIGI by ticker, ISIN or CUSIP and upserts the result
+/// into securities.csv. OpenFIGI does not return currencies, so the currency is
+/// inferred from the listing exchange.
+#[tauri::command]
+fn lookup_security(
+    app_handle: tauri::AppHandle,
+    identifier: String,
+    id_type: String,
+) -> Result<SecurityInfo, AppError> {
+    let identifier = identifier.trim().to_uppercase();
+    if identifier.is_empty() {
+        return Err(AppError::validation("identifier", "Identifier is required"));
+    }
+    let figi_id_type = match id_type.trim().to_lowercase().as_str() {
+        "ticker" => "TICKER",
+        "isin" => "ID_ISIN",
+        "cusip" => "ID_CUSIP",
+        other => {
+            return Err(AppError::validation(
+                "id_type",
+                format!("Unsupported id_type '{}'", other),
+            ))
+        }
+    };
+    let id_value = if figi_id_type == "TICKER" {
+        get_exchange_and_symbol(&identifier).1
+    } else {
+        identifier.clone()
+    };
+
+    let body = serde_json::json!([{ "idType": figi_id_type, "idValue": id_value }]);
+    let client = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .user_agent("portfolio-manager-desktop/1.0")
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+    let mut request = client
+        .post(OPENFIGI_MAPPING_URL)
+        .header("Content-Type", "application/json")
+        .body(body.to_string());
+    if let Some(api_key) = read_setting_value_internal(&app_handle, "openfigi_api_key")?
+        .filter(|key| !key.trim().is_empty())
+    {
+        request = request.header("X-OPENFIGI-APIKEY", api_key.trim());
+    }
+
+    if let Some(limiter) = app_handle.try_state::<OpenFigiRateLimiter>() {
+        limiter.acquire();
+    }
+    let response = request
+        .send()
+        .map_err(|e| format!("OpenFIGI request failed: {}", e))?;
+    let status = response.status();
+    let text = response
+        .text()
+        .map_err(|e| format!("Failed to read OpenFIGI response: {}", e))?;
+    if !status.is_success() {
+        return Err(AppError::from_status(
+            status,
+            format!("OpenFIGI error {}: {}", status, text),
+        ));
+    }
+
+    let mut results: Vec<OpenFigiMappingResult> =
+        serde_json::from_str(&text).map_err(|e| format!("Invalid OpenFIGI JSON: {}", e))?;
+    let result = results
+        .pop()
+        .ok_or_else(|| "OpenFIGI response was empty".to_string())?;
+    if let Some(error) = result.error.or(result.warning) {
+        return Err(AppError::not_found(
+            identifier.clone(),
+            format!("OpenFIGI lookup for {} failed: {}", identifier, error),
+        ));
+    }
+    let instruments = result.data.unwrap_or_default();
+    let (requested_exchange, _) = get_exchange_and_symbol(&identifier);
+    let instrument = instruments
+        .iter()
+        .find(|inst| {
+            let exchange = inst.exch_code.as_deref().and_then(exchange_for_figi_code);
+            match requested_exchange.as_deref() {
+                Some(requested) => exchange == Some(requested),
+                None => exchange.is_some(),
+            }
+        })
+        .or_else(|| instruments.first())
+        .ok_or_else(|| format!("No OpenFIGI match for {}", identifier))?;
+
+    let exchange = instrument
+        .exch_code
+        .as_deref()
+        .and_then(exchange_for_figi_code);
+    let base_ticker = instrument.ticker.clone().unwrap_or(id_value);
+    let currency = currency_for_exchange(exchange).to_string();
+    // US listings are stored without an exchange prefix, like in the transaction files.
+    let ticker = match exchange {
+        Some(exchange) if currency != "USD" => format!("{}:{}", exchange, base_ticker),
+        _ => base_ticker.clone(),
+    };
+    let info = SecurityInfo {
+        ticker: ticker.clone(),
+        name: instrument.name.clone().unwrap_or_default(),
+        exchange: exchange.unwrap_or_default().to_string(),
+        currency,
+        type_: instrument
+            .security_type2
+            .clone()
+            .or_else(|| instrument.security_type.clone())
+            .unwrap_or_default(),
+        sector: instrument.market_sector.clone().unwrap_or_default(),
+        figi: instrument.figi.clone().unwrap_or_default(),
+    };
+
+    upsert_security(
+        &app_handle,
+        &ticker,
+        &[
+            ("name", info.name.clone()),
+            ("exchange", info.exchange.clone()),
+            ("currency", info.currency.clone()),
+            ("type", info.type_.clone()),
+            ("sector", info.sector.clone()),
+            ("api_symbol", yahoo_symbol_for(exchange, &base_ticker)),
+        ],
+    )?;
+
+    Ok(info)
+}
+
+/// Disagreements between the currencies one symbol's sources claim:
+/// transactions, securities.csv `(currency, exchange)`, the exchange's own
+/// currency, dividend files and the Yahoo meta.
+fn symbol_currency_findings(
+    symbol: &str,
+    txn_currencies: &std::collections::BTreeSet<String>,
+    security: Option<&(String, String)>,
+    dividend_currencies: &[String],
+    meta_currency: Option<&str>,
+) -> Vec<CurrencyFinding> {
+    let mut findings = Vec::new();
+    let mut sources: Vec<CurrencySource> = Vec::new();
+    let mut push = |source: &str, currency: &str| {
+        if !currency.is_empty() {
+            sources.push(CurrencySource {
+                source: source.to_string(),
+                currency: currency.to_string(),
+            });
+        }
+    };
+
+    for currency in txn_currencies {
+        push("transactions", currency);
+    }
+
+    if let Some((currency, _)) = security {
+        push("securities.csv", currency);
+    }
+
+    let exchange = get_exchange_and_symbol(symbol).0.or_else(|| {
+        security
+            .map(|(_, ex)| ex.clone())
+            .filter(|ex| !ex.is_empty())
+    });
+    if let Some(exchange) = exchange.as_deref() {
+        push(
+            &format!("exchange {}", exchange),
+            currency_for_exchange(Some(exchange)),
+        );
+    }
+
+    for currency in dividend_currencies {
+        push("dividends", currency);
+    }
+
+    if let Some(currency) = meta_currency {
+        // Yahoo quotes London listings in pence (GBp); the currency is still GBP.
+        let currency = if currency == "GBp" || currency == "GBX" {
+            "GBP".to_string()
+        } else {
+            currency.to_uppercase()
+        };
+        push("yahoo meta", &currency);
+    }
+
+    if txn_currencies.len() > 1 {
+        findings.push(CurrencyFinding {
+            symbol: Some(symbol.to_string()),
+            severity: "error".to_string(),
+            kind: "mixed_transaction_currencies".to_string(),
+            message: format!(
+                "{} has transactions in {}; some are probably in the wrong market file",
+                symbol,
+                txn_currencies
+                    .iter()
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            sources: sources
+                .iter()
+                .filter(|s| s.source == "transactions")
+                .cloned()
+                .collect(),
+        });
+    }
+
+    let distinct: std::collections::BTreeSet<&str> =
+        sources.iter().map(|s| s.currency.as_str()).collect();
+    if distinct.len() > 1 {
+        // Amounts in the transaction files feed every report, so disagreements
+        // touching them are errors; the rest are metadata drift.
+        let touches_transactions = sources.iter().any(|s| {
+            s.source == "transactions"
+                && sources
+                    .iter()
+                    .any(|o| o.source != "transactions" && o.currency != s.currency)
+        });
+        let message = sources
+            .iter()
+            .map(|s| format!("{}={}", s.source, s.currency))
+            .collect::<Vec<_>>()
+            .join(", ");
+        findings.push(CurrencyFinding {
+            symbol: Some(symbol.to_string()),
+            severity: if touches_transactions {
+                "error"
+            } else {
+                "warning"
+            }
+            .to_string(),
+            kind: "currency_mismatch".to_string(),
+            message: format!("{} currency sources disagree: {}", symbol, message),
+            sources,
+        });
+    }
+
+    findings
+}
+
+#[tauri::command]
+fn audit_currencies(app_handle: tauri::AppHandle) -> Result<Vec<CurrencyFinding>, AppError> {
+    let transactions = load_all_transactions(&app_handle)?;
+    let securities = load_security_currencies(&app_handle)?;
+
+    let mut transaction_currencies: HashMap<String, std::collections::BTreeSet<String>> =
+        HashMap::new();
+    for txn in transactions.iter() {
+        let symbol = txn.stock.trim();
+        if !symbol.is_empty() {
+            transaction_currencies
+                .entry(symbol.to_string())
+                .or_default()
+                .insert(txn.currency.trim().to_uppercase());
+        }
+    }
+
+    let mut symbols: Vec<&String> = transaction_currencies.keys().collect();
+    symbols.sort();
+
+    let mut findings = Vec::new();
+    let mut used_currencies: std::collections::BTreeSet<String> = std::collections::BTreeSet::new();
+
+    for symbol in symbols {
+        let txn_currencies = &transaction_currencies[symbol];
+        used_currencies.extend(txn_currencies.iter().cloned());
+        let security = securities.get(symbol.as_str());
+
+        let mut dividend_currencies: Vec<String> = load_dividend_events(&app_handle, symbol)?
+            .into_iter()
+            .map(|(_, _, currency)| currency.trim().to_uppercase())
+            .collect();
+        dividend_currencies.sort();
+        dividend_currencies.dedup();
+        used_currencies.extend(dividend_currencies.iter().cloned());
+
+        let meta_currency = load_yahoo_meta(&app_handle, symbol).and_then(|meta| {
+            meta.get("currency")
+                .and_then(|c| c.as_str())
+                .map(str::to_string)
+        });
+
+        findings.extend(symbol_currency_findings(
+            symbol,
+            txn_currencies,
+            security,
+            &dividend_currencies,
+            meta_currency.as_deref(),
+        ));
+    }
+
+    // Reports convert every holding and dividend currency into the base currency.
+    let base_currency = configured_base_currency(&app_handle);
+    let fx_dir = get_fx_rates_dir(&app_handle)?;
+    for currency in used_currencies.iter().filter(|c| **c != base_currency) {
+        let has_file = [
+            format!("{}_{}.csv", currency, base_currency),
+            format!("{}_{}.csv", base_currency, currency),
+        ]
+        .iter()
+        .any(|name| fx_dir.join(name).exists());
+        if !has_file {
+            findings.push(CurrencyFinding {
+                symbol: None,
+                severity: "warning".to_string(),
+                kind: "missing_fx_rates".to_string(),
+                message: format!(
+                    "No fx_rates file for {}/{} (or its inverse)",
+                    currency, base_currency
+                ),
+                sources: vec![CurrencySource {
+                    source: "settings baseCurrency".to_string(),
+                    currency: base_currency.clone(),
+                }],
+            });
+        }
+    }
+
+    Ok(findings)
+}
+
+/// Symbols that have a base price file (override files are excluded).
+fn price_file_symbols(app_handle: &tauri::AppHandle) -> Result<Vec<String>, String> {
+    let prices_dir = get_prices_dir(app_handle)?;
+    let mut symbols = Vec::new();
+    if let Ok(entries) = std::fs::read_dir(&prices_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_file() || path.extension().is_none_or(|e| e != "csv") {
+                continue;
+            }
+            if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                if !stem.ends_with("-override") {
+                    symbols.push(symbol_from_file_stem(stem));
+                }
+            }
+        }
+    }
+    symbols.sort();
+    Ok(symbols)
+}
+
+#[derive(Serialize, Clone)]
+struct LeveragedPosition {
+    symbol: String,
+    leverage_factor: f64,
+    underlying_symbol: String,
+}
+
+/// Leveraged and inverse funds from data/leveraged_positions.csv
+/// (`symbol,leverage_factor,underlying_symbol`, e.g. `TQQQ,3,QQQ`). Rows without a
+/// non-zero factor or an underlying are ignored; later rows win.
+fn load_leveraged_positions(
+    app_handle: &tauri::AppHandle,
+) -> Result<Vec<LeveragedPosition>, String> {
+    let path = get_data_dir(app_handle)?.join("leveraged_positions.csv");
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .comment(Some(b'#'))
+        .trim(csv::Trim::All)
+        .from_path(&path)
+        .map_err(|e| format!("Failed to read leveraged_positions.csv: {}", e))?;
+
+    let mut positions: Vec<LeveragedPosition> = Vec::new();
+    for record in reader.records().flatten() {
+        let symbol = canonical_symbol(record.get(0).unwrap_or(""));
+        let leverage_factor = parse_f64_str(record.get(1).unwrap_or("")).unwrap_or(0.0);
+        let underlying_symbol = canonical_symbol(record.get(2).unwrap_or(""));
+        if symbol.is_empty() || underlying_symbol.is_empty() || leverage_factor == 0.0 {
+            continue;
+        }
+        positions.retain(|p| p.symbol != symbol);
+        positions.push(LeveragedPosition {
+            symbol,
+            leverage_factor,
+            underlying_symbol,
+        });
+    }
+    Ok(positions)
+}
+
+#[derive(Serialize)]
+struct LeverageDecayResult {
+    symbol: String,
+    underlying_symbol: String,
+    leverage_factor: f64,
+    start_date: String,
+    end_date: String,
+    underlying_return: f64,
+    actual_return: f64,
+    theoretical_leveraged_return: f64,
+    /// Theoretical minus actual return, in percentage points.
+    decay_pct: f64,
+}
+
+/// How far a leveraged fund has lagged `leverage_factor` times its underlying's
+/// return since `start_date` (default: the first transaction in the fund, or the
+/// first day both have prices). Returns are fractions over split-adjusted closes,
+/// measured between the first and last days both series have a price.
+#[tauri::command]
+fn calculate_leverage_decay(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+    start_date: Option<String>,
+) -> Result<LeverageDecayResult, AppError> {
+    let symbol = canonical_symbol(symbol.trim());
+    let position = load_leveraged_positions(&app_handle)?
+        .into_iter()
+        .find(|p| p.symbol == symbol)
+        .ok_or_else(|| {
+            AppError::not_found(
+                "leveraged_position",
+                format!("{} is not listed in leveraged_positions.csv", symbol),
+            )
+        })?;
+    let start = match start_date
+        .as_deref()
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+    {
+        Some(raw) => Some(NaiveDate::parse_from_str(raw, "%Y-%m-%d").map_err(|e| {
+            AppError::validation("start_date", format!("Invalid date {}: {}", raw, e))
+        })?),
+        None => load_symbol_transactions(&app_handle, &symbol)
+            .ok()
+            .and_then(|txns| txns.iter().map(|t| t.date).min()),
+    };
+
+    let closes = |symbol: &str| -> Result<HashMap<NaiveDate, f64>, String> {
+        Ok(load_ascending_price_series(&app_handle, symbol)?
+            .into_iter()
+            .filter(|(date, r)| r.close > 0.0 && start.is_none_or(|start| *date >= start))
+            .map(|(date, r)| (date, r.close))
+            .collect())
+    };
+    let fund = closes(&symbol)?;
+    let underlying = closes(&position.underlying_symbol)?;
+    let common: Vec<NaiveDate> = fund
+        .keys()
+        .filter(|date| underlying.contains_key(date))
+        .copied()
+        .collect();
+    let (Some(first), Some(last)) = (common.iter().min(), common.iter().max()) else {
+        return Err(AppError::not_found(
+            "prices",
+            format!(
+                "No common price history for {} and {}",
+                symbol, position.underlying_symbol
+            ),
+        ));
+    };
+
+    let underlying_return = underlying[last] / underlying[first] - 1.0;
+    let actual_return = fund[last] / fund[first] - 1.0;
+    let theoretical_leveraged_return = position.leverage_factor * underlying_return;
+    Ok(LeverageDecayResult {
+        start_date: first.format("%Y-%m-%d").to_string(),
+        end_date: last.format("%Y-%m-%d").to_string(),
+        underlying_return,
+        actual_return,
+        theoretical_leveraged_return,
+        decay_pct: (theoretical_leveraged_return - actual_return) * 100.0,
+        symbol,
+        underlying_symbol: position.underlying_symbol,
+        leverage_factor: position.leverage_factor,
+    })
+}
+
+/// Price series for a symbol in ascending date order, read by header name.
+fn load_ascending_price_series(
+    app_handle: &tauri::AppHandle,
+    symbol: &str,
+) -> Result<Vec<(NaiveDate, PriceRecordEntry)>, String> {
+    let prices_dir = get_prices_dir(app_handle)?;
+    let safe_symbol = symbol_file_stem(symbol);
+    let path = prices_dir.join(format!("{}.csv", safe_symbol));
+    let mut series: Vec<(NaiveDate, PriceRecordEntry)> = cached_price_series(app_handle, &path)?
+        .iter()
+        .map(|record| (record.date, record.clone()))
+        .collect();
+    series.sort_by_key(|(date, _)| *date);
+    Ok(series)
+}
+
+#[derive(Serialize)]
+struct IndexedPricePoint {
+    date: String,
+    values: HashMap<String, Option<f64>>,
+}
+
+/// Closes rebased to 100 on a common start date for comparison charts. Only dates
+/// present in every series are returned; the base is the first such date on or
+/// after `base_date` (or the earliest common date when omitted).
+#[tauri::command]
+fn get_indexed_prices(
+    app_handle: tauri::AppHandle,
+    symbols: Vec<String>,
+    base_date: Option<String>,
+) -> Result<Vec<IndexedPricePoint>, AppError> {
+    let base_date = match base_date.as_deref().map(str::trim) {
+        Some(date) if !date.is_empty() => Some(
+            NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map_err(|_| AppError::validation("base_date", format!("Invalid date {}", date)))?,
+        ),
+        _ => None,
+    };
+    let mut symbols: Vec<String> = symbols
+        .into_iter()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    let mut seen = std::collections::HashSet::new();
+    symbols.retain(|s| seen.insert(s.clone()));
+    if symbols.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut series: Vec<std::collections::BTreeMap<NaiveDate, f64>> = Vec::new();
+    for symbol in &symbols {
+        let closes: std::collections::BTreeMap<NaiveDate, f64> =
+            load_ascending_price_series(&app_handle, symbol)?
+                .into_iter()
+                .filter(|(date, record)| {
+                    record.close > 0.0 && base_date.is_none_or(|base| *date >= base)
+                })
+                .map(|(date, record)| (date, record.close))
+                .collect();
+        if closes.is_empty() {
+            return Err(AppError::not_found(
+                "prices",
+                format!("No prices for {}", symbol),
+            ));
+        }
+        series.push(closes);
+    }
+
+    let common_dates: Vec<NaiveDate> = series[0]
+        .keys()
+        .filter(|date| series[1..].iter().all(|s| s.contains_key(date)))
+        .copied()
+        .collect();
+    let Some(first) = common_dates.first() else {
+        return Ok(Vec::new());
+    };
+    let bases: Vec<f64> = series.iter().map(|s| s[first]).collect();
+
+    Ok(common_dates
+        .iter()
+        .map(|date| IndexedPricePoint {
+            date: date.format("%Y-%m-%d").to_string(),
+            values: symbols
+                .iter()
+                .zip(&series)
+                .zip(&bases)
+                .map(|((symbol, closes), base)| {
+                    (
+                        symbol.clone(),
+                        closes.get(date).map(|close| close / base * 100.0),
+                    )
+                })
+                .collect(),
+        })
+        .collect())
+}
+
+#[derive(Serialize)]
+struct ReturnBin {
+    bin_start: f64,
+    bin_end: f64,
+    count: u32,
+    frequency: f64,
+}
+
+#[derive(Serialize)]
+struct ReturnDistribution {
+    bins: Vec<ReturnBin>,
+    observations: usize,
+    mean: f64,
+    std_dev: f64,
+    skewness: f64,
+    /// Excess kurtosis (0 for a normal distribution).
+    kurtosis: f64,
+}
+
+/// Histogram of daily log returns between the optional dates, in `bins` equal-width
+/// bins (default 30) spanning the smallest to largest return.
+#[tauri::command]
+fn get_return_distribution(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+    bins: Option<u32>,
+    start_date: Option<String>,
+    end_date: Option<String>,
+) -> Result<ReturnDistribution, AppError> {
+    let bins = bins.unwrap_or(30);
+    if bins == 0 {
+        return Err(AppError::validation("bins", "At least one bin is required"));
+    }
+    let parse_bound = |field: &str, value: Option<String>| -> Result<Option<NaiveDate>, AppError> {
+        match value.as_deref().map(str::trim).filter(|v| !v.is_empty()) {
+            Some(v) => NaiveDate::parse_from_str(v, "%Y-%m-%d")
+                .map(Some)
+                .map_err(|_| AppError::validation(field, format!("Invalid date {}", v))),
+            None => Ok(None),
+        }
+    };
+    let start = parse_bound("start_date", start_date)?;
+    let end = parse_bound("end_date", end_date)?;
+
+    let closes: Vec<f64> = load_ascending_price_series(&app_handle, symbol.trim())?
+        .into_iter()
+        .filter(|(date, record)| {
+            record.close > 0.0 && start.is_none_or(|s| *date >= s) && end.is_none_or(|e| *date <= e)
+        })
+        .map(|(_, record)| record.close)
+        .collect();
+    let returns: Vec<f64> = closes.windows(2).map(|w| (w[1] / w[0]).ln()).collect();
+    let n = returns.len();
+    if n == 0 {
+        return Ok(ReturnDistribution {
+            bins: Vec::new(),
+            observations: 0,
+            mean: 0.0,
+            std_dev: 0.0,
+            skewness: 0.0,
+            kurtosis: 0.0,
+        });
+    }
+
+    let mean = returns.iter().sum::<f64>() / n as f64;
+    let moment =
+        |power: i32| returns.iter().map(|r| (r - mean).powi(power)).sum::<f64>() / n as f64;
+    let variance = moment(2);
+    let std_dev = variance.sqrt();
+    let (skewness, kurtosis) = if variance > 0.0 {
+        (
+            moment(3) / variance.powf(1.5),
+            moment(4) / (variance * variance) - 3.0,
+        )
+    } else {
+        (0.0, 0.0)
+    };
+
+    let min = returns.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = returns.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let width = (max - min) / bins as f64;
+    let mut counts = vec![0u32; bins as usize];
+    for r in &returns {
+        let idx = if width > 0.0 {
+            (((r - min) / width) as usize).min(bins as usize - 1)
+        } else {
+            0
+        };
+        counts[idx] += 1;
+    }
+
+    Ok(ReturnDistribution {
+        bins: counts
+            .into_iter()
+            .enumerate()
+            .map(|(i, count)| ReturnBin {
+                bin_start: min + width * i as f64,
+                bin_end: min + width * (i + 1) as f64,
+                count,
+                frequency: count as f64 / n as f64,
+            })
+            .collect(),
+        observations: n,
+        mean,
+        std_dev,
+        skewness,
+        kurtosis,
+    })
+}
+
+#[derive(Serialize, Clone)]
+struct PriceAnomaly {
+    symbol: String,
+    date: String,
+    kind: String,
+    value: f64,
+    context: String,
+}
+
+/// Matches a day-over-day close ratio against clean split ratios (within 2%).
+/// Returns (numerator, denominator): (2, 1) when the price halves, (1, 5) when it
+/// jumps five-fold.
+fn clean_split_ratio(previous_close: f64, close: f64) -> Option<(i32, i32)> {
+    if previous_close <= 0.0 || close <= 0.0 {
+        return None;
+    }
+    let (drop_ratio, reverse) = if close < previous_close {
+        (previous_close / close, false)
+    } else {
+        (close / previous_close, true)
+    };
+
+    let candidates: Vec<(f64, (i32, i32))> = std::iter::once((1.5, (3, 2)))
+        .chain((2..=20).map(|n| (n as f64, (n, 1))))
+        .collect();
+    candidates
+        .into_iter()
+        .find(|(ratio, _)| (drop_ratio / ratio - 1.0).abs() <= 0.02)
+        .map(|(_, (num, den))| if reverse { (den, num) } else { (num, den) })
+}
+
+fn scan_price_anomalies(
+    symbol: &str,
+    series: &[(NaiveDate, PriceRecordEntry)],
+    split_events: &[(NaiveDate, f64)],
+    threshold: f64,
+) -> Vec<PriceAnomaly> {
+    const VOLUME_WINDOW: usize = 20;
+    const VOLUME_SPIKE_MULTIPLE: f64 = 50.0;
+
+    let mut anomalies = Vec::new();
+    let format_date = |d: &NaiveDate| d.format("%Y-%m-%d").to_string();
+
+    for (idx, (date, record)) in series.iter().enumerate() {
+        if record.close <= 0.0 {
+            anomalies.push(PriceAnomaly {
+                symbol: symbol.to_string(),
+                date: format_date(date),
+                kind: "non_positive_close".to_string(),
+                value: record.close,
+                context: format!("close is {}", record.close),
+            });
+            continue;
+        }
+
+        if idx > 0 {
+            let (previous_date, previous) = &series[idx - 1];
+            if previous.close > 0.0 {
+                let change = record.close / previous.close - 1.0;
+                // A split recorded anywhere between the two rows explains the move.
+                let split_nearby = split_events
+                    .iter()
+                    .any(|(split_date, _)| split_date > previous_date && split_date <= date);
+                if change.abs() > threshold && !split_nearby {
+                    let suggested = clean_split_ratio(previous.close, record.close);
+                    let context = match suggested {
+                        Some((num, den)) => format!(
+                            "close {:.4} -> {:.4} ({:+.1}%), matches an unrecorded {}:{} split",
+                            previous.close,
+                            record.close,
+                            change * 100.0,
+                            num,
+                            den
+                        ),
+                        None => format!(
+                            "close {:.4} -> {:.4} ({:+.1}%) since {}",
+                            previous.close,
+                            record.close,
+                            change * 100.0,
+                            format_date(previous_date)
+                        ),
+                    };
+                    anomalies.push(PriceAnomaly {
+                        symbol: symbol.to_string(),
+                        date: format_date(date),
+                        kind: if suggested.is_some() {
+                            "unrecorded_split".to_string()
+                        } else {
+                            "price_jump".to_string()
+                        },
+                        value: change,
+                        context,
+                    });
+                }
+            }
+        }
+
+        if let Some(volume) = record.volume.filter(|v| *v > 0.0) {
+            let mut trailing: Vec<f64> = series[idx.saturating_sub(VOLUME_WINDOW)..idx]
+                .iter()
+                .filter_map(|(_, r)| r.volume.filter(|v| *v > 0.0))
+                .collect();
+            if trailing.len() >= VOLUME_WINDOW / 2 {
+                trailing.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+                let median = trailing[trailing.len() / 2];
+                if median > 0.0 && volume > median * VOLUME_SPIKE_MULTIPLE {
+                    anomalies.push(PriceAnomaly {
+                        symbol: symbol.to_string(),
+                        date: format_date(date),
+                        kind: "volume_spike".to_string(),
+                        value: volume / median,
+                        context: format!(
+                            "volume {:.0} is {:.0}x the trailing {}-day median {:.0}",
+                            volume,
+                            volume / median,
+                            VOLUME_WINDOW,
+                            median
+                        ),
+                    });
+                }
+            }
+        }
+    }
+
+    anomalies
+}
+
+#[tauri::command]
+fn detect_price_anomalies(
+    app_handle: tauri::AppHandle,
+    symbol: Option<String>,
+    threshold: Option<f64>,
+) -> Result<Vec<PriceAnomaly>, AppError> {
+    let threshold = threshold.unwrap_or(0.4);
+    if threshold <= 0.0 {
+        return Err(AppError::validation(
+            "threshold",
+            "Threshold must be positive",
+        ));
+    }
+
+    let symbols = match symbol {
+        Some(symbol) => vec![symbol],
+        None => price_file_symbols(&app_handle)?,
+    };
+
+    let mut anomalies = Vec::new();
+    for symbol in symbols {
+        let series = load_ascending_price_series(&app_handle, &symbol)?;
+        let split_events = load_split_events(&app_handle, &symbol)?;
+        anomalies.extend(scan_price_anomalies(
+            &symbol,
+            &series,
+            &split_events,
+            threshold,
+        ));
+    }
+
+    Ok(anomalies)
+}
+
+#[derive(Serialize, Clone)]
+struct SuggestedSplit {
+    symbol: String,
+    date: String,
+    numerator: i32,
+    denominator: i32,
+    confidence: f64,
+    before_price: f64,
+    after_price: f64,
+}
+
+/// Day-over-day moves that match a clean split ratio with no split recorded in
+/// between. Confidence starts from how tightly the ratio matches and is halved when
+/// the next few closes do not stay at the new level.
+fn find_missing_splits(
+    symbol: &str,
+    series: &[(NaiveDate, PriceRecordEntry)],
+    split_events: &[(NaiveDate, f64)],
+) -> Vec<SuggestedSplit> {
+    const FOLLOW_THROUGH_DAYS: usize = 5;
+
+    let mut suggestions = Vec::new();
+    for idx in 1..series.len() {
+        let (previous_date, previous) = &series[idx - 1];
+        let (date, record) = &series[idx];
+        let Some((numerator, denominator)) = clean_split_ratio(previous.close, record.close) else {
+            continue;
+        };
+        let recorded = split_events.iter().any(|(split_date, ratio)| {
+            split_date > previous_date && split_date <= date && (ratio - 1.0).abs() > 1e-9
+        });
+        if recorded {
+            continue;
+        }
+
+        let ratio = numerator as f64 / denominator as f64;
+        let deviation = (previous.close / record.close / ratio - 1.0).abs();
+        let mut confidence = 1.0 - deviation / 0.02 * 0.5;
+
+        let following: Vec<f64> = series[idx + 1..]
+            .iter()
+            .take(FOLLOW_THROUGH_DAYS)
+            .map(|(_, r)| r.close)
+            .filter(|close| *close > 0.0)
+            .collect();
+        let holds_level = following
+            .iter()
+            .all(|close| (close / record.close - 1.0).abs() <= 0.25);
+        if following.is_empty() || !holds_level {
+            confidence *= 0.5;
+        }
+
+        suggestions.push(SuggestedSplit {
+            symbol: symbol.to_string(),
+            date: date.format("%Y-%m-%d").to_string(),
+            numerator,
+            denominator,
+            confidence: (confidence * 100.0).round() / 100.0,
+            before_price: previous.close,
+            after_price: record.close,
+        });
+    }
+    suggestions
+}
+
+#[tauri::command]
+fn suggest_missing_splits(
+    app_handle: tauri::AppHandle,
+    symbol: Option<String>,
+) -> Result<Vec<SuggestedSplit>, AppError> {
+    let symbols = match symbol {
+        Some(symbol) => vec![symbol],
+        None => price_file_symbols(&app_handle)?,
+    };
+
+    let mut suggestions = Vec::new();
+    for symbol in symbols {
+        let series = load_ascending_price_series(&app_handle, &symbol)?;
+        let split_events = load_split_events(&app_handle, &symbol)?;
+        suggestions.extend(find_missing_splits(&symbol, &series, &split_events));
+    }
+    Ok(suggestions)
+}
+
+/// Rewrites a price file so closes before each split are split-adjusted and
+/// `split_unadjusted_close` holds the price quoted on the day.
+///
+/// `new_split` is the split just recorded: rows before it still carry pre-split
+/// quotes (that is the cliff), so their OHLC is divided by its ratio and volume
+/// scaled up. Older splits are assumed to be adjusted already. Without one, only
+/// `split_unadjusted_close` is re-derived; every other column and the row order
+/// are left as they are.
+fn rewrite_split_adjusted_prices(
+    app_handle: &tauri::AppHandle,
+    path: &Path,
+    split_events: &[(NaiveDate, f64)],
+    new_split: Option<(NaiveDate, f64)>,
+) -> Result<(), String> {
+    with_file_lock(app_handle, path, || {
+        let file = read_raw_price_file(path)?;
+        let content = split_adjusted_price_content(&file, split_events, new_split)?;
+        atomic_write(app_handle, path, &content)
+    })
+}
+
+/// Price file content with the adjustments `rewrite_split_adjusted_prices` describes.
+fn split_adjusted_price_content(
+    file: &RawPriceFile,
+    split_events: &[(NaiveDate, f64)],
+    new_split: Option<(NaiveDate, f64)>,
+) -> Result<Vec<u8>, String> {
+    let column = |name: &str| file.headers.iter().position(|h| h.trim() == name);
+    let date_idx = column("date").unwrap_or(0);
+    let price_columns: Vec<usize> = ["close", "open", "high", "low", "adjusted_close"]
+        .iter()
+        .filter_map(|name| column(name))
+        .collect();
+    let close_idx = column("close").unwrap_or(1);
+    let volume_idx = column("volume");
+    let unadjusted_idx = column("split_unadjusted_close");
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record(&file.headers)
+        .map_err(|e| format!("Failed to write header: {}", e))?;
+    for (_, record) in &file.rows {
+        let mut fields: Vec<String> = record.iter().map(str::to_string).collect();
+        let date = match NaiveDate::parse_from_str(
+            fields
+                .get(date_idx)
+                .map(String::as_str)
+                .unwrap_or("")
+                .trim(),
+            "%Y-%m-%d",
+        ) {
+            Ok(d) => d,
+            Err(_) => {
+                writer
+                    .write_record(&fields)
+                    .map_err(|e| format!("Failed to write row: {}", e))?;
+                continue;
+            }
+        };
+
+        if let Some((_, split_ratio)) = new_split.filter(|(d, _)| date < *d) {
+            for idx in &price_columns {
+                if let Some(value) = fields.get(*idx).and_then(|v| parse_f64_str(v)) {
+                    fields[*idx] = (value / split_ratio).to_string();
+                }
+            }
+            if let Some(idx) = volume_idx {
+                if let Some(volume) = fields.get(idx).and_then(|v| parse_f64_str(v)) {
+                    fields[idx] = (volume * split_ratio).round().to_string();
+                }
+            }
+        }
+
+        if let Some(idx) = unadjusted_idx.filter(|idx| *idx < fields.len()) {
+            if let Some(close) = fields.get(close_idx).and_then(|v| parse_f64_str(v)) {
+                fields[idx] = (close * split_unadjust_factor(date, split_events)).to_string();
+            }
+        }
+
+        writer
+            .write_record(&fields)
+            .map_err(|e| format!("Failed to write row: {}", e))?;
+    }
+
+    writer
+        .into_inner()
+        .map_err(|e| format!("Failed to build price file: {}", e))
+}
+
+/// Re-derives `split_unadjusted_close` from the current split file for one symbol,
+/// or every price file when `symbol` is omitted, so files downloaded before a split
+/// was known match ones downloaded after it. Returns the number of files rewritten.
+#[tauri::command]
+fn recompute_split_adjustments(
+    app_handle: tauri::AppHandle,
+    symbol: Option<String>,
+) -> Result<usize, AppError> {
+    let symbols = match symbol.map(|s| s.trim().to_string()) {
+        Some(s) if !s.is_empty() => vec![s],
+        _ => price_file_symbols(&app_handle)?,
+    };
+    let prices_dir = get_prices_dir(&app_handle)?;
+    let mut rewritten = 0;
+    for symbol in &symbols {
+        let price_path = prices_dir.join(format!("{}.csv", symbol_file_stem(symbol)));
+        if !price_path.exists() {
+            continue;
+        }
+        let split_events = load_split_events(&app_handle, symbol)?;
+        rewrite_split_adjusted_prices(&app_handle, &price_path, &split_events, None)?;
+        rewritten += 1;
+    }
+    if rewritten > 0 {
+        invalidate_coverage_cache(&app_handle);
+    }
+    write_worker_log(
+        &app_handle,
+        &format!(
+            "Recomputed split-unadjusted closes for {} price files",
+            rewritten
+        ),
+    )?;
+    Ok(rewritten)
+}
+
+#[tauri::command]
+fn apply_suggested_split(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+    date: String,
+    num: i32,
+    den: i32,
+) -> Result<(), AppError> {
+    if num <= 0 || den <= 0 || num == den {
+        return Err(AppError::validation(
+            "ratio",
+            format!("Invalid split ratio {}:{}", num, den),
+        ));
+    }
+    let split_date = NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+        .map_err(|e| format!("Invalid split date {}: {}", date, e))?;
+
+    let existing = load_split_events(&app_handle, &symbol)?;
+    if existing
+        .iter()
+        .any(|(d, ratio)| *d == split_date && (ratio - 1.0).abs() > 1e-9)
+    {
+        return Err(AppError::validation(
+            "date",
+            format!("{} already has a split recorded on {}", symbol, split_date),
+        ));
+    }
+
+    let series = load_ascending_price_series(&app_handle, &symbol)?;
+    let after_price = series
+        .iter()
+        .find(|(d, _)| *d >= split_date)
+        .map(|(_, r)| r.close.to_string())
+        .unwrap_or_default();
+    let before_price = series
+        .iter()
+        .rev()
+        .find(|(d, _)| *d < split_date)
+        .map(|(_, r)| r.close.to_string())
+        .unwrap_or_default();
+
+    let safe_symbol = symbol_file_stem(&symbol);
+    let split_path = get_splits_dir(&app_handle)?.join(format!("{}.csv", safe_symbol));
+    with_file_lock(&app_handle, &split_path, || -> Result<(), String> {
+        let mut split_content = if split_path.exists() {
+            let existing = read_to_string(&split_path)
+                .map_err(|e| format!("Failed to read split file for '{}': {}", symbol, e))?;
+            convert_legacy_split_content(&existing).unwrap_or(existing)
+        } else {
+            SPLIT_FILE_HEADER.to_string()
+        };
+        if !split_content.ends_with('\n') {
+            split_content.push('\n');
+        }
+        split_content.push_str(&format!(
+            "{},{},{},{},{}\n",
+            split_date.format("%Y-%m-%d"),
+            num,
+            den,
+            before_price,
+            after_price
+        ));
+        atomic_write(&app_handle, &split_path, split_content.as_bytes())
+            .map_err(|e| format!("Failed to write split file for '{}': {}", symbol, e))?;
+        Ok(())
+    })?;
+
+    let price_path = get_prices_dir(&app_handle)?.join(format!("{}.csv", safe_symbol));
+    if price_path.exists() {
+        let split_events = load_split_events(&app_handle, &symbol)?;
+        rewrite_split_adjusted_prices(
+            &app_handle,
+            &price_path,
+            &split_events,
+            Some((split_date, num as f64 / den as f64)),
+        )?;
+    }
+
+    Ok(write_worker_log(
+        &app_handle,
+        &format!(
+            "Recorded {}:{} split for {} on {} and re-adjusted prices",
+            num, den, symbol, split_date
+        ),
+    )?)
+}
+
+#[derive(Serialize, Clone)]
+struct AdjustedCloseDivergence {
+    date: String,
+    column: String,
+    stored: f64,
+    expected: f64,
+    deviation: f64,
+}
+
+#[derive(Serialize)]
+struct AdjustedCloseAudit {
+    symbol: String,
+    rows_checked: usize,
+    max_adjusted_deviation: f64,
+    max_unadjusted_deviation: f64,
+    divergences: Vec<AdjustedCloseDivergence>,
+    repaired: bool,
+}
+
+/// Dividend adjustment multipliers in the Yahoo convention: each ex-date scales
+/// earlier closes by `1 - amount / close before the ex-date`.
+fn dividend_multipliers(
+    closes: &[(NaiveDate, f64)],
+    dividends: &[(NaiveDate, f64, String)],
+) -> Vec<(NaiveDate, f64)> {
+    dividends
+        .iter()
+        .filter_map(|(ex_date, amount, _)| {
+            let previous_close = closes
+                .iter()
+                .rev()
+                .find(|(date, close)| date < ex_date && *close > 0.0)
+                .map(|(_, close)| *close)?;
+            let multiplier = 1.0 - amount / previous_close;
+            (multiplier > 0.0 && multiplier < 1.0).then_some((*ex_date, multiplier))
+        })
+        .collect()
+}
+
+fn audit_price_file(
+    app_handle: &tauri::AppHandle,
+    symbol: &str,
+    tolerance: f64,
+    repair: bool,
+) -> Result<AdjustedCloseAudit, String> {
+    let safe_symbol = symbol_file_stem(symbol);
+    let path = get_prices_dir(app_handle)?.join(format!("{}.csv", safe_symbol));
+    with_file_lock(app_handle, &path, || {
+        let file = read_raw_price_file(&path)?;
+        let column = |name: &str| file.headers.iter().position(|h| h.trim() == name);
+        let date_idx = column("date").unwrap_or(0);
+        let close_idx = column("close").unwrap_or(1);
+        let adjusted_idx = column("adjusted_close");
+        let unadjusted_idx = column("split_unadjusted_close");
+
+        let parse_row = |record: &csv::StringRecord| -> Option<(NaiveDate, f64)> {
+            let date = NaiveDate::parse_from_str(record.get(date_idx)?.trim(), "%Y-%m-%d").ok()?;
+            let close = parse_f64_str(record.get(close_idx)?)?;
+            Some((date, close))
+        };
+        let mut closes: Vec<(NaiveDate, f64)> =
+            file.rows.iter().filter_map(|(_, r)| parse_row(r)).collect();
+        closes.sort_by_key(|(date, _)| *date);
+
+        let split_events = load_split_events(app_handle, symbol)?;
+        let multipliers = dividend_multipliers(&closes, &load_dividend_events(app_handle, symbol)?);
+
+        let mut audit = AdjustedCloseAudit {
+            symbol: symbol.to_string(),
+            rows_checked: 0,
+            max_adjusted_deviation: 0.0,
+            max_unadjusted_deviation: 0.0,
+            divergences: Vec::new(),
+            repaired: false,
+        };
+        let mut repaired_rows = Vec::with_capacity(file.rows.len());
+
+        for (_, record) in &file.rows {
+            let mut fields: Vec<String> = record.iter().map(str::to_string).collect();
+            let Some((date, close)) = parse_row(record).filter(|(_, close)| *close > 0.0) else {
+                repaired_rows.push(fields);
+                continue;
+            };
+            audit.rows_checked += 1;
+
+            let expected_adjusted = close
+                * multipliers
+                    .iter()
+                    .filter(|(ex_date, _)| date < *ex_date)
+                    .map(|(_, m)| *m)
+                    .product::<f64>();
+            let expected_unadjusted = close * split_unadjust_factor(date, &split_events);
+
+            for (idx, name, expected) in [
+                (adjusted_idx, "adjusted_close", expected_adjusted),
+                (
+                    unadjusted_idx,
+                    "split_unadjusted_close",
+                    expected_unadjusted,
+                ),
+            ] {
+                let Some(idx) = idx.filter(|idx| *idx < fields.len()) else {
+                    continue;
+                };
+                let deviation = match parse_f64_str(&fields[idx]) {
+                    Some(stored) => {
+                        let deviation = (stored / expected - 1.0).abs();
+                        if name == "adjusted_close" {
+                            audit.max_adjusted_deviation =
+                                audit.max_adjusted_deviation.max(deviation);
+                        } else {
+                            audit.max_unadjusted_deviation =
+                                audit.max_unadjusted_deviation.max(deviation);
+                        }
+                        if deviation > tolerance {
+                            audit.divergences.push(AdjustedCloseDivergence {
+                                date: date.format("%Y-%m-%d").to_string(),
+                                column: name.to_string(),
+                                stored,
+                                expected,
+                                deviation,
+                            });
+                        }
+                        deviation
+                    }
+                    // Empty cells are filled on repair but are not a divergence.
+                    None => f64::INFINITY,
+                };
+                if repair && deviation > tolerance {
+                    fields[idx] = expected.to_string();
+                    audit.repaired = true;
+                }
+            }
+            repaired_rows.push(fields);
+        }
+
+        if audit.repaired {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            writer
+                .write_record(&file.headers)
+                .map_err(|e| format!("Failed to write header: {}", e))?;
+            for fields in &repaired_rows {
+                writer
+                    .write_record(fields)
+                    .map_err(|e| format!("Failed to write row: {}", e))?;
+            }
+            let content = writer
+                .into_inner()
+                .map_err(|e| format!("Failed to build price file: {}", e))?;
+            atomic_write(app_handle, &path, &content)?;
+        }
+
+        audit.divergences.sort_by(|a, b| a.date.cmp(&b.date));
+        Ok(audit)
+    })
+}
+
+#[tauri::command]
+fn audit_adjusted_closes(
+    app_handle: tauri::AppHandle,
+    symbol: Option<String>,
+    tolerance: Option<f64>,
+    repair: Option<bool>,
+) -> Result<Vec<AdjustedCloseAudit>, AppError> {
+    let tolerance = tolerance.unwrap_or(0.005);
+    if tolerance <= 0.0 {
+        return Err(AppError::validation(
+            "tolerance",
+            "Tolerance must be positive",
+        ));
+    }
+    let repair = repair.unwrap_or(false);
+
+    let symbols = match symbol {
+        Some(symbol) => vec![symbol],
+        None => price_file_symbols(&app_handle)?,
+    };
+
+    symbols
+        .iter()
+        .map(|symbol| audit_price_file(&app_handle, symbol, tolerance, repair))
+        .collect::<Result<Vec<_>, String>>()
+        .map_err(AppError::from)
+}
+
+#[tauri::command]
+fn get_data_stats(
+    app_handle: tauri::AppHandle,
+    force_refresh: Option<bool>,
+) -> Result<DataReadinessStats, AppError> {
+    let fingerprint = data_fingerprint(&app_handle)?;
+    let cache = app_handle.try_state::<CoverageCache>();
+    if let Some(cache) = cache.as_ref().filter(|_| !force_refresh.unwrap_or(false)) {
+        if let Some((cached_fingerprint, stats)) = cache
+            .stats
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+        {
+            if *cached_fingerprint == fingerprint {
+                return Ok(with_sync_health(&app_handle, stats.clone()));
+            }
+        }
+    }
+
+    let transactions = load_all_transactions(&app_handle)?;
+    let price_frames = load_price_frames(&app_handle)?;
+
+    let unique_stocks: std::collections::HashSet<String> =
+        transactions.iter().map(|t| t.stock.clone()).collect();
+
+    let spans: Vec<(NaiveDate, NaiveDate)> =
+        price_frames.values().filter_map(price_frame_span).collect();
+    let oldest_date = spans
+        .iter()
+        .map(|(earliest, _)| *earliest)
+        .min()
+        .map(|d| d.format("%Y-%m-%d").to_string());
+
+    let newest_date = spans
+        .iter()
+        .map(|(_, latest)| *latest)
+        .max()
+        .map(|d| d.format("%Y-%m-%d").to_string());
+
+    let coverage = build_data_coverage(&app_handle, &transactions, &price_frames, true)?;
+
+    let complete_data = coverage.iter().filter(|c| c.status == "complete").count() as i32;
+    let partial_data = coverage.iter().filter(|c| c.status == "partial").count() as i32;
+    let missing_data = coverage.iter().filter(|c| c.status == "missing").count() as i32;
+    let stale_data = coverage.iter().filter(|c| c.stale).count() as i32;
+
+    let stats = DataReadinessStats {
+        total_stocks: unique_stocks.len() as i32,
+        complete_data,
+        partial_data,
+        missing_data,
+        stale_data,
+        total_price_records: price_frames
+            .values()
+            .map(|frame| frame.height() as i32)
+            .sum(),
+        oldest_date,
+        newest_date,
+        markets: market_breakdown(&app_handle, &coverage, &price_frames)?,
+        stale_symbols: coverage
+            .iter()
+            .filter(|c| c.days_since_latest_price.is_some_and(|days| days > 2))
+            .count(),
+        symbols_with_gaps: coverage.iter().filter(|c| c.missing_days > 0).count(),
+        total_dividend_files: count_csv_files(&get_dividends_dir(&app_handle)?),
+        total_split_files: count_csv_files(&get_splits_dir(&app_handle)?),
+        ..Default::default()
+    };
+
+    if let Some(cache) = cache {
+        *cache.stats.lock().unwrap_or_else(|e| e.into_inner()) = Some((fingerprint, stats.clone()));
+        cache
+            .coverage
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(true, (fingerprint, coverage));
+    }
+
+    Ok(with_sync_health(&app_handle, stats))
+}
+
+fn dir_size_bytes(dir: &Path) -> u64 {
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| match entry.metadata() {
+                    Ok(meta) if meta.is_dir() => dir_size_bytes(&entry.path()),
+                    Ok(meta) => meta.len(),
+                    Err(_) => 0,
+                })
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+/// Fills the stats fields that change without touching the data files, so cached
+/// stats still report the current sync state and disk usage.
+fn with_sync_health(
+    app_handle: &tauri::AppHandle,
+    stats: DataReadinessStats,
+) -> DataReadinessStats {
+    DataReadinessStats {
+        last_sync_at: last_sync_at(app_handle),
+        sync_in_progress: app_handle.try_state::<PortfolioState>().is_some_and(|s| {
+            s.inner
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .sync_active
+        }),
+        disk_usage_bytes: get_data_dir(app_handle)
+            .map(|dir| dir_size_bytes(&dir))
+            .unwrap_or(0),
+        ..stats
+    }
+}
+
+/// Pre-serialized form of `get_data_stats`, kept for one release for older frontends.
+#[tauri::command]
+fn get_data_stats_json(
+    app_handle: tauri::AppHandle,
+    force_refresh: Option<bool>,
+) -> Result<String, AppError> {
+    let stats = get_data_stats(app_handle, force_refresh)?;
+    Ok(serde_json::to_string(&stats).map_err(|e| format!("Failed to serialize stats: {}", e))?)
+}
+
+fn cash_positions_path(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+    Ok(get_data_dir(app_handle)?.join("cash_positions.csv"))
+}
+
+fn load_cash_positions(app_handle: &tauri::AppHandle) -> Result<Vec<CashPosition>, String> {
+    let path = cash_positions_path(app_handle)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .trim(csv::Trim::All)
+        .from_path(&path)
+        .map_err(|e| format!("Failed to read cash_positions.csv: {}", e))?;
+
+    reader
+        .deserialize::<CashPosition>()
+        .map(|result| result.map_err(|e| format!("Invalid row in cash_positions.csv: {}", e)))
+        .collect()
+}
+
+/// Total of all cash balances in `base` at `date`.
+fn cash_total(
+    app_handle: &tauri::AppHandle,
+    base: &str,
+    date: NaiveDate,
+    fx: &mut FxConverter,
+) -> Result<f64, String> {
+    let mut total = 0.0;
+    for cash in load_cash_positions(app_handle)? {
+        total += fx.convert(cash.amount, &cash.currency, base, date)?;
+    }
+    Ok(total)
+}
+
+/// Sets the cash balance held in `currency`, per `account` when given, replacing
+/// any previous balance for that pair. A zero amount removes the balance; negative
+/// amounts are kept so margin debt lowers the NAV.
+#[tauri::command]
+fn update_cash_balance(
+    app_handle: tauri::AppHandle,
+    currency: String,
+    amount: f64,
+    account: Option<String>,
+) -> Result<Vec<CashPosition>, AppError> {
+    let currency = validate_currency_code("currency", &currency)?;
+    if !amount.is_finite() {
+        return Err(AppError::validation("amount", "Amount must be a number"));
+    }
+    let account = account
+        .map(|a| a.trim().to_string())
+        .filter(|a| !a.is_empty());
+
+    let path = cash_positions_path(&app_handle)?;
+    let positions = with_file_lock(&app_handle, &path, || -> Result<_, AppError> {
+        let mut positions = load_cash_positions(&app_handle)?;
+        positions.retain(|p| !(p.currency == currency && p.account == account));
+        if amount != 0.0 {
+            positions.push(CashPosition {
+                currency: currency.clone(),
+                amount,
+                account: account.clone(),
+            });
+        }
+        positions.sort_by(|a, b| {
+            a.currency
+                .cmp(&b.currency)
+                .then_with(|| a.account.cmp(&b.account))
+        });
+
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        // Written explicitly so an empty file still has its header.
+        writer
+            .write_record(["currency", "amount", "account"])
+            .map_err(|e| format!("Failed to build cash_positions.csv: {}", e))?;
+        for p in &positions {
+            writer
+                .write_record([
+                    p.currency.clone(),
+                    p.amount.to_string(),
+                    p.account.clone().unwrap_or_default(),
+                ])
+                .map_err(|e| format!("Failed to build cash_positions.csv: {}", e))?;
+        }
+        let content = writer
+            .into_inner()
+            .map_err(|e| format!("Failed to build cash_positions.csv: {}", e))?;
+        atomic_write(&app_handle, &path, &content)?;
+        Ok(positions)
+    })?;
+
+    write_worker_log(
+        &app_handle,
+        &format!(
+            "Cash balance {}{} set to {}",
+            currency,
+            account
+                .as_deref()
+                .map(|a| format!(" ({})", a))
+                .unwrap_or_default(),
+            amount
+        ),
+    )?;
+    Ok(positions)
+}
+
+#[tauri::command]
+fn get_cash_balances(app_handle: tauri::AppHandle) -> Result<Vec<CashPosition>, AppError> {
+    Ok(load_cash_positions(&app_handle)?)
+}
+
+/// Writes a NAV snapshot. When the payload carries no cash positions and is dated
+/// today or later, the stored balances are attached and their USD value is added
+/// to `total_value_usd`, so the NAV covers uninvested cash. Only current balances
+/// are stored, so backdated snapshots get no cash rather than today's balances;
+/// a payload with cash positions is saved as sent.
+#[tauri::command]
+fn save_nav_snapshot(
+    app_handle: tauri::AppHandle,
+    mut snapshot: NavSnapshotPayload,
+) -> Result<String, AppError> {
+    let today = Utc::now().date_naive();
+    let date = DateTime::parse_from_rfc3339(&snapshot.timestamp)
+        .map(|dt| dt.date_naive())
+        .unwrap_or(today);
+    if snapshot.cash_positions.is_empty() && date >= today {
+        let mut fx = FxConverter::new(&app_handle);
+        snapshot.total_value_usd += cash_total(&app_handle, "USD", date, &mut fx)?;
+        snapshot.cash_positions = load_cash_positions(&app_handle)?;
+    }
+    let navs_dir = get_navs_dir(&app_handle)?;
+    let safe_id = sanitize_timestamp(&snapshot.timestamp);
+    let file_path = navs_dir.join(format!("nav_{}.json", safe_id));
+    let content = serde_json::to_string_pretty(&snapshot)
+        .map_err(|e| format!("Failed to serialize NAV snapshot: {}", e))?;
+
+    write(&file_path, content).map_err(|e| format!("Failed to write NAV snapshot: {}", e))?;
+
+    Ok(file_path.to_string_lossy().to_string())
+}
+
+const NAV_HISTORY_FILE: &str = "nav_history.csv";
+const NAV_HISTORY_HEADER: &str = "timestamp,total_value_usd,base_currency\n";
+/// Snapshot JSON files younger than this stay on disk after compaction.
+const NAV_SNAPSHOT_RETENTION_DAYS: i64 = 30;
+
+/// Portfolio-level fields of a NAV snapshot, one row of navs/nav_history.csv.
+#[derive(Serialize, Deserialize)]
+struct NavSnapshotSummary {
+    timestamp: String,
+    total_value_usd: f64,
+    base_currency: String,
+}
+
+#[derive(Serialize)]
+struct NavCompactionResult {
+    snapshots_merged: usize,
+    files_removed: usize,
+}
+
+fn read_nav_history(path: &Path) -> Result<Vec<NavSnapshotSummary>, String> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_path(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    reader
+        .deserialize()
+        .map(|row| row.map_err(|e| format!("Failed to parse {}: {}", path.display(), e)))
+        .collect()
+}
+
+/// Folds `nav_*.json` snapshots into navs/nav_history.csv, skipping timestamps
+/// already there, then deletes snapshot files older than
+/// `NAV_SNAPSHOT_RETENTION_DAYS`. Files that fail to parse are left alone.
+#[tauri::command]
+fn compact_nav_snapshots(app_handle: tauri::AppHandle) -> Result<NavCompactionResult, AppError> {
+    let navs_dir = get_navs_dir(&app_handle)?;
+    let history_path = navs_dir.join(NAV_HISTORY_FILE);
+    let mut known: std::collections::HashSet<String> = read_nav_history(&history_path)?
+        .into_iter()
+        .map(|row| row.timestamp)
+        .collect();
+
+    let mut snapshot_files: Vec<PathBuf> = std::fs::read_dir(&navs_dir)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("nav_") && name.ends_with(".json"))
+        })
+        .collect();
+    snapshot_files.sort();
+
+    let cutoff = Utc::now() - ChronoDuration::days(NAV_SNAPSHOT_RETENTION_DAYS);
+    let mut new_rows: Vec<NavSnapshotSummary> = Vec::new();
+    let mut expired: Vec<PathBuf> = Vec::new();
+    for path in snapshot_files {
+        let summary: NavSnapshotSummary = match read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| serde_json::from_str(&content).map_err(|e| e.to_string()))
+        {
+            Ok(summary) => summary,
+            Err(e) => {
+                eprintln!("[RUST] Skipping NAV snapshot {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        let taken_at = DateTime::parse_from_rfc3339(&summary.timestamp)
+            .map(|dt| dt.with_timezone(&Utc))
+            .ok();
+        if taken_at.is_some_and(|dt| dt < cutoff) {
+            expired.push(path);
+        }
+        if known.insert(summary.timestamp.clone()) {
+            new_rows.push(summary);
+        }
+    }
+
+    new_rows.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    let rows: Vec<Vec<String>> = new_rows
+        .iter()
+        .map(|row| {
+            vec![
+                row.timestamp.clone(),
+                row.total_value_usd.to_string(),
+                row.base_currency.clone(),
+            ]
+        })
+        .collect();
+    append_csv_rows(&app_handle, &history_path, NAV_HISTORY_HEADER, &rows)?;
+
+    for path in &expired {
+        std::fs::remove_file(path)?;
+    }
+
+    write_worker_log(
+        &app_handle,
+        &format!(
+            "Compacted NAV snapshots: {} merged, {} files removed",
+            rows.len(),
+            expired.len()
+        ),
+    )?;
+    Ok(NavCompactionResult {
+        snapshots_merged: rows.len(),
+        files_removed: expired.len(),
+    })
+}
+
+/// Portfolio NAV history from navs/nav_history.csv, newest first, capped at `limit`.
+#[tauri::command]
+fn get_nav_snapshots(
+    app_handle: tauri::AppHandle,
+    limit: Option<usize>,
+) -> Result<Vec<NavSnapshotSummary>, AppError> {
+    let mut rows = read_nav_history(&get_navs_dir(&app_handle)?.join(NAV_HISTORY_FILE))?;
+    rows.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    if let Some(limit) = limit {
+        rows.truncate(limit);
+    }
+    Ok(rows)
+}
+
+#[derive(Serialize)]
+struct PositionNavResult {
+    path: String,
+    rows: usize,
+    /// Trades that fell outside the price history, see `build_position_timeline`,
+    /// and snapshot figures that disagree with the derived ones.
+    warnings: Vec<String>,
+    shares: f64,
+    /// Average cost per share including fees, adjusted for splits.
+    average_cost: f64,
+    latest_price: f64,
+    market_value: f64,
+    /// `open` while shares are held, `closed` otherwise.
+    status: String,
+}
+
+/// Relative tolerance for snapshot figures sent by the frontend.
+const SNAPSHOT_TOLERANCE: f64 = 0.005;
+
+#[tauri::command]
+fn save_position_snapshot(
+    app_handle: tauri::AppHandle,
+    snapshot: PositionSnapshotPayload,
+) -> Result<PositionNavResult, AppError> {
+    let mut result = write_position_nav(&app_handle, &snapshot.stock, &snapshot.currency)?;
+
+    let checks = [
+        ("shares", snapshot.shares, result.shares),
+        ("average_cost", snapshot.average_cost, result.average_cost),
+        ("latest_price", snapshot.latest_price, result.latest_price),
+        ("market_value", snapshot.market_value, result.market_value),
+    ];
+    for (field, sent, derived) in checks {
+        let Some(sent) = sent else {
+            continue;
+        };
+        if (sent - derived).abs() > SNAPSHOT_TOLERANCE * derived.abs().max(1e-9) {
+            result.warnings.push(format!(
+                "Snapshot {} {} differs from the computed {} by more than {}%",
+                field,
+                sent,
+                derived,
+                SNAPSHOT_TOLERANCE * 100.0
+            ));
+        }
+    }
+    if let Some(status) = snapshot
+        .status
+        .filter(|status| !status.trim().eq_ignore_ascii_case(&result.status))
+    {
+        result.warnings.push(format!(
+            "Snapshot status '{}' differs from the computed '{}'",
+            status, result.status
+        ));
+    }
+    if !result.warnings.is_empty() {
+        write_worker_log(
+            &app_handle,
+            &format!(
+                "Position snapshot for {}: {}",
+                snapshot.stock,
+                result.warnings.join("; ")
+            ),
+        )?;
+    }
+    Ok(result)
+}
+
+/// Rebuilds navs/<symbol>.csv from the symbol's transactions and price history.
+/// `fallback_currency` is used only when the transactions do not carry one.
+fn write_position_nav(
+    app_handle: &tauri::AppHandle,
+    symbol: &str,
+    fallback_currency: &str,
+) -> Result<PositionNavResult, AppError> {
+    let navs_dir = get_navs_dir(app_handle)?;
+    let symbol = symbol.to_string();
+
+    let transactions = load_symbol_transactions(app_handle, &symbol)?;
+    let currency = transactions
+        .first()
+        .map(|t| t.currency.clone())
+        .unwrap_or_else(|| fallback_currency.to_string());
+    let mut prices = load_price_history_for_symbol(app_handle, &symbol)?;
+
+    if let Some(first_txn_date) = transactions.first().map(|t| t.date) {
+        prices.retain(|record| record.date >= first_txn_date);
+    }
+
+    if prices.is_empty() {
+        return Err(AppError::not_found(
+            symbol.clone(),
+            format!("No price history available for {}", symbol),
+        ));
+    }
+
+    let (mut timeline, warnings) = build_position_timeline(&prices, &transactions);
+    if timeline.is_empty() {
+        return Err(format!("Failed to calculate position history for {}", symbol).into());
+    }
+
+    timeline.sort_by(|a, b| CANONICAL_ROW_ORDER.compare(&a.0, &b.0));
+
+    let dates: Vec<String> = timeline.iter().map(|(d, _, _)| d.clone()).collect();
+    let closes: Vec<f64> = timeline.iter().map(|(_, close, _)| *close).collect();
+    let shares_vec: Vec<f64> = timeline.iter().map(|(_, _, shares)| *shares).collect();
+    // Cost basis as of each row; empty while nothing is held.
+    let costs: Vec<Option<f64>> = timeline
+        .iter()
+        .map(|(date, _, shares)| {
+            let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+            let (_, cost, _) = replay_average_cost(&transactions, date);
+            (*shares > 1e-9).then_some(cost)
+        })
+        .collect();
+    let avg_costs: Vec<Option<f64>> = costs
+        .iter()
+        .zip(&shares_vec)
+        .map(|(cost, shares)| cost.map(|c| c / shares))
+        .collect();
+    let unrealized: Vec<Option<f64>> = costs
+        .iter()
+        .zip(closes.iter().zip(&shares_vec))
+        .map(|(cost, (close, shares))| cost.map(|c| close * shares - c))
+        .collect();
+
+    let base_df = DataFrame::new(vec![
+        Series::new("date", dates),
+        Series::new("close", closes),
+        Series::new("shares", shares_vec),
+        Series::new("avg_cost", avg_costs),
+        Series::new("unrealized_gain", unrealized),
+    ])
+    .map_err(|e| format!("Failed to build dataframe: {}", e))?;
+
+    let mut calculated = position_nav_frame(base_df, &currency, &symbol)?;
+
+    let safe_symbol = symbol_file_stem(&symbol);
+    let file_path = navs_dir.join(format!("{}.csv", safe_symbol));
+    let mut file =
+        File::create(&file_path).map_err(|e| format!("Failed to create {:?}: {}", file_path, e))?;
+
+    CsvWriter::new(&mut file)
+        .include_header(true)
+        .finish(&mut calculated)
+        .map_err(|e| format!("Failed to write CSV: {}", e))?;
+
+    let today = Utc::now().date_naive();
+    let (shares, cost, _) = replay_average_cost(&transactions, today);
+    let latest_price = prices.last().map(|p| p.close).unwrap_or(0.0);
+    let held = shares > 1e-9;
+    Ok(PositionNavResult {
+        path: file_path.to_string_lossy().to_string(),
+        rows: calculated.height(),
+        warnings,
+        shares,
+        average_cost: if held { cost / shares } else { 0.0 },
+        latest_price,
+        market_value: if held { shares * latest_price } else { 0.0 },
+        status: if held { "open" } else { "closed" }.to_string(),
+    })
+}
+
+/// Adds `position_value`, `currency` and `symbol` to a NAV base frame in one lazy
+/// plan, so only the final frame is materialized; the literal columns broadcast
+/// instead of allocating a repeated Vec each.
+fn position_nav_frame(base: DataFrame, currency: &str, symbol: &str) -> Result<DataFrame, String> {
+    base.lazy()
+        .with_columns([
+            (col("close") * col("shares")).alias("position_value"),
+            lit(currency).alias("currency"),
+            lit(symbol).alias("symbol"),
+        ])
+        .collect()
+        .map_err(|e| format!("Failed to evaluate dataframe: {}", e))
+}
+
+#[derive(Serialize)]
+struct NavRebuildOutcome {
+    symbol: String,
+    status: String,
+    rows: usize,
+    reason: Option<String>,
+}
+
+#[derive(Serialize, Clone)]
+struct NavRebuildProgress {
+    symbol: String,
+    completed: usize,
+    total: usize,
+}
+
+/// Regenerates every traded symbol's NAV file, emitting `rebuild_navs_progress`
+/// after each one. Symbols without a price file are skipped; other failures are
+/// reported per symbol and do not stop the run.
+fn rebuild_navs(app_handle: &tauri::AppHandle) -> Result<Vec<NavRebuildOutcome>, String> {
+    let mut symbols: Vec<String> = load_all_transactions(app_handle)?
+        .iter()
+        .map(|txn| txn.stock.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    symbols.sort();
+    symbols.dedup();
+
+    let prices_dir = get_prices_dir(app_handle)?;
+    let total = symbols.len();
+    let mut outcomes = Vec::with_capacity(total);
+    for (idx, symbol) in symbols.into_iter().enumerate() {
+        let price_path = prices_dir.join(format!("{}.csv", symbol_file_stem(&symbol)));
+        let outcome = if !price_path.exists() {
+            NavRebuildOutcome {
+                symbol: symbol.clone(),
+                status: "skipped".to_string(),
+                rows: 0,
+                reason: Some("No price file".to_string()),
+            }
+        } else {
+            match write_position_nav(app_handle, &symbol, "USD") {
+                Ok(result) => NavRebuildOutcome {
+                    symbol: symbol.clone(),
+                    status: "rebuilt".to_string(),
+                    rows: result.rows,
+                    reason: (!result.warnings.is_empty()).then(|| result.warnings.join("; ")),
+                },
+                Err(err) => NavRebuildOutcome {
+                    symbol: symbol.clone(),
+                    status: "failed".to_string(),
+                    rows: 0,
+                    reason: Some(err.to_string()),
+                },
+            }
+        };
+        outcomes.push(outcome);
+        let _ = app_handle.emit_all(
+            "rebuild_navs_progress",
+            NavRebuildProgress {
+                symbol,
+                completed: idx + 1,
+                total,
+            },
+        );
+    }
+    Ok(outcomes)
+}
+
+#[tauri::command]
+fn rebuild_all_navs(app_handle: tauri::AppHandle) -> Result<Vec<NavRebuildOutcome>, AppError> {
+    let outcomes = rebuild_navs(&app_handle)?;
+    let rebuilt = outcomes.iter().filter(|o| o.status == "rebuilt").count();
+    write_worker_log(
+        &app_handle,
+        &format!("Rebuilt {} of {} NAV files", rebuilt, outcomes.len()),
+    )?;
+    Ok(outcomes)
+}
+
+const DEMO_SOURCE: &str = "demo";
+const DEMO_MANIFEST_FILE: &str = "demo_manifest.json";
+
+/// Symbols, markets and starting prices for the demo portfolio. Tickers are
+/// fictional so they never collide with real holdings or Yahoo data.
+const DEMO_SYMBOLS: [(&str, &str, f64); 9] = [
+    ("DEMOA", "USD", 120.0),
+    ("DEMOB", "USD", 340.0),
+    ("DEMOC", "USD", 45.0),
+    ("TWSE:9901", "TWD", 420.0),
+    ("TWSE:9902", "TWD", 88.0),
+    ("JPX:9903", "JPY", 2600.0),
+    ("JPX:9904", "JPY", 5400.0),
+    ("HKEX:9905", "HKD", 310.0),
+    ("HKEX:9906", "HKD", 24.0),
+];
+/// `(symbol, days before the end date, ratio)`.
+const DEMO_SPLITS: [(&str, i64, f64); 2] = [("DEMOB", 700, 4.0), ("JPX:9903", 400, 2.0)];
+/// `(symbol, payments per year, yield per payment)`.
+const DEMO_DIVIDENDS: [(&str, u32, f64); 2] = [("DEMOA", 4, 0.006), ("HKEX:9905", 2, 0.018)];
+/// `(currency, starting units per USD)`.
+const DEMO_FX: [(&str, f64); 3] = [("TWD", 30.5), ("JPY", 128.0), ("HKD", 7.8)];
+const DEMO_HISTORY_DAYS: i64 = 4 * 365;
+
+/// xorshift64* generator so demo data is identical for a given seed.
+struct DemoRng(u64);
+
+impl DemoRng {
+    fn new(seed: u64) -> Self {
+        DemoRng(seed.max(1))
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Uniform in [0, 1).
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Standard normal via Box-Muller.
+    fn normal(&mut self) -> f64 {
+        let u1 = self.next_f64().max(f64::MIN_POSITIVE);
+        let u2 = self.next_f64();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+}
+
+/// What `generate_demo_data` created, so `clear_demo_data` removes exactly that.
+#[derive(Serialize, Deserialize, Default)]
+struct DemoManifest {
+    symbols: Vec<String>,
+    fx_files: Vec<String>,
+    created_at: String,
+    seed: u64,
+}
+
+fn load_demo_manifest(app_handle: &tauri::AppHandle) -> Result<DemoManifest, String> {
+    let path = get_data_dir(app_handle)?.join(DEMO_MANIFEST_FILE);
+    if !path.exists() {
+        return Ok(DemoManifest::default());
+    }
+    let content = read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", DEMO_MANIFEST_FILE, e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Invalid {}: {}", DEMO_MANIFEST_FILE, e))
+}
+
+fn demo_trading_days(start: NaiveDate, end: NaiveDate) -> Vec<NaiveDate> {
+    start
+        .iter_days()
+        .take_while(|d| *d <= end)
+        .filter(|d| d.weekday().num_days_from_monday() < 5)
+        .collect()
+}
+
+#[derive(Serialize)]
+struct DemoDataSummary {
+    symbols: Vec<String>,
+    transactions: usize,
+    price_rows: usize,
+    dividend_files: usize,
+    split_files: usize,
+    fx_files: usize,
+}
+
+/// Everything the demo generator writes, before it touches the data directory.
+#[derive(Debug, PartialEq)]
+struct DemoDataset {
+    /// Newest-first price rows per symbol.
+    prices: Vec<(&'static str, Vec<PriceRecordEntry>)>,
+    /// Split file content per symbol.
+    splits: Vec<(&'static str, String)>,
+    dividends: Vec<(&'static str, Vec<Vec<String>>)>,
+    /// Transaction rows per trading currency, in date order and tagged `demo`.
+    transactions: std::collections::BTreeMap<&'static str, Vec<Vec<String>>>,
+    /// FX rate file content per quote currency.
+    fx: Vec<(&'static str, String)>,
+}
+
+/// A demo transaction row. The ninth field marks the row as demo data so
+/// `clear_demo_data` can remove it without touching trades the user entered; the
+/// eighth, `lot_id`, stays empty.
+fn demo_transaction_row(
+    date: NaiveDate,
+    symbol: &str,
+    kind: &str,
+    quantity: String,
+    price: String,
+    fees: String,
+    split_ratio: String,
+) -> Vec<String> {
+    vec![
+        date.format("%Y-%m-%d").to_string(),
+        symbol.to_string(),
+        kind.to_string(),
+        quantity,
+        price,
+        fees,
+        split_ratio,
+        String::new(),
+        DEMO_SOURCE.to_string(),
+    ]
+}
+
+/// Builds the seeded demo portfolio covering `DEMO_HISTORY_DAYS` up to `end`. The
+/// same seed, end date and `updated_at` always give the same dataset.
+fn build_demo_dataset(seed: u64, end: NaiveDate, updated_at: &str) -> DemoDataset {
+    let mut rng = DemoRng::new(seed);
+    let start = end - ChronoDuration::days(DEMO_HISTORY_DAYS);
+    let days = demo_trading_days(start, end);
+    let mut dataset = DemoDataset {
+        prices: Vec::new(),
+        splits: Vec::new(),
+        dividends: Vec::new(),
+        transactions: std::collections::BTreeMap::new(),
+        fx: Vec::new(),
+    };
+
+    for (symbol, currency, start_price) in DEMO_SYMBOLS {
+        let split = DEMO_SPLITS
+            .iter()
+            .find(|(s, _, _)| *s == symbol)
+            .map(|(_, days_ago, ratio)| (end - ChronoDuration::days(*days_ago), *ratio));
+
+        // Quoted prices follow a random walk and drop by the ratio on a split.
+        let mut quoted: Vec<(NaiveDate, f64)> = Vec::with_capacity(days.len());
+        let mut price = start_price;
+        let mut split_applied = false;
+        for date in &days {
+            if let Some((_, ratio)) = split.filter(|(d, _)| !split_applied && date >= d) {
+                price /= ratio;
+                split_applied = true;
+            }
+            price *= (0.0003 + 0.016 * rng.normal()).exp();
+            quoted.push((*date, price));
+        }
+
+        let entries: Vec<PriceRecordEntry> = quoted
+            .iter()
+            .rev()
+            .map(|(date, close)| {
+                let factor = match split {
+                    Some((split_date, ratio)) if *date < split_date => ratio,
+                    _ => 1.0,
+                };
+                let adjusted = close / factor;
+                let spread = 0.01 * adjusted;
+                PriceRecordEntry {
+                    symbol: symbol.to_string(),
+                    date: *date,
+                    close: adjusted,
+                    open: Some(adjusted - spread * 0.5),
+                    high: Some(adjusted + spread),
+                    low: Some(adjusted - spread),
+                    volume: Some((200_000.0 + 800_000.0 * rng.next_f64()).round()),
+                    adjusted_close: Some(adjusted),
+                    split_unadjusted_close: Some(*close),
+                    source: DEMO_SOURCE.to_string(),
+                }
+            })
+            .collect();
+
+        if let Some((split_date, ratio)) = split {
+            let before = quoted
+                .iter()
+                .rev()
+                .find(|(d, _)| *d < split_date)
+                .map(|(_, p)| *p)
+                .unwrap_or(0.0);
+            let after = quoted
+                .iter()
+                .find(|(d, _)| *d >= split_date)
+                .map(|(_, p)| *p)
+                .unwrap_or(0.0);
+            dataset.splits.push((
+                symbol,
+                format!(
+                    "{}{},{},1,{:.4},{:.4}\n",
+                    SPLIT_FILE_HEADER,
+                    split_date.format("%Y-%m-%d"),
+                    ratio,
+                    before,
+                    after
+                ),
+            ));
+        }
+
+        if let Some((_, per_year, yield_per_payment)) =
+            DEMO_DIVIDENDS.iter().find(|(s, _, _)| *s == symbol)
+        {
+            let step = 12 / per_year;
+            let rows: Vec<Vec<String>> = (1..)
+                .map_while(|n| {
+                    start
+                        .checked_add_months(chrono::Months::new(n * step))
+                        .filter(|d| *d <= end)
+                })
+                .filter_map(|ex_date| {
+                    let (_, close) = entries
+                        .iter()
+                        .find(|e| e.date <= ex_date)
+                        .map(|e| (e.date, e.close))?;
+                    Some(vec![
+                        ex_date.format("%Y-%m-%d").to_string(),
+                        format!("{:.4}", close * yield_per_payment),
+                        currency.to_string(),
+                        updated_at.to_string(),
+                        DEMO_SOURCE.to_string(),
+                    ])
+                })
+                .collect();
+            dataset.dividends.push((symbol, rows));
+        }
+
+        // An opening buy in the first half year, a few top-ups and sometimes a
+        // partial sale, all at the quoted close of the day.
+        let price_on = |date: NaiveDate| {
+            quoted
+                .iter()
+                .rev()
+                .find(|(d, _)| *d <= date)
+                .map(|(_, p)| *p)
+                .unwrap_or(start_price)
+        };
+        let lot_size = if currency == "TWD" || currency == "JPY" {
+            100.0
+        } else {
+            10.0
+        };
+        let mut events: Vec<(NaiveDate, &str, f64, f64)> = Vec::new();
+        let open_date = days[(rng.next_f64() * 120.0) as usize];
+        let mut held = lot_size * (1.0 + (rng.next_f64() * 5.0).floor());
+        events.push((open_date, "buy", held, price_on(open_date)));
+        for _ in 0..(1 + (rng.next_f64() * 3.0) as usize) {
+            let date = days[(rng.next_f64() * (days.len() - 1) as f64) as usize].max(open_date);
+            let quantity = lot_size * (1.0 + (rng.next_f64() * 3.0).floor());
+            events.push((date, "buy", quantity, price_on(date)));
+        }
+        if rng.next_f64() < 0.5 {
+            let date = days[days.len() - 1 - (rng.next_f64() * 250.0) as usize].max(open_date);
+            events.push((date, "sell", lot_size, price_on(date)));
+        }
+        events.sort_by_key(|(date, _, _, _)| *date);
+
+        let split_row = |split_date: NaiveDate, ratio: f64| {
+            demo_transaction_row(
+                split_date,
+                symbol,
+                "split",
+                "0".to_string(),
+                "0".to_string(),
+                "0".to_string(),
+                ratio.to_string(),
+            )
+        };
+        let rows = dataset.transactions.entry(currency).or_default();
+        let mut split_recorded = false;
+        for (date, kind, quantity, price) in events {
+            if let Some((split_date, ratio)) = split.filter(|(d, _)| !split_recorded && *d <= date)
+            {
+                if held > 0.0 {
+                    rows.push(split_row(split_date, ratio));
+                    held *= ratio;
+                }
+                split_recorded = true;
+            }
+            let quantity = if kind == "sell" {
+                quantity.min(held)
+            } else {
+                quantity
+            };
+            if quantity <= 0.0 {
+                continue;
+            }
+            held += if kind == "sell" { -quantity } else { quantity };
+            let fees = (quantity * price * 0.001).max(1.0);
+            rows.push(demo_transaction_row(
+                date,
+                symbol,
+                kind,
+                quantity.to_string(),
+                format!("{:.4}", price),
+                format!("{:.2}", fees),
+                String::new(),
+            ));
+        }
+        if let Some((split_date, ratio)) = split.filter(|(d, _)| !split_recorded && *d <= end) {
+            rows.push(split_row(split_date, ratio));
+        }
+
+        dataset.prices.push((symbol, entries));
+    }
+    for rows in dataset.transactions.values_mut() {
+        rows.sort_by(|a, b| a[0].cmp(&b[0]));
+    }
+
+    for (currency, start_rate) in DEMO_FX {
+        let mut rate = start_rate;
+        let mut content = FX_RATES_HEADER.to_string();
+        for date in &days {
+            rate *= (0.003 * rng.normal()).exp();
+            content.push_str(&format!(
+                "USD,{},{},{:.6},{},{}\n",
+                currency,
+                date.format("%Y-%m-%d"),
+                rate,
+                DEMO_SOURCE,
+                updated_at
+            ));
+        }
+        dataset.fx.push((currency, content));
+    }
+    dataset
+}
+
+/// Writes a seeded four-year demo portfolio ending on `end_date` (default today):
+/// nine fictional symbols across the US, Taiwan, Japan and Hong Kong files,
+/// random-walk prices, two splits, two dividend payers and USD FX series. Every
+/// file and transaction row is tagged `source=demo`, `demo_mode` is set and a
+/// manifest records what to remove. Refused when transactions already exist
+/// unless `force` is set; existing FX files are left alone.
+#[tauri::command]
+fn generate_demo_data(
+    app_handle: tauri::AppHandle,
+    seed: Option<u64>,
+    end_date: Option<String>,
+    force: Option<bool>,
+) -> Result<DemoDataSummary, AppError> {
+    if !load_all_transactions(&app_handle)?.is_empty() && !force.unwrap_or(false) {
+        return Err(AppError::validation(
+            "force",
+            "Transactions already exist; pass force to add demo data anyway",
+        ));
+    }
+    let end = match end_date.as_deref().map(str::trim) {
+        Some(raw) if !raw.is_empty() => NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+            .map_err(|_| AppError::validation("end_date", "Use YYYY-MM-DD"))?,
+        _ => Utc::now().date_naive(),
+    };
+    let seed = seed.unwrap_or(42);
+    let updated_at = Utc::now().to_rfc3339();
+    let dataset = build_demo_dataset(seed, end, &updated_at);
+
+    let mut price_rows = 0;
+    for (symbol, entries) in &dataset.prices {
+        price_rows += entries.len();
+        persist_price_file_content(&app_handle, symbol, &build_price_csv_content(entries)?)?;
+    }
+    for (symbol, content) in &dataset.splits {
+        let path = get_splits_dir(&app_handle)?.join(format!("{}.csv", symbol_file_stem(symbol)));
+        atomic_write(&app_handle, &path, content.as_bytes())?;
+    }
+    for (symbol, rows) in &dataset.dividends {
+        write_dividend_rows(
+            &app_handle,
+            &dividend_file_path(&app_handle, symbol)?,
+            rows.clone(),
+        )?;
+    }
+
+    let mut transactions = 0;
+    for (currency, rows) in &dataset.transactions {
+        transactions += rows.len();
+        append_transaction_rows(&app_handle, currency, rows)?;
+    }
+
+    let mut fx_files = Vec::new();
+    let fx_dir = get_fx_rates_dir(&app_handle)?;
+    for (currency, content) in &dataset.fx {
+        let name = format!("USD_{}.csv", currency);
+        let path = fx_dir.join(&name);
+        if path.exists() {
+            continue;
+        }
+        atomic_write(&app_handle, &path, content.as_bytes())?;
+        fx_files.push(name);
+    }
+
+    let symbols: Vec<String> = DEMO_SYMBOLS.iter().map(|(s, _, _)| s.to_string()).collect();
+    let manifest = DemoManifest {
+        symbols: symbols.clone(),
+        fx_files: fx_files.clone(),
+        created_at: updated_at,
+        seed,
+    };
+    let manifest_json = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize demo manifest: {}", e))?;
+    write(
+        get_data_dir(&app_handle)?.join(DEMO_MANIFEST_FILE),
+        manifest_json,
+    )?;
+    set_setting(
+        app_handle.clone(),
+        "demo_mode".to_string(),
+        "true".to_string(),
+    )?;
+    invalidate_coverage_cache(&app_handle);
+    write_worker_log(
+        &app_handle,
+        &format!(
+            "Generated demo data (seed {}): {} transactions, {} price rows",
+            seed, transactions, price_rows
+        ),
+    )?;
+
+    Ok(DemoDataSummary {
+        symbols,
+        transactions,
+        price_rows,
+        dividend_files: dataset.dividends.len(),
+        split_files: dataset.splits.len(),
+        fx_files: fx_files.len(),
+    })
+}
+
+/// Drops the transaction rows tagged as demo data from a transaction file's
+/// content, keeping every other row's fields as they were. Returns the new content
+/// and how many rows were removed.
+fn strip_demo_transaction_rows(content: &[u8]) -> Result<(Vec<u8>, usize), String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(content);
+    let mut writer = csv::WriterBuilder::new()
+        .flexible(true)
+        .from_writer(Vec::new());
+    let mut removed = 0;
+    for record in reader.records() {
+        let record = record.map_err(|e| format!("Failed to parse CSV record: {}", e))?;
+        if record.get(8).map(str::trim) == Some(DEMO_SOURCE) {
+            removed += 1;
+            continue;
+        }
+        writer
+            .write_record(&record)
+            .map_err(|e| format!("Failed to write CSV record: {}", e))?;
+    }
+    let content = writer
+        .into_inner()
+        .map_err(|e| format!("Failed to write CSV: {}", e))?;
+    Ok((content, removed))
+}
+
+/// Removes everything the demo generator created: transaction rows tagged `demo`,
+/// the manifest's price, split, dividend and NAV files, and the FX files it wrote.
+/// Trades the user entered for a demo symbol stay. Returns the number of files
+/// deleted.
+#[tauri::command]
+fn clear_demo_data(app_handle: tauri::AppHandle) -> Result<usize, AppError> {
+    let manifest = load_demo_manifest(&app_handle)?;
+    if manifest.symbols.is_empty() {
+        return Err(AppError::not_found("demo_data", "No demo data to clear"));
+    }
+    for (filename, currency) in TRANSACTION_FILES {
+        let path = resolve_transaction_file(&app_handle, currency)?;
+        if !path.exists() {
+            continue;
+        }
+        with_file_lock(&app_handle, &path, || -> Result<(), String> {
+            let content =
+                std::fs::read(&path).map_err(|e| format!("Failed to read {}: {}", filename, e))?;
+            let (updated, removed) = strip_demo_transaction_rows(&content)
+                .map_err(|e| format!("{}: {}", filename, e))?;
+            if removed > 0 {
+                atomic_write(&app_handle, &path, &updated)?;
+            }
+            Ok(())
+        })?;
+    }
+    invalidate_transaction_cache(&app_handle);
+
+    let mut removed = 0;
+    let mut remove = |path: PathBuf| -> Result<(), String> {
+        if path.exists() {
+            std::fs::remove_file(&path)
+                .map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+            if let Some(store) = app_handle.try_state::<PriceStore>() {
+                store.invalidate(&path);
+            }
+            removed += 1;
+        }
+        Ok(())
+    };
+    for symbol in &manifest.symbols {
+        let file = format!("{}.csv", symbol_file_stem(symbol));
+        remove(get_prices_dir(&app_handle)?.join(&file))?;
+        remove(get_splits_dir(&app_handle)?.join(&file))?;
+        remove(get_dividends_dir(&app_handle)?.join(&file))?;
+        remove(get_navs_dir(&app_handle)?.join(&file))?;
+    }
+    for file in &manifest.fx_files {
+        remove(get_fx_rates_dir(&app_handle)?.join(file))?;
+    }
+    remove(get_data_dir(&app_handle)?.join(DEMO_MANIFEST_FILE))?;
+
+    set_setting(
+        app_handle.clone(),
+        "demo_mode".to_string(),
+        "false".to_string(),
+    )?;
+    invalidate_coverage_cache(&app_handle);
+    mark_data_dirty(&app_handle, DataKind::Prices);
+    write_worker_log(
+        &app_handle,
+        &format!("Cleared demo data: {} files removed", removed),
+    )?;
+    Ok(removed)
+}
+
+#[tauri::command]
+fn get_all_daily_prices(app_handle: tauri::AppHandle) -> Result<Vec<DailyPriceData>, AppError> {
+    let prices_dir = get_prices_dir(&app_handle)?;
+    let mut daily_prices = Vec::new();
+
+    if let Ok(entries) = std::fs::read_dir(&prices_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("csv") {
+                continue;
+            }
+
+            if let Some(filename) = path.file_name().and_then(|n| n.to_str()) {
+                let symbol = symbol_from_file_stem(filename.trim_end_matches(".csv"));
+
+                // Read only first 3 lines (header + latest 2 prices)
+                // Price files are sorted by date descending, so top 2 data rows are what we need
+                if let Ok(content) = read_file_head(&path, 3) {
+                    let lines: Vec<&str> = content.lines().collect();
+                    if lines.len() < 2 {
+                        continue; // Skip if no data (only header)
+                    }
+
+                    let latest_line = lines.get(1);
+                    let previous_line = lines.get(2);
+
+                    if let Some(latest_str) = latest_line {
+                        let fields: Vec<&str> = latest_str.split(',').collect();
+                        if fields.len() < 2 {
+                            continue;
+                        }
+
+                        if let (Ok(latest_date), Ok(latest_close)) = (
+                            NaiveDate::parse_from_str(fields[0].trim(), "%Y-%m-%d"),
+                            fields[1].trim().parse::<f64>(),
+                        ) {
+                            let mut previous_close: Option<f64> = None;
+                            let mut previous_date: Option<String> = None;
+
+                            if let Some(prev_str) = previous_line {
+                                let prev_fields: Vec<&str> = prev_str.split(',').collect();
+                                if prev_fields.len() >= 2 {
+                                    if let (Ok(prev_date), Ok(prev_close_val)) = (
+                                        NaiveDate::parse_from_str(
+                                            prev_fields[0].trim(),
+                                            "%Y-%m-%d",
+                                        ),
+                                        prev_fields[1].trim().parse::<f64>(),
+                                    ) {
+                                        previous_date =
+                                            Some(prev_date.format("%Y-%m-%d").to_string());
+                                        previous_close = Some(prev_close_val);
+                                    }
+                                }
+                            }
+
+                            daily_prices.push(DailyPriceData {
+                                symbol,
+                                latest_close,
+                                latest_date: latest_date.format("%Y-%m-%d").to_string(),
+                                previous_close,
+                                previous_date,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(daily_prices)
+}
+
+#[tauri::command]
+fn get_all_daily_fx_rates(app_handle: tauri::AppHandle) -> Result<Vec<DailyFxRateData>, AppError> {
+    let fx_rates_dir = get_fx_rates_dir(&app_handle)?;
+    let mut daily_rates = Vec::new();
+
+    if let Ok(entries) = std::fs::read_dir(&fx_rates_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("csv") {
+                continue;
+            }
+
+            if let Some(filename) = path.file_name().and_then(|n| n.to_str()) {
+                let pair = filename.trim_end_matches(".csv").replace('_', "/");
+
+                // Read only first 3 lines (header + latest 2 rates)
+                // FX rate files are sorted by date descending
+                if let Ok(content) = read_file_head(&path, 3) {
+                    let lines: Vec<&str> = content.lines().collect();
+                    if lines.len() < 2 {
+                        continue; // Skip if no data (only header)
+                    }
+
+                    let latest_line = lines.get(1);
+                    let previous_line = lines.get(2);
+
+                    if let Some(latest_str) = latest_line {
+                        let fields: Vec<&str> = latest_str.split(',').collect();
+                        // FX CSV format: from_currency,to_currency,date,rate,source,updated_at
+                        if fields.len() < 4 {
+                            continue;
+                        }
+
+                        // Parse date (column 2) and rate (column 3)
+                        if let (Ok(latest_date), Ok(latest_rate)) = (
+                            NaiveDate::parse_from_str(fields[2].trim(), "%Y-%m-%d"),
+                            fields[3].trim().parse::<f64>(),
+                        ) {
+                            let mut previous_rate: Option<f64> = None;
+                            let mut previous_date: Option<String> = None;
+
+                            if let Some(prev_str) = previous_line {
+                                let prev_fields: Vec<&str> = prev_str.split(',').collect();
+                                if prev_fields.len() >= 4 {
+                                    if let (Ok(prev_date), Ok(prev_rate_val)) = (
+                                        NaiveDate::parse_from_str(
+                                            prev_fields[2].trim(),
+                                            "%Y-%m-%d",
+                                        ),
+                                        prev_fields[3].trim().parse::<f64>(),
+                                    ) {
+                                        previous_date =
+                                            Some(prev_date.format("%Y-%m-%d").to_string());
+                                        previous_rate = Some(prev_rate_val);
+                                    }
+                                }
+                            }
+
+                            daily_rates.push(DailyFxRateData {
+                                pair,
+                                latest_rate,
+                                latest_date: latest_date.format("%Y-%m-%d").to_string(),
+                                previous_rate,
+                                previous_date,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(daily_rates)
+}
+
+#[tauri::command]
+fn read_fx_rates_polars(
+    app_handle: tauri::AppHandle,
+    #[allow(non_snake_case)] fromCurrency: String,
+    #[allow(non_snake_case)] toCurrency: String,
+    #[allow(non_snake_case)] latestOnly: Option<bool>,
+    #[allow(non_snake_case)] includeOverrides: Option<bool>,
+    limit: Option<usize>,
+) -> Result<Vec<FxRateRecordResponse>, AppError> {
+    let include_overrides = includeOverrides.unwrap_or(true);
+    let mut records =
+        load_fx_pair_with_polars(&app_handle, &fromCurrency, &toCurrency, include_overrides)?;
+
+    if records.is_empty() {
+        return Ok(records);
+    }
+
+    let latest_only = latestOnly.unwrap_or(true);
+    if latest_only && records.len() > 1 {
+        records.truncate(1);
+    } else if let Some(limit) = limit {
+        if limit < records.len() {
+            records.truncate(limit);
+        }
+    }
+
+    Ok(records)
+}
+
+#[derive(Serialize)]
+struct NavFileContent {
+    file: String,
+    content: String,
+    /// How the file was found when it is not `<safe_symbol>.csv`.
+    fallback: Option<String>,
+}
+
+/// True for legacy NAV files named `<safe_symbol>_<timestamp>.csv` (or with '-').
+/// The remainder must look like a date or timestamp, so `HKEX_0700.csv` is not a
+/// legacy file for `HKEX` and `AAPL.csv` is not one for `A`.
+fn is_legacy_nav_file_for(file_stem: &str, safe_symbol: &str) -> bool {
+    file_stem
+        .strip_prefix(safe_symbol)
+        .and_then(|rest| rest.strip_prefix(['_', '-']))
+        .is_some_and(|stamp| {
+            stamp.chars().filter(|c| c.is_ascii_digit()).count() >= 8
+                && stamp
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || matches!(c, '-' | '_' | 'T' | ':'))
+        })
+}
+
+/// NAV file for `safe_symbol` in `navs_dir`: `<safe_symbol>.csv`, or else the most
+/// recently modified legacy timestamped file together with a note saying so.
+fn resolve_nav_file(
+    navs_dir: &Path,
+    safe_symbol: &str,
+) -> Result<Option<(PathBuf, Option<String>)>, String> {
+    let exact = navs_dir.join(format!("{}.csv", safe_symbol));
+    if exact.is_file() {
+        return Ok(Some((exact, None)));
+    }
+    let newest = std::fs::read_dir(navs_dir)
+        .map_err(|e| format!("Failed to read navs directory: {}", e))?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension().is_some_and(|e| e == "csv")
+                && path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .is_some_and(|stem| is_legacy_nav_file_for(stem, safe_symbol))
+        })
+        .max_by_key(|path| {
+            std::fs::metadata(path)
+                .and_then(|m| m.modified())
+                .unwrap_or(std::time::SystemTime::UNIX_EPOCH)
+        });
+    Ok(newest.map(|path| {
+        (
+            path,
+            Some("Newest legacy timestamped NAV file by modification time".to_string()),
+        )
+    }))
+}
+
+/// The symbol's NAV CSV with rows in `order` (`"asc"`/`"desc"`, newest first by
+/// default). The file is `<safe_symbol>.csv`; only when that does not exist is the
+/// most recently modified legacy timestamped file used, and `fallback` says so.
+#[tauri::command]
+fn read_nav_file(
+    app_handle: tauri::AppHandle,
+    symbol: String,
+    order: Option<String>,
+) -> Result<NavFileContent, AppError> {
+    let order = RowOrder::parse(order.as_deref())?;
+    let navs_dir = get_navs_dir(&app_handle)?;
+    let Some((path, fallback)) = resolve_nav_file(&navs_dir, &symbol_file_stem(&symbol))? else {
+        return Err(AppError::not_found(
+            symbol.clone(),
+            format!("No NAV file found for symbol '{}'", symbol),
+        ));
+    };
+
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read NAV file for '{}': {}", symbol, e))?;
+    Ok(NavFileContent {
+        file: path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        content: order_csv_rows(&content, order),
+        fallback,
+    })
+}
+
+#[derive(Serialize)]
+struct NavFileInfo {
+    file: String,
+    symbol: String,
+    file_size_bytes: u64,
+    last_modified: String,
+}
+
+/// Every per-symbol NAV file in the navs directory, sorted by file name. The
+/// portfolio history file is left out; legacy timestamped files are listed as-is.
+#[tauri::command]
+fn list_nav_files(app_handle: tauri::AppHandle) -> Result<Vec<NavFileInfo>, AppError> {
+    let navs_dir = get_navs_dir(&app_handle)?;
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(&navs_dir)?.flatten() {
+        let path = entry.path();
+        let Some(file) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        let Some(stem) = file.strip_suffix(".csv") else {
+            continue;
+        };
+        if file == NAV_HISTORY_FILE || !path.is_file() {
+            continue;
+        }
+        let meta = entry.metadata()?;
+        files.push(NavFileInfo {
+            file: file.to_string(),
+            symbol: symbol_from_file_stem(stem),
+            file_size_bytes: meta.len(),
+            last_modified: meta
+                .modified()
+                .map(|t| DateTime::<Utc>::from(t).to_rfc3339())
+                .unwrap_or_default(),
+        });
+    }
+    files.sort_by(|a, b| a.file.cmp(&b.file));
+    Ok(files)
+}
+
+fn main() {
+    tauri::Builder::default()
+        .manage(FileLockRegistry::default())
+        .manage(CoverageCache::default())
+        .manage(PriceStore::default())
+        .manage(TransactionCache::default())
+        .manage(OpenFigiRateLimiter::default())
+        .manage(RealtimeQuoteCache::default())
+        .manage(PortfolioState::default())
+        .manage(ActivePortfolio::default())
+        .manage(ChartFetcher::default())
+        .setup(|app| {
+            if let Err(e) = initialize_storage(&app.handle()) {
+                return Err(Box::new(std::io::Error::other(e)));
+            }
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![
+            greet,
+            is_file_locked,
+            get_portfolio_state,
+            list_portfolios,
+            create_portfolio,
+            switch_portfolio,
+            delete_portfolio,
+            create_backup,
+            read_csv,
+            read_csv_json,
+            get_setting,
+            set_setting,
+            read_storage_csv,
+            write_storage_csv,
+            append_storage_csv,
+            read_data_csv,
             write_data_csv,
             append_data_csv,
             write_price_file,
+            add_manual_price,
+            delete_price_row,
+            import_manual_prices,
+            add_manual_dividend,
+            delete_dividend_row,
+            add_manual_split,
+            delete_split_row,
             read_price_file,
             read_price_file_head,
+            read_price_file_tail,
+            get_latest_prices,
+            clear_price_cache,
+            get_price_cache_stats,
             read_prices_polars,
             list_price_files,
+            get_price_file_metadata,
+            get_data_sources,
+            find_duplicate_price_rows,
+            repair_price_file,
             read_price_override_file,
             write_price_override_file,
             get_all_daily_prices,
             write_split_file,
             read_split_file,
+            migrate_split_files,
+            migrate_price_files,
+            migrate_symbol_filenames,
+            get_schema_status,
+            set_offline_mode,
+            get_offline_mode,
             list_split_files,
             write_dividend_file,
             read_dividend_file,
             list_dividend_files,
+            write_preferred_holding,
+            read_preferred_holdings,
+            calculate_current_yield,
+            calculate_yield_to_maturity,
+            get_dividend_income,
+            get_all_dividends_for_company,
+            record_spinoff_event,
+            validate_transaction,
+            add_transaction,
+            delete_transaction,
+            record_rights_issue,
+            record_adr_conversion,
             write_fx_rate_file,
             write_fx_rate_override_file,
             read_fx_rate_file,
@@ -2790,16 +17517,2610 @@ fn main() {
             get_all_daily_fx_rates,
             sync_history_once,
             download_symbol_history,
+            bulk_download_history,
+            get_realtime_quote,
             start_history_worker,
             get_history_log,
+            parse_symbol,
+            list_symbol_overrides,
+            set_symbol_override,
+            get_app_info,
             proxy_get,
             get_data_coverage,
+            get_data_coverage_json,
+            get_symbol_coverage,
+            get_position_detail,
+            calculate_efficient_frontier,
+            get_timing_analysis,
+            export_report,
+            export_tax_lots_csv,
+            calculate_currency_pnl,
+            get_incomplete_run,
+            discard_incomplete_run,
+            calculate_leverage_decay,
+            get_drawdown_series,
+            backfill_price_range,
+            get_cost_basis_history,
+            set_history_start,
+            get_symbol_config,
+            set_symbol_config,
+            set_security_tags,
+            get_securities_by_tag,
+            update_cash_balance,
+            get_cash_balances,
+            export_interop,
+            generate_report_html,
+            import_interop,
+            get_open_lots,
+            sell_from_lot,
+            get_indexed_prices,
+            get_return_distribution,
+            get_market_caps,
+            get_sector_rotation_signals,
+            get_fundamental_data,
+            get_portfolio_fundamentals,
+            refresh_symbol_fundamentals,
+            get_pnl_by_period,
+            get_transaction_summary,
+            simulate_average_down,
+            simulate_average_down_ladder,
+            audit_currencies,
+            find_parse_issues,
+            validate_securities_csv,
+            repair_securities_csv,
+            count_duplicate_securities,
+            deduplicate_securities,
+            lookup_security,
+            record_corporate_event,
+            check_for_delistings,
+            get_corporate_events,
+            get_watchlist,
+            add_watch_symbol,
+            remove_watch_symbol,
+            get_sync_status,
             get_split_history,
+            get_split_history_json,
             get_data_stats,
+            get_data_stats_json,
+            detect_price_anomalies,
+            suggest_missing_splits,
+            apply_suggested_split,
+            recompute_split_adjustments,
+            audit_adjusted_closes,
             save_nav_snapshot,
+            compact_nav_snapshots,
+            get_nav_snapshots,
             save_position_snapshot,
-            read_nav_file
+            rebuild_all_navs,
+            generate_demo_data,
+            clear_demo_data,
+            read_nav_file,
+            list_nav_files
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    fn txn(
+        day: &str,
+        txn_type: &str,
+        quantity: f64,
+        price: f64,
+        split_ratio: f64,
+    ) -> ProcessedTransaction {
+        ProcessedTransaction {
+            date: date(day),
+            txn_type: txn_type.to_string(),
+            quantity,
+            price,
+            fees: 0.0,
+            split_ratio,
+            currency: "USD".to_string(),
+            lot_id: None,
+        }
+    }
+
+    fn fixture_path(relative: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("fixtures")
+            .join(relative)
+    }
+
+    /// A fixture price file the way `load_ascending_price_series` returns it.
+    fn ascending_fixture_series(relative: &str) -> Vec<(NaiveDate, PriceRecordEntry)> {
+        let mut series: Vec<(NaiveDate, PriceRecordEntry)> =
+            scan_price_file_records(&fixture_path(relative))
+                .unwrap()
+                .into_iter()
+                .map(|record| (record.date, record))
+                .collect();
+        series.sort_by_key(|(day, _)| *day);
+        series
+    }
+
+    fn fixture_fetcher() -> FixtureFetcher {
+        FixtureFetcher {
+            dir: PathBuf::from(OFFLINE_FIXTURE_DIR),
+        }
+    }
+
+    #[test]
+    fn download_merge_keeps_manual_rows() {
+        let (downloaded, _, _) = fetch_yahoo_chunk(
+            &fixture_fetcher(),
+            "AAPL",
+            "AAPL",
+            date("2024-02-01"),
+            date("2024-02-29"),
+            "1d",
+        )
+        .unwrap();
+        let manual_day = date("2024-02-06");
+        assert!(downloaded.iter().any(|r| r.date == manual_day));
+
+        let manual = manual_price_entry("AAPL", manual_day, 123.45, None, None, None, None);
+        let older = PriceRecordEntry {
+            source: "yahoo_finance".to_string(),
+            ..manual_price_entry("AAPL", date("2023-12-29"), 190.0, None, None, None, None)
+        };
+        // A row from a file without a source column is not protected.
+        let legacy_day = date("2024-02-07");
+        let legacy = PriceRecordEntry {
+            source: LEGACY_SOURCE.to_string(),
+            ..manual_price_entry("AAPL", legacy_day, 99.0, None, None, None, None)
+        };
+        let merged = merge_price_entries(
+            "AAPL",
+            &[manual.clone(), older.clone(), legacy],
+            &downloaded,
+        )
+        .unwrap();
+
+        let kept = merged.iter().find(|r| r.date == manual_day).unwrap();
+        assert_eq!(kept.close, 123.45);
+        assert_eq!(kept.source, MANUAL_SOURCE);
+        let replaced = merged.iter().find(|r| r.date == legacy_day).unwrap();
+        let synced = downloaded.iter().find(|r| r.date == legacy_day).unwrap();
+        assert_eq!(replaced.close, synced.close);
+        assert_eq!(replaced.source, synced.source);
+        assert!(merged
+            .iter()
+            .any(|r| r.date == older.date && r.close == 190.0));
+        assert_eq!(merged.len(), downloaded.len() + 1);
+    }
+
+    #[test]
+    fn reverse_split_unadjusts_earlier_closes() {
+        // 1-for-10 on 2024-03-01: adjusted closes before it are 10x the quoted ones.
+        let splits = [(date("2024-03-01"), 0.1)];
+        assert_eq!(split_unadjust_factor(date("2024-02-29"), &splits), 0.1);
+        assert_eq!(split_unadjust_factor(date("2024-03-01"), &splits), 1.0);
+        assert_eq!(split_unadjust_factor(date("2024-03-04"), &splits), 1.0);
+
+        let adjusted_close = 25.0;
+        let quoted = adjusted_close * split_unadjust_factor(date("2024-02-01"), &splits);
+        assert!((quoted - 2.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn reverse_and_forward_splits_compound() {
+        let splits = [(date("2020-06-01"), 2.0), (date("2024-03-01"), 0.1)];
+        assert!((split_unadjust_factor(date("2019-01-02"), &splits) - 0.2).abs() < 1e-12);
+        assert!((split_unadjust_factor(date("2021-01-04"), &splits) - 0.1).abs() < 1e-12);
+        assert_eq!(split_unadjust_factor(date("2024-03-01"), &splits), 1.0);
+    }
+
+    #[test]
+    fn reverse_split_divides_shares_and_keeps_cost_basis() {
+        let transactions = vec![
+            txn("2024-01-02", "buy", 1000.0, 2.0, 0.0),
+            txn("2024-03-01", "split", 0.0, 0.0, 0.1),
+        ];
+        let (shares, cost, realized) = replay_average_cost(&transactions, date("2024-12-31"));
+        assert!((shares - 100.0).abs() < 1e-9);
+        assert!((cost - 2000.0).abs() < 1e-9);
+        assert_eq!(realized, 0.0);
+
+        let lots = fifo_open_lots(&transactions, date("2024-12-31"));
+        assert_eq!(lots.len(), 1);
+        assert!((lots[0].quantity - 100.0).abs() < 1e-9);
+        assert!((lots[0].cost_per_share - 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn spinoff_row_moves_cost_off_the_parent_without_changing_shares() {
+        // The parent row record_spinoff_event writes when 15% of a 100-share,
+        // 5000-cost position moves to the child.
+        let transactions = vec![
+            txn("2024-01-02", "buy", 100.0, 50.0, 0.0),
+            txn("2024-06-03", "spinoff", 100.0, -750.0 / 100.0, 0.0),
+        ];
+        let (shares, cost) = position_cost_basis(&transactions, date("2024-12-31"));
+        assert!((shares - 100.0).abs() < 1e-9);
+        assert!((cost - 4250.0).abs() < 1e-9);
+        assert!((transaction_cash_flow(&transactions[1]) + 750.0).abs() < 1e-9);
+
+        let lots = fifo_open_lots(&transactions, date("2024-12-31"));
+        assert_eq!(lots.len(), 1);
+        assert!((lots[0].quantity - 100.0).abs() < 1e-9);
+        assert!((lots[0].cost_per_share - 42.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn adr_conversion_carries_lots_so_a_later_sale_is_long_term() {
+        // Two ADR lots, 10 and 5 ADRs, converted at 5 ordinary shares each; the
+        // ordinary shares are sold eight months after the exchange.
+        let adr = vec![
+            txn("2022-01-03", "buy", 10.0, 100.0, 0.0),
+            txn("2022-06-01", "buy", 5.0, 120.0, 0.0),
+        ];
+        let conversion = date("2023-03-01");
+        let carried = conversion_carried_lots(&fifo_open_lots(&adr, conversion), 12.0, 5.0);
+        assert_eq!(carried.len(), 2);
+        assert_eq!(carried[0].date, date("2022-01-03"));
+        assert!((carried[0].quantity - 50.0).abs() < 1e-9);
+        assert!((carried[0].quantity * carried[0].cost_per_share - 1000.0).abs() < 1e-9);
+        assert_eq!(carried[1].date, date("2022-06-01"));
+        assert!((carried[1].quantity - 10.0).abs() < 1e-9);
+        assert!((carried[1].quantity * carried[1].cost_per_share - 240.0).abs() < 1e-9);
+
+        let mut ordinary: Vec<ProcessedTransaction> = carried
+            .iter()
+            .map(|lot| ProcessedTransaction {
+                lot_id: Some(lot.id.clone()),
+                ..txn(
+                    "2023-03-01",
+                    "adr_convert_to_ordinary",
+                    lot.quantity,
+                    lot.cost_per_share,
+                    0.0,
+                )
+            })
+            .collect();
+        ordinary.push(txn("2023-11-01", "sell", 60.0, 30.0, 0.0));
+
+        let (open, realized) = fifo_replay(&ordinary, date("2023-12-31"));
+        assert!(open.is_empty());
+        assert_eq!(realized.len(), 2);
+        assert_eq!(realized[0].acquired_date, date("2022-01-03"));
+        assert!((realized[0].cost_basis - 1000.0).abs() < 1e-9);
+        assert_eq!(realized[1].acquired_date, date("2022-06-01"));
+        assert!((realized[1].cost_basis - 240.0).abs() < 1e-9);
+        for lot in &realized {
+            assert!(is_long_term(lot.acquired_date, lot.sell_date));
+        }
+        assert!(!is_long_term(conversion, date("2023-11-01")));
+    }
+
+    #[test]
+    fn anomaly_scan_labels_fabricated_unrecorded_split() {
+        let series = ascending_fixture_series("prices/UNSPLIT.csv");
+        assert_eq!(series.len(), 25);
+        let anomalies = scan_price_anomalies("UNSPLIT", &series, &[], 0.4);
+        let kinds: Vec<(&str, &str)> = anomalies
+            .iter()
+            .map(|a| (a.date.as_str(), a.kind.as_str()))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("2024-01-23", "unrecorded_split"),
+                ("2024-01-30", "volume_spike"),
+                ("2024-02-01", "non_positive_close"),
+            ]
+        );
+        assert!(anomalies[0].context.contains("2:1"));
+        assert!((anomalies[0].value + 0.5).abs() < 0.01);
+    }
+
+    #[test]
+    fn anomaly_scan_ignores_recorded_split() {
+        let series = ascending_fixture_series("prices/UNSPLIT.csv");
+        let splits = [(date("2024-01-23"), 2.0)];
+        let anomalies = scan_price_anomalies("UNSPLIT", &series, &splits, 0.4);
+        assert!(anomalies
+            .iter()
+            .all(|a| a.kind != "unrecorded_split" && a.kind != "price_jump"));
+        assert!(find_missing_splits("UNSPLIT", &series, &splits).is_empty());
+        assert_eq!(find_missing_splits("UNSPLIT", &series, &[]).len(), 1);
+    }
+
+    #[test]
+    fn first_trade_date_shrinks_coverage_window() {
+        let meta: serde_json::Value = serde_json::from_str(
+            &read_to_string(fixture_path("yahoo_metas/NYSE_IPO.json")).unwrap(),
+        )
+        .unwrap();
+        let first_trade = meta_first_trade_date(&meta);
+        assert_eq!(first_trade, Some(date("2021-06-15")));
+
+        let today = date("2024-06-14");
+        let bound = date("2009-06-18");
+        let full = TradingCalendar::new(Some("NYSE"), bound, today);
+        let listed_from = coverage_window_start(bound, first_trade);
+        assert_eq!(listed_from, date("2021-06-15"));
+        let listed = TradingCalendar::new(Some("NYSE"), listed_from, today);
+
+        let full_days = full.count_trading_days(bound, today);
+        let listed_days = listed.count_trading_days(listed_from, today);
+        assert!(listed_days < full_days / 4);
+        // Three years of NYSE sessions, give or take holiday-calendar detail.
+        assert!((740..=770).contains(&listed_days), "{}", listed_days);
+
+        // Complete prices since listing now count as full coverage.
+        let prices: std::collections::HashSet<NaiveDate> = listed_from
+            .iter_days()
+            .take_while(|d| *d <= today)
+            .collect();
+        assert!(listed
+            .missing_trading_days(&prices, listed_from, today)
+            .is_empty());
+    }
+
+    #[test]
+    fn coverage_window_without_first_trade_keeps_bound() {
+        let bound = date("2009-06-18");
+        assert_eq!(coverage_window_start(bound, None), bound);
+        // A first trade before the bound never widens the window.
+        assert_eq!(
+            coverage_window_start(bound, Some(date("1980-12-12"))),
+            bound
+        );
+        assert_eq!(
+            meta_first_trade_date(&serde_json::json!({"symbol": "X"})),
+            None
+        );
+    }
+
+    fn currencies(values: &[&str]) -> std::collections::BTreeSet<String> {
+        values.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn currency_audit_flags_dividends_in_wrong_currency() {
+        let security = ("TWD".to_string(), "TWSE".to_string());
+        let findings = symbol_currency_findings(
+            "TWSE:2330",
+            &currencies(&["TWD"]),
+            Some(&security),
+            &["USD".to_string()],
+            Some("TWD"),
+        );
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].kind, "currency_mismatch");
+        assert_eq!(findings[0].severity, "error");
+        assert!(findings[0]
+            .sources
+            .iter()
+            .any(|s| s.source == "dividends" && s.currency == "USD"));
+        assert!(findings[0].message.contains("exchange TWSE=TWD"));
+    }
+
+    #[test]
+    fn currency_audit_flags_transactions_in_wrong_market_file() {
+        let findings = symbol_currency_findings(
+            "HKEX:0700",
+            &currencies(&["HKD", "USD"]),
+            None,
+            &[],
+            Some("HKD"),
+        );
+        let kinds: Vec<&str> = findings.iter().map(|f| f.kind.as_str()).collect();
+        assert_eq!(
+            kinds,
+            vec!["mixed_transaction_currencies", "currency_mismatch"]
+        );
+        assert!(findings.iter().all(|f| f.severity == "error"));
+        assert_eq!(findings[0].sources.len(), 2);
+    }
+
+    #[test]
+    fn currency_audit_treats_metadata_only_drift_as_warning() {
+        let security = ("USD".to_string(), "JPX".to_string());
+        let findings = symbol_currency_findings(
+            "JPX:7203",
+            &currencies(&["JPY"]),
+            Some(&security),
+            &[],
+            Some("JPY"),
+        );
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].severity, "error");
+
+        // Only securities.csv disagrees with the meta, no transactions involved.
+        let findings = symbol_currency_findings(
+            "JPX:7203",
+            &currencies(&[]),
+            Some(&security),
+            &[],
+            Some("JPY"),
+        );
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].severity, "warning");
+    }
+
+    #[test]
+    fn currency_audit_accepts_pence_quotes_for_london() {
+        let security = ("GBP".to_string(), "LSE".to_string());
+        let findings = symbol_currency_findings(
+            "LSE:VOD",
+            &currencies(&["GBP"]),
+            Some(&security),
+            &["GBP".to_string()],
+            Some("GBp"),
+        );
+        assert!(findings.is_empty());
+    }
+
+    /// build_price_csv_content as it was before it stopped cloning the frame, kept
+    /// as the reference the current output must match.
+    fn reference_price_csv_content(entries: &[PriceRecordEntry]) -> String {
+        let updated_at = Utc::now().to_rfc3339();
+        let mut df = DataFrame::new(vec![
+            Series::new(
+                "date",
+                entries
+                    .iter()
+                    .map(|e| e.date.format("%Y-%m-%d").to_string())
+                    .collect::<Vec<_>>(),
+            ),
+            Series::new("close", entries.iter().map(|e| e.close).collect::<Vec<_>>()),
+            Series::new("open", entries.iter().map(|e| e.open).collect::<Vec<_>>()),
+            Series::new("high", entries.iter().map(|e| e.high).collect::<Vec<_>>()),
+            Series::new("low", entries.iter().map(|e| e.low).collect::<Vec<_>>()),
+            Series::new(
+                "volume",
+                entries.iter().map(|e| e.volume).collect::<Vec<_>>(),
+            ),
+            Series::new(
+                "adjusted_close",
+                entries.iter().map(|e| e.adjusted_close).collect::<Vec<_>>(),
+            ),
+            Series::new(
+                "split_unadjusted_close",
+                entries
+                    .iter()
+                    .map(|e| e.split_unadjusted_close)
+                    .collect::<Vec<_>>(),
+            ),
+            Series::new(
+                "source",
+                entries
+                    .iter()
+                    .map(|e| e.source.as_str())
+                    .collect::<Vec<_>>(),
+            ),
+            Series::new("updated_at", vec![updated_at.as_str(); entries.len()]),
+        ])
+        .unwrap();
+        let mut buf = Vec::new();
+        CsvWriter::new(&mut buf).finish(&mut df).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    /// Price CSV lines without the trailing `updated_at` timestamp.
+    fn without_updated_at(content: &str) -> Vec<String> {
+        content
+            .lines()
+            .map(|line| line[..line.rfind(',').unwrap()].to_string())
+            .collect()
+    }
+
+    fn synthetic_price_entries(rows: usize) -> Vec<PriceRecordEntry> {
+        let last = date("2024-12-31");
+        (0..rows)
+            .map(|i| {
+                let close = 100.0 + (i % 97) as f64 * 0.37;
+                PriceRecordEntry {
+                    symbol: "SYN".to_string(),
+                    date: last - ChronoDuration::days(i as i64),
+                    close,
+                    open: (i % 5 != 0).then_some(close - 0.5),
+                    high: Some(close + 1.25),
+                    low: Some(close - 1.125),
+                    volume: (i % 7 != 0).then_some(1_000_000.0 + i as f64),
+                    adjusted_close: Some(close * 0.98),
+                    split_unadjusted_close: (i % 3 == 0).then_some(close * 2.0),
+                    source: if i % 11 == 0 {
+                        MANUAL_SOURCE
+                    } else {
+                        "yahoo_finance"
+                    }
+                    .to_string(),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn price_csv_content_matches_reference_for_fixture() {
+        let mut entries = scan_price_file_records(&fixture_path("prices/UNSPLIT.csv")).unwrap();
+        assert_eq!(entries.len(), 25);
+        entries[3].open = None;
+        entries[4].volume = None;
+        entries[5].adjusted_close = None;
+        entries[6].split_unadjusted_close = None;
+
+        let current = build_price_csv_content(&entries).unwrap();
+        let reference = reference_price_csv_content(&entries);
+        assert_eq!(without_updated_at(&current), without_updated_at(&reference));
+        assert!(current.starts_with(PRICE_FILE_HEADER));
+        assert_eq!(
+            build_price_csv_content(&[]).unwrap(),
+            format!("{}\n", PRICE_FILE_HEADER)
+        );
+    }
+
+    #[test]
+    fn price_csv_content_matches_reference_for_synthetic_rows() {
+        let entries = synthetic_price_entries(4000);
+        assert_eq!(
+            without_updated_at(&build_price_csv_content(&entries).unwrap()),
+            without_updated_at(&reference_price_csv_content(&entries))
+        );
+    }
+
+    /// `cargo test --release -- --ignored --nocapture bench_` prints timings.
+    #[test]
+    #[ignore]
+    fn bench_price_csv_content_4000_rows() {
+        let entries = synthetic_price_entries(4000);
+        let runs = 200;
+        let time = |f: &dyn Fn() -> String| {
+            let started = std::time::Instant::now();
+            for _ in 0..runs {
+                std::hint::black_box(f());
+            }
+            started.elapsed() / runs
+        };
+        let reference = time(&|| reference_price_csv_content(&entries));
+        let current = time(&|| build_price_csv_content(&entries).unwrap());
+        println!(
+            "build_price_csv_content, 4000 rows: reference {:?}, current {:?}",
+            reference, current
+        );
+    }
+
+    /// load_price_records as it was before polars: one `csv::Reader` pass per file,
+    /// building a `PriceRecordEntry` per row. Kept as the benchmark baseline.
+    fn reference_load_price_records(paths: &[PathBuf]) -> Vec<PriceRecordEntry> {
+        let mut records = Vec::new();
+        for path in paths {
+            let filename = match path.file_stem().and_then(|s| s.to_str()) {
+                Some(f) => f.replace('_', ":"),
+                None => continue,
+            };
+            let mut reader = match csv::ReaderBuilder::new().has_headers(true).from_path(path) {
+                Ok(r) => r,
+                Err(_) => continue,
+            };
+            for result in reader.records() {
+                let record = match result {
+                    Ok(r) => r,
+                    Err(_) => continue,
+                };
+                if record.len() < 3 {
+                    continue;
+                }
+                let date_str = record.get(0).unwrap_or("").trim();
+                let date = match NaiveDate::parse_from_str(date_str, "%Y-%m-%d") {
+                    Ok(d) => d,
+                    Err(_) => continue,
+                };
+                let close = parse_f64_str(record.get(1).unwrap_or("").trim()).unwrap_or(0.0);
+                records.push(PriceRecordEntry {
+                    symbol: filename.clone(),
+                    date,
+                    close,
+                    open: record.get(2).and_then(|v| parse_f64_str(v.trim())),
+                    high: record.get(3).and_then(|v| parse_f64_str(v.trim())),
+                    low: record.get(4).and_then(|v| parse_f64_str(v.trim())),
+                    volume: record.get(5).and_then(|v| parse_f64_str(v.trim())),
+                    adjusted_close: None,
+                    split_unadjusted_close: None,
+                    source: match record.get(6).map(str::trim) {
+                        Some(source) if !source.is_empty() => source.to_string(),
+                        _ => LEGACY_SOURCE.to_string(),
+                    },
+                });
+            }
+        }
+        records
+    }
+
+    /// Writes `symbols` synthetic price files of `rows` rows each and returns their
+    /// paths; the caller removes `dir`.
+    fn write_synthetic_price_files(dir: &Path, symbols: usize, rows: usize) -> Vec<PathBuf> {
+        std::fs::create_dir_all(dir).unwrap();
+        let content = build_price_csv_content(&synthetic_price_entries(rows)).unwrap();
+        (0..symbols)
+            .map(|idx| {
+                let path = dir.join(format!("NYSE_SYN{}.csv", idx));
+                std::fs::write(&path, &content).unwrap();
+                path
+            })
+            .collect()
+    }
+
+    #[test]
+    fn price_frames_hold_every_row_per_symbol() {
+        let paths = vec![
+            fixture_path("prices/NASDAQ_AAPL.csv"),
+            fixture_path("prices/UNSPLIT.csv"),
+            fixture_path("prices/legacy/TWSE_2330.csv"),
+        ];
+        let frames = scan_price_frames(&paths).unwrap();
+        assert_eq!(
+            frames.keys().cloned().collect::<Vec<_>>(),
+            vec!["NASDAQ:AAPL", "TWSE:2330", "UNSPLIT"]
+        );
+        for path in &paths {
+            let records = scan_price_file_records(path).unwrap();
+            let symbol = symbol_from_file_stem(path.file_stem().unwrap().to_str().unwrap());
+            let frame = &frames[&symbol];
+            assert_eq!(price_entries_from_frame(&symbol, frame).unwrap(), records);
+            assert_eq!(
+                price_frame_span(frame),
+                Some((
+                    records.iter().map(|r| r.date).min().unwrap(),
+                    records.iter().map(|r| r.date).max().unwrap()
+                ))
+            );
+        }
+    }
+
+    /// `cargo test --release -- --ignored --nocapture bench_price_loading` prints
+    /// timings for 50 symbols x 3750 rows.
+    #[test]
+    #[ignore]
+    fn bench_price_loading_50_symbols() {
+        let dir = std::env::temp_dir().join(format!("price-bench-{}", std::process::id()));
+        let paths = write_synthetic_price_files(&dir, 50, 3750);
+        let runs = 10;
+        let time = |f: &dyn Fn() -> usize| {
+            let started = std::time::Instant::now();
+            for _ in 0..runs {
+                assert_eq!(std::hint::black_box(f()), 50 * 3750);
+            }
+            started.elapsed() / runs
+        };
+        let reference = time(&|| reference_load_price_records(&paths).len());
+        let frames = time(&|| {
+            scan_price_frames(&paths)
+                .unwrap()
+                .values()
+                .map(|f| f.height())
+                .sum()
+        });
+        let records = time(&|| {
+            scan_price_frames(&paths)
+                .unwrap()
+                .iter()
+                .map(|(symbol, frame)| price_entries_from_frame(symbol, frame).unwrap().len())
+                .sum()
+        });
+        println!(
+            "price loading, 50 x 3750 rows on {} threads: reference {:?}, frames {:?}, frames + records {:?}",
+            rayon::current_num_threads(),
+            reference,
+            frames,
+            records
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// `cargo test --release -- --ignored --nocapture bench_price_file_records`
+    /// prints timings for reading 50 x 3750 rows one file at a time.
+    #[test]
+    #[ignore]
+    fn bench_price_file_records_50_symbols() {
+        let dir = std::env::temp_dir().join(format!("price-records-bench-{}", std::process::id()));
+        let paths = write_synthetic_price_files(&dir, 50, 3750);
+        let runs = 10;
+        let time = |f: &dyn Fn() -> usize| {
+            let started = std::time::Instant::now();
+            for _ in 0..runs {
+                assert_eq!(std::hint::black_box(f()), 50 * 3750);
+            }
+            started.elapsed() / runs
+        };
+        let reference = time(&|| reference_load_price_records(&paths).len());
+        let current = time(&|| {
+            paths
+                .iter()
+                .map(|path| scan_price_file_records(path).unwrap().len())
+                .sum()
+        });
+        println!(
+            "price file records, 50 x 3750 rows: reference {:?}, current {:?}",
+            reference, current
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn price_file_reads_keep_zero_closes_and_report_unreadable_files() {
+        let dir = std::env::temp_dir().join(format!("price-read-errors-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let zero = dir.join("ZERO.csv");
+        std::fs::write(&zero, "date,close\n2024-01-03,10.5\n2024-01-02,0\n").unwrap();
+        let records = scan_price_file_records(&zero).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].close, 0.0);
+
+        let ragged = dir.join("RAGGED.csv");
+        std::fs::write(&ragged, "date,close\n2024-01-03,10.5,1,2,3\n").unwrap();
+        assert!(scan_price_file_records(&ragged).is_err());
+        assert!(scan_price_frames(&[zero, ragged]).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn data_file_readers_return_specific_errors() {
+        let dir = std::env::temp_dir().join(format!("reader-errors-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let missing = dir.join("US_Trx.csv");
+        assert!(matches!(
+            read_csv_file(missing.to_str().unwrap(), "USD"),
+            Err(AppError::NotFound { .. })
+        ));
+        assert!(matches!(
+            scan_price_file_records(&dir.join("MISSING.csv")),
+            Err(AppError::NotFound { .. })
+        ));
+
+        let binary = dir.join("TW_Trx.csv");
+        std::fs::write(
+            &binary,
+            b"2024-01-02,TWSE:2330,buy,1,600,0,1\n2024-01-03,\xff\xfe,buy\n",
+        )
+        .unwrap();
+        match read_csv_file(binary.to_str().unwrap(), "TWD") {
+            Err(AppError::Parse { file, line, .. }) => {
+                assert_eq!(file, binary.to_str().unwrap());
+                assert_eq!(line, Some(2));
+            }
+            other => panic!("expected a parse error, got {:?}", other.map(|t| t.len())),
+        }
+
+        let ragged = dir.join("RAGGED.csv");
+        std::fs::write(&ragged, "date,close\n2024-01-03,10.5,1,2,3\n").unwrap();
+        assert!(matches!(
+            scan_price_file_records(&ragged),
+            Err(AppError::Parse { .. })
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn corrupt_sync_status_is_a_parse_error() {
+        let dir = std::env::temp_dir().join(format!("sync-status-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("sync_status.json");
+        assert!(read_sync_status_file(&path).unwrap().is_empty());
+
+        std::fs::write(
+            &path,
+            r#"{"AAPL": {"symbol": "AAPL", "last_error": "timeout"}}"#,
+        )
+        .unwrap();
+        let statuses = read_sync_status_file(&path).unwrap();
+        assert_eq!(statuses["AAPL"].last_error.as_deref(), Some("timeout"));
+
+        std::fs::write(&path, "{\n  \"AAPL\": {\"symbol\": ").unwrap();
+        assert!(matches!(
+            read_sync_status_file(&path),
+            Err(AppError::Parse { line: Some(2), .. })
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// A NAV base frame of `rows` rows, as write_position_nav builds it.
+    fn synthetic_nav_base(rows: usize) -> DataFrame {
+        let last = date("2024-12-31");
+        let closes: Vec<f64> = (0..rows).map(|i| 100.0 + (i % 97) as f64 * 0.37).collect();
+        let shares: Vec<f64> = (0..rows).map(|i| 10.0 + (i / 100) as f64).collect();
+        DataFrame::new(vec![
+            Series::new(
+                "date",
+                (0..rows)
+                    .map(|i| (last - ChronoDuration::days(i as i64)).to_string())
+                    .collect::<Vec<_>>(),
+            ),
+            Series::new("close", closes.clone()),
+            Series::new("shares", shares),
+            Series::new("avg_cost", vec![Some(95.0); rows]),
+            Series::new(
+                "unrealized_gain",
+                closes.iter().map(|c| Some(c - 95.0)).collect::<Vec<_>>(),
+            ),
+        ])
+        .unwrap()
+    }
+
+    /// position_nav_frame as it was before the single lazy plan: collect, then
+    /// append the currency and symbol columns one at a time.
+    fn reference_position_nav_frame(base: DataFrame, currency: &str, symbol: &str) -> DataFrame {
+        let mut calculated = base
+            .lazy()
+            .with_columns([(col("close") * col("shares")).alias("position_value")])
+            .collect()
+            .unwrap();
+        calculated
+            .with_column(Series::new(
+                "currency",
+                vec![currency.to_string(); calculated.height()],
+            ))
+            .unwrap();
+        calculated
+            .with_column(Series::new(
+                "symbol",
+                vec![symbol.to_string(); calculated.height()],
+            ))
+            .unwrap();
+        calculated
+    }
+
+    #[test]
+    fn position_nav_frame_matches_reference() {
+        let base = synthetic_nav_base(1000);
+        let current = position_nav_frame(base.clone(), "USD", "NASDAQ:AAPL").unwrap();
+        let reference = reference_position_nav_frame(base, "USD", "NASDAQ:AAPL");
+        assert!(current.equals_missing(&reference));
+    }
+
+    /// `cargo test --release -- --ignored --nocapture bench_position_nav_frame`
+    /// prints timings for a 1000-row timeline.
+    #[test]
+    #[ignore]
+    fn bench_position_nav_frame_1000_rows() {
+        let base = synthetic_nav_base(1000);
+        let runs = 2000;
+        let time = |f: &dyn Fn() -> DataFrame| {
+            let started = std::time::Instant::now();
+            for _ in 0..runs {
+                std::hint::black_box(f());
+            }
+            started.elapsed() / runs
+        };
+        let reference = time(&|| reference_position_nav_frame(base.clone(), "USD", "NASDAQ:AAPL"));
+        let current = time(&|| position_nav_frame(base.clone(), "USD", "NASDAQ:AAPL").unwrap());
+        println!(
+            "position_nav_frame, 1000 rows: reference {:?}, current {:?}",
+            reference, current
+        );
+    }
+
+    #[test]
+    fn data_fingerprint_changes_with_file_count_and_mtime() {
+        let dir = std::env::temp_dir().join(format!("fingerprint-{}", std::process::id()));
+        let paths = write_synthetic_price_files(&dir, 3, 10);
+        let before = fingerprint_files(std::slice::from_ref(&dir), Vec::new());
+        assert_eq!(before.file_count, 3);
+        assert_eq!(
+            fingerprint_files(std::slice::from_ref(&dir), Vec::new()),
+            before
+        );
+
+        std::fs::File::options()
+            .write(true)
+            .open(&paths[0])
+            .unwrap()
+            .set_modified(std::time::SystemTime::now() + Duration::from_secs(60))
+            .unwrap();
+        let touched = fingerprint_files(std::slice::from_ref(&dir), Vec::new());
+        assert_ne!(touched, before);
+
+        std::fs::remove_file(&paths[1]).unwrap();
+        assert_eq!(
+            fingerprint_files(std::slice::from_ref(&dir), Vec::new()).file_count,
+            2
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// `cargo test --release -- --ignored --nocapture bench_coverage_cache` prints
+    /// what a cached coverage poll costs (the fingerprint check) against the price
+    /// reading a cold poll has to do for 50 x 3750 rows.
+    #[test]
+    #[ignore]
+    fn bench_coverage_cache_fingerprint_vs_price_scan() {
+        let dir = std::env::temp_dir().join(format!("coverage-bench-{}", std::process::id()));
+        let paths = write_synthetic_price_files(&dir, 50, 3750);
+        let runs = 10;
+        let time = |f: &dyn Fn()| {
+            let started = std::time::Instant::now();
+            for _ in 0..runs {
+                f();
+            }
+            started.elapsed() / runs
+        };
+        let cached = time(&|| {
+            std::hint::black_box(fingerprint_files(std::slice::from_ref(&dir), Vec::new()));
+        });
+        let cold = time(&|| {
+            std::hint::black_box(scan_price_frames(&paths).unwrap());
+        });
+        println!(
+            "coverage poll, 50 x 3750 rows: fingerprint {:?}, price scan {:?}",
+            cached, cold
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn transaction_files_are_classified_by_name() {
+        for (name, _) in TRANSACTION_FILES {
+            assert!(matches!(
+                DataKind::for_path(&Path::new("/data/portfolios/main").join(name)),
+                DataKind::Transactions
+            ));
+        }
+        assert!(matches!(
+            DataKind::for_path(Path::new("/data/prices/US_Trx.csv")),
+            DataKind::Transactions
+        ));
+        assert!(matches!(
+            DataKind::for_path(Path::new("/data/prices/NASDAQ_AAPL.csv")),
+            DataKind::Prices
+        ));
+    }
+
+    /// `cargo test --release -- --ignored --nocapture bench_transaction_cache` prints
+    /// what a cached `load_all_transactions` hit costs (fingerprint plus `Arc` clone)
+    /// against copying the cached `Vec` and against parsing the file again.
+    #[test]
+    #[ignore]
+    fn bench_transaction_cache_hit_vs_clone_vs_parse() {
+        let dir = std::env::temp_dir().join(format!("transaction-bench-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("US_Trx.csv");
+        let mut content = String::from("Date,Stock,Type,Quantity,Price,Fees,Split_Ratio\n");
+        let start = NaiveDate::from_ymd_opt(2000, 1, 3).unwrap();
+        for i in 0..20_000 {
+            let day = start + chrono::Duration::days(i / 4);
+            let kind = if i % 3 == 0 { "sell" } else { "buy" };
+            content.push_str(&format!(
+                "{},NASDAQ:SYM{},{},{},{:.2},1.00,1\n",
+                day.format("%Y-%m-%d"),
+                i % 50,
+                kind,
+                1 + i % 20,
+                100.0 + (i % 97) as f64
+            ));
+        }
+        std::fs::write(&path, content).unwrap();
+        let path_str = path.to_string_lossy().to_string();
+        let cached = Arc::new(read_csv_file(&path_str, "USD").unwrap());
+
+        let runs = 20;
+        let time = |f: &dyn Fn()| {
+            let started = std::time::Instant::now();
+            for _ in 0..runs {
+                f();
+            }
+            started.elapsed() / runs
+        };
+        let hit = time(&|| {
+            std::hint::black_box(file_fingerprint(&path));
+            std::hint::black_box(Arc::clone(&cached));
+        });
+        let copy = time(&|| {
+            std::hint::black_box(cached.as_ref().clone());
+        });
+        let parse = time(&|| {
+            std::hint::black_box(read_csv_file(&path_str, "USD").unwrap());
+        });
+        println!(
+            "20000 transactions: cache hit {:?}, Vec clone {:?}, parse {:?}",
+            hit, copy, parse
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn price_file_reads_every_column_of_the_current_layout() {
+        let records = scan_price_file_records(&fixture_path("prices/NASDAQ_AAPL.csv")).unwrap();
+        assert_eq!(
+            records,
+            vec![
+                PriceRecordEntry {
+                    symbol: "NASDAQ:AAPL".to_string(),
+                    date: date("2024-06-11"),
+                    close: 207.15,
+                    open: Some(193.65),
+                    high: Some(207.16),
+                    low: Some(193.63),
+                    volume: Some(172_373_300.0),
+                    adjusted_close: Some(206.4),
+                    split_unadjusted_close: Some(828.6),
+                    source: "yahoo_finance".to_string(),
+                },
+                PriceRecordEntry {
+                    symbol: "NASDAQ:AAPL".to_string(),
+                    date: date("2024-06-10"),
+                    close: 193.12,
+                    open: Some(196.9),
+                    high: Some(197.3),
+                    low: Some(192.15),
+                    volume: Some(97_262_100.0),
+                    adjusted_close: Some(192.42),
+                    split_unadjusted_close: None,
+                    source: MANUAL_SOURCE.to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn price_file_reads_legacy_seven_column_layout() {
+        let records =
+            scan_price_file_records(&fixture_path("prices/legacy/HKEX_0700.csv")).unwrap();
+        assert_eq!(
+            records,
+            vec![
+                PriceRecordEntry {
+                    symbol: "HKEX:0700".to_string(),
+                    date: date("2023-03-02"),
+                    close: 372.4,
+                    open: Some(369.0),
+                    high: Some(375.8),
+                    low: Some(366.2),
+                    volume: Some(18_204_500.0),
+                    adjusted_close: None,
+                    split_unadjusted_close: None,
+                    source: "yahoo_finance".to_string(),
+                },
+                PriceRecordEntry {
+                    symbol: "HKEX:0700".to_string(),
+                    date: date("2023-03-01"),
+                    close: 1368.2,
+                    open: Some(360.0),
+                    high: None,
+                    low: Some(355.4),
+                    volume: None,
+                    adjusted_close: None,
+                    split_unadjusted_close: None,
+                    source: LEGACY_SOURCE.to_string(),
+                },
+            ]
+        );
+    }
+
+    /// Serializes `value`, reads it back and checks the second serialization is
+    /// identical, returning the JSON the frontend receives.
+    fn assert_round_trip<T: Serialize + serde::de::DeserializeOwned>(
+        value: &T,
+    ) -> serde_json::Value {
+        let json = serde_json::to_value(value).unwrap();
+        let decoded: T = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&decoded).unwrap(), json);
+        json
+    }
+
+    #[test]
+    fn transaction_round_trips_and_accepts_numbers() {
+        let transaction = Transaction {
+            date: "2024-03-01".to_string(),
+            stock: "NASDAQ:AAPL".to_string(),
+            transaction_type: "buy".to_string(),
+            quantity: "10".to_string(),
+            price: "123.45".to_string(),
+            fees: "1.5".to_string(),
+            split_ratio: "1".to_string(),
+            currency: "USD".to_string(),
+            lot_id: String::new(),
+            source: Some(("US_Trx.csv".to_string(), 2)),
+        };
+        let json = assert_round_trip(&transaction);
+        assert_eq!(json["quantity"], 10.0);
+        assert_eq!(json["price"], 123.45);
+        assert_eq!(json["fees"], 1.5);
+        assert_eq!(json["split_ratio"], 1.0);
+        assert!(json.get("source").is_none());
+
+        let raw = Transaction {
+            quantity: "1,234".to_string(),
+            price: "N/A".to_string(),
+            fees: String::new(),
+            split_ratio: "4:1".to_string(),
+            ..transaction.clone()
+        };
+        let json = serde_json::to_value(&raw).unwrap();
+        assert_eq!(json["quantity"], 1234.0);
+        assert!(json["price"].is_null());
+        assert!(json["fees"].is_null());
+        assert_eq!(json["split_ratio"], 4.0);
+
+        let typed: Transaction = serde_json::from_value(serde_json::json!({
+            "date": "2024-03-01",
+            "stock": "NASDAQ:AAPL",
+            "transaction_type": "sell",
+            "quantity": 10,
+            "price": 123.45,
+            "fees": null,
+            "split_ratio": "1",
+            "currency": "USD",
+        }))
+        .unwrap();
+        assert_eq!(typed.quantity, "10");
+        assert_eq!(typed.price, "123.45");
+        assert_eq!(typed.fees, "");
+        assert_eq!(typed.lot_id, "");
+    }
+
+    #[test]
+    fn coverage_payload_round_trips() {
+        let coverage = StockDataCoverage {
+            ticker: "HKEX:0700".to_string(),
+            exchange: "HKEX".to_string(),
+            currency: "HKD".to_string(),
+            earliest_transaction: "2020-01-02".to_string(),
+            earliest_price: Some("2020-01-02".to_string()),
+            latest_price: None,
+            total_days: 1000,
+            missing_days: 3,
+            coverage_percent: 99.7,
+            split_count: 1,
+            last_split: Some("2014-05-15".to_string()),
+            status: "partial".to_string(),
+            delist_reason: None,
+            days_since_latest_price: Some(4),
+            stale: true,
+            last_sync_error: Some("timeout".to_string()),
+            first_trade_date: None,
+            tags: vec!["core".to_string(), "asia".to_string()],
+        };
+        let json = assert_round_trip(&coverage);
+        assert_eq!(json["coverage_percent"], 99.7);
+        assert_eq!(json["tags"], serde_json::json!(["core", "asia"]));
+
+        let older: StockDataCoverage = serde_json::from_value(serde_json::json!({
+            "ticker": "AAPL",
+            "exchange": "NASDAQ",
+            "currency": "USD",
+            "earliest_transaction": "2020-01-02",
+            "earliest_price": null,
+            "latest_price": null,
+            "total_days": 0,
+            "missing_days": 0,
+            "coverage_percent": 0.0,
+            "split_count": 0,
+            "last_split": null,
+            "status": "missing",
+            "delist_reason": null,
+        }))
+        .unwrap();
+        assert!(!older.stale);
+        assert!(older.tags.is_empty());
+    }
+
+    #[test]
+    fn split_history_payload_round_trips() {
+        let split = SplitHistory {
+            ticker: "NASDAQ:NVDA".to_string(),
+            date: "2024-06-10".to_string(),
+            numerator: 10,
+            denominator: 1,
+            ratio: "10:1".to_string(),
+            ratio_factor: 10.0,
+            before_price: Some(1208.88),
+            after_price: None,
+        };
+        let json = assert_round_trip(&split);
+        assert_eq!(json["ratio_factor"], 10.0);
+        assert_eq!(json["after_price"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn data_stats_payload_round_trips() {
+        let mut stats = DataReadinessStats {
+            total_stocks: 2,
+            complete_data: 1,
+            partial_data: 1,
+            total_price_records: 4000,
+            oldest_date: Some("2015-01-02".to_string()),
+            newest_date: Some("2024-06-11".to_string()),
+            last_sync_at: Some("2024-06-12T00:00:00Z".to_string()),
+            stale_symbols: 1,
+            disk_usage_bytes: 123_456,
+            ..Default::default()
+        };
+        stats.markets.insert(
+            "HK".to_string(),
+            MarketReadinessStats {
+                total_stocks: 1,
+                partial_data: 1,
+                oldest_date: Some("2015-01-02".to_string()),
+                fx_pairs: 1,
+                ..Default::default()
+            },
+        );
+        let json = assert_round_trip(&stats);
+        assert_eq!(json["markets"]["HK"]["fx_pairs"], 1);
+        assert_eq!(json["disk_usage_bytes"], 123_456);
+    }
+
+    fn yahoo_row(day: &str, close: f64, volume: Option<f64>) -> PriceRecordEntry {
+        PriceRecordEntry {
+            symbol: "NASDAQ:MSFT".to_string(),
+            date: date(day),
+            close,
+            open: Some(close - 1.0),
+            high: Some(close + 1.0),
+            low: Some(close - 2.0),
+            volume,
+            adjusted_close: Some(close),
+            split_unadjusted_close: None,
+            source: "yahoo_finance".to_string(),
+        }
+    }
+
+    #[test]
+    fn merge_updates_existing_rows_and_appends_new_ones() {
+        let existing = vec![
+            yahoo_row("2024-01-03", 370.0, Some(1_000.0)),
+            yahoo_row("2024-01-02", 368.0, Some(2_000.0)),
+        ];
+        let incoming = vec![
+            // Restated close without a volume: the close is replaced, the old
+            // volume is kept.
+            yahoo_row("2024-01-03", 371.5, None),
+            yahoo_row("2024-01-04", 367.75, Some(3_000.0)),
+        ];
+
+        let merged = merge_price_entries("NASDAQ:MSFT", &existing, &incoming).unwrap();
+        assert_eq!(
+            merged.iter().map(|r| r.date).collect::<Vec<_>>(),
+            vec![date("2024-01-02"), date("2024-01-03"), date("2024-01-04")]
+        );
+        assert_eq!(merged[0], existing[1]);
+        assert_eq!(
+            merged[1],
+            PriceRecordEntry {
+                volume: Some(1_000.0),
+                ..incoming[0].clone()
+            }
+        );
+        assert_eq!(merged[2], incoming[1]);
+    }
+
+    #[test]
+    fn merge_with_empty_sides_keeps_the_other() {
+        let rows = vec![
+            yahoo_row("2024-01-02", 368.0, Some(2_000.0)),
+            yahoo_row("2024-01-03", 370.0, None),
+        ];
+        assert_eq!(
+            merge_price_entries("NASDAQ:MSFT", &[], &rows).unwrap(),
+            rows
+        );
+        assert_eq!(
+            merge_price_entries("NASDAQ:MSFT", &rows, &[]).unwrap(),
+            rows
+        );
+    }
+
+    type FixtureHolding = (
+        &'static str,
+        Vec<ProcessedTransaction>,
+        Vec<(NaiveDate, f64, String)>,
+    );
+
+    /// The small portfolio behind the interop golden files: a US stock with a
+    /// split and a sale, a Hong Kong stock relying on the exchange currency, a
+    /// Taiwan stock, and a dotted US class share.
+    fn interop_fixture_portfolio() -> Vec<FixtureHolding> {
+        let with_fees = |mut t: ProcessedTransaction, fees: f64, currency: &str| {
+            t.fees = fees;
+            t.currency = currency.to_string();
+            t
+        };
+        vec![
+            (
+                "NASDAQ:AAPL",
+                vec![
+                    with_fees(txn("2020-03-02", "buy", 10.0, 298.81, 1.0), 1.0, "USD"),
+                    txn("2020-08-31", "split", 0.0, 0.0, 4.0),
+                    with_fees(txn("2021-01-04", "sell", 15.0, 129.41, 1.0), 1.0, "USD"),
+                    with_fees(txn("2021-01-05", "fee", 0.0, 2.0, 1.0), 0.0, "USD"),
+                ],
+                vec![
+                    (date("2020-11-06"), 0.205, "USD".to_string()),
+                    (date("2020-02-07"), 0.77, "USD".to_string()),
+                ],
+            ),
+            (
+                "HKEX:0700",
+                vec![with_fees(
+                    txn("2022-03-15", "buy", 100.0, 298.0, 1.0),
+                    55.5,
+                    "",
+                )],
+                vec![(date("2022-05-19"), 1.6, "HKD".to_string())],
+            ),
+            (
+                "TWSE:2330",
+                vec![with_fees(
+                    txn("2023-01-03", "buy", 1000.0, 453.0, 1.0),
+                    645.0,
+                    "TWD",
+                )],
+                vec![(date("2023-03-16"), 2.75, "TWD".to_string())],
+            ),
+            (
+                "NYSE:BRK.B",
+                vec![with_fees(
+                    txn("2023-06-01", "buy", 3.0, 322.5, 1.0),
+                    0.0,
+                    "USD",
+                )],
+                Vec::new(),
+            ),
+        ]
+    }
+
+    fn interop_fixture_activities() -> (Vec<InteropActivity>, usize) {
+        let mut activities = Vec::new();
+        let mut skipped = 0;
+        for (symbol, transactions, dividends) in &interop_fixture_portfolio() {
+            let (exchange, base) = get_exchange_and_symbol(symbol);
+            let yahoo_symbol = yahoo_symbol_for(exchange.as_deref(), &base);
+            let (symbol_activities, symbol_skipped) =
+                symbol_interop_activities(symbol, &yahoo_symbol, transactions, dividends);
+            activities.extend(symbol_activities);
+            skipped += symbol_skipped;
+        }
+        activities.sort_by(|a, b| a.date.cmp(&b.date).then(a.symbol.cmp(&b.symbol)));
+        (activities, skipped)
+    }
+
+    #[test]
+    fn ghostfolio_export_matches_golden_file() {
+        let (activities, skipped) = interop_fixture_activities();
+        assert_eq!(skipped, 1);
+        let mut exported: serde_json::Value =
+            serde_json::from_slice(&ghostfolio_export(&activities).unwrap()).unwrap();
+        // The export stamps the current time; the golden file holds a fixed one.
+        exported["meta"]["date"] = "2000-01-01T00:00:00+00:00".into();
+        let golden: serde_json::Value =
+            serde_json::from_str(&read_to_string(fixture_path("interop/ghostfolio.json")).unwrap())
+                .unwrap();
+        assert_eq!(exported, golden);
+    }
+
+    #[test]
+    fn ghostfolio_export_reimports_as_duplicates_only() {
+        let portfolio = interop_fixture_portfolio();
+        let existing: Vec<Transaction> = portfolio
+            .iter()
+            .flat_map(|(symbol, transactions, _)| {
+                transactions.iter().map(move |t| Transaction {
+                    date: t.date.format("%Y-%m-%d").to_string(),
+                    stock: symbol.to_string(),
+                    transaction_type: t.txn_type.clone(),
+                    quantity: t.quantity.to_string(),
+                    price: t.price.to_string(),
+                    fees: t.fees.to_string(),
+                    split_ratio: t.split_ratio.to_string(),
+                    currency: t.currency.clone(),
+                    lot_id: String::new(),
+                    source: None,
+                })
+            })
+            .collect();
+        let reverse: HashMap<String, String> = portfolio
+            .iter()
+            .map(|(symbol, _, _)| {
+                let (exchange, base) = get_exchange_and_symbol(symbol);
+                (
+                    yahoo_symbol_for(exchange.as_deref(), &base),
+                    symbol.to_string(),
+                )
+            })
+            .collect();
+
+        let (activities, _) = interop_fixture_activities();
+        let exported: serde_json::Value =
+            serde_json::from_slice(&ghostfolio_export(&activities).unwrap()).unwrap();
+        let (rows, invalid_rows) = interop_import_rows(
+            exported["activities"].as_array().unwrap(),
+            &existing,
+            &reverse,
+            |symbol| {
+                Ok(portfolio
+                    .iter()
+                    .find(|(s, _, _)| *s == symbol)
+                    .map(|(_, _, dividends)| dividends.clone())
+                    .unwrap_or_default())
+            },
+        )
+        .unwrap();
+
+        assert!(invalid_rows.is_empty());
+        assert_eq!(rows.len(), activities.len());
+        let fresh = || rows.iter().filter(|r| !r.duplicate);
+        let new_transactions = fresh().filter(|r| r.kind != "DIVIDEND").count();
+        let new_dividends = fresh().filter(|r| r.kind == "DIVIDEND").count();
+        assert!(new_transactions == 0 && new_dividends == 0);
+    }
+
+    #[test]
+    fn portfolio_performance_export_matches_golden_file() {
+        let (activities, _) = interop_fixture_activities();
+        let exported =
+            String::from_utf8(portfolio_performance_export(&activities).unwrap()).unwrap();
+        let golden = read_to_string(fixture_path("interop/portfolio_performance.csv")).unwrap();
+        assert_eq!(exported, golden);
+    }
+
+    #[test]
+    fn report_html_has_every_section_and_is_utf8() {
+        let row = |cells: &[&str]| cells.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+        let report = ReportHtml {
+            generated_at: "2024-06-12T00:00:00+00:00".to_string(),
+            base: "EUR".to_string(),
+            total_value: 12_345.678,
+            cash_value: 500.0,
+            holding_rows: vec![row(&[
+                "HKEX:0700",
+                "HKD",
+                "100",
+                "298.00",
+                "29800.00",
+                "31000.00",
+                "1200.00",
+            ])],
+            allocations: [
+                ("Currency", vec![row(&["HKD", "11845.68", "95.9%"])]),
+                (
+                    "Sector",
+                    vec![row(&["Télécom & médias", "11845.68", "95.9%"])],
+                ),
+                ("Type", vec![row(&["Cash", "500.00", "4.1%"])]),
+                ("Tag", vec![row(&["<asia>", "11845.68", "95.9%"])]),
+            ],
+            series: vec![
+                (date("2024-06-03"), 11_000.0),
+                (date("2024-06-10"), 12_345.678),
+            ],
+            dividend_rows: vec![row(&["2024", "160.00"])],
+            gain_rows: Vec::new(),
+            caveats: vec![
+                "HKEX:0700 騰訊控股: latest price 2024-05-01 (28 trading days old)".to_string(),
+            ],
+        };
+
+        let dir = std::env::temp_dir().join(format!("report-html-{}", std::process::id()));
+        create_dir_all(&dir).unwrap();
+        let path = dir.join("report.html");
+        write(&path, render_report_html(&report)).unwrap();
+        let html = String::from_utf8(std::fs::read(&path).unwrap()).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<meta charset=\"utf-8\">"));
+        for section in [
+            "holdings",
+            "allocation",
+            "nav",
+            "dividends",
+            "realized-gains",
+            "data-caveats",
+        ] {
+            assert!(
+                html.contains(&format!("<section id=\"{}\">", section)),
+                "missing section {}",
+                section
+            );
+        }
+        for heading in ["By currency", "By sector", "By type", "By tag"] {
+            assert!(html.contains(&format!("<h3>{}</h3>", heading)));
+        }
+        assert!(html.contains("total value 12345.68 (cash 500.00)"));
+        assert!(html.contains("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(html.contains("Télécom &amp; médias"));
+        assert!(html.contains("&lt;asia&gt;"));
+        assert!(html.contains("<li class=\"caveat\">HKEX:0700 騰訊控股:"));
+        assert!(!html.contains("src=\"http") && !html.contains("href=\"http"));
+        assert_eq!(
+            html.matches("<section").count(),
+            html.matches("</section>").count()
+        );
+    }
+
+    const DEMO_TEST_STAMP: &str = "2024-06-28T00:00:00+00:00";
+
+    #[test]
+    fn demo_dataset_is_fixed_by_seed_and_end_date() {
+        let end = date("2024-06-28");
+        let dataset = build_demo_dataset(42, end, DEMO_TEST_STAMP);
+        assert_eq!(dataset, build_demo_dataset(42, end, DEMO_TEST_STAMP));
+        assert_ne!(dataset, build_demo_dataset(7, end, DEMO_TEST_STAMP));
+
+        assert_eq!(dataset.prices.len(), DEMO_SYMBOLS.len());
+        let (symbol, demoa) = &dataset.prices[0];
+        assert_eq!(*symbol, "DEMOA");
+        assert_eq!(demoa.len(), 1045);
+        assert_eq!(demoa[0].date, end);
+        assert_eq!(format!("{:.4}", demoa[0].close), "110.9808");
+        assert_eq!(demoa[demoa.len() - 1].date, date("2020-06-29"));
+        assert!(dataset
+            .prices
+            .iter()
+            .all(|(_, rows)| rows.iter().all(|r| r.source == DEMO_SOURCE)));
+
+        assert_eq!(
+            dataset.splits,
+            vec![
+                (
+                    "DEMOB",
+                    format!("{}2022-07-29,4,1,316.9751,78.8035\n", SPLIT_FILE_HEADER)
+                ),
+                (
+                    "JPX:9903",
+                    format!("{}2023-05-25,2,1,2931.0448,1467.3291\n", SPLIT_FILE_HEADER)
+                ),
+            ]
+        );
+        assert_eq!(
+            dataset.dividends[0].1[0],
+            ["2020-09-29", "0.7394", "USD", DEMO_TEST_STAMP, "demo"]
+        );
+        assert_eq!(
+            dataset.fx[0].1.lines().nth(1),
+            Some("USD,TWD,2020-06-29,30.563404,demo,2024-06-28T00:00:00+00:00")
+        );
+
+        let usd = &dataset.transactions["USD"];
+        assert_eq!(usd.len(), 13);
+        assert_eq!(
+            usd[..2],
+            [
+                [
+                    "2020-08-20",
+                    "DEMOC",
+                    "buy",
+                    "20",
+                    "45.5294",
+                    "1.00",
+                    "",
+                    "",
+                    "demo"
+                ],
+                [
+                    "2020-09-11",
+                    "DEMOB",
+                    "buy",
+                    "50",
+                    "395.8593",
+                    "19.79",
+                    "",
+                    "",
+                    "demo"
+                ],
+            ]
+        );
+        assert!(usd.contains(&demo_transaction_row(
+            date("2022-07-29"),
+            "DEMOB",
+            "split",
+            "0".to_string(),
+            "0".to_string(),
+            "0".to_string(),
+            "4".to_string(),
+        )));
+        assert!(dataset
+            .transactions
+            .values()
+            .flatten()
+            .all(|row| row.len() == 9 && row[8] == DEMO_SOURCE));
+    }
+
+    #[test]
+    fn demo_dataset_follows_the_end_date() {
+        let dataset = build_demo_dataset(42, date("2021-03-31"), DEMO_TEST_STAMP);
+        for (_, rows) in &dataset.prices {
+            assert_eq!(rows[0].date, date("2021-03-31"));
+        }
+        assert!(dataset
+            .transactions
+            .values()
+            .flatten()
+            .all(|row| row[0].as_str() <= "2021-03-31"));
+    }
+
+    #[test]
+    fn clearing_demo_rows_keeps_user_trades() {
+        let content = "date,stock,type,quantity,price,fees,split_ratio\n\
+2023-01-03,DEMOA,buy,5,100,1,\n\
+2023-01-04,DEMOA,buy,10,101.5,1.00,,,demo\n\
+2023-01-05,\"NASDAQ:AAPL\",sell,2,\"1,234.50\",1,,2022-01-03_150\n\
+2023-01-06,DEMOB,split,0,0,0,4,,demo\n";
+        let (kept, removed) = strip_demo_transaction_rows(content.as_bytes()).unwrap();
+        assert_eq!(removed, 2);
+        assert_eq!(
+            String::from_utf8(kept).unwrap(),
+            "date,stock,type,quantity,price,fees,split_ratio\n\
+2023-01-03,DEMOA,buy,5,100,1,\n\
+2023-01-05,NASDAQ:AAPL,sell,2,\"1,234.50\",1,,2022-01-03_150\n"
+        );
+
+        let transactions: Vec<Transaction> = {
+            let dir = std::env::temp_dir().join(format!("demo-clear-{}", std::process::id()));
+            create_dir_all(&dir).unwrap();
+            let path = dir.join("US_Trx.csv");
+            let (kept, _) = strip_demo_transaction_rows(content.as_bytes()).unwrap();
+            write(&path, kept).unwrap();
+            let read = read_csv_file(path.to_str().unwrap(), "USD").unwrap();
+            std::fs::remove_dir_all(&dir).unwrap();
+            read
+        };
+        assert_eq!(transactions.len(), 2);
+        assert_eq!(transactions[1].price, "1,234.50");
+        assert_eq!(transactions[1].lot_id, "2022-01-03_150");
+    }
+
+    /// Runs a legacy fixture through the same steps as `migrate_price_files` and
+    /// returns the rewritten content.
+    fn migrate_price_fixture(relative: &str, splits: &[(NaiveDate, f64)]) -> String {
+        let path = fixture_path(relative);
+        let content = read_to_string(&path).unwrap();
+        assert!(price_file_is_legacy(&content), "{} is not legacy", relative);
+        let records =
+            migrate_legacy_price_records(&content, scan_price_file_records(&path).unwrap(), splits);
+        let migrated = build_price_csv_content(&records).unwrap();
+        assert!(
+            !price_file_is_legacy(&migrated),
+            "{} still legacy",
+            relative
+        );
+        migrated
+    }
+
+    #[test]
+    fn migrates_seven_column_newest_first_layout() {
+        let migrated = migrate_price_fixture("prices/legacy/HKEX_0700.csv", &[]);
+        assert_eq!(
+            without_updated_at(&migrated),
+            [
+                "date,close,open,high,low,volume,adjusted_close,split_unadjusted_close,source",
+                "2023-03-02,372.4,369.0,375.8,366.2,18204500.0,,372.4,yahoo_finance",
+                "2023-03-01,1368.2,360.0,,355.4,,,1368.2,legacy",
+            ]
+        );
+    }
+
+    #[test]
+    fn migrates_oldest_first_layout_with_duplicates_and_splits() {
+        let splits = [(date("2024-06-10"), 10.0)];
+        let migrated = migrate_price_fixture("prices/legacy/NASDAQ_NVDA.csv", &splits);
+        assert_eq!(
+            without_updated_at(&migrated),
+            [
+                "date,close,open,high,low,volume,adjusted_close,split_unadjusted_close,source",
+                "2024-06-11,120.91,121.77,122.87,118.74,222551200.0,,120.91,yahoo_finance",
+                "2024-06-10,121.79,120.37,123.1,117.01,314162700.0,,121.79,yahoo_finance",
+                "2024-06-07,120.888,119.77,121.69,118.02,412100000.0,,1208.88,yahoo_finance",
+                "2024-06-06,120.998,125.0,125.57,118.32,412386000.0,,1209.98,yahoo_finance",
+            ]
+        );
+    }
+
+    #[test]
+    fn migrates_six_column_layout_without_source() {
+        let migrated = migrate_price_fixture("prices/legacy/TWSE_2330.csv", &[]);
+        assert_eq!(
+            without_updated_at(&migrated),
+            [
+                "date,close,open,high,low,volume,adjusted_close,split_unadjusted_close,source",
+                "2024-01-03,578.0,584.0,585.0,576.0,37148887.0,,578.0,legacy",
+                "2024-01-02,593.0,590.0,593.0,589.0,26059058.0,,593.0,legacy",
+            ]
+        );
+    }
+
+    /// A chart fixture's Yahoo symbol, price CSV and dividends.
+    type SyncedFixture = (String, String, Vec<(NaiveDate, f64)>);
+
+    /// Every chart fixture after the download, merge and write steps a sync runs.
+    fn synced_fixture_files() -> Vec<SyncedFixture> {
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(OFFLINE_FIXTURE_DIR)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|e| e == "json"))
+            .collect();
+        paths.sort();
+        paths
+            .iter()
+            .map(|path| {
+                let yahoo_symbol = path.file_stem().unwrap().to_str().unwrap().to_string();
+                let symbol = canonical_symbol(&yahoo_symbol);
+                let (downloaded, dividends, meta) = fetch_yahoo_chunk(
+                    &fixture_fetcher(),
+                    &yahoo_symbol,
+                    &symbol,
+                    date("2000-01-01"),
+                    date("2030-12-31"),
+                    "1d",
+                )
+                .unwrap();
+                assert!(meta.is_some(), "{} has no meta", yahoo_symbol);
+                let merged = merge_price_entries(&symbol, &[], &downloaded).unwrap();
+                let csv = build_price_csv_content(&merged).unwrap();
+                (yahoo_symbol, csv, dividends)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fixture_sync_writes_expected_price_files() {
+        let synced = synced_fixture_files();
+        assert_eq!(
+            synced
+                .iter()
+                .map(|(s, _, _)| s.as_str())
+                .collect::<Vec<_>>(),
+            ["0700.HK", "AAPL", "NVDA", "VFIAX"]
+        );
+        for (yahoo_symbol, csv, _) in &synced {
+            let expected = read_to_string(fixture_path(&format!(
+                "yahoo_expected/{}.csv",
+                yahoo_symbol
+            )))
+            .unwrap();
+            assert_eq!(
+                without_updated_at(csv),
+                without_updated_at(&expected),
+                "{}",
+                yahoo_symbol
+            );
+        }
+    }
+
+    #[test]
+    fn fixture_sync_extracts_dividends() {
+        let dividends: HashMap<String, Vec<(NaiveDate, f64)>> = synced_fixture_files()
+            .into_iter()
+            .map(|(symbol, _, dividends)| (symbol, dividends))
+            .collect();
+        assert_eq!(dividends["AAPL"], [(date("2024-02-09"), 0.24)]);
+        assert_eq!(dividends["VFIAX"], [(date("2024-06-06"), 1.6)]);
+        assert!(dividends["NVDA"].is_empty());
+        assert!(dividends["0700.HK"].is_empty());
+    }
+
+    #[test]
+    fn fixture_sync_trims_to_requested_window() {
+        let (records, dividends, _) = fetch_yahoo_chunk(
+            &fixture_fetcher(),
+            "AAPL",
+            "AAPL",
+            date("2024-02-08"),
+            date("2024-02-12"),
+            "1d",
+        )
+        .unwrap();
+        assert_eq!(
+            records.iter().map(|r| r.date).collect::<Vec<_>>(),
+            [date("2024-02-08"), date("2024-02-09"), date("2024-02-12")]
+        );
+        assert_eq!(dividends, [(date("2024-02-09"), 0.24)]);
+
+        let missing = fetch_yahoo_chunk(
+            &fixture_fetcher(),
+            "MSFT",
+            "MSFT",
+            date("2024-02-08"),
+            date("2024-02-12"),
+            "1d",
+        );
+        assert!(matches!(missing, Err(AppError::NotFound { .. })));
+    }
+
+    #[test]
+    fn chart_parse_error_quotes_the_response() {
+        let Err(AppError::Parse { message, .. }) = parse_yahoo_chart("AAPL", "Too Many Requests")
+        else {
+            panic!("expected a parse error");
+        };
+        assert!(message.contains("\"Too Many Requests\""), "{}", message);
+    }
+
+    #[test]
+    fn transaction_header_detection() {
+        assert!(is_transaction_header("date"));
+        assert!(is_transaction_header("Date"));
+        assert!(is_transaction_header(" DATE "));
+        assert!(is_transaction_header("\u{feff}Date"));
+        assert!(!is_transaction_header("2019-03-01"));
+        assert!(!is_transaction_header("03/01/2019"));
+        assert!(!is_transaction_header(""));
+        assert!(!is_transaction_header("dates"));
+    }
+
+    #[test]
+    fn headered_and_headerless_transaction_files_read_the_same() {
+        let read = |name: &str| {
+            let path = fixture_path(&format!("transactions/{}", name));
+            read_csv_file(path.to_str().unwrap(), "USD").unwrap()
+        };
+        let headered = read("headered.csv");
+        let headerless = read("headerless.csv");
+        assert_eq!(headered.len(), 4);
+        assert_eq!(
+            serde_json::to_value(&headered).unwrap(),
+            serde_json::to_value(&headerless).unwrap()
+        );
+
+        let first = &headerless[0];
+        assert_eq!(first.date, "2019-03-01");
+        assert_eq!(first.stock, "NASDAQ:AAPL");
+        assert_eq!(first.quantity, "10");
+        assert_eq!(headerless[2].stock, "AAPL");
+        // Rows that stop before fees or split_ratio read them as empty.
+        assert_eq!(headerless[2].fees, "1");
+        assert_eq!(headerless[2].split_ratio, "");
+        assert_eq!(headerless[3].fees, "");
+        assert_eq!(headerless[3].split_ratio, "");
+        // Line numbers point at the file line, header included.
+        assert_eq!(headered[0].source.as_ref().unwrap().1, 2);
+        assert_eq!(headerless[0].source.as_ref().unwrap().1, 1);
+    }
+
+    fn timeline_prices() -> Vec<PriceRecordEntry> {
+        vec![
+            yahoo_row("2024-01-02", 368.0, None),
+            yahoo_row("2024-01-03", 370.0, None),
+            yahoo_row("2024-01-04", 367.75, None),
+        ]
+    }
+
+    #[test]
+    fn timeline_values_trades_after_the_last_price() {
+        let transactions = [
+            txn("2024-01-03", "buy", 10.0, 370.0, 1.0),
+            txn("2024-01-08", "buy", 5.0, 380.0, 1.0),
+        ];
+        let (timeline, warnings) = build_position_timeline(&timeline_prices(), &transactions);
+        assert_eq!(
+            timeline,
+            [
+                ("2024-01-02".to_string(), 368.0, 0.0),
+                ("2024-01-03".to_string(), 370.0, 10.0),
+                ("2024-01-04".to_string(), 367.75, 10.0),
+                ("2024-01-08".to_string(), 367.75, 15.0),
+            ]
+        );
+        assert_eq!(
+            warnings,
+            ["buy on 2024-01-08 is after the last price (2024-01-04); valued at that close"]
+        );
+    }
+
+    #[test]
+    fn timeline_applies_trades_before_the_first_price_on_that_day() {
+        let transactions = [
+            txn("2023-12-15", "buy", 10.0, 360.0, 1.0),
+            txn("2023-12-20", "sell", 4.0, 365.0, 1.0),
+            txn("2024-01-03", "buy", 1.0, 370.0, 1.0),
+        ];
+        let (timeline, warnings) = build_position_timeline(&timeline_prices(), &transactions);
+        assert_eq!(
+            timeline,
+            [
+                ("2024-01-02".to_string(), 368.0, 6.0),
+                ("2024-01-03".to_string(), 370.0, 7.0),
+                ("2024-01-04".to_string(), 367.75, 7.0),
+            ]
+        );
+        assert_eq!(
+            warnings,
+            [
+                "buy on 2023-12-15 predates price history (starts 2024-01-02); applied on that day",
+                "sell on 2023-12-20 predates price history (starts 2024-01-02); applied on that day",
+            ]
+        );
+    }
+
+    #[test]
+    fn timeline_without_prices_is_empty() {
+        let (timeline, warnings) =
+            build_position_timeline(&[], &[txn("2024-01-03", "buy", 10.0, 370.0, 1.0)]);
+        assert!(timeline.is_empty());
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn parse_number_accepts_spreadsheet_and_broker_formats() {
+        let cases = [
+            ("42", 42.0),
+            (" 1234.5 ", 1234.5),
+            ("1,234.50", 1234.5),
+            ("1.234,50", 1234.5),
+            ("1 234", 1234.0),
+            ("1'234.5", 1234.5),
+            ("1_000", 1000.0),
+            ("12,5", 12.5),
+            ("1,234", 1234.0),
+            // Not a valid thousands grouping, so the comma is a decimal comma.
+            ("1,2345", 1.2345),
+            ("1.234.567", 1_234_567.0),
+            ("$1,234.50", 1234.5),
+            ("€12,50", 12.5),
+            ("(12.50)", -12.5),
+            ("-3", -3.0),
+            ("+3", 3.0),
+            (".5", 0.5),
+            ("1e3", 1000.0),
+            ("1\u{a0}234,5", 1234.5),
+        ];
+        for (raw, expected) in cases {
+            assert_eq!(parse_number(raw), Ok(Some(expected)), "{:?}", raw);
+        }
+        assert_eq!(parse_number(""), Ok(None));
+        assert_eq!(parse_number("   "), Ok(None));
+    }
+
+    #[test]
+    fn parse_number_rejects_ambiguous_or_non_numeric_text() {
+        for raw in [
+            "N/A", "12.34.56", "1,23.4", "12,34,5", "abc", "1.2.3,4", "inf", "NaN", "--5", "$",
+            "-(2)",
+        ] {
+            assert!(parse_number(raw).is_err(), "{:?} parsed", raw);
+        }
+        assert_eq!(parse_f64_str("N/A"), None);
+    }
+
+    fn raw_transaction(line: usize, date: &str, quantity: &str, split_ratio: &str) -> Transaction {
+        Transaction {
+            date: date.to_string(),
+            stock: "NASDAQ:AAPL".to_string(),
+            transaction_type: "Buy".to_string(),
+            quantity: quantity.to_string(),
+            price: "150".to_string(),
+            fees: String::new(),
+            split_ratio: split_ratio.to_string(),
+            currency: "USD".to_string(),
+            lot_id: String::new(),
+            source: Some(("US_Trx.csv".to_string(), line)),
+        }
+    }
+
+    fn issue_labels(issues: &[ParseIssue]) -> Vec<String> {
+        issues.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn process_transactions_returns_every_issue() {
+        let rows = || {
+            vec![
+                raw_transaction(2, "2024-01-03", "10", ""),
+                raw_transaction(3, "2024-01-04", "N/A", ""),
+                raw_transaction(4, "01/05/2024", "5", ""),
+                raw_transaction(5, "2024-01-02", "1,000", "2:0"),
+            ]
+        };
+        let expected = [
+            "US_Trx.csv:3: invalid quantity 'N/A'",
+            "US_Trx.csv:4: invalid date '01/05/2024'",
+            "US_Trx.csv:5: invalid split_ratio '2:0'",
+        ];
+
+        let (strict, issues) = process_transactions(rows(), false);
+        assert_eq!(issue_labels(&issues), expected);
+        assert_eq!(strict.len(), 1);
+        assert_eq!(strict[0].quantity, 10.0);
+        assert_eq!(strict[0].txn_type, "buy");
+
+        let (lenient, issues) = process_transactions(rows(), true);
+        assert_eq!(issue_labels(&issues), expected);
+        assert_eq!(
+            lenient
+                .iter()
+                .map(|t| (t.date, t.quantity, t.split_ratio))
+                .collect::<Vec<_>>(),
+            [
+                (date("2024-01-02"), 1000.0, 1.0),
+                (date("2024-01-03"), 10.0, 1.0),
+                (date("2024-01-04"), 0.0, 1.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn complete_transactions_fail_with_all_blocking_issues() {
+        let rows = vec![
+            raw_transaction(2, "2024-01-03", "N/A", ""),
+            raw_transaction(3, "2024-01-04", "12.34.56", ""),
+        ];
+        assert_eq!(
+            process_complete_transactions(rows.clone(), false).unwrap_err(),
+            "US_Trx.csv:2: invalid quantity 'N/A'; US_Trx.csv:3: invalid quantity '12.34.56'"
+        );
+        assert_eq!(process_complete_transactions(rows, true).unwrap().len(), 2);
+
+        let undated = vec![raw_transaction(2, "", "1", "")];
+        assert_eq!(
+            process_complete_transactions(undated, true).unwrap_err(),
+            "US_Trx.csv:2: invalid date ''"
+        );
+    }
+
+    #[test]
+    fn spinoff_markers_are_not_read_as_splits() {
+        let content = format!(
+            "{}2022-07-29,4,1,316.9751,78.8035\n2024-06-03,1,1,,,spinoff\n",
+            SPLIT_FILE_HEADER
+        );
+        let dir = std::env::temp_dir().join(format!("spinoff-marker-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("NYSE_MMM.csv");
+        std::fs::write(&path, &content).unwrap();
+        let rows = read_split_rows(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0][0], "2022-07-29");
+
+        assert_eq!(
+            strip_spinoff_markers(&content).unwrap(),
+            format!("{}2022-07-29,4,1,316.9751,78.8035\n", SPLIT_FILE_HEADER)
+        );
+        assert_eq!(
+            strip_spinoff_markers(&format!("{}2022-07-29,4,1,,\n", SPLIT_FILE_HEADER)),
+            None
+        );
+    }
+
+    #[test]
+    fn split_row_ratio_rejects_bad_components() {
+        let row = |n: &str, d: &str| {
+            vec![
+                "2024-06-10".to_string(),
+                n.to_string(),
+                d.to_string(),
+                String::new(),
+                String::new(),
+            ]
+        };
+        assert_eq!(split_row_ratio(&row("10", "1")), Ok(10.0));
+        assert_eq!(split_row_ratio(&row("1", "8")), Ok(0.125));
+        for (n, d) in [("", "1"), ("10", ""), ("x", "1"), ("0", "1"), ("3", "-1")] {
+            assert!(split_row_ratio(&row(n, d)).is_err(), "{}:{}", n, d);
+        }
+    }
+
+    #[test]
+    fn canonical_symbol_unifies_exchange_spellings() {
+        for spelling in [
+            "0700.HK",
+            "0700.hk",
+            "HKEX:0700",
+            "0700:HKEX",
+            " HKEX:0700 ",
+        ] {
+            assert_eq!(canonical_symbol(spelling), "HKEX:0700", "{}", spelling);
+        }
+        assert_eq!(canonical_symbol("2330.TW"), "TWSE:2330");
+        assert_eq!(canonical_symbol("6488.TWO"), "TPEX:6488");
+        assert_eq!(canonical_symbol("7203.T"), "JPX:7203");
+        assert_eq!(canonical_symbol("AAPL:NASDAQ"), "NASDAQ:AAPL");
+        // No known suffix: share classes and bare tickers are left alone.
+        assert_eq!(canonical_symbol("BRK.B"), "BRK.B");
+        assert_eq!(canonical_symbol("AAPL"), "AAPL");
+        assert_eq!(canonical_symbol(".HK"), ".HK");
+    }
+
+    #[test]
+    fn exchange_spellings_merge_into_one_position() {
+        let dir = std::env::temp_dir().join(format!("symbol-merge-{}", std::process::id()));
+        create_dir_all(&dir).unwrap();
+        let path = dir.join("HK_Trx.csv");
+        write(
+            &path,
+            "Date,Stock,Action,Quantity,Price,Fees,Split Ratio\n\
+             2024-01-02,0700.HK,buy,100,300,0,\n\
+             2024-02-01,HKEX:0700,buy,200,330,0,\n\
+             2024-03-01,0700:HKEX,sell,50,350,0,\n",
+        )
+        .unwrap();
+        let transactions = read_csv_file(path.to_str().unwrap(), "HKD").unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let mut by_symbol: HashMap<String, Vec<Transaction>> = HashMap::new();
+        for txn in transactions {
+            by_symbol.entry(txn.stock.clone()).or_default().push(txn);
+        }
+        assert_eq!(by_symbol.len(), 1);
+        let processed =
+            process_complete_transactions(by_symbol.remove("HKEX:0700").unwrap(), false).unwrap();
+        let (shares, cost, realized) = replay_average_cost(&processed, date("2024-12-31"));
+        assert!((shares - 250.0).abs() < 1e-9);
+        assert!((cost - 80_000.0).abs() < 1e-6);
+        assert!((realized - 1_500.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn legacy_stems_include_pre_canonical_spellings() {
+        let spellings: Vec<String> = ["0700.HK", "0700:HKEX", "HKEX:0700", "AAPL", "BRK_B"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let stems = legacy_symbol_stems(&spellings);
+        let legacy = |stem: &str| -> Vec<&str> { stems[stem].iter().map(String::as_str).collect() };
+        assert_eq!(stems.len(), 2);
+        assert_eq!(legacy("HKEX_0700"), vec!["0700.HK", "0700_HKEX"]);
+        assert_eq!(legacy("BRK%5FB"), vec!["BRK_B"]);
+    }
+
+    #[test]
+    fn rename_symbol_files_moves_pre_canonical_files() {
+        let dir = std::env::temp_dir().join(format!("symbol-rename-{}", std::process::id()));
+        let prices = dir.join("prices");
+        let splits = dir.join("splits");
+        create_dir_all(&prices).unwrap();
+        create_dir_all(&splits).unwrap();
+        write(prices.join("0700.HK.csv"), "price").unwrap();
+        write(prices.join("0700.HK-override.csv"), "override").unwrap();
+        write(splits.join("0700_HKEX.csv"), "split").unwrap();
+        write(splits.join("HKEX_0700.csv"), "current split").unwrap();
+
+        let spellings = vec!["0700.HK".to_string(), "0700:HKEX".to_string()];
+        let locations = [
+            (prices.clone(), ".csv"),
+            (prices.clone(), "-override.csv"),
+            (splits.clone(), ".csv"),
+        ];
+        let (renamed, conflicts) =
+            rename_symbol_files(&locations, &legacy_symbol_stems(&spellings)).unwrap();
+
+        assert_eq!(renamed, 2);
+        assert_eq!(
+            conflicts,
+            vec![splits.join("0700_HKEX.csv").display().to_string()]
+        );
+        assert_eq!(
+            read_to_string(prices.join("HKEX_0700.csv")).unwrap(),
+            "price"
+        );
+        assert_eq!(
+            read_to_string(prices.join("HKEX_0700-override.csv")).unwrap(),
+            "override"
+        );
+        assert!(!prices.join("0700.HK.csv").exists());
+        assert_eq!(
+            read_to_string(splits.join("HKEX_0700.csv")).unwrap(),
+            "current split"
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn recomputed_split_adjustments_match_a_fresh_download() {
+        let dir = std::env::temp_dir().join(format!("split-recompute-{}", std::process::id()));
+        create_dir_all(&dir).unwrap();
+        // NVDA as downloaded before Yahoo reported the 2024-06-10 split.
+        let mut chart: serde_json::Value =
+            serde_json::from_str(&read_to_string(fixture_path("yahoo/NVDA.json")).unwrap())
+                .unwrap();
+        let events = chart["chart"]["result"][0]["events"]
+            .as_object_mut()
+            .unwrap();
+        assert!(events.remove("splits").is_some());
+        write(dir.join("NVDA.json"), chart.to_string()).unwrap();
+
+        let fetcher = FixtureFetcher { dir: dir.clone() };
+        let (downloaded, _, _) = fetch_yahoo_chunk(
+            &fetcher,
+            "NVDA",
+            "NVDA",
+            date("2000-01-01"),
+            date("2030-12-31"),
+            "1d",
+        )
+        .unwrap();
+        let stale =
+            build_price_csv_content(&merge_price_entries("NVDA", &[], &downloaded).unwrap())
+                .unwrap();
+        let fresh = read_to_string(fixture_path("yahoo_expected/NVDA.csv")).unwrap();
+        assert_ne!(without_updated_at(&stale), without_updated_at(&fresh));
+
+        let price_path = dir.join("NVDA.csv");
+        write(&price_path, &stale).unwrap();
+        let file = read_raw_price_file(&price_path).unwrap();
+        let splits = [(date("2024-06-10"), 10.0)];
+        let recomputed =
+            String::from_utf8(split_adjusted_price_content(&file, &splits, None).unwrap()).unwrap();
+        assert_eq!(without_updated_at(&recomputed), without_updated_at(&fresh));
+
+        // Running it again changes nothing.
+        write(&price_path, &recomputed).unwrap();
+        let file = read_raw_price_file(&price_path).unwrap();
+        let again =
+            String::from_utf8(split_adjusted_price_content(&file, &splits, None).unwrap()).unwrap();
+        assert_eq!(again, recomputed);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn new_split_adjusts_earlier_rows_only() {
+        let dir = std::env::temp_dir().join(format!("split-new-{}", std::process::id()));
+        create_dir_all(&dir).unwrap();
+        let price_path = dir.join("ACME.csv");
+        write(
+            &price_path,
+            "date,close,open,volume,split_unadjusted_close\n\
+             2024-06-07,100,98,10,100\n\
+             2024-06-10,10.5,10,120,10.5\n",
+        )
+        .unwrap();
+        let file = read_raw_price_file(&price_path).unwrap();
+        let split = (date("2024-06-10"), 10.0);
+        let content =
+            String::from_utf8(split_adjusted_price_content(&file, &[split], Some(split)).unwrap())
+                .unwrap();
+        assert_eq!(
+            content,
+            "date,close,open,volume,split_unadjusted_close\n\
+             2024-06-07,10,9.8,100,100\n\
+             2024-06-10,10.5,10,120,10.5\n"
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn symbol_file_stems_round_trip() {
+        let symbols = [
+            "AAPL",
+            "NASDAQ:AAPL",
+            "NYSE:BRK_B",
+            "NYSE:BRK.B",
+            "BRK.B",
+            "HKEX:0700",
+            "LSE:BP.",
+            "A/B",
+            "A\\B",
+            "WEIRD*?<>|\"",
+            "TAB\tNAME",
+            "TRAILING ",
+            "50%",
+            "__",
+            "_:_",
+            "東証:7203",
+            "ÉTF:ü",
+            "CON",
+            "con.txt",
+            "COM1",
+            "LPT9:X",
+            "AUX_",
+            "NUL:NUL",
+            "COMX",
+        ];
+        let mut stems = std::collections::HashSet::new();
+        for symbol in symbols {
+            let stem = symbol_file_stem(symbol);
+            assert_eq!(
+                symbol_from_file_stem(&stem),
+                symbol,
+                "{} -> {}",
+                symbol,
+                stem
+            );
+            assert!(
+                stems.insert(stem.clone()),
+                "{} collides as {}",
+                symbol,
+                stem
+            );
+            // Portable on every platform we ship.
+            assert!(
+                !stem.chars().any(
+                    |c| matches!(c, '/' | '\\' | '*' | '?' | '"' | '<' | '>' | '|')
+                        || c.is_control()
+                ),
+                "{}",
+                stem
+            );
+            assert!(!stem.ends_with('.') && !stem.ends_with(' '), "{}", stem);
+            let device = stem.split('.').next().unwrap().to_ascii_uppercase();
+            assert!(
+                !["CON", "PRN", "AUX", "NUL", "COM1", "LPT9"].contains(&device.as_str()),
+                "{}",
+                stem
+            );
+        }
+        // Ordinary symbols keep the names they have always had.
+        assert_eq!(symbol_file_stem("NASDAQ:AAPL"), "NASDAQ_AAPL");
+        assert_eq!(symbol_file_stem("NYSE:BRK_B"), "NYSE_BRK%5FB");
+        assert_eq!(symbol_file_stem("COMX"), "COMX");
+    }
+
+    #[test]
+    fn ambiguous_legacy_names_are_reported() {
+        let dir = std::env::temp_dir().join(format!("symbol-undecodable-{}", std::process::id()));
+        let prices = dir.join("prices");
+        let metas = dir.join("yahoo_metas");
+        create_dir_all(&prices).unwrap();
+        create_dir_all(&metas).unwrap();
+        for name in [
+            "NYSE_BRK_B.csv",
+            "NYSE_BRK_B-override.csv",
+            "NYSE_ABC_D.csv",
+            "NASDAQ_AAPL.csv",
+            "NYSE_BRK%5FB.csv",
+        ] {
+            write(prices.join(name), "").unwrap();
+        }
+        write(metas.join("X_Y_Z.json"), "{}").unwrap();
+
+        let locations = [
+            (prices.clone(), ".csv"),
+            (prices.clone(), "-override.csv"),
+            (metas.clone(), ".json"),
+        ];
+        let known: std::collections::HashSet<String> = ["NYSE_ABC_D".to_string()].into();
+        let mut expected: Vec<String> = [
+            prices.join("NYSE_BRK_B-override.csv"),
+            prices.join("NYSE_BRK_B.csv"),
+            metas.join("X_Y_Z.json"),
+        ]
+        .iter()
+        .map(|p| p.display().to_string())
+        .collect();
+        expected.sort();
+        assert_eq!(undecodable_symbol_files(&locations, &known), expected);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn nav_files_resolve_by_exact_name() {
+        let dir = std::env::temp_dir().join(format!("nav-resolve-{}", std::process::id()));
+        create_dir_all(&dir).unwrap();
+        let resolved = |symbol: &str| {
+            resolve_nav_file(&dir, &symbol_file_stem(symbol))
+                .unwrap()
+                .map(|(path, fallback)| {
+                    (
+                        path.file_name().unwrap().to_string_lossy().into_owned(),
+                        fallback.is_some(),
+                    )
+                })
+        };
+
+        // AAPL.csv sorts after A.csv and used to win for "A".
+        write(dir.join("AAPL.csv"), "date,nav\n").unwrap();
+        assert_eq!(resolved("A"), None);
+        write(dir.join("A.csv"), "date,nav\n").unwrap();
+        assert_eq!(resolved("A"), Some(("A.csv".to_string(), false)));
+        assert_eq!(resolved("AAPL"), Some(("AAPL.csv".to_string(), false)));
+
+        // Legacy timestamped files only when there is no exact file, newest first.
+        write(dir.join("MSFT_2024-01-31.csv"), "").unwrap();
+        let older = File::options()
+            .write(true)
+            .open(dir.join("MSFT_2024-01-31.csv"))
+            .unwrap();
+        older
+            .set_modified(std::time::SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000))
+            .unwrap();
+        write(dir.join("MSFT_20240229T120000.csv"), "").unwrap();
+        assert_eq!(
+            resolved("MSFT"),
+            Some(("MSFT_20240229T120000.csv".to_string(), true))
+        );
+
+        // Another symbol's file is never a legacy match.
+        write(dir.join("HKEX_0700.csv"), "").unwrap();
+        assert!(is_legacy_nav_file_for("A_20240131", "A"));
+        assert!(!is_legacy_nav_file_for("AAPL", "A"));
+        assert!(!is_legacy_nav_file_for("HKEX_0700", "HKEX"));
+        assert_eq!(resolved("HKEX"), None);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn security_types_parse_configured_and_yahoo_names() {
+        for (raw, expected) in [
+            ("ETF", SecurityType::Etf),
+            (" etf ", SecurityType::Etf),
+            ("Fund", SecurityType::Fund),
+            ("MUTUALFUND", SecurityType::Fund),
+            ("mutual-fund", SecurityType::Fund),
+            ("Mutual Fund", SecurityType::Fund),
+            ("bond", SecurityType::Bond),
+            ("EQUITY", SecurityType::Stock),
+            ("", SecurityType::Stock),
+            ("crypto", SecurityType::Stock),
+        ] {
+            assert_eq!(SecurityType::parse(raw), expected, "{:?}", raw);
+        }
+    }
+
+    fn fixture_chunk(yahoo_symbol: &str) -> (Vec<PriceRecordEntry>, Vec<(NaiveDate, f64)>) {
+        let (records, dividends, _) = fetch_yahoo_chunk(
+            &fixture_fetcher(),
+            yahoo_symbol,
+            &canonical_symbol(yahoo_symbol),
+            date("2000-01-01"),
+            date("2030-12-31"),
+            SecurityType::Fund.fetch_profile().interval,
+        )
+        .unwrap();
+        (records, dividends)
+    }
+
+    #[test]
+    fn fund_fixture_is_close_only_and_survives_the_fund_profile() {
+        let (mut records, mut dividends) = fixture_chunk("VFIAX");
+        assert_eq!(records.len(), 6);
+        assert!(records.iter().all(|r| r.open.is_none()
+            && r.high.is_none()
+            && r.low.is_none()
+            && r.volume.is_none()
+            && r.split_unadjusted_close == Some(r.close)));
+        let before = records.clone();
+        SecurityType::Fund
+            .fetch_profile()
+            .apply(&mut records, &mut dividends);
+        assert_eq!(records, before);
+    }
+
+    #[test]
+    fn fetch_profiles_strip_what_the_type_does_not_store() {
+        let (downloaded, dividends) = fixture_chunk("NVDA");
+        assert!(downloaded
+            .iter()
+            .any(|r| r.open.is_some() && r.volume.is_some()));
+        assert!(downloaded
+            .iter()
+            .any(|r| r.split_unadjusted_close != Some(r.close)));
+
+        for kind in [SecurityType::Stock, SecurityType::Etf] {
+            let (mut records, mut kept) = (downloaded.clone(), dividends.clone());
+            kind.fetch_profile().apply(&mut records, &mut kept);
+            assert_eq!((records, kept), (downloaded.clone(), dividends.clone()));
+        }
+
+        let (mut fund, mut fund_dividends) = (downloaded.clone(), dividends.clone());
+        SecurityType::Fund
+            .fetch_profile()
+            .apply(&mut fund, &mut fund_dividends);
+        assert_eq!(fund_dividends, dividends);
+        for (record, original) in fund.iter().zip(&downloaded) {
+            assert_eq!(
+                (record.open, record.high, record.low, record.volume),
+                (None, None, None, None)
+            );
+            assert_eq!(record.close, original.close);
+            assert_eq!(
+                record.split_unadjusted_close,
+                original.split_unadjusted_close
+            );
+        }
+
+        let (mut bond, mut bond_dividends) = fixture_chunk("AAPL");
+        assert!(!bond_dividends.is_empty());
+        SecurityType::Bond
+            .fetch_profile()
+            .apply(&mut bond, &mut bond_dividends);
+        assert!(bond_dividends.is_empty());
+        assert!(bond
+            .iter()
+            .all(|r| r.split_unadjusted_close == Some(r.close) && r.open.is_some()));
+    }
+
+    #[test]
+    fn truncate_price_rows_keeps_the_newest_rows() {
+        // Newest first, one row a day back from 2024-12-31.
+        let all = synthetic_price_entries(10);
+
+        let mut records = all.clone();
+        assert_eq!(truncate_price_rows(&mut records, 4, date("2025-01-01")), 6);
+        assert_eq!(records, all[..4]);
+
+        // Rows from the first trade window onward survive even past the limit.
+        let mut records = all.clone();
+        assert_eq!(truncate_price_rows(&mut records, 4, date("2024-12-25")), 3);
+        assert_eq!(records.last().unwrap().date, date("2024-12-25"));
+        assert_eq!(records.len(), 7);
+
+        let mut records = all.clone();
+        assert_eq!(truncate_price_rows(&mut records, 4, date("2000-01-01")), 0);
+        assert_eq!(records, all);
+
+        let mut records = all.clone();
+        assert_eq!(truncate_price_rows(&mut records, 20, date("2025-01-01")), 0);
+        assert_eq!(records, all);
+
+        let mut empty = Vec::new();
+        assert_eq!(truncate_price_rows(&mut empty, 0, date("2024-01-01")), 0);
+    }
+
+    #[test]
+    fn file_lock_keys_match_across_path_spellings() {
+        let dir = std::env::temp_dir().join(format!("file-lock-key-{}", std::process::id()));
+        create_dir_all(dir.join("prices")).unwrap();
+        // The file does not need to exist yet.
+        let direct = dir.join("prices").join("AAPL.csv");
+        let dotted = dir
+            .join("prices")
+            .join(".")
+            .join("..")
+            .join("prices")
+            .join("AAPL.csv");
+        assert_eq!(file_lock_key(&direct), file_lock_key(&dotted));
+        assert_ne!(
+            file_lock_key(&direct),
+            file_lock_key(&dir.join("prices").join("MSFT.csv"))
+        );
+
+        let registry = FileLockRegistry::default();
+        let lock = registry.lock_for(&file_lock_key(&direct));
+        assert!(Arc::ptr_eq(
+            &lock,
+            &registry.lock_for(&file_lock_key(&dotted))
+        ));
+        let _guard = lock.lock().unwrap();
+        assert!(registry
+            .lock_for(&file_lock_key(&dotted))
+            .try_lock()
+            .is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn flat_layout_staging_resumes_after_an_interruption() {
+        let root = std::env::temp_dir().join(format!("flat-layout-{}", std::process::id()));
+        create_dir_all(root.join("prices")).unwrap();
+        write(root.join("prices").join("AAPL.csv"), "date,close\n").unwrap();
+        write(root.join("settings.csv"), "key,value\n").unwrap();
+        write(root.join("US_Trx.csv"), "").unwrap();
+        write(root.join(APP_SETTINGS_FILE), "key,value\n").unwrap();
+        write(root.join("portfolios.csv"), PORTFOLIOS_HEADER).unwrap();
+
+        // A first attempt that stopped after moving one entry.
+        let staged = root
+            .join(MIGRATING_PORTFOLIOS_DIR)
+            .join(DEFAULT_PORTFOLIO_ID);
+        create_dir_all(&staged).unwrap();
+        std::fs::rename(root.join("US_Trx.csv"), staged.join("US_Trx.csv")).unwrap();
+        assert!(!root.join("portfolios").exists());
+
+        assert_eq!(stage_flat_data_layout(&root).unwrap(), 2);
+        let mut names: Vec<String> = std::fs::read_dir(&staged)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["US_Trx.csv", "prices", "settings.csv"]);
+        assert!(staged.join("prices").join("AAPL.csv").is_file());
+        // Root-level files shared by every portfolio stay where they are.
+        assert!(root.join(APP_SETTINGS_FILE).is_file());
+        assert!(root.join("portfolios.csv").is_file());
+
+        assert_eq!(stage_flat_data_layout(&root).unwrap(), 0);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn stooq_symbols_only_cover_listed_markets() {
+        assert_eq!(stooq_symbol_for(None, "BRK.B").as_deref(), Some("BRK-B.US"));
+        assert_eq!(
+            stooq_symbol_for(Some("WSE"), "CDR").as_deref(),
+            Some("CDR.PL")
+        );
+        for exchange in ["TWSE", "ASX", "TSX", "KRX"] {
+            assert_eq!(stooq_symbol_for(Some(exchange), "2330"), None);
+        }
+    }
+}
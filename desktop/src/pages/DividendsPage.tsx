@@ -125,18 +125,17 @@ export function DividendsPage() {
 
   const getDividendAmount = useCallback(
     (txn: Transaction): number | null => {
-      const cleanPrice = txn.price.replace(/[$,]/g, '');
-      const cleanQuantity = txn.quantity.replace(/[$,]/g, '');
-      let amount = parseFloat(cleanQuantity) * parseFloat(cleanPrice);
+      if (txn.quantity === null || txn.price === null) {
+        return null;
+      }
+      let amount = txn.quantity * txn.price;
 
       if (!Number.isFinite(amount) || amount === 0) {
         return null;
       }
 
-      const cleanFees = (txn.fees || '').replace(/[$,]/g, '');
-      const feeAmount = parseFloat(cleanFees);
-      if (Number.isFinite(feeAmount)) {
-        amount -= feeAmount;
+      if (txn.fees !== null) {
+        amount -= txn.fees;
       }
 
       if (!Number.isFinite(amount) || amount <= 0) {
@@ -405,12 +404,9 @@ export function DividendsPage() {
 
     const transactionRows: DividendTransactionRow[] = dividendTransactions
       .map(txn => {
-        const cleanQuantity = txn.quantity.replace(/[$,]/g, '');
-        const cleanPrice = txn.price.replace(/[$,]/g, '');
-        const cleanFees = (txn.fees || '').replace(/[$,]/g, '');
-        const quantity = parseFloat(cleanQuantity);
-        const pricePerShare = parseFloat(cleanPrice);
-        const fees = parseFloat(cleanFees);
+        const quantity = txn.quantity ?? NaN;
+        const pricePerShare = txn.price ?? NaN;
+        const fees = txn.fees ?? NaN;
         const netAmount = getDividendAmount(txn);
 
         if (netAmount === null) {
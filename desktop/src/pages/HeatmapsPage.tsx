@@ -380,7 +380,7 @@ export function HeatmapsPage() {
       const year = date.getFullYear();
       const month = date.getMonth() + 1;
       const key = `${year}-${String(month).padStart(2, '0')}`;
-      const amount = parseFloat(txn.quantity) * parseFloat(txn.price);
+      const amount = (txn.quantity ?? 0) * (txn.price ?? 0);
 
       if (!byYearMonth.has(key)) {
         byYearMonth.set(key, { year, month, total: 0, count: 0, stocks: new Set() });
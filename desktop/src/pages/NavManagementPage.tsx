@@ -14,6 +14,7 @@ import { navService } from '../services/navService';
 import { PriceRecord } from '../types/PriceData';
 import { ResponsiveContainer, AreaChart, Area, XAxis, YAxis, Tooltip, CartesianGrid } from 'recharts';
 import { CURRENCY_SYMBOLS } from '../config/currencies';
+import { getErrorMessage } from '../types/AppError';
 
 const StatsGrid = styled.div`
   display: grid;
@@ -290,12 +291,13 @@ export function NavManagementPage() {
           last_transaction: row.lastTransaction,
         };
 
-        const savedPath = await navService.savePositionSnapshot(payload);
-        setStatusMessage(`Snapshot for ${row.stock} saved to ${savedPath}`);
+        const result = await navService.savePositionSnapshot(payload);
+        const warningNote = result.warnings.length > 0 ? ` (${result.warnings.join('; ')})` : '';
+        setStatusMessage(`Snapshot for ${row.stock} saved to ${result.path}${warningNote}`);
       } catch (error) {
         console.error('Failed to save position snapshot:', error);
         setErrorMessage(
-          error instanceof Error ? error.message : `Failed to save snapshot for ${row.stock}`
+          getErrorMessage(error, `Failed to save snapshot for ${row.stock}`)
         );
       } finally {
         setSavingRow(null);
@@ -241,7 +241,7 @@ export function TransactionsPage() {
       accessorKey: 'quantity',
       header: 'Quantity',
       enableSorting: true,
-      cell: info => privacyMode ? '***' : (info.getValue() as string),
+      cell: info => privacyMode ? '***' : (info.getValue() as number | null) ?? '—',
       meta: {
         headerStyle: { textAlign: 'right' },
         cellStyle: { textAlign: 'right', fontFamily: 'monospace' },
@@ -251,7 +251,7 @@ export function TransactionsPage() {
       accessorKey: 'price',
       header: 'Price (per unit)',
       enableSorting: true,
-      cell: info => privacyMode ? '***' : (info.getValue() as string),
+      cell: info => privacyMode ? '***' : (info.getValue() as number | null) ?? '—',
       meta: {
         headerStyle: { textAlign: 'right' },
         cellStyle: { textAlign: 'right', fontFamily: 'monospace' },
@@ -261,7 +261,7 @@ export function TransactionsPage() {
       accessorKey: 'fees',
       header: 'Fees',
       enableSorting: true,
-      cell: info => privacyMode ? '***' : (info.getValue() as string),
+      cell: info => privacyMode ? '***' : (info.getValue() as number | null) ?? '—',
       meta: {
         headerStyle: { textAlign: 'right' },
         cellStyle: { textAlign: 'right', fontFamily: 'monospace' },
@@ -271,7 +271,7 @@ export function TransactionsPage() {
       accessorKey: 'split_ratio',
       header: 'Split Ratio',
       enableSorting: true,
-      cell: info => (info.getValue() as string) || '—',
+      cell: info => (info.getValue() as number | null) ?? '—',
       meta: {
         headerStyle: { textAlign: 'right' },
         cellStyle: { textAlign: 'right', fontFamily: 'monospace' },
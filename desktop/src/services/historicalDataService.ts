@@ -15,6 +15,11 @@ interface RustStockDataCoverage {
   last_split: string | null;
   status: string;
   delist_reason?: string;
+  days_since_latest_price?: number | null;
+  stale?: boolean;
+  last_sync_error?: string | null;
+  first_trade_date?: string | null;
+  tags?: string[];
 }
 
 interface RustSplitHistory {
@@ -33,9 +38,32 @@ interface RustDataReadinessStats {
   complete_data: number;
   partial_data: number;
   missing_data: number;
+  stale_data?: number;
   total_price_records: number;
   oldest_date: string | null;
   newest_date: string | null;
+  markets?: Record<string, RustMarketReadinessStats>;
+  last_sync_at?: string | null;
+  sync_in_progress?: boolean;
+  stale_symbols?: number;
+  symbols_with_gaps?: number;
+  total_dividend_files?: number;
+  total_split_files?: number;
+  disk_usage_bytes?: number;
+}
+
+interface RustMarketReadinessStats {
+  total_stocks: number;
+  complete_data: number;
+  partial_data: number;
+  missing_data: number;
+  stale_data: number;
+  total_price_records: number;
+  oldest_date: string | null;
+  newest_date: string | null;
+  dividend_files: number;
+  split_count: number;
+  fx_pairs: number;
 }
 
 export class HistoricalDataService {
@@ -54,6 +82,11 @@ export class HistoricalDataService {
       lastSplit: rust.last_split,
       status: rust.status as 'complete' | 'partial' | 'missing' | 'delisted',
       delistReason: rust.delist_reason,
+      daysSinceLatestPrice: rust.days_since_latest_price ?? null,
+      stale: rust.stale ?? false,
+      lastSyncError: rust.last_sync_error ?? null,
+      firstTradeDate: rust.first_trade_date ?? null,
+      tags: rust.tags ?? [],
     };
   }
 
@@ -76,18 +109,43 @@ export class HistoricalDataService {
       completeData: rust.complete_data,
       partialData: rust.partial_data,
       missingData: rust.missing_data,
+      staleData: rust.stale_data ?? 0,
       totalPriceRecords: rust.total_price_records,
       oldestDate: rust.oldest_date,
       newestDate: rust.newest_date,
+      markets: Object.fromEntries(
+        Object.entries(rust.markets ?? {}).map(([market, stats]) => [
+          market,
+          {
+            totalStocks: stats.total_stocks,
+            completeData: stats.complete_data,
+            partialData: stats.partial_data,
+            missingData: stats.missing_data,
+            staleData: stats.stale_data,
+            totalPriceRecords: stats.total_price_records,
+            oldestDate: stats.oldest_date,
+            newestDate: stats.newest_date,
+            dividendFiles: stats.dividend_files,
+            splitCount: stats.split_count,
+            fxPairs: stats.fx_pairs,
+          },
+        ])
+      ),
+      lastSyncAt: rust.last_sync_at ?? null,
+      syncInProgress: rust.sync_in_progress ?? false,
+      staleSymbols: rust.stale_symbols ?? 0,
+      symbolsWithGaps: rust.symbols_with_gaps ?? 0,
+      totalDividendFiles: rust.total_dividend_files ?? 0,
+      totalSplitFiles: rust.total_split_files ?? 0,
+      diskUsageBytes: rust.disk_usage_bytes ?? 0,
     };
   }
 
   async getDataCoverage(includeCompleteness = false): Promise<StockDataCoverage[]> {
     try {
-      const result = await invoke<string>('get_data_coverage', {
+      const rustData = await invoke<RustStockDataCoverage[]>('get_data_coverage', {
         includeCompleteness,
       });
-      const rustData: RustStockDataCoverage[] = JSON.parse(result);
       return rustData.map(item => this.convertCoverage(item));
     } catch (error) {
       console.error('Failed to get data coverage:', error);
@@ -97,8 +155,7 @@ export class HistoricalDataService {
 
   async getSplitHistory(): Promise<SplitHistory[]> {
     try {
-      const result = await invoke<string>('get_split_history');
-      const rustData: RustSplitHistory[] = JSON.parse(result);
+      const rustData = await invoke<RustSplitHistory[]>('get_split_history');
       return rustData.map(item => this.convertSplit(item));
     } catch (error) {
       console.error('Failed to get split history:', error);
@@ -108,8 +165,7 @@ export class HistoricalDataService {
 
   async getDataStats(): Promise<DataReadinessStats> {
     try {
-      const result = await invoke<string>('get_data_stats');
-      const rustData: RustDataReadinessStats = JSON.parse(result);
+      const rustData = await invoke<RustDataReadinessStats>('get_data_stats');
       return this.convertStats(rustData);
     } catch (error) {
       console.error('Failed to get data stats:', error);
@@ -118,9 +174,18 @@ export class HistoricalDataService {
         completeData: 0,
         partialData: 0,
         missingData: 0,
+        staleData: 0,
         totalPriceRecords: 0,
         oldestDate: null,
         newestDate: null,
+        markets: {},
+        lastSyncAt: null,
+        syncInProgress: false,
+        staleSymbols: 0,
+        symbolsWithGaps: 0,
+        totalDividendFiles: 0,
+        totalSplitFiles: 0,
+        diskUsageBytes: 0,
       };
     }
   }
@@ -1,11 +1,15 @@
 import { invoke } from '@tauri-apps/api/tauri';
 import Papa from 'papaparse';
 import { NavRecord } from '../types/NavData';
+import type { NavFileContent } from './navService';
 
 export class NavDataService {
   async getNavForSymbol(symbol: string): Promise<NavRecord[]> {
     try {
-      const csvContent = await invoke<string>('read_nav_file', { symbol });
+      const { content: csvContent } = await invoke<NavFileContent>('read_nav_file', {
+        symbol,
+        order: 'asc',
+      });
       const parsed = Papa.parse<NavRecord>(csvContent, {
         header: true,
         dynamicTyping: true,
@@ -12,23 +12,33 @@ export interface NavSnapshotEntry {
   last_transaction: string | null;
 }
 
+export interface CashPosition {
+  currency: string;
+  amount: number;
+  account: string | null;
+}
+
+// Without cash_positions the backend attaches the stored balances and adds them
+// to total_value_usd.
 export interface NavSnapshotPayload {
   timestamp: string;
   base_currency: string;
   total_value_usd: number;
   entries: NavSnapshotEntry[];
+  cash_positions?: CashPosition[];
 }
 
+// Figures are optional: the backend derives them and only cross-checks these.
 export interface PositionSnapshotPayload {
   timestamp: string;
   stock: string;
   currency: string;
-  shares: number;
-  average_cost: number;
-  latest_price: number;
-  market_value: number;
-  market_value_usd: number;
-  status: string;
+  shares?: number;
+  average_cost?: number;
+  latest_price?: number;
+  market_value?: number;
+  market_value_usd?: number;
+  status?: string;
   last_transaction: string | null;
 }
 
@@ -41,18 +51,85 @@ export interface NavHistoryPoint {
   symbol: string;
 }
 
+export interface PositionNavResult {
+  path: string;
+  rows: number;
+  warnings: string[];
+  shares: number;
+  average_cost: number;
+  latest_price: number;
+  market_value: number;
+  status: 'open' | 'closed';
+}
+
+export interface NavSnapshotSummary {
+  timestamp: string;
+  total_value_usd: number;
+  base_currency: string;
+}
+
+export interface NavCompactionResult {
+  snapshots_merged: number;
+  files_removed: number;
+}
+
+export interface NavFileContent {
+  file: string;
+  content: string;
+  fallback: string | null;
+}
+
+export interface NavFileInfo {
+  file: string;
+  symbol: string;
+  file_size_bytes: number;
+  last_modified: string;
+}
+
 export const navService = {
   async saveSnapshot(payload: NavSnapshotPayload): Promise<string> {
     return invoke<string>('save_nav_snapshot', { snapshot: payload });
   },
 
-  async savePositionSnapshot(payload: PositionSnapshotPayload): Promise<string> {
-    return invoke<string>('save_position_snapshot', { snapshot: payload });
+  async savePositionSnapshot(payload: PositionSnapshotPayload): Promise<PositionNavResult> {
+    return invoke<PositionNavResult>('save_position_snapshot', { snapshot: payload });
+  },
+
+  async compactSnapshots(): Promise<NavCompactionResult> {
+    return invoke<NavCompactionResult>('compact_nav_snapshots');
+  },
+
+  async getSnapshots(limit?: number): Promise<NavSnapshotSummary[]> {
+    return invoke<NavSnapshotSummary[]>('get_nav_snapshots', { limit: limit ?? null });
   },
 
   async loadPositionHistory(symbol: string): Promise<NavHistoryPoint[]> {
-    const csv = await invoke<string>('read_nav_file', { symbol });
-    return parseNavCsv(csv);
+    const navFile = await invoke<NavFileContent>('read_nav_file', { symbol, order: 'asc' });
+    if (navFile.fallback) {
+      console.warn(`NAV for ${symbol} read from ${navFile.file}: ${navFile.fallback}`);
+    }
+    return parseNavCsv(navFile.content);
+  },
+
+  async listNavFiles(): Promise<NavFileInfo[]> {
+    return invoke<NavFileInfo[]>('list_nav_files');
+  },
+
+  /** Sets one balance (zero removes it) and returns all balances. */
+  async updateCashBalance(
+    currency: string,
+    amount: number,
+    account?: string,
+  ): Promise<CashPosition[]> {
+    return invoke<CashPosition[]>('update_cash_balance', {
+      currency,
+      amount,
+      account: account ?? null,
+    });
+  },
+
+  async getCashBalances(): Promise<CashPosition[]> {
+    return invoke<CashPosition[]>('get_cash_balances');
   },
 };
 
@@ -3,20 +3,47 @@ import { Transaction } from '../types/Transaction';
 
 export class TransactionService {
   async loadTransactions(): Promise<Transaction[]> {
-    const data = await invoke<string>('read_csv');
-    const parsed = JSON.parse(data);
+    const parsed = await invoke<any[]>('read_csv');
 
     return parsed.map((txn: any) => ({
       date: txn.date || '',
       stock: txn.stock || '',
       type: txn.transaction_type || '',
-      quantity: txn.quantity || '',
-      price: txn.price || '',
-      fees: txn.fees || '',
-      split_ratio: txn.split_ratio || '',
+      quantity: txn.quantity ?? null,
+      price: txn.price ?? null,
+      fees: txn.fees ?? null,
+      split_ratio: txn.split_ratio ?? null,
       currency: txn.currency || 'USD',
     }));
   }
+
+  /** Appends a transaction and returns the target file's transaction count. */
+  async addTransaction(transaction: Transaction, targetFile?: string): Promise<number> {
+    return invoke<number>('add_transaction', {
+      transaction: {
+        date: transaction.date,
+        stock: transaction.stock,
+        transaction_type: transaction.type,
+        quantity: transaction.quantity,
+        price: transaction.price,
+        fees: transaction.fees,
+        split_ratio: transaction.split_ratio,
+        currency: transaction.currency,
+        lot_id: transaction.lot_id || '',
+      },
+      targetFile: targetFile ?? null,
+    });
+  }
+
+  /** Removes matching rows and returns the file's remaining transaction count. */
+  async deleteTransaction(
+    file: string,
+    date: string,
+    stock: string,
+    transactionType: string,
+  ): Promise<number> {
+    return invoke<number>('delete_transaction', { file, date, stock, transactionType });
+  }
 }
 
 export const transactionService = new TransactionService();
@@ -1,13 +1,14 @@
 import { invoke } from '@tauri-apps/api/tauri';
 import { YahooMeta } from '../types/YahooMeta';
+import { symbolFileStem } from '../utils/symbolFiles';
 
 export class YahooMetaService {
   async getMeta(symbol: string): Promise<YahooMeta | null> {
     try {
       // The backend 'read_storage_csv' reads from the data directory.
       // We assume the meta files are stored in data/yahoo_metas/{symbol}.json
-      // Symbols with colons are usually replaced by underscores in filenames (e.g. HKEX:0700 -> HKEX_0700)
-      const safeSymbol = symbol.replace(':', '_');
+      // Filenames use the backend's symbol encoding (e.g. HKEX:0700 -> HKEX_0700)
+      const safeSymbol = symbolFileStem(symbol);
       const filename = `yahoo_metas/${safeSymbol}.json`;
       
       const content = await invoke<string>('read_storage_csv', { filename });
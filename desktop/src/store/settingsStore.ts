@@ -1,6 +1,7 @@
 import { create } from 'zustand';
 import { AppSettings, CurrencyType } from '../types/Settings';
 import { settingsService } from '../services/settingsService';
+import { getErrorMessage } from '../types/AppError';
 
 const DEFAULT_SETTINGS: AppSettings = {
   baseCurrency: 'USD',
@@ -39,7 +40,7 @@ export const useSettingsStore = create<SettingsState>((set, get) => ({
       });
     } catch (err) {
       set({
-        error: err instanceof Error ? err.message : 'Failed to load settings',
+        error: getErrorMessage(err, 'Failed to load settings'),
         loading: false,
       });
     }
@@ -55,7 +56,7 @@ export const useSettingsStore = create<SettingsState>((set, get) => ({
         privacyMode: newSettings.privacyMode || false,
       });
     } catch (err) {
-      const error = err instanceof Error ? err.message : 'Failed to save settings';
+      const error = getErrorMessage(err, 'Failed to save settings');
       set({ error });
       throw err;
     }
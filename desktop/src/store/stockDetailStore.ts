@@ -11,6 +11,7 @@ import {
   calculateDividendSummary,
   extractTransactionEvents,
 } from '../utils/stockDetailCalculations';
+import { getErrorMessage } from '../types/AppError';
 
 export type ChartTimeRange = '1W' | '1M' | '3M' | '6M' | 'YTD' | '1Y' | '5Y' | 'ALL' | 'MTD';
 
@@ -123,7 +124,7 @@ export const useStockDetailStore = create<StockDetailState>((set, get) => ({
       });
     } catch (err) {
       set({
-        error: err instanceof Error ? err.message : 'Failed to load stock detail',
+        error: getErrorMessage(err, 'Failed to load stock detail'),
         loading: false,
       });
     }
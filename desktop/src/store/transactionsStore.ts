@@ -1,6 +1,7 @@
 import { create } from 'zustand';
 import { Transaction } from '../types/Transaction';
 import { transactionService } from '../services/transactionService';
+import { getErrorMessage } from '../types/AppError';
 
 interface TransactionsState {
   transactions: Transaction[];
@@ -21,7 +22,7 @@ export const useTransactionsStore = create<TransactionsState>((set) => ({
       set({ transactions: data, loading: false });
     } catch (err) {
       set({
-        error: err instanceof Error ? err.message : 'Failed to load transactions',
+        error: getErrorMessage(err, 'Failed to load transactions'),
         loading: false,
       });
     }
@@ -0,0 +1,27 @@
+export type AppError =
+  | { kind: 'io'; message: string }
+  | { kind: 'not_found'; what: string; message: string }
+  | { kind: 'network'; status: number | null; message: string }
+  | { kind: 'parse'; file: string; line: number | null; message: string }
+  | { kind: 'validation'; field: string; reason: string; message: string }
+  | { kind: 'rate_limited'; retry_after: number | null; message: string }
+  | { kind: 'other'; message: string };
+
+export function isAppError(err: unknown): err is AppError {
+  return (
+    typeof err === 'object' &&
+    err !== null &&
+    typeof (err as { kind?: unknown }).kind === 'string' &&
+    typeof (err as { message?: unknown }).message === 'string'
+  );
+}
+
+export function getErrorMessage(err: unknown, fallback: string): string {
+  if (isAppError(err) || err instanceof Error) {
+    return err.message;
+  }
+  if (typeof err === 'string') {
+    return err;
+  }
+  return fallback;
+}
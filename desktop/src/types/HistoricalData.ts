@@ -12,6 +12,11 @@ export interface StockDataCoverage {
   lastSplit: string | null;
   status: 'complete' | 'partial' | 'missing' | 'delisted';
   delistReason?: string;
+  daysSinceLatestPrice: number | null;
+  stale: boolean;
+  lastSyncError: string | null;
+  firstTradeDate: string | null;
+  tags: string[];
 }
 
 export interface SplitHistory {
@@ -30,7 +35,30 @@ export interface DataReadinessStats {
   completeData: number;
   partialData: number;
   missingData: number;
+  staleData: number;
   totalPriceRecords: number;
   oldestDate: string | null;
   newestDate: string | null;
+  markets: Record<string, MarketReadinessStats>;
+  lastSyncAt: string | null;
+  syncInProgress: boolean;
+  staleSymbols: number;
+  symbolsWithGaps: number;
+  totalDividendFiles: number;
+  totalSplitFiles: number;
+  diskUsageBytes: number;
+}
+
+export interface MarketReadinessStats {
+  totalStocks: number;
+  completeData: number;
+  partialData: number;
+  missingData: number;
+  staleData: number;
+  totalPriceRecords: number;
+  oldestDate: string | null;
+  newestDate: string | null;
+  dividendFiles: number;
+  splitCount: number;
+  fxPairs: number;
 }
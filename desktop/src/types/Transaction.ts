@@ -2,11 +2,12 @@ export interface Transaction {
   date: string;
   stock: string;
   type: string;
-  quantity: string;
-  price: string;
-  fees: string;
-  split_ratio: string;
+  quantity: number | null;
+  price: number | null;
+  fees: number | null;
+  split_ratio: number | null;
   currency: string;
+  lot_id?: string;
 }
 
 export interface TransactionStats {
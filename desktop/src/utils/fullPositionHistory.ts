@@ -1,5 +1,4 @@
 import { Transaction } from '../types/Transaction';
-import { parseNumericString } from './csvUtils';
 
 export interface FullPositionEntry {
   stock: string;
@@ -53,13 +52,13 @@ export function buildFullPositionHistory(transactions: Transaction[]): FullPosit
     const entry = map.get(key)!;
     entry.lastTransaction = txn.date || entry.lastTransaction;
 
-    const quantity = parseNumericString(txn.quantity, 0);
-    const price = parseNumericString(txn.price, 0);
-    const fees = parseNumericString(txn.fees, 0);
-    const splitRatio = parseNumericString(txn.split_ratio, 1);
+    const quantity = txn.quantity ?? 0;
+    const price = txn.price ?? 0;
+    const fees = txn.fees ?? 0;
+    const splitRatio = txn.split_ratio ?? 1;
     const type = normalizeType(txn.type);
 
-    if (type === 'buy' || type === 'purchase') {
+    if (type === 'buy' || type === 'purchase' || type === 'rights_subscribe') {
       const cost = quantity * price + fees;
       entry.invested += cost;
       entry.remainingCost += cost;
@@ -73,6 +72,17 @@ export function buildFullPositionHistory(transactions: Transaction[]): FullPosit
       entry.remainingCost = Math.max(0, entry.remainingCost - costBasis);
       entry.averageCost = entry.shares > 0 ? entry.remainingCost / entry.shares : 0;
       entry.realizedPnl += proceeds - costBasis;
+    } else if (type === 'adr_convert_to_ordinary' || type === 'ordinary_convert_to_adr') {
+      // Non-taxable exchange: cost basis moves with the shares, no realized P&L.
+      if (quantity < 0) {
+        const moved = Math.min(-quantity, entry.shares);
+        entry.remainingCost = Math.max(0, entry.remainingCost - entry.averageCost * moved);
+        entry.shares = Math.max(0, entry.shares - moved);
+      } else {
+        entry.remainingCost += quantity * price;
+        entry.shares += quantity;
+      }
+      entry.averageCost = entry.shares > 0 ? entry.remainingCost / entry.shares : 0;
     } else if (type === 'dividend' || type === 'div') {
       const payout = quantity * price;
       entry.realizedPnl += payout;
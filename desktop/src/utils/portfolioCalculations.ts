@@ -1,6 +1,5 @@
 import { Transaction } from '../types/Transaction';
 import { Position, PortfolioSummary } from '../types/Portfolio';
-import { parseNumericString } from './csvUtils';
 
 export function calculatePositions(transactions: Transaction[]): Position[] {
   const positionMap = new Map<string, Position>();
@@ -13,10 +12,10 @@ export function calculatePositions(transactions: Transaction[]): Position[] {
     const key = `${txn.stock}_${txn.currency}`;
     const type = txn.type.toLowerCase();
 
-    const quantity = parseNumericString(txn.quantity, 0);
-    const price = parseNumericString(txn.price, 0);
-    const fees = parseNumericString(txn.fees, 0);
-    const splitRatio = parseNumericString(txn.split_ratio, 1);
+    const quantity = txn.quantity ?? 0;
+    const price = txn.price ?? 0;
+    const fees = txn.fees ?? 0;
+    const splitRatio = txn.split_ratio ?? 1;
 
     if (!positionMap.has(key)) {
       positionMap.set(key, {
@@ -30,7 +29,7 @@ export function calculatePositions(transactions: Transaction[]): Position[] {
 
     const position = positionMap.get(key)!;
 
-    if (type === 'buy' || type === 'purchase') {
+    if (type === 'buy' || type === 'purchase' || type === 'rights_subscribe') {
       const cost = quantity * price + fees;
       const newShares = position.shares + quantity;
       const newTotalCost = position.totalCost + cost;
@@ -48,6 +47,21 @@ export function calculatePositions(transactions: Transaction[]): Position[] {
         position.totalCost = 0;
         position.averageCost = 0;
       }
+    } else if (type === 'adr_convert_to_ordinary' || type === 'ordinary_convert_to_adr') {
+      // Signed quantity: negative leaves at average cost, positive arrives with the carried-over cost.
+      if (quantity < 0) {
+        const removed = Math.min(-quantity, position.shares);
+        position.shares -= removed;
+        position.totalCost -= position.averageCost * removed;
+      } else {
+        position.shares += quantity;
+        position.totalCost += quantity * price;
+      }
+      if (position.shares <= 0) {
+        position.shares = 0;
+        position.totalCost = 0;
+      }
+      position.averageCost = position.shares > 0 ? position.totalCost / position.shares : 0;
     } else if (type === 'split') {
       if (splitRatio > 0 && position.shares > 0) {
         position.shares = position.shares * splitRatio;
@@ -9,7 +9,6 @@ import {
   SplitRecord,
   DividendPeriodSummary,
 } from '../types/StockDetail';
-import { parseNumericString } from './csvUtils';
 
 const normalizeTransactionType = (type: string) => type?.trim().toLowerCase() || '';
 
@@ -81,9 +80,9 @@ export function calculateCostBasisTimeSeries(
   const sortedTransactions = [...transactions].sort((a, b) => a.date.localeCompare(b.date));
 
   for (const txn of sortedTransactions) {
-    const quantity = parseNumericString(txn.quantity, 0);
-    const price = parseNumericString(txn.price, 0);
-    const fees = parseNumericString(txn.fees, 0);
+    const quantity = txn.quantity ?? 0;
+    const price = txn.price ?? 0;
+    const fees = txn.fees ?? 0;
 
     if (txn.type.toLowerCase() === 'buy') {
       totalCost += quantity * price + fees;
@@ -191,8 +190,8 @@ export function calculateDividendSummary(
   const dividendTransactions = transactions.filter(t => isDividendType(t.type));
 
   const totalDividends = dividendTransactions.reduce((sum, txn) => {
-    const quantity = parseNumericString(txn.quantity, 0);
-    const price = parseNumericString(txn.price, 0);
+    const quantity = txn.quantity ?? 0;
+    const price = txn.price ?? 0;
     return sum + quantity * price;
   }, 0);
 
@@ -206,7 +205,7 @@ export function calculateDividendSummary(
   const perQuarterMap = new Map<string, { total: number; count: number }>();
   for (const txn of dividendTransactions) {
     const year = txn.date?.slice(0, 4) || 'Unknown';
-    const amount = parseNumericString(txn.quantity, 0) * parseNumericString(txn.price, 0);
+    const amount = (txn.quantity ?? 0) * (txn.price ?? 0);
     const current = perYearMap.get(year) || { total: 0, count: 0 };
     current.total += amount;
     current.count += 1;
@@ -248,8 +247,8 @@ export function calculateDividendSummary(
       return txnDate >= oneYearAgo;
     })
     .reduce((sum, txn) => {
-      const quantity = parseNumericString(txn.quantity, 0);
-      const price = parseNumericString(txn.price, 0);
+      const quantity = txn.quantity ?? 0;
+      const price = txn.price ?? 0;
       return sum + quantity * price;
     }, 0);
 
@@ -274,22 +273,25 @@ export function extractTransactionEvents(transactions: Transaction[]): Transacti
   const sortedTransactions = [...transactions].sort((a, b) => a.date.localeCompare(b.date));
 
   for (const txn of sortedTransactions) {
-    const quantity = parseNumericString(txn.quantity, 0);
-    const price = parseNumericString(txn.price, 0);
+    const quantity = txn.quantity ?? 0;
+    const price = txn.price ?? 0;
     const type = normalizeTransactionType(txn.type);
 
     let eventType: 'buy' | 'sell' | 'dividend' | 'split';
-    if (type === 'buy' || type === 'purchase') {
+    if (type === 'buy' || type === 'purchase' || type === 'rights_subscribe') {
       eventType = 'buy';
       runningShares += quantity;
     } else if (type === 'sell' || type === 'sale') {
       eventType = 'sell';
       runningShares -= quantity;
+    } else if (type === 'adr_convert_to_ordinary' || type === 'ordinary_convert_to_adr') {
+      eventType = quantity < 0 ? 'sell' : 'buy';
+      runningShares += quantity;
     } else if (isDividendType(type)) {
       eventType = 'dividend';
     } else if (type === 'split' || type.includes('split')) {
       eventType = 'split';
-      const splitRatio = parseNumericString(txn.split_ratio, 1);
+      const splitRatio = txn.split_ratio ?? 1;
       runningShares *= splitRatio;
     } else {
       continue;
@@ -334,17 +336,20 @@ export function buildChartData(
   for (const record of priceHistory) {
     while (txnIndex < sortedTransactions.length && sortedTransactions[txnIndex].date <= record.date) {
       const txn = sortedTransactions[txnIndex];
-      const quantity = parseNumericString(txn.quantity, 0);
+      const quantity = txn.quantity ?? 0;
       const type = normalizeTransactionType(txn.type);
 
-      if (type === 'buy' || type === 'purchase') {
+      if (type === 'buy' || type === 'purchase' || type === 'rights_subscribe') {
         runningShares += quantity;
         console.log(`  [${txn.date}] BUY ${quantity} shares, total: ${runningShares}`);
       } else if (type === 'sell' || type === 'sale') {
         runningShares -= quantity;
         console.log(`  [${txn.date}] SELL ${quantity} shares, total: ${runningShares}`);
+      } else if (type === 'adr_convert_to_ordinary' || type === 'ordinary_convert_to_adr') {
+        runningShares += quantity;
+        console.log(`  [${txn.date}] CONVERT ${quantity} shares, total: ${runningShares}`);
       } else if (type === 'split' || type.includes('split')) {
-        const splitRatio = parseNumericString(txn.split_ratio, 1);
+        const splitRatio = txn.split_ratio ?? 1;
         const oldShares = runningShares;
         runningShares *= splitRatio;
         console.log(`  [${txn.date}] SPLIT ${splitRatio}:1, shares: ${oldShares} -> ${runningShares}`);
@@ -0,0 +1,56 @@
+// Mirrors symbol_file_stem / symbol_from_file_stem in the Tauri backend so the
+// frontend can locate per-symbol data files by name.
+
+const RESERVED = new Set(['_', '%', '/', '\\', '*', '?', '"', '<', '>', '|']);
+const DEVICE_NAME = /^(CON|PRN|AUX|NUL|COM[0-9]|LPT[0-9])$/;
+
+function isControl(codePoint: number): boolean {
+  return codePoint < 0x20 || (codePoint >= 0x7f && codePoint <= 0x9f);
+}
+
+function percentEncode(char: string): string {
+  return Array.from(new TextEncoder().encode(char))
+    .map((byte) => `%${byte.toString(16).toUpperCase().padStart(2, '0')}`)
+    .join('');
+}
+
+export function symbolFileStem(symbol: string): string {
+  const chars = Array.from(symbol);
+  let stem = chars
+    .map((char, idx) => {
+      const last = idx === chars.length - 1;
+      if (char === ':') return '_';
+      if (
+        RESERVED.has(char) ||
+        isControl(char.codePointAt(0) ?? 0) ||
+        (last && (char === '.' || char === ' '))
+      ) {
+        return percentEncode(char);
+      }
+      return char;
+    })
+    .join('');
+
+  if (DEVICE_NAME.test(stem.split('.')[0].toUpperCase())) {
+    stem = percentEncode(stem[0]) + stem.slice(1);
+  }
+  return stem;
+}
+
+export function symbolFromFileStem(stem: string): string {
+  const bytes: number[] = [];
+  const encoder = new TextEncoder();
+  let idx = 0;
+  while (idx < stem.length) {
+    const hex = stem.slice(idx + 1, idx + 3);
+    if (stem[idx] === '%' && /^[0-9A-Fa-f]{2}$/.test(hex)) {
+      bytes.push(parseInt(hex, 16));
+      idx += 3;
+      continue;
+    }
+    const char = String.fromCodePoint(stem.codePointAt(idx) ?? 0);
+    bytes.push(...encoder.encode(char === '_' ? ':' : char));
+    idx += char.length;
+  }
+  return new TextDecoder().decode(new Uint8Array(bytes));
+}
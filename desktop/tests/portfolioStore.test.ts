@@ -46,20 +46,20 @@ describe('portfolioStore', () => {
         {
           date: '2024-01-01',
           stock: 'AAPL',
-          quantity: '10',
-          price: '150',
-          fees: '0',
-          split_ratio: '',
+          quantity: 10,
+          price: 150,
+          fees: 0,
+          split_ratio: null,
           type: 'Buy',
           currency: 'USD',
         },
         {
           date: '2024-01-02',
           stock: 'AAPL',
-          quantity: '5',
-          price: '160',
-          fees: '0',
-          split_ratio: '',
+          quantity: 5,
+          price: 160,
+          fees: 0,
+          split_ratio: null,
           type: 'Buy',
           currency: 'USD',
         },
@@ -84,10 +84,10 @@ describe('portfolioStore', () => {
         {
           date: '2024-01-01',
           stock: 'NASDAQ:AAPL',
-          quantity: '10',
-          price: '150',
-          fees: '0',
-          split_ratio: '',
+          quantity: 10,
+          price: 150,
+          fees: 0,
+          split_ratio: null,
           type: 'Buy',
           currency: 'USD',
         },
@@ -116,10 +116,10 @@ describe('portfolioStore', () => {
         {
           date: '2024-01-01',
           stock: 'NASDAQ:AAPL',
-          quantity: '10',
-          price: '150',
-          fees: '0',
-          split_ratio: '',
+          quantity: 10,
+          price: 150,
+          fees: 0,
+          split_ratio: null,
           type: 'Buy',
           currency: 'USD',
         },
@@ -157,10 +157,10 @@ describe('portfolioStore', () => {
         {
           date: '2024-01-01',
           stock: 'NASDAQ:AAPL',
-          quantity: '10',
-          price: '150',
-          fees: '0',
-          split_ratio: '',
+          quantity: 10,
+          price: 150,
+          fees: 0,
+          split_ratio: null,
           type: 'Buy',
           currency: 'USD',
         },
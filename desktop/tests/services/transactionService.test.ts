@@ -21,25 +21,25 @@ describe('TransactionService', () => {
           date: '2024-01-01',
           stock: 'AAPL',
           transaction_type: 'Buy',
-          quantity: '10',
-          price: '150',
-          fees: '1',
-          split_ratio: '1',
+          quantity: 10,
+          price: 150,
+          fees: 1,
+          split_ratio: 1,
           currency: 'USD',
         },
         {
           date: '2024-01-02',
           stock: '2330',
           transaction_type: 'Sell',
-          quantity: '100',
-          price: '600',
-          fees: '20',
-          split_ratio: '1',
+          quantity: 100,
+          price: 600,
+          fees: 20,
+          split_ratio: 1,
           currency: 'TWD',
         },
       ];
 
-      vi.mocked(invoke).mockResolvedValue(JSON.stringify(mockData));
+      vi.mocked(invoke).mockResolvedValue(mockData);
 
       const result = await service.loadTransactions();
 
@@ -49,20 +49,20 @@ describe('TransactionService', () => {
         date: '2024-01-01',
         stock: 'AAPL',
         type: 'Buy',
-        quantity: '10',
-        price: '150',
-        fees: '1',
-        split_ratio: '1',
+        quantity: 10,
+        price: 150,
+        fees: 1,
+        split_ratio: 1,
         currency: 'USD',
       });
       expect(result[1]).toEqual({
         date: '2024-01-02',
         stock: '2330',
         type: 'Sell',
-        quantity: '100',
-        price: '600',
-        fees: '20',
-        split_ratio: '1',
+        quantity: 100,
+        price: 600,
+        fees: 20,
+        split_ratio: 1,
         currency: 'TWD',
       });
     });
@@ -76,7 +76,7 @@ describe('TransactionService', () => {
         },
       ];
 
-      vi.mocked(invoke).mockResolvedValue(JSON.stringify(mockData));
+      vi.mocked(invoke).mockResolvedValue(mockData);
 
       const result = await service.loadTransactions();
 
@@ -84,16 +84,16 @@ describe('TransactionService', () => {
         date: '2024-01-01',
         stock: 'AAPL',
         type: 'Buy',
-        quantity: '',
-        price: '',
-        fees: '',
-        split_ratio: '',
+        quantity: null,
+        price: null,
+        fees: null,
+        split_ratio: null,
         currency: 'USD',
       });
     });
 
     it('should handle empty data', async () => {
-      vi.mocked(invoke).mockResolvedValue(JSON.stringify([]));
+      vi.mocked(invoke).mockResolvedValue([]);
 
       const result = await service.loadTransactions();
 
@@ -112,14 +112,14 @@ describe('TransactionService', () => {
           date: '2024-01-01',
           stock: 'AAPL',
           transaction_type: 'Buy',
-          quantity: '10',
-          price: '150',
-          fees: '1',
-          split_ratio: '1',
+          quantity: 10,
+          price: 150,
+          fees: 1,
+          split_ratio: 1,
         },
       ];
 
-      vi.mocked(invoke).mockResolvedValue(JSON.stringify(mockData));
+      vi.mocked(invoke).mockResolvedValue(mockData);
 
       const result = await service.loadTransactions();
 
@@ -10,10 +10,10 @@ describe('portfolioCalculations', () => {
           date: '2024-01-01',
           stock: 'NASDAQ:AAPL',
           type: 'Buy',
-          quantity: '100',
-          price: '$150.00',
-          fees: '$10.00',
-          split_ratio: '',
+          quantity: 100,
+          price: 150,
+          fees: 10,
+          split_ratio: null,
           currency: 'USD',
         },
       ];
@@ -36,20 +36,20 @@ describe('portfolioCalculations', () => {
           date: '2020-01-01',
           stock: 'NASDAQ:AAPL',
           type: 'Buy',
-          quantity: '50',
-          price: '$400.00',
-          fees: '$0.00',
-          split_ratio: '',
+          quantity: 50,
+          price: 400,
+          fees: 0,
+          split_ratio: null,
           currency: 'USD',
         },
         {
           date: '2020-08-31',
           stock: 'NASDAQ:AAPL',
           type: 'Split',
-          quantity: '0',
-          price: '$0.00',
-          fees: '$0.00',
-          split_ratio: '4',
+          quantity: 0,
+          price: 0,
+          fees: 0,
+          split_ratio: 4,
           currency: 'USD',
         },
       ];
@@ -72,20 +72,20 @@ describe('portfolioCalculations', () => {
           date: '2022-01-01',
           stock: 'NASDAQ:SHOP',
           type: 'Buy',
-          quantity: '8',
-          price: '$1000.00',
-          fees: '$0.00',
-          split_ratio: '',
+          quantity: 8,
+          price: 1000,
+          fees: 0,
+          split_ratio: null,
           currency: 'USD',
         },
         {
           date: '2022-06-29',
           stock: 'NASDAQ:SHOP',
           type: 'Split',
-          quantity: '0',
-          price: '$0.00',
-          fees: '$0.00',
-          split_ratio: '10',
+          quantity: 0,
+          price: 0,
+          fees: 0,
+          split_ratio: 10,
           currency: 'USD',
         },
       ];
@@ -108,20 +108,20 @@ describe('portfolioCalculations', () => {
           date: '2019-01-01',
           stock: 'NASDAQ:NVDA',
           type: 'Buy',
-          quantity: '100',
-          price: '$40.00',
-          fees: '$0.00',
-          split_ratio: '',
+          quantity: 100,
+          price: 40,
+          fees: 0,
+          split_ratio: null,
           currency: 'USD',
         },
         {
           date: '2021-07-19',
           stock: 'NASDAQ:NVDA',
           type: 'Split',
-          quantity: '0',
-          price: '$0.00',
-          fees: '$0.00',
-          split_ratio: '4',
+          quantity: 0,
+          price: 0,
+          fees: 0,
+          split_ratio: 4,
           currency: 'USD',
         },
       ];
@@ -144,30 +144,30 @@ describe('portfolioCalculations', () => {
           date: '2020-01-01',
           stock: 'NASDAQ:AAPL',
           type: 'Buy',
-          quantity: '100',
-          price: '$400.00',
-          fees: '$0.00',
-          split_ratio: '',
+          quantity: 100,
+          price: 400,
+          fees: 0,
+          split_ratio: null,
           currency: 'USD',
         },
         {
           date: '2020-08-31',
           stock: 'NASDAQ:AAPL',
           type: 'Split',
-          quantity: '0',
-          price: '$0.00',
-          fees: '$0.00',
-          split_ratio: '4',
+          quantity: 0,
+          price: 0,
+          fees: 0,
+          split_ratio: 4,
           currency: 'USD',
         },
         {
           date: '2021-01-01',
           stock: 'NASDAQ:AAPL',
           type: 'Sell',
-          quantity: '200',
-          price: '$120.00',
-          fees: '$0.00',
-          split_ratio: '',
+          quantity: 200,
+          price: 120,
+          fees: 0,
+          split_ratio: null,
           currency: 'USD',
         },
       ];
@@ -190,20 +190,20 @@ describe('portfolioCalculations', () => {
           date: '2020-01-01',
           stock: 'NASDAQ:TEST',
           type: 'Buy',
-          quantity: '100',
-          price: '$10.00',
-          fees: '$0.00',
-          split_ratio: '',
+          quantity: 100,
+          price: 10,
+          fees: 0,
+          split_ratio: null,
           currency: 'USD',
         },
         {
           date: '2020-06-01',
           stock: 'NASDAQ:TEST',
           type: 'Split',
-          quantity: '0',
-          price: '$0.00',
-          fees: '$0.00',
-          split_ratio: '0.5',
+          quantity: 0,
+          price: 0,
+          fees: 0,
+          split_ratio: 0.5,
           currency: 'USD',
         },
       ];
@@ -226,10 +226,10 @@ describe('portfolioCalculations', () => {
           date: '2020-08-31',
           stock: 'NASDAQ:AAPL',
           type: 'Split',
-          quantity: '0',
-          price: '$0.00',
-          fees: '$0.00',
-          split_ratio: '4',
+          quantity: 0,
+          price: 0,
+          fees: 0,
+          split_ratio: 4,
           currency: 'USD',
         },
       ];
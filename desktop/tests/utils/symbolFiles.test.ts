@@ -0,0 +1,45 @@
+import { describe, it, expect } from 'vitest';
+import { symbolFileStem, symbolFromFileStem } from '../../src/utils/symbolFiles';
+
+describe('symbolFiles', () => {
+  it('should keep the colon-to-underscore names for plain symbols', () => {
+    expect(symbolFileStem('AAPL')).toBe('AAPL');
+    expect(symbolFileStem('HKEX:0700')).toBe('HKEX_0700');
+    expect(symbolFileStem('NYSE:BRK.B')).toBe('NYSE_BRK.B');
+  });
+
+  it('should percent-encode literal underscores and reserved characters', () => {
+    expect(symbolFileStem('NYSE:BRK_B')).toBe('NYSE_BRK%5FB');
+    expect(symbolFileStem('A/B')).toBe('A%2FB');
+    expect(symbolFileStem('x*y?')).toBe('x%2Ay%3F');
+    expect(symbolFileStem('50%')).toBe('50%25');
+    expect(symbolFileStem('a.')).toBe('a%2E');
+    expect(symbolFileStem('CON')).toBe('%43ON');
+  });
+
+  it('should round-trip awkward symbols', () => {
+    const symbols = [
+      'AAPL',
+      'NYSE:BRK_B',
+      'NYSE:BRK:B',
+      'NYSE:A_B:C',
+      'HKEX:0700',
+      'A/B',
+      'A\\B',
+      'x*y?',
+      '日本:株',
+      'a%41',
+      'COM1',
+      'con.x',
+      'tab\tz',
+      'trailing ',
+    ];
+    for (const symbol of symbols) {
+      expect(symbolFromFileStem(symbolFileStem(symbol))).toBe(symbol);
+    }
+  });
+
+  it('should give distinct stems to symbols that used to collide', () => {
+    expect(symbolFileStem('NYSE:BRK_B')).not.toBe(symbolFileStem('NYSE:BRK:B'));
+  });
+});
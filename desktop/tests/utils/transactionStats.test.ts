@@ -21,11 +21,11 @@ describe('transactionStats', () => {
 
     it('should count transaction types correctly', () => {
       const transactions: Transaction[] = [
-        { stock: 'AAPL', type: 'Buy', date: '2024-01-01', quantity: '10', price: '150', fees: '1', currency: 'USD', split_ratio: '1' },
-        { stock: 'TSLA', type: 'Buy', date: '2024-01-02', quantity: '5', price: '200', fees: '1', currency: 'USD', split_ratio: '1' },
-        { stock: 'AAPL', type: 'Sell', date: '2024-01-03', quantity: '5', price: '160', fees: '1', currency: 'USD', split_ratio: '1' },
-        { stock: 'AAPL', type: 'Dividend', date: '2024-01-04', quantity: '0', price: '0.5', fees: '0', currency: 'USD', split_ratio: '1' },
-        { stock: 'GOOGL', type: 'Split', date: '2024-01-05', quantity: '0', price: '0', fees: '0', currency: 'USD', split_ratio: '2' },
+        { stock: 'AAPL', type: 'Buy', date: '2024-01-01', quantity: 10, price: 150, fees: 1, currency: 'USD', split_ratio: 1 },
+        { stock: 'TSLA', type: 'Buy', date: '2024-01-02', quantity: 5, price: 200, fees: 1, currency: 'USD', split_ratio: 1 },
+        { stock: 'AAPL', type: 'Sell', date: '2024-01-03', quantity: 5, price: 160, fees: 1, currency: 'USD', split_ratio: 1 },
+        { stock: 'AAPL', type: 'Dividend', date: '2024-01-04', quantity: 0, price: 0.5, fees: 0, currency: 'USD', split_ratio: 1 },
+        { stock: 'GOOGL', type: 'Split', date: '2024-01-05', quantity: 0, price: 0, fees: 0, currency: 'USD', split_ratio: 2 },
       ];
       const stats = calculateTransactionStats(transactions);
       expect(stats.total).toBe(5);
@@ -37,10 +37,10 @@ describe('transactionStats', () => {
 
     it('should handle case-insensitive type matching', () => {
       const transactions: Transaction[] = [
-        { stock: 'AAPL', type: 'buy', date: '2024-01-01', quantity: '10', price: '150', fees: '1', currency: 'USD', split_ratio: '1' },
-        { stock: 'TSLA', type: 'BUY', date: '2024-01-02', quantity: '5', price: '200', fees: '1', currency: 'USD', split_ratio: '1' },
-        { stock: 'AAPL', type: 'SELL', date: '2024-01-03', quantity: '5', price: '160', fees: '1', currency: 'USD', split_ratio: '1' },
-        { stock: 'AAPL', type: 'dividend', date: '2024-01-04', quantity: '0', price: '0.5', fees: '0', currency: 'USD', split_ratio: '1' },
+        { stock: 'AAPL', type: 'buy', date: '2024-01-01', quantity: 10, price: 150, fees: 1, currency: 'USD', split_ratio: 1 },
+        { stock: 'TSLA', type: 'BUY', date: '2024-01-02', quantity: 5, price: 200, fees: 1, currency: 'USD', split_ratio: 1 },
+        { stock: 'AAPL', type: 'SELL', date: '2024-01-03', quantity: 5, price: 160, fees: 1, currency: 'USD', split_ratio: 1 },
+        { stock: 'AAPL', type: 'dividend', date: '2024-01-04', quantity: 0, price: 0.5, fees: 0, currency: 'USD', split_ratio: 1 },
       ];
       const stats = calculateTransactionStats(transactions);
       expect(stats.buys).toBe(2);
@@ -50,12 +50,12 @@ describe('transactionStats', () => {
 
     it('should count currencies correctly', () => {
       const transactions: Transaction[] = [
-        { stock: 'AAPL', type: 'Buy', date: '2024-01-01', quantity: '10', price: '150', fees: '1', currency: 'USD', split_ratio: '1' },
-        { stock: 'TSLA', type: 'Buy', date: '2024-01-02', quantity: '5', price: '200', fees: '1', currency: 'USD', split_ratio: '1' },
-        { stock: '2330', type: 'Buy', date: '2024-01-03', quantity: '100', price: '600', fees: '20', currency: 'TWD', split_ratio: '1' },
-        { stock: '2330', type: 'Sell', date: '2024-01-04', quantity: '50', price: '620', fees: '15', currency: 'TWD', split_ratio: '1' },
-        { stock: '7203', type: 'Buy', date: '2024-01-05', quantity: '50', price: '2000', fees: '100', currency: 'JPY', split_ratio: '1' },
-        { stock: '0700', type: 'Buy', date: '2024-01-06', quantity: '10', price: '350', fees: '5', currency: 'HKD', split_ratio: '1' },
+        { stock: 'AAPL', type: 'Buy', date: '2024-01-01', quantity: 10, price: 150, fees: 1, currency: 'USD', split_ratio: 1 },
+        { stock: 'TSLA', type: 'Buy', date: '2024-01-02', quantity: 5, price: 200, fees: 1, currency: 'USD', split_ratio: 1 },
+        { stock: '2330', type: 'Buy', date: '2024-01-03', quantity: 100, price: 600, fees: 20, currency: 'TWD', split_ratio: 1 },
+        { stock: '2330', type: 'Sell', date: '2024-01-04', quantity: 50, price: 620, fees: 15, currency: 'TWD', split_ratio: 1 },
+        { stock: '7203', type: 'Buy', date: '2024-01-05', quantity: 50, price: 2000, fees: 100, currency: 'JPY', split_ratio: 1 },
+        { stock: '0700', type: 'Buy', date: '2024-01-06', quantity: 10, price: 350, fees: 5, currency: 'HKD', split_ratio: 1 },
       ];
       const stats = calculateTransactionStats(transactions);
       expect(stats.total).toBe(6);
@@ -67,9 +67,9 @@ describe('transactionStats', () => {
 
     it('should handle dividend variations', () => {
       const transactions: Transaction[] = [
-        { stock: 'AAPL', type: 'Dividend', date: '2024-01-01', quantity: '0', price: '0.5', fees: '0', currency: 'USD', split_ratio: '1' },
-        { stock: 'MSFT', type: 'dividend', date: '2024-01-02', quantity: '0', price: '0.6', fees: '0', currency: 'USD', split_ratio: '1' },
-        { stock: 'GOOGL', type: 'DIV', date: '2024-01-03', quantity: '0', price: '0.4', fees: '0', currency: 'USD', split_ratio: '1' },
+        { stock: 'AAPL', type: 'Dividend', date: '2024-01-01', quantity: 0, price: 0.5, fees: 0, currency: 'USD', split_ratio: 1 },
+        { stock: 'MSFT', type: 'dividend', date: '2024-01-02', quantity: 0, price: 0.6, fees: 0, currency: 'USD', split_ratio: 1 },
+        { stock: 'GOOGL', type: 'DIV', date: '2024-01-03', quantity: 0, price: 0.4, fees: 0, currency: 'USD', split_ratio: 1 },
       ];
       const stats = calculateTransactionStats(transactions);
       expect(stats.dividends).toBe(3);
@@ -77,11 +77,11 @@ describe('transactionStats', () => {
 
     it('should handle mixed transaction data', () => {
       const transactions: Transaction[] = [
-        { stock: 'AAPL', type: 'Buy', date: '2024-01-01', quantity: '10', price: '150', fees: '1', currency: 'USD', split_ratio: '1' },
-        { stock: '2330', type: 'Buy', date: '2024-01-02', quantity: '100', price: '600', fees: '20', currency: 'TWD', split_ratio: '1' },
-        { stock: 'AAPL', type: 'Sell', date: '2024-01-03', quantity: '5', price: '160', fees: '1', currency: 'USD', split_ratio: '1' },
-        { stock: '7203', type: 'Buy', date: '2024-01-04', quantity: '50', price: '2000', fees: '100', currency: 'JPY', split_ratio: '1' },
-        { stock: 'AAPL', type: 'Dividend', date: '2024-01-05', quantity: '0', price: '0.5', fees: '0', currency: 'USD', split_ratio: '1' },
+        { stock: 'AAPL', type: 'Buy', date: '2024-01-01', quantity: 10, price: 150, fees: 1, currency: 'USD', split_ratio: 1 },
+        { stock: '2330', type: 'Buy', date: '2024-01-02', quantity: 100, price: 600, fees: 20, currency: 'TWD', split_ratio: 1 },
+        { stock: 'AAPL', type: 'Sell', date: '2024-01-03', quantity: 5, price: 160, fees: 1, currency: 'USD', split_ratio: 1 },
+        { stock: '7203', type: 'Buy', date: '2024-01-04', quantity: 50, price: 2000, fees: 100, currency: 'JPY', split_ratio: 1 },
+        { stock: 'AAPL', type: 'Dividend', date: '2024-01-05', quantity: 0, price: 0.5, fees: 0, currency: 'USD', split_ratio: 1 },
       ];
       const stats = calculateTransactionStats(transactions);
       expect(stats.total).toBe(5);